@@ -1,4 +1,5 @@
-use crate::services::{AIConfig, AITranslator, ConfigDraft};
+use crate::services::ai::GenerationParams;
+use crate::services::{AIConfig, AITranslator, AppConfig, ConfigDraft};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize)]
@@ -11,11 +12,14 @@ pub struct AIConfigSummary {
     pub base_url: Option<String>,
     pub model: Option<String>,
     pub proxy: Option<crate::services::ProxyConfig>,
+    pub generation: GenerationParams,
     pub is_active: bool,
+    /// 在备用供应商链中的位置（从 0 开始），不是备用配置时为 None
+    pub fallback_order: Option<usize>,
 }
 
 impl AIConfigSummary {
-    fn from_config(index: usize, config: &AIConfig, active_index: Option<usize>) -> Self {
+    fn from_config(index: usize, config: &AIConfig, app_config: &AppConfig) -> Self {
         Self {
             index,
             provider_id: config.provider_id.clone(),
@@ -24,7 +28,12 @@ impl AIConfigSummary {
             base_url: config.base_url.clone(),
             model: config.model.clone(),
             proxy: config.proxy.clone(),
-            is_active: active_index == Some(index),
+            generation: config.generation.clone(),
+            is_active: app_config.active_config_index == Some(index),
+            fallback_order: app_config
+                .fallback_config_indices
+                .iter()
+                .position(|fallback| *fallback == index),
         }
     }
 }
@@ -33,13 +42,12 @@ impl AIConfigSummary {
 pub async fn get_all_ai_configs() -> Result<Vec<AIConfigSummary>, String> {
     let draft = ConfigDraft::global().await;
     let config = draft.data();
-    let active_index = config.active_config_index;
 
     Ok(config
         .get_all_ai_configs()
         .iter()
         .enumerate()
-        .map(|(index, item)| AIConfigSummary::from_config(index, item, active_index))
+        .map(|(index, item)| AIConfigSummary::from_config(index, item, &config))
         .collect())
 }
 
@@ -51,7 +59,25 @@ pub async fn get_active_ai_config() -> Result<Option<AIConfigSummary>, String> {
     Ok(config
         .active_config_index
         .and_then(|index| config.ai_configs.get(index).map(|item| (index, item)))
-        .map(|(index, item)| AIConfigSummary::from_config(index, item, config.active_config_index)))
+        .map(|(index, item)| AIConfigSummary::from_config(index, item, &config)))
+}
+
+/// 按模型能力校验生成参数（未指定模型时使用供应商默认模型）
+fn validate_generation(config: &AIConfig) -> Result<(), String> {
+    use crate::services::ai::provider::with_global_registry;
+
+    let model = match &config.model {
+        Some(model) => model.clone(),
+        None => with_global_registry(|registry| {
+            registry
+                .get_provider_info(&config.provider_id)
+                .map(|info| info.default_model)
+                .unwrap_or_default()
+        }),
+    };
+    AITranslator::resolve_generation_params(&config.provider_id, &model, &config.generation)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
 }
 
 fn mask_api_key(api_key: &str) -> String {
@@ -74,6 +100,7 @@ pub async fn add_ai_config(config: AIConfig) -> Result<(), String> {
     if config.api_key.trim().is_empty() {
         return Err("API Key 不能为空".to_string());
     }
+    validate_generation(&config)?;
 
     crate::app_log!(
         "[AI配置] 添加新配置: provider={:?}, url={}, model={}, key={}",
@@ -102,6 +129,7 @@ pub async fn add_ai_config(config: AIConfig) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn update_ai_config(index: usize, config: AIConfig) -> Result<(), String> {
+    validate_generation(&config)?;
     let draft = ConfigDraft::global().await;
 
     // 修复死锁：在独立作用域内获取写锁
@@ -188,6 +216,27 @@ pub async fn set_active_ai_config(index: usize) -> Result<(), String> {
     Ok(())
 }
 
+/// 设置备用供应商链（`ai_configs` 索引，按顺序尝试；空列表表示不使用备用供应商）
+#[tauri::command]
+pub async fn set_fallback_ai_configs(indices: Vec<usize>) -> Result<(), String> {
+    crate::app_log!("[AI配置] 设置备用供应商: {:?}", indices);
+
+    let draft = ConfigDraft::global().await;
+    {
+        let mut draft_config = draft.draft();
+        draft_config.set_fallback_ai_configs(indices).map_err(|e| {
+            crate::app_log!("[AI配置] 设置备用供应商失败: {}", e);
+            e.to_string()
+        })?;
+    }
+
+    draft.apply().map_err(|e| {
+        crate::app_log!("[AI配置] 保存配置失败: {}", e);
+        e.to_string()
+    })?;
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")] // 序列化时使用 camelCase 命名，与前端保持一致
 pub struct TestConnectionRequest {
@@ -196,6 +245,8 @@ pub struct TestConnectionRequest {
     pub base_url: Option<String>,
     pub model: Option<String>,
     pub proxy: Option<crate::services::ProxyConfig>,
+    #[serde(default)]
+    pub generation: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -234,6 +285,7 @@ pub async fn test_ai_connection(
         base_url: request.base_url,
         model: request.model,
         proxy: request.proxy,
+        generation: request.generation,
     };
 
     let start = Instant::now();
@@ -243,7 +295,8 @@ pub async fn test_ai_connection(
             let test_text = "The answer to life, universe and everything?";
 
             let user_prompt = translator.build_user_prompt(&[test_text.to_string()]);
-            let request_json = serde_json::json!({
+            let generation = translator.generation_params();
+            let mut request_json = serde_json::json!({
                 "model": ai_config.model,
                 "messages": [
                     {
@@ -255,8 +308,16 @@ pub async fn test_ai_connection(
                         "content": user_prompt
                     }
                 ],
-                "temperature": 1.0
+                "temperature": generation.temperature,
+                "top_p": generation.top_p,
+                "max_tokens": generation.max_tokens,
             });
+            // 与实际请求一致：extra_body 合并到请求体顶层
+            if let serde_json::Value::Object(map) = &mut request_json {
+                for (key, value) in &generation.extra_body {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
 
             let full_prompt = format!(
                 "【真实AI请求】:\n{}",
@@ -288,6 +349,12 @@ pub async fn test_ai_connection(
                     {
                         let response = format!("测试成功 ({}ms)\n结果: {}", elapsed, results[0]);
                         crate::services::update_prompt_response(last_idx, response);
+                        if let Some(reasoning) = translator.last_reasoning() {
+                            crate::services::update_prompt_reasoning(
+                                last_idx,
+                                reasoning.to_string(),
+                            );
+                        }
                     }
 
                     Ok(TestConnectionResult {
@@ -1,3 +1,4 @@
+use crate::services::ai::cost_calculator::TranslationEstimate;
 use crate::services::ai::provider::with_global_registry;
 use crate::services::ai::{CostBreakdown, CostCalculator, ModelInfo, ProviderInfo};
 
@@ -24,17 +25,23 @@ pub fn get_model_info(provider_id: String, model_id: String) -> Result<Option<Mo
     })
 }
 
+/// 快速估算成本：原文按模型声明的分词器计数（不含提示词）
 #[tauri::command]
 pub fn estimate_translation_cost(
     provider_id: String,
     model_id: String,
-    char_count: usize,
+    texts: Vec<String>,
+    target_language: Option<String>,
     cache_hit_rate: Option<f64>,
 ) -> Result<f64, String> {
-    let model = with_global_registry(|registry| {
+    let (model, tokenizer) = with_global_registry(|registry| {
         registry
             .get_provider(&provider_id)
-            .and_then(|provider| provider.get_model_info(&model_id))
+            .and_then(|provider| {
+                provider
+                    .get_model_info(&model_id)
+                    .map(|model| (model, provider.tokenizer(&model_id)))
+            })
             .ok_or_else(|| format!("未找到模型: {} (供应商: {})", model_id, provider_id))
     })?;
 
@@ -44,7 +51,59 @@ pub fn estimate_translation_cost(
     }
 
     Ok(CostCalculator::estimate_batch_cost(
-        &model, char_count, hit_rate,
+        &model,
+        tokenizer,
+        &texts,
+        target_language.as_deref(),
+        hit_rate,
+    ))
+}
+
+/// 翻译开始前的精确预估：按模型声明的分词器计算系统提示词与各批用户提示词的 token
+#[tauri::command]
+pub async fn estimate_translation_tokens(
+    provider_id: String,
+    model_id: String,
+    texts: Vec<String>,
+    target_language: Option<String>,
+    cache_hit_rate: Option<f64>,
+) -> Result<TranslationEstimate, String> {
+    let (model, tokenizer) = with_global_registry(|registry| {
+        registry
+            .get_provider(&provider_id)
+            .and_then(|provider| {
+                provider
+                    .get_model_info(&model_id)
+                    .map(|model| (model, provider.tokenizer(&model_id)))
+            })
+            .ok_or_else(|| format!("未找到模型: {} (供应商: {})", model_id, provider_id))
+    })?;
+
+    let hit_rate = cache_hit_rate.unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&hit_rate) {
+        return Err("缓存命中率必须在 0.0-1.0 之间".to_string());
+    }
+
+    // 与翻译器相同的方式构建系统提示词（自定义提示词 + 术语库风格总结）
+    let custom_prompt = {
+        let draft = crate::services::ConfigDraft::global().await;
+        draft.data().system_prompt.clone()
+    };
+    let term_library = crate::services::tm_store::shared()
+        .and_then(|store| store.load_term_library())
+        .ok();
+    let system_prompt = crate::services::prompt_builder::build_system_prompt(
+        custom_prompt.as_deref(),
+        term_library.as_ref(),
+    );
+
+    Ok(CostCalculator::estimate_translation(
+        &model,
+        tokenizer,
+        &system_prompt,
+        &texts,
+        target_language.as_deref(),
+        hit_rate,
     ))
 }
 
@@ -149,14 +208,15 @@ mod tests {
         let cost = estimate_translation_cost(
             "openai".to_string(),
             "gpt-4o-mini".to_string(),
-            10000,
+            vec!["Open the selected file".to_string(); 500],
+            Some("zh-Hans".to_string()),
             Some(0.3),
         );
 
         assert!(cost.is_ok());
         let cost_value = cost.unwrap();
         assert!(cost_value > 0.0);
-        assert!(cost_value < 1.0); // 10000字符不应超过1美元
+        assert!(cost_value < 1.0); // 500 条短句不应超过1美元
     }
 
     #[tokio::test]
@@ -189,7 +249,8 @@ mod tests {
         let cost = estimate_translation_cost(
             "openai".to_string(),
             "gpt-4o-mini".to_string(),
-            10000,
+            vec!["Open".to_string()],
+            None,
             Some(1.5), // 无效：超过1.0
         );
 
@@ -209,7 +270,8 @@ mod tests {
         let cost = estimate_translation_cost(
             "openai".to_string(),
             "nonexistent-model".to_string(),
-            10000,
+            vec!["Open".to_string()],
+            None,
             None,
         );
         assert!(cost.is_err());
@@ -2,16 +2,32 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tauri::Emitter;
 
+use crate::services::ai::currency;
+use crate::services::budget::{self, PausedEntry, PausedJob};
+use crate::services::catalog_alignment::{self, CatalogFilePair, CatalogImportReport};
+use crate::services::response_cache;
+use crate::services::segment_memory::{SegmentApproval, SegmentMemoryStats};
+use crate::services::tm_maintenance::{
+    self, TmCleanupOptions, TmCleanupReport, TmDuplicateGroup, TmRecordRef, TmReplaceReport,
+    TmReplaceRequest, TmSearchPage, TmSearchQuery, TmUsageReport,
+};
+use crate::services::tm_registry::{self, MemoryInfo, MemoryStack};
+use crate::services::tm_store::{self, TmStore};
+use crate::services::tmx::{self, TmxImportReport};
+use crate::services::translation_memory::TmKey;
+use crate::services::usage_ledger::{self, UsageAggregate, UsageContext, UsageGroupBy};
 use crate::services::{
-    AITranslator, BatchTranslator, ConfigDraft, POParser, TermLibrary, TranslationMemory,
-    TranslationReport,
+    AITranslator, BatchTranslator, ConfigDraft, POParser, SegmentMemory, TermLibrary,
+    TranslationMemory, TranslationReport,
 };
 use crate::utils::path_validator::SafePathValidator;
-use crate::utils::paths::get_translation_memory_path;
 
 #[cfg(feature = "ts-rs")]
 use ts_rs::TS;
 
+/// 切换到备用供应商后，经过这段冷却时间，下一批重新从主供应商开始尝试
+const PRIMARY_RETRY_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(60);
+
 async fn get_custom_system_prompt() -> Option<String> {
     let draft = ConfigDraft::global().await;
     let config = draft.data();
@@ -61,6 +77,9 @@ pub struct TranslationStats {
     pub ai_translated: usize,
     pub token_stats: crate::services::TokenStats,
     pub tm_learned: usize,
+    /// 响应缓存命中数（未付费）
+    #[serde(default)]
+    pub cache_hits: usize,
 }
 
 // TranslationReport 已从 services 模块导入
@@ -84,22 +103,77 @@ pub struct ContextualRefineRequest {
     pub next_entry: Option<String>,
 }
 
+/// 批量翻译的记忆库查询上下文
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmQueryContext {
+    /// 源语言（参与记忆库键）
+    #[serde(default)]
+    pub source_language: Option<String>,
+    /// 与待翻译文本按下标对应的 msgctxt
+    #[serde(default)]
+    pub contexts: Vec<Option<String>>,
+    /// 可写的项目记忆库（未指定时为默认记忆库）
+    #[serde(default)]
+    pub project_memory: Option<String>,
+    /// 只读参考记忆库（按优先级排列）
+    #[serde(default)]
+    pub reference_memories: Vec<String>,
+}
+
 // TokenStats 已从 services 模块导入
 
-fn auto_save_translation_memory(translator: &AITranslator) {
-    if let Some(tm) = translator.get_translation_memory() {
-        let tm_path = get_translation_memory_path().to_string_lossy().to_string();
-        if let Some(parent) = std::path::Path::new(&tm_path).parent() {
-            let _ = std::fs::create_dir_all(parent);
+/// 把翻译器新学习的短语与句段增量写入其项目记忆库
+pub(crate) fn auto_save_translation_memory(translator: &mut AITranslator) {
+    let store = match tm_registry::open(translator.memory_name()) {
+        Ok(store) => store,
+        Err(e) => {
+            log::error!("[TM] 打开记忆库失败: {}", e);
+            return;
         }
-        if let Err(e) = tm.save_to_file(&tm_path) {
+    };
+    if let Some(tm) = translator.get_translation_memory_mut() {
+        if let Err(e) = tm.save_to_store(&store) {
             log::error!("[TM] 保存失败: {}", e);
         }
     }
+    if let Some(segments) = translator.get_segment_memory_mut() {
+        if let Err(e) = segments.save_to_store(&store) {
+            log::error!("[TM句段] 保存失败: {}", e);
+        }
+    }
+    evict_over_capacity(&store);
+}
+
+/// 写入记忆库后按容量配置淘汰超出的条目（失败只记录日志）
+fn evict_over_capacity(store: &TmStore) {
+    if let Err(e) = tm_maintenance::evict(store, &tm_maintenance::capacity()) {
+        log::error!("[TM] 按容量淘汰失败: {}", e);
+    }
+}
+
+fn open_tm_store() -> Result<std::sync::Arc<TmStore>, String> {
+    tm_store::shared().map_err(|e| format!("打开记忆库失败: {}", e))
+}
+
+/// 打开指定的记忆库（未指定时为默认记忆库）
+fn open_memory(memory: Option<&str>) -> Result<std::sync::Arc<TmStore>, String> {
+    tm_registry::open(tm_registry::resolve_name(memory))
+        .map_err(|e| format!("打开记忆库失败: {}", e))
+}
+
+fn load_term_library() -> Result<TermLibrary, String> {
+    open_tm_store()?
+        .load_term_library()
+        .map_err(|e| format!("加载术语库失败: {}", e))
 }
 
-fn save_term_library(library: &TermLibrary, path: &std::path::PathBuf) -> Result<(), String> {
-    library.save_to_file(path).map_err(|e| e.to_string())
+fn save_term_library(library: &TermLibrary) -> Result<(), String> {
+    open_tm_store()?
+        .save_term_library(library)
+        .map_err(|e| format!("保存术语库失败: {}", e))
 }
 
 #[tauri::command]
@@ -119,13 +193,16 @@ pub fn parse_po_file(file_path: String) -> Result<Vec<POEntry>, String> {
         .map_err(|e| e.to_string())
 }
 
+/// 翻译单条文本（记忆库组合与批量翻译相同：学习结果写入项目记忆库，参考记忆库只读）
 #[tauri::command]
 pub async fn translate_entry(
     app_handle: tauri::AppHandle,
     text: String,
     target_language: Option<String>,
+    project_memory: Option<String>,
+    reference_memories: Option<Vec<String>>,
 ) -> Result<String, String> {
-    let mut translator = {
+    let (mut translator, budget_config) = {
         let draft = ConfigDraft::global().await;
         let config = draft.data();
         let ai_config = config
@@ -134,16 +211,41 @@ pub async fn translate_entry(
             .clone();
 
         let custom_prompt = config.system_prompt.clone();
-        AITranslator::new_with_config(ai_config, true, custom_prompt.as_deref(), target_language)
-            .map_err(|e| format!("AI翻译器初始化失败: {}", e))?
+        let translator = AITranslator::new_with_config(
+            ai_config,
+            true,
+            custom_prompt.as_deref(),
+            target_language,
+        )
+        .map_err(|e| format!("AI翻译器初始化失败: {}", e))?;
+        (translator, config.budget.clone())
     };
+    let memory_stack = MemoryStack::open(
+        project_memory.as_deref(),
+        &reference_memories.unwrap_or_default(),
+    )
+    .map_err(|e| format!("加载记忆库失败: {}", e))?;
+    translator
+        .set_memory_stack(&memory_stack)
+        .map_err(|e| format!("加载记忆库失败: {}", e))?;
 
-    let result = translator
-        .translate_batch(vec![text], None)
-        .await
-        .map_err(|e| e.to_string())?;
+    // 每日/每月上限与批量翻译共用花费台账，单条翻译按预估花费检查
+    let estimated_cost = translator
+        .estimate_cost(std::slice::from_ref(&text))
+        .unwrap_or(0.0);
+    let mut budget_tracker =
+        budget::BudgetTracker::new(budget_config, "entry", 0.0, budget::shared_ledger())
+            .with_estimated_batch_cost(estimated_cost);
+    budget_tracker
+        .check_next_batch()
+        .map_err(|e| budget_error_message(e, "未翻译该条目"))?;
 
-    auto_save_translation_memory(&translator);
+    let result = translator.translate_batch(vec![text], None).await;
+    let warnings = budget_tracker.record_batch(translator.get_token_stats().cost);
+    emit_budget_warnings(&app_handle, warnings);
+    let result = result.map_err(|e| e.to_string())?;
+
+    auto_save_translation_memory(&mut translator);
 
     let batch_stats = &translator.batch_stats;
     let token_stats = translator.get_token_stats();
@@ -154,6 +256,7 @@ pub async fn translate_entry(
             "deduplicated": batch_stats.deduplicated,
             "ai_translated": batch_stats.ai_translated,
             "tm_learned": batch_stats.tm_learned,
+            "cache_hits": batch_stats.cache_hits,
             "token_stats": {
                 "input_tokens": token_stats.input_tokens,
                 "output_tokens": token_stats.output_tokens,
@@ -173,22 +276,31 @@ pub async fn translate_entry(
 #[derive(Debug, Serialize)]
 pub struct TranslationResult {
     pub translation: String,
-    pub source: String, // 'tm', 'dedup', 'ai'
+    pub source: String, // 'tm', 'fuzzy', 'dedup', 'ai'
 }
 
 #[derive(Debug, Serialize)]
 pub struct BatchResult {
     pub translations: Vec<String>,
-    pub translation_sources: Vec<String>, // 每个翻译的来源：'tm', 'dedup', 'ai'
+    pub translation_sources: Vec<String>, // 每个翻译的来源：'tm', 'fuzzy', 'dedup', 'ai'（命名记忆库为 'tm:<名称>'）
     pub stats: TranslationStats,
 }
 
+/// 产生译文的供应商与模型
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationProvider {
+    pub provider_id: String,
+    pub model: String,
+}
+
 /// 带任务 ID 的批量翻译结果
 #[derive(Debug, Serialize)]
 pub struct BatchResultWithTaskId {
     pub task_id: u64,
     pub translations: Vec<String>,
-    pub translation_sources: Vec<String>, // 每个翻译的来源：'tm', 'dedup', 'ai'
+    pub translation_sources: Vec<String>, // 每个翻译的来源：'tm', 'fuzzy', 'dedup', 'ai'（命名记忆库为 'tm:<名称>'）
+    /// 与 translation_sources 一一对应：'ai' 条目记录实际使用的供应商/模型（含备用切换），其余为 null
+    pub translation_providers: Vec<Option<TranslationProvider>>,
     pub stats: TranslationStats,
 }
 
@@ -212,9 +324,9 @@ pub fn cancel_all_translations() -> Result<usize, String> {
 // 统一使用 translate_batch_with_channel (Channel API)
 
 #[tauri::command]
-pub fn get_translation_memory() -> Result<TranslationMemory, String> {
-    let memory_path = get_translation_memory_path().to_string_lossy().to_string();
-    TranslationMemory::new_from_file(memory_path).map_err(|e| {
+pub fn get_translation_memory(memory_name: Option<String>) -> Result<TranslationMemory, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    TranslationMemory::load_from_store(&store).map_err(|e| {
         log::error!("[TM] 加载记忆库失败: {}", e);
         format!("加载记忆库失败: {}", e)
     })
@@ -228,27 +340,25 @@ pub fn get_builtin_phrases() -> Result<serde_json::Value, String> {
 }
 
 #[tauri::command]
-pub fn merge_builtin_phrases() -> Result<usize, String> {
-    use crate::services::translation_memory::{TranslationMemory, get_builtin_memory};
-    use crate::utils::paths::get_translation_memory_path;
-
-    let memory_path = get_translation_memory_path();
-    let mut tm = TranslationMemory::new_from_file(&memory_path)
-        .map_err(|e| format!("加载记忆库失败: {}", e))?;
+pub fn merge_builtin_phrases(memory_name: Option<String>) -> Result<usize, String> {
+    use crate::services::translation_memory::get_builtin_memory;
 
+    let store = open_memory(memory_name.as_deref())?;
     let builtin = get_builtin_memory();
     let builtin_count = builtin.len();
 
-    let mut added_count = 0;
-    for (source, target) in builtin {
-        if !tm.memory.contains_key(&source) {
-            tm.memory.insert(source, target);
-            added_count += 1;
+    let mut missing = Vec::new();
+    for (source, target) in &builtin {
+        let exists = store
+            .phrase(source)
+            .map_err(|e| format!("加载记忆库失败: {}", e))?
+            .is_some();
+        if !exists {
+            missing.push((source.as_str(), target.as_str()));
         }
     }
-
-    tm.stats.total_entries = tm.memory.len();
-    tm.save_all(&memory_path)
+    let added_count = store
+        .upsert_pinned_phrases(missing)
         .map_err(|e| format!("保存记忆库失败: {}", e))?;
 
     crate::app_log!(
@@ -261,14 +371,282 @@ pub fn merge_builtin_phrases() -> Result<usize, String> {
 }
 
 #[tauri::command]
-pub fn save_translation_memory(memory: TranslationMemory) -> Result<(), String> {
-    let memory_path = get_translation_memory_path().to_string_lossy().to_string();
+pub fn save_translation_memory(
+    memory: TranslationMemory,
+    memory_name: Option<String>,
+) -> Result<(), String> {
+    open_memory(memory_name.as_deref())?
+        .replace_phrases(&memory.memory)
+        .map_err(|e| format!("保存记忆库失败: {}", e))?;
+    crate::app_log!("[TM] 保存记忆库: {} 条记录", memory.memory.len());
+    Ok(())
+}
+
+/// 记录用户确认的译文到句段级记忆库（保存文件时调用），返回新增或更新的条数
+#[tauri::command]
+pub fn approve_tm_segments(
+    segments: Vec<SegmentApproval>,
+    target_language: String,
+    source_language: Option<String>,
+    file: Option<String>,
+    memory_name: Option<String>,
+) -> Result<usize, String> {
+    // 只按索引取出涉及的句段，不加载整个句段库
+    let store = open_memory(memory_name.as_deref())?;
+    let mut existing = Vec::new();
+    for approval in &segments {
+        let key = TmKey::new(&approval.source, Some(&target_language))
+            .with_source_lang(source_language.as_deref())
+            .with_context(approval.context.as_deref());
+        if let Some(entry) = store
+            .segment(&key)
+            .map_err(|e| format!("加载句段记忆库失败: {}", e))?
+        {
+            existing.push(entry);
+        }
+    }
+    let mut memory = SegmentMemory::from_entries(existing);
+    let changed = memory.approve(
+        &segments,
+        source_language.as_deref(),
+        &target_language,
+        file.as_deref(),
+    );
+    if changed > 0 {
+        memory
+            .save_to_store(&store)
+            .map_err(|e| format!("保存句段记忆库失败: {}", e))?;
+        crate::app_log!("[TM句段] 记录已确认译文 {} 条", changed);
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub fn get_segment_memory_stats(memory_name: Option<String>) -> Result<SegmentMemoryStats, String> {
+    open_memory(memory_name.as_deref())?
+        .segment_stats()
+        .map_err(|e| format!("加载句段记忆库失败: {}", e))
+}
+
+/// 导入 TMX 到短语表与句段库
+#[tauri::command]
+pub fn import_tmx(
+    file_path: String,
+    memory_name: Option<String>,
+) -> Result<TmxImportReport, String> {
+    let safe_path = SafePathValidator::new()
+        .validate_file_path(&file_path)
+        .map_err(|e| format!("路径验证失败: {}", e))?;
+    let content =
+        std::fs::read_to_string(&safe_path).map_err(|e| format!("读取 TMX 失败: {}", e))?;
+
+    let store = open_memory(memory_name.as_deref())?;
+    let mut tm =
+        TranslationMemory::load_from_store(&store).map_err(|e| format!("加载记忆库失败: {}", e))?;
+    let mut segments =
+        SegmentMemory::load_from_store(&store).map_err(|e| format!("加载句段记忆库失败: {}", e))?;
+
+    let report = tmx::import_tmx(&content, &mut tm, &mut segments).map_err(|e| e.to_string())?;
+    tm.save_imported_to_store(&store)
+        .map_err(|e| format!("保存记忆库失败: {}", e))?;
+    segments
+        .save_to_store(&store)
+        .map_err(|e| format!("保存句段记忆库失败: {}", e))?;
+    evict_over_capacity(&store);
+    Ok(report)
+}
+
+/// 导出短语表与句段库为 TMX 1.4b，返回导出的译文单元数
+#[tauri::command]
+pub fn export_tmx(
+    file_path: String,
+    source_language: Option<String>,
+    memory_name: Option<String>,
+) -> Result<usize, String> {
+    let path = std::path::PathBuf::from(&file_path);
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tmx"))
+    {
+        return Err(format!("导出文件必须为 .tmx: {}", file_path));
+    }
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("无效的导出路径: {}", file_path));
+    };
+    let parent = SafePathValidator::new()
+        .validate_dir_path(&parent.to_string_lossy())
+        .map_err(|e| format!("路径验证失败: {}", e))?;
+
+    let store = open_memory(memory_name.as_deref())?;
+    let tm =
+        TranslationMemory::load_from_store(&store).map_err(|e| format!("加载记忆库失败: {}", e))?;
+    let segments =
+        SegmentMemory::load_from_store(&store).map_err(|e| format!("加载句段记忆库失败: {}", e))?;
+    let source_language = source_language
+        .filter(|lang| !lang.trim().is_empty())
+        .unwrap_or_else(|| "en".to_string());
+    let (xml, count) = tmx::export_tmx(&tm, Some(&segments), &source_language);
+    std::fs::write(parent.join(file_name), xml).map_err(|e| format!("导出 TMX 失败: {}", e))?;
+    crate::app_log!("[TMX] 导出 {} 个译文单元: {}", count, file_path);
+    Ok(count)
+}
+
+/// 对齐已翻译的历史文件（目录中的 PO/JSON，或显式指定的 JSON 文件对）并导入记忆库
+///
+/// 同一原文存在不同译文或与记忆库已有译文不同时不写入，记入返回的冲突报告
+#[tauri::command]
+pub async fn import_translated_catalogs(
+    directory: Option<String>,
+    pairs: Option<Vec<CatalogFilePair>>,
+    source_language: Option<String>,
+    target_language: Option<String>,
+    memory_name: Option<String>,
+) -> Result<CatalogImportReport, String> {
+    // 遍历目录、解析文件与写入记忆库都是阻塞操作，放到阻塞线程池执行，避免界面卡顿
+    tokio::task::spawn_blocking(move || {
+        import_catalogs_blocking(
+            directory,
+            pairs,
+            source_language,
+            target_language,
+            memory_name,
+        )
+    })
+    .await
+    .map_err(|e| format!("导入任务执行失败: {}", e))?
+}
 
-    if let Some(parent) = std::path::Path::new(&memory_path).parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+fn import_catalogs_blocking(
+    directory: Option<String>,
+    pairs: Option<Vec<CatalogFilePair>>,
+    source_language: Option<String>,
+    target_language: Option<String>,
+    memory_name: Option<String>,
+) -> Result<CatalogImportReport, String> {
+    let validator = SafePathValidator::new();
+    let source_language = source_language.filter(|lang| !lang.trim().is_empty());
+    let target_language = target_language.filter(|lang| !lang.trim().is_empty());
+    let mut report = CatalogImportReport::default();
+    let mut aligned = Vec::new();
+
+    if let Some(directory) = directory {
+        let dir = validator
+            .validate_dir_path(&directory)
+            .map_err(|e| format!("路径验证失败: {}", e))?;
+        aligned.extend(
+            catalog_alignment::collect_from_directory(
+                &dir,
+                source_language.as_deref(),
+                target_language.as_deref(),
+                &mut report,
+            )
+            .map_err(|e| format!("扫描目录失败: {}", e))?,
+        );
     }
+    let mut file_pairs = pairs.unwrap_or_default();
+    for pair in &mut file_pairs {
+        for path in [&mut pair.source, &mut pair.target] {
+            *path = validator
+                .validate_file_path(path)
+                .map_err(|e| format!("路径验证失败: {}", e))?
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+    aligned.extend(catalog_alignment::collect_from_pairs(
+        &file_pairs,
+        target_language.as_deref(),
+        &mut report,
+    ));
+
+    let store = open_memory(memory_name.as_deref())?;
+    catalog_alignment::import_pairs(aligned, source_language.as_deref(), &store, &mut report)
+        .map_err(|e| format!("写入记忆库失败: {}", e))?;
+    evict_over_capacity(&store);
+    Ok(report)
+}
 
-    memory.save_to_file(memory_path).map_err(|e| e.to_string())
+/// 列出记忆库（默认记忆库在前）
+#[tauri::command]
+pub fn list_translation_memories() -> Result<Vec<MemoryInfo>, String> {
+    tm_registry::list().map_err(|e| format!("加载记忆库列表失败: {}", e))
+}
+
+/// 新建命名记忆库（项目记忆库或参考记忆库）
+#[tauri::command]
+pub fn create_translation_memory(name: String) -> Result<(), String> {
+    tm_registry::create(name.trim()).map_err(|e| e.to_string())
+}
+
+/// 删除命名记忆库（默认记忆库不可删除）
+#[tauri::command]
+pub fn delete_translation_memory(name: String) -> Result<(), String> {
+    tm_registry::delete(name.trim()).map_err(|e| e.to_string())
+}
+
+/// 分页检索记忆库（短语与句段）
+#[tauri::command]
+pub fn search_translation_memory(
+    query: TmSearchQuery,
+    memory_name: Option<String>,
+) -> Result<TmSearchPage, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    tm_maintenance::search(&store, &query).map_err(|e| format!("检索记忆库失败: {}", e))
+}
+
+/// 批量查找替换译文（`dry_run` 时只返回预览）
+#[tauri::command]
+pub fn replace_in_translation_memory(
+    request: TmReplaceRequest,
+    memory_name: Option<String>,
+) -> Result<TmReplaceReport, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    tm_maintenance::replace_targets(&store, &request).map_err(|e| format!("批量替换失败: {}", e))
+}
+
+/// 删除译文与原文相同或含未翻译占位标记的条目
+#[tauri::command]
+pub fn cleanup_translation_memory(
+    options: TmCleanupOptions,
+    memory_name: Option<String>,
+) -> Result<TmCleanupReport, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    tm_maintenance::cleanup(&store, &options).map_err(|e| format!("清理记忆库失败: {}", e))
+}
+
+/// 查找重复与冲突条目（最多 500 组）
+#[tauri::command]
+pub fn find_translation_memory_duplicates(
+    target_language: Option<String>,
+    memory_name: Option<String>,
+) -> Result<Vec<TmDuplicateGroup>, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    let target_language = target_language.filter(|lang| !lang.trim().is_empty());
+    tm_maintenance::find_duplicates(&store, target_language.as_deref(), 500)
+        .map_err(|e| format!("查找重复条目失败: {}", e))
+}
+
+/// 命中最多与从未命中的条目（各最多 `limit` 条，默认 50）
+#[tauri::command]
+pub fn get_translation_memory_usage(
+    limit: Option<usize>,
+    memory_name: Option<String>,
+) -> Result<TmUsageReport, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    tm_maintenance::usage_report(&store, limit.unwrap_or(50).clamp(1, 1000))
+        .map_err(|e| format!("统计记忆库使用情况失败: {}", e))
+}
+
+/// 删除指定的记忆库条目，返回删除条数
+#[tauri::command]
+pub fn delete_translation_memory_entries(
+    records: Vec<TmRecordRef>,
+    memory_name: Option<String>,
+) -> Result<usize, String> {
+    let store = open_memory(memory_name.as_deref())?;
+    store
+        .delete_records(&records)
+        .map_err(|e| format!("删除记忆库条目失败: {}", e))
 }
 
 #[tauri::command]
@@ -359,11 +737,23 @@ pub async fn update_app_config(config: serde_json::Value) -> Result<(), String>
     let draft = ConfigDraft::global().await;
     let mut draft_config = draft.draft();
 
+    // 只改显示货币时，预算上限随之换算（同时提交的上限按新货币理解）
+    let sets_budget = config.get("budget").is_some();
     let mut current_config = serde_json::to_value(&**draft_config).map_err(|e| e.to_string())?;
     merge_json(&mut current_config, config);
 
-    let app_config: crate::services::AppConfig =
+    let mut app_config: crate::services::AppConfig =
         serde_json::from_value(current_config).map_err(|e| e.to_string())?;
+    app_config
+        .fuzzy_match
+        .validate()
+        .map_err(|e| format!("模糊匹配配置无效: {}", e))?;
+    if app_config.currency != draft_config.currency && !sets_budget {
+        app_config
+            .budget
+            .convert_currency(&draft_config.currency, &app_config.currency)
+            .map_err(|e| format!("预算上限换算失败: {}", e))?;
+    }
 
     **draft_config = app_config;
     drop(draft_config);
@@ -384,6 +774,10 @@ pub fn validate_config(config: serde_json::Value) -> Result<bool, String> {
             return Err(format!("AI 配置 {} 缺少 API Key", idx));
         }
     }
+    app_config
+        .fuzzy_match
+        .validate()
+        .map_err(|e| format!("模糊匹配配置无效: {}", e))?;
 
     Ok(true)
 }
@@ -581,8 +975,7 @@ pub fn get_frontend_logs() -> Result<Vec<String>, String> {
 /// 获取术语库
 #[tauri::command]
 pub fn get_term_library() -> Result<TermLibrary, String> {
-    let path = crate::utils::paths::get_term_library_path();
-    TermLibrary::load_from_file(path).map_err(|e| e.to_string())
+    load_term_library()
 }
 
 /// 添加术语到术语库
@@ -593,14 +986,13 @@ pub fn add_term_to_library(
     ai_translation: String,
     context: Option<String>,
 ) -> Result<(), String> {
-    let path = crate::utils::paths::get_term_library_path();
-    let mut library = TermLibrary::load_from_file(&path).map_err(|e| e.to_string())?;
+    let mut library = load_term_library()?;
 
     library
         .add_term(source, user_translation, ai_translation, context)
         .map_err(|e| e.to_string())?;
 
-    save_term_library(&library, &path)?;
+    save_term_library(&library)?;
 
     Ok(())
 }
@@ -608,21 +1000,19 @@ pub fn add_term_to_library(
 /// 从术语库删除术语
 #[tauri::command]
 pub fn remove_term_from_library(source: String) -> Result<(), String> {
-    let path = crate::utils::paths::get_term_library_path();
-    let mut library = TermLibrary::load_from_file(&path).map_err(|e| e.to_string())?;
+    let mut library = load_term_library()?;
 
     library.remove_term(&source).map_err(|e| e.to_string())?;
 
-    save_term_library(&library, &path)?;
+    save_term_library(&library)?;
 
     Ok(())
 }
 
 /// 生成风格总结（调用AI）
 #[tauri::command]
-pub async fn generate_style_summary() -> Result<String, String> {
-    let path = crate::utils::paths::get_term_library_path();
-    let mut library = TermLibrary::load_from_file(&path).map_err(|e| e.to_string())?;
+pub async fn generate_style_summary(app: tauri::AppHandle) -> Result<String, String> {
+    let mut library = load_term_library()?;
 
     if library.terms.is_empty() {
         crate::app_log!("[风格总结] 术语库为空，无法生成");
@@ -632,13 +1022,14 @@ pub async fn generate_style_summary() -> Result<String, String> {
     crate::app_log!("[风格总结] 开始生成，基于 {} 条术语", library.terms.len());
 
     // 获取当前活动的 AI 配置（克隆后释放锁，避免跨越 .await）
-    let active_config = {
+    let (active_config, budget_config) = {
         let draft = ConfigDraft::global().await;
         let config_guard = draft.data();
-        config_guard
+        let active_config = config_guard
             .get_active_ai_config()
             .cloned() // 克隆配置
-            .ok_or_else(|| "未找到活动的AI配置".to_string())?
+            .ok_or_else(|| "未找到活动的AI配置".to_string())?;
+        (active_config, config_guard.budget.clone())
     }; // config_guard 在此释放
 
     crate::app_log!(
@@ -671,18 +1062,34 @@ pub async fn generate_style_summary() -> Result<String, String> {
     });
     crate::services::log_prompt("风格分析", analysis_prompt.clone(), Some(metadata));
 
+    // 与单条翻译相同，按预估花费检查每日/每月上限
+    let estimated_cost = translator
+        .estimate_cost(std::slice::from_ref(&analysis_prompt))
+        .unwrap_or(0.0);
+    let mut budget_tracker =
+        budget::BudgetTracker::new(budget_config, "style_summary", 0.0, budget::shared_ledger())
+            .with_estimated_batch_cost(estimated_cost);
+    budget_tracker
+        .check_next_batch()
+        .map_err(|e| budget_error_message(e, "未生成风格总结"))?;
+
     let summary = translator
         .translate_with_custom_user_prompt(analysis_prompt.clone())
-        .await
-        .map_err(|e| {
-            crate::app_log!("[风格总结] AI调用失败: {}", e);
-            e.to_string()
-        })?;
+        .await;
+    let warnings = budget_tracker.record_batch(translator.get_token_stats().cost);
+    emit_budget_warnings(&app, warnings);
+    let summary = summary.map_err(|e| {
+        crate::app_log!("[风格总结] AI调用失败: {}", e);
+        e.to_string()
+    })?;
 
     // 更新提示词日志的响应
     let logs = crate::services::get_prompt_logs();
     if let Some(last_idx) = logs.len().checked_sub(1) {
         crate::services::update_prompt_response(last_idx, summary.clone());
+        if let Some(reasoning) = translator.last_reasoning() {
+            crate::services::update_prompt_reasoning(last_idx, reasoning.to_string());
+        }
     }
 
     crate::app_log!("[风格总结] AI生成成功，总结长度: {} 字符", summary.len());
@@ -731,7 +1138,7 @@ pub async fn generate_style_summary() -> Result<String, String> {
 
     // 更新术语库（使用清理后的内容）
     library.update_style_summary(cleaned_summary.clone());
-    save_term_library(&library, &path)?;
+    save_term_library(&library)?;
 
     crate::app_log!(
         "[风格总结] 风格总结已保存 (v{})",
@@ -837,7 +1244,7 @@ pub async fn contextual_refine(
     }
 
     // 1-4. 获取配置并创建翻译器（在单独的作用域中以释放guard）
-    let mut translator = {
+    let (mut translator, budget_config) = {
         let draft = ConfigDraft::global().await;
         let config = draft.data();
 
@@ -848,7 +1255,7 @@ pub async fn contextual_refine(
 
         let custom_prompt = config.system_prompt.clone();
 
-        AITranslator::new_with_config(
+        let translator = AITranslator::new_with_config(
             ai_config,
             false, // 绕过翻译记忆库
             custom_prompt.as_deref(),
@@ -857,7 +1264,8 @@ pub async fn contextual_refine(
         .map_err(|e| {
             crate::app_log!("[精翻] 创建翻译器失败: {}", e);
             format!("AI翻译器初始化失败: {}", e)
-        })?
+        })?;
+        (translator, config.budget.clone())
     };
 
     crate::app_log!("[精翻] 翻译器已创建（已绕过TM）");
@@ -870,6 +1278,12 @@ pub async fn contextual_refine(
 
     crate::app_log!("[精翻] 已构建 {} 条精翻提示词", prompts.len());
 
+    // 每条精翻是一次付费请求：与批量任务相同，逐条按预计花费检查预算
+    let estimated_cost = translator.estimate_cost(&prompts).unwrap_or(0.0) / prompts.len() as f64;
+    let mut budget_tracker =
+        budget::BudgetTracker::new(budget_config, "refine", 0.0, budget::shared_ledger())
+            .with_estimated_batch_cost(estimated_cost);
+
     // 6. 发送进度事件：开始
     let _ = app.emit(
         "refine:start",
@@ -894,21 +1308,34 @@ pub async fn contextual_refine(
             "index": idx,
             "msgid": requests.get(idx).map(|r| &r.msgid),
             "target_language": &target_language,
-            "model": "auto",
-            "temperature": 1.0,
+            "model": translator.model(),
+            "temperature": translator.generation_params().temperature,
         });
         crate::services::log_prompt("精翻", full_prompt, Some(metadata));
 
+        if let Err(e) = budget_tracker.check_next_batch() {
+            let message = budget_error_message(e, "已停止精翻");
+            crate::app_log!("[精翻] {}", message);
+            let _ = app.emit("refine:error", serde_json::json!({ "error": &message }));
+            return Err(message);
+        }
+
         // 使用自定义提示词方法，直接发送精翻提示词
-        match translator
+        let cost_before = translator.get_token_stats().cost;
+        let result = translator
             .translate_with_custom_user_prompt(prompt.clone())
-            .await
-        {
+            .await;
+        let warnings = budget_tracker.record_batch(translator.get_token_stats().cost - cost_before);
+        emit_budget_warnings(&app, warnings);
+        match result {
             Ok(result) => {
                 // 更新提示词日志的响应
                 let logs = crate::services::get_prompt_logs();
                 if let Some(last_idx) = logs.len().checked_sub(1) {
                     crate::services::update_prompt_response(last_idx, result.clone());
+                    if let Some(reasoning) = translator.last_reasoning() {
+                        crate::services::update_prompt_reasoning(last_idx, reasoning.to_string());
+                    }
                 }
                 results.push(result);
             }
@@ -937,6 +1364,7 @@ pub async fn contextual_refine(
             "deduplicated": batch_stats.deduplicated,
             "ai_translated": batch_stats.ai_translated,
             "tm_learned": batch_stats.tm_learned,
+            "cache_hits": batch_stats.cache_hits,
             "token_stats": {
                 "input_tokens": token_stats.input_tokens,
                 "output_tokens": token_stats.output_tokens,
@@ -962,8 +1390,7 @@ pub async fn contextual_refine(
 /// 检查是否需要更新风格总结
 #[tauri::command]
 pub fn should_update_style_summary() -> Result<bool, String> {
-    let path = crate::utils::paths::get_term_library_path();
-    let library = TermLibrary::load_from_file(&path).map_err(|e| e.to_string())?;
+    let library = load_term_library()?;
     Ok(library.should_update_style_summary())
 }
 
@@ -976,11 +1403,196 @@ pub fn should_update_style_summary() -> Result<bool, String> {
 /// - 内存占用降低 ~30%
 /// - 更适合大文件处理
 ///
-/// 返回任务 ID，可用于取消翻译
+/// 返回任务 ID，可用于取消翻译；超出花费预算时任务暂停，可通过 `resume_translation_job` 恢复。
+/// `file_path` 用于用量台账按文件/项目归属花费；`tm_context` 提供源语言与各条 msgctxt，
+/// 记忆库按上下文查询和学习
 #[tauri::command]
 pub async fn translate_batch_with_channel(
+    app: tauri::AppHandle,
     texts: Vec<String>,
     target_language: Option<String>,
+    file_path: Option<String>,
+    tm_context: Option<TmQueryContext>,
+    progress_channel: tauri::ipc::Channel<crate::services::BatchProgressEvent>,
+    stats_channel: tauri::ipc::Channel<crate::services::BatchStatsEvent>,
+) -> Result<BatchResultWithTaskId, String> {
+    let mut job = PausedJob::new(texts, target_language);
+    job.file_path = file_path;
+    let tm_context = tm_context.unwrap_or_default();
+    job.contexts = tm_context.contexts;
+    job.source_language = tm_context.source_language;
+    job.project_memory = tm_context.project_memory;
+    job.reference_memories = tm_context.reference_memories;
+    let settings = ChannelJobSettings::load().await?;
+    run_channel_job(app, job, settings, progress_channel, stats_channel).await
+}
+
+/// 恢复因预算超限暂停的翻译任务（只翻译剩余条目，结果包含暂停前已完成的译文）
+#[tauri::command]
+pub async fn resume_translation_job(
+    app: tauri::AppHandle,
+    job_id: String,
+    progress_channel: tauri::ipc::Channel<crate::services::BatchProgressEvent>,
+    stats_channel: tauri::ipc::Channel<crate::services::BatchStatsEvent>,
+) -> Result<BatchResultWithTaskId, String> {
+    let job = budget::load_paused_job(&job_id)?;
+    crate::app_log!(
+        "[翻译任务] 恢复暂停的任务 {}（已完成 {}/{}）",
+        job.id,
+        job.completed_count(),
+        job.texts.len()
+    );
+    let settings = ChannelJobSettings::load().await?;
+    run_channel_job(app, job, settings, progress_channel, stats_channel).await
+}
+
+/// 列出因预算超限暂停的翻译任务
+#[tauri::command]
+pub fn list_paused_translation_jobs() -> Result<Vec<budget::PausedJobSummary>, String> {
+    Ok(budget::list_paused_jobs()?)
+}
+
+/// 丢弃暂停的翻译任务
+#[tauri::command]
+pub fn discard_paused_translation_job(job_id: String) -> Result<(), String> {
+    Ok(budget::remove_paused_job(&job_id)?)
+}
+
+/// 今日/本月花费与上限
+#[tauri::command]
+pub async fn get_spending_summary() -> Result<budget::SpendingSummary, String> {
+    let config = ConfigDraft::global().await.data().budget.clone();
+    Ok(budget::spending_summary(&config))
+}
+
+/// 当前显示货币与汇率表
+#[tauri::command]
+pub fn get_currency_settings() -> currency::CurrencySettings {
+    currency::currency_settings()
+}
+
+/// 重新读取用户汇率文件（`currency_rates.toml`）
+#[tauri::command]
+pub fn reload_currency_rates() -> Result<currency::CurrencySettings, String> {
+    currency::reload_currency_table()?;
+    // 汇率表变化后重新校验显示货币
+    currency::set_display_currency(&currency::display_currency());
+    Ok(currency::currency_settings())
+}
+
+/// 响应缓存条目数与占用空间
+#[tauri::command]
+pub fn get_response_cache_stats() -> Result<response_cache::ResponseCacheStats, String> {
+    Ok(response_cache::shared().stats()?)
+}
+
+/// 清空响应缓存，返回删除的条目数
+#[tauri::command]
+pub fn clear_response_cache() -> Result<u64, String> {
+    let removed = response_cache::shared().clear()?;
+    crate::app_log!("[响应缓存] 已清空 {} 条", removed);
+    Ok(removed)
+}
+
+/// 读取用量记录并换算为当前显示货币
+fn load_usage_in_display_currency(
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<usage_ledger::UsageRecord>, String> {
+    let mut records = usage_ledger::load(parse_date_arg(from)?, parse_date_arg(to)?)?;
+    usage_ledger::convert_currency(
+        &mut records,
+        &currency::display_currency(),
+        &currency::currency_table(),
+    );
+    Ok(records)
+}
+
+/// 解析 `YYYY-MM-DD` 日期参数
+fn parse_date_arg(date: Option<String>) -> Result<Option<chrono::NaiveDate>, String> {
+    date.map(|date| {
+        chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("无效的日期 {}: {}", date, e))
+    })
+    .transpose()
+}
+
+/// 按日 / 供应商 / 模型 / 项目汇总用量台账（日期范围包含两端）
+#[tauri::command]
+pub fn query_usage(
+    group_by: UsageGroupBy,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<UsageAggregate>, String> {
+    let records = load_usage_in_display_currency(from, to)?;
+    Ok(usage_ledger::aggregate(&records, group_by))
+}
+
+/// 导出用量台账明细为 CSV，返回导出的记录数
+#[tauri::command]
+pub fn export_usage_csv(
+    file_path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, String> {
+    let path = std::path::PathBuf::from(&file_path);
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+    {
+        return Err(format!("导出文件必须为 .csv: {}", file_path));
+    }
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("无效的导出路径: {}", file_path));
+    };
+    let parent = SafePathValidator::new()
+        .validate_dir_path(&parent.to_string_lossy())
+        .map_err(|e| format!("路径验证失败: {}", e))?;
+    let path = parent.join(file_name);
+    let records = load_usage_in_display_currency(from, to)?;
+    std::fs::write(&path, usage_ledger::to_csv(&records))
+        .map_err(|e| format!("导出 CSV 失败: {}", e))?;
+    Ok(records.len())
+}
+
+/// 翻译任务的运行参数（任务开始时从配置读取）
+pub(crate) struct ChannelJobSettings {
+    /// 启用的配置在前，随后是备用供应商
+    pub ai_configs: Vec<crate::services::AIConfig>,
+    pub custom_prompt: Option<String>,
+    pub batch_size: usize,
+    pub adaptive_batch_size: bool,
+    pub max_concurrent: usize,
+    pub budget: budget::BudgetConfig,
+    /// 切换到备用供应商后重新尝试主供应商前的冷却时间
+    pub primary_retry_cooldown: std::time::Duration,
+}
+
+impl ChannelJobSettings {
+    /// 读取当前配置
+    async fn load() -> Result<Self, String> {
+        let draft = ConfigDraft::global().await;
+        let config = draft.data();
+        if config.get_active_ai_config().is_none() {
+            return Err("未找到启用的AI配置，请在设置中配置并启用AI服务".to_string());
+        }
+        Ok(Self {
+            ai_configs: config.get_ai_config_chain().into_iter().cloned().collect(),
+            custom_prompt: config.system_prompt.clone(),
+            batch_size: config.batch_size.max(1),
+            adaptive_batch_size: config.adaptive_batch_size,
+            max_concurrent: config.max_concurrent.max(1),
+            budget: config.budget.clone(),
+            primary_retry_cooldown: PRIMARY_RETRY_COOLDOWN,
+        })
+    }
+}
+
+/// 执行（或继续执行）翻译任务：只翻译 `job.entries` 中尚未完成的条目
+pub(crate) async fn run_channel_job<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    mut job: PausedJob,
+    settings: ChannelJobSettings,
     progress_channel: tauri::ipc::Channel<crate::services::BatchProgressEvent>,
     stats_channel: tauri::ipc::Channel<crate::services::BatchStatsEvent>,
 ) -> Result<BatchResultWithTaskId, String> {
@@ -993,163 +1605,578 @@ pub async fn translate_batch_with_channel(
     let task_id = task.id();
     let cancel_token = task.token()?;
 
+    let total_count = job.texts.len();
+    // 待翻译条目的全局索引（新任务为全部条目）
+    let pending = std::sync::Arc::new(job.pending_indices());
+    let texts: Vec<String> = pending.iter().map(|&i| job.texts[i].clone()).collect();
+    let contexts: Vec<Option<String>> = pending
+        .iter()
+        .map(|&i| job.contexts.get(i).cloned().flatten())
+        .collect();
+    let target_language = job.target_language.clone();
+    let source_language = job.source_language.clone();
+    // 记忆库组合在任务开始时打开一次，各 worker 与备用供应商的翻译器共用
+    let memory_stack = std::sync::Arc::new(
+        MemoryStack::open(job.project_memory.as_deref(), &job.reference_memories)
+            .map_err(|e| format!("加载记忆库失败: {}", e))?,
+    );
+
     crate::app_log!(
-        "[翻译任务] 开始任务 #{}，共 {} 条文本",
+        "[翻译任务] 开始任务 #{}，共 {} 条文本，待翻译 {} 条",
         task_id,
+        total_count,
         texts.len()
     );
 
     // 立即发送初始进度事件，包含任务ID（用于前端取消翻译）
     let init_event = crate::services::BatchProgressEvent {
-        processed: 0,
-        total: texts.len(),
-        current_item: None,
-        percentage: 0.0,
-        estimated_remaining_seconds: None,
-        index: None,
         task_id: Some(task_id),
+        ..crate::services::BatchProgressEvent::new(job.completed_count(), total_count, None)
     };
     let _ = progress_channel.send(init_event);
 
-    // 初始化配置和翻译器（在单独的作用域中以释放guard）
-    let mut translator = {
-        let draft = ConfigDraft::global().await;
-        let config = draft.data();
-        let ai_config = config
-            .get_active_ai_config()
-            .ok_or_else(|| "未找到启用的AI配置，请在设置中配置并启用AI服务".to_string())?
-            .clone();
-
-        let custom_prompt = config.system_prompt.clone();
-        AITranslator::new_with_config(ai_config, true, custom_prompt.as_deref(), target_language)
-            .map_err(|e| format!("AI翻译器初始化失败: {}", e))?
+    let ChannelJobSettings {
+        ai_configs,
+        custom_prompt,
+        batch_size,
+        adaptive_batch_size,
+        max_concurrent,
+        budget: budget_config,
+        primary_retry_cooldown,
+    } = settings;
+    if ai_configs.is_empty() {
+        return Err("未找到启用的AI配置，请在设置中配置并启用AI服务".to_string());
+    }
+    let batch_size = batch_size.max(1);
+    let max_concurrent = max_concurrent.max(1);
+
+    let pending_count = texts.len();
+    let usage_context = UsageContext::for_file(job.file_path.as_deref());
+    let new_primary_translator = || {
+        let mut translator = AITranslator::new_with_config(
+            ai_configs[0].clone(),
+            true,
+            custom_prompt.as_deref(),
+            target_language.clone(),
+        )
+        .map_err(|e| format!("AI翻译器初始化失败: {}", e))?;
+        translator.set_usage_context(usage_context.clone());
+        translator.set_source_language(source_language.clone());
+        translator
+            .set_memory_stack(&memory_stack)
+            .map_err(|e| format!("加载记忆库失败: {}", e))?;
+        Ok::<_, String>(translator)
+    };
+    let first_translator = new_primary_translator()?;
+
+    // 划分批次：自适应模式按主供应商模型的上下文窗口与文本长度切分，
+    // 并限制单批条数以便所有并发 worker 都有批次可处理
+    let chunk_ranges: Vec<std::ops::Range<usize>> = if adaptive_batch_size {
+        first_translator
+            .batch_sizer()
+            .with_max_items(pending_count.div_ceil(max_concurrent))
+            .plan(&texts, first_translator.prompt_overhead_tokens())
+    } else {
+        (0..pending_count)
+            .step_by(batch_size)
+            .map(|start| start..(start + batch_size).min(pending_count))
+            .collect()
     };
+    let chunk_count = chunk_ranges.len();
+    let worker_count = max_concurrent.min(chunk_count).max(1);
+    let first_batch_estimate = first_translator.estimate_cost(&texts).unwrap_or(0.0);
+
+    // 每个并发 worker 使用独立的翻译器（各自维护对话历史、TM 副本和 token 统计）
+    // 主供应商的翻译器立即创建以尽早暴露配置错误，备用供应商在首次切换时创建
+    let mut worker_chains = Vec::with_capacity(worker_count);
+    let mut first_translator = Some(first_translator);
+    for _ in 0..worker_count {
+        let mut chain: Vec<Option<AITranslator>> = ai_configs.iter().map(|_| None).collect();
+        chain[0] = Some(match first_translator.take() {
+            Some(translator) => translator,
+            None => new_primary_translator()?,
+        });
+        worker_chains.push(chain);
+    }
+
+    crate::app_log!(
+        "[翻译任务] 任务 #{}: {} 批（{}），并发 {}，供应商链: {}",
+        task_id,
+        chunk_count,
+        if adaptive_batch_size {
+            "自适应批次大小".to_string()
+        } else {
+            format!("每批 {} 条", batch_size)
+        },
+        worker_count,
+        ai_configs
+            .iter()
+            .map(|c| c.provider_id.as_str())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    );
+    let ai_configs = std::sync::Arc::new(ai_configs);
+
+    // 待处理批次队列
+    let queue: std::collections::VecDeque<ChunkItem> = chunk_ranges
+        .into_iter()
+        .enumerate()
+        .map(|(chunk_idx, range)| {
+            (
+                chunk_idx,
+                range.start,
+                texts[range.clone()].to_vec(),
+                contexts[range].to_vec(),
+            )
+        })
+        .collect();
+    let queue = std::sync::Arc::new(parking_lot::Mutex::new(queue));
+
+    // 花费预算：每批开始前检查，恢复的任务计入暂停前的花费；
+    // 第一批按开始前的预估检查（全部待译条目的预估花费按批次均摊）
+    let estimated_batch_cost = first_batch_estimate / chunk_count.max(1) as f64;
+    let budget_tracker = std::sync::Arc::new(parking_lot::Mutex::new(
+        budget::BudgetTracker::new(
+            budget_config,
+            job.id.clone(),
+            job.token_stats.cost,
+            budget::shared_ledger(),
+        )
+        .with_estimated_batch_cost(estimated_batch_cost),
+    ));
+    // 预算超限后不再领取新批次，进行中的批次完成后任务暂停
+    let pause_token = tokio_util::sync::CancellationToken::new();
 
     // 创建进度节流器（100ms 间隔，避免高频更新导致 UI 卡顿）
     let progress_throttler = std::sync::Arc::new(ProgressThrottler::with_default_interval());
+    // 已上报条目（并发时按条目计数，保证进度单调递增）
+    let reported: std::sync::Arc<Vec<std::sync::atomic::AtomicBool>> = std::sync::Arc::new(
+        job.entries
+            .iter()
+            .map(|entry| std::sync::atomic::AtomicBool::new(entry.is_some()))
+            .collect(),
+    );
+    let processed = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(job.completed_count()));
+
+    // 任一批次失败时中止其余 worker；用户取消会级联到子令牌
+    let abort_token = cancel_token.child_token();
+    let (outcome_tx, mut outcome_rx) = tokio::sync::mpsc::unbounded_channel::<ChunkMessage>();
+    let mut workers = tokio::task::JoinSet::new();
+
+    for mut chain in worker_chains {
+        let ai_configs = std::sync::Arc::clone(&ai_configs);
+        let custom_prompt = custom_prompt.clone();
+        let target_language = target_language.clone();
+        let source_language = source_language.clone();
+        let memory_stack = std::sync::Arc::clone(&memory_stack);
+        let usage_context = usage_context.clone();
+        let queue = std::sync::Arc::clone(&queue);
+        let pending = std::sync::Arc::clone(&pending);
+        let budget_tracker = std::sync::Arc::clone(&budget_tracker);
+        let pause_token = pause_token.clone();
+        let abort_token = abort_token.clone();
+        let outcome_tx = outcome_tx.clone();
+        let progress_channel = progress_channel.clone();
+        let progress_throttler = std::sync::Arc::clone(&progress_throttler);
+        let reported = std::sync::Arc::clone(&reported);
+        let processed = std::sync::Arc::clone(&processed);
+
+        workers.spawn(async move {
+            let mut prev_token_stats = vec![crate::services::TokenStats::default(); chain.len()];
+            // 当前使用的供应商（某供应商重试耗尽后，本 worker 后续批次直接使用下一个，
+            // 冷却时间过后再回到主供应商）
+            let mut current = 0;
+            let mut fallback_since: Option<std::time::Instant> = None;
+
+            loop {
+                if abort_token.is_cancelled() || pause_token.is_cancelled() {
+                    break;
+                }
+                let Some((chunk_idx, chunk_start, chunk, chunk_contexts)) =
+                    queue.lock().pop_front()
+                else {
+                    break;
+                };
+
+                // 预算检查（计入其他 worker 进行中的批次），超限时放回队列并暂停任务
+                let budget_check = budget_tracker.lock().check_next_batch();
+                if let Err(e) = budget_check {
+                    queue
+                        .lock()
+                        .push_front((chunk_idx, chunk_start, chunk, chunk_contexts));
+                    pause_token.cancel();
+                    let _ = outcome_tx.send(ChunkMessage::Paused(e));
+                    break;
+                }
 
-    // 翻译处理（按批次）
-    let mut translations = Vec::with_capacity(texts.len());
-    let mut translation_sources = Vec::with_capacity(texts.len()); // 收集翻译来源
-    let batch_size = 20; // 单批 20 条
-    let mut global_index = 0; // 全局索引，用于追踪整体进度
-    let total_count = texts.len(); // 提前保存总数，避免闭包中借用
-
-    // 记录上一批的 token 统计，用于计算增量
-    let mut prev_token_input = 0u32;
-    let mut prev_token_output = 0u32;
-    let mut prev_token_total = 0u32;
-    let mut prev_token_cost = 0f64;
-
-    // 累加所有批次的统计（因为每次 translate_batch 都会重置 batch_stats）
-    let mut total_tm_hits = 0usize;
-    let mut total_deduplicated = 0usize;
-    let mut total_ai_translated = 0usize;
-    let mut total_tm_learned = 0usize;
-
-    for chunk in texts.chunks(batch_size) {
-        // 检查是否被取消
-        if cancel_token.is_cancelled() {
-            crate::app_log!("[翻译任务] 任务 #{} 已被用户取消", task_id);
-            return Err("翻译已取消".to_string());
-        }
+                // 创建 progress_callback，实时推送 TM 命中和 AI 翻译结果（带节流优化）
+                // 切换供应商重试时需要新的回调，因此按需构造
+                let make_progress_callback = || {
+                    let progress_channel_clone = progress_channel.clone();
+                    let throttler_clone = std::sync::Arc::clone(&progress_throttler);
+                    let abort_token_clone = abort_token.clone();
+                    let reported_clone = std::sync::Arc::clone(&reported);
+                    let processed_clone = std::sync::Arc::clone(&processed);
+                    let pending_clone = std::sync::Arc::clone(&pending);
+                    Box::new(move |local_idx: usize, translation: String| {
+                        // 检查取消状态
+                        if abort_token_clone.is_cancelled() {
+                            return;
+                        }
 
-        let chunk_vec = chunk.to_vec();
-        let chunk_start_index = global_index;
-
-        // 创建 progress_callback，实时推送 TM 命中和 AI 翻译结果（带节流优化）
-        let progress_channel_clone = progress_channel.clone();
-        let throttler_clone = std::sync::Arc::clone(&progress_throttler);
-        let cancel_token_clone = std::sync::Arc::clone(&cancel_token);
-        let progress_callback = Box::new(move |local_idx: usize, translation: String| {
-            // 检查取消状态
-            if cancel_token_clone.is_cancelled() {
-                return;
-            }
+                        let Some(&global_idx) = pending_clone.get(chunk_start + local_idx) else {
+                            return;
+                        };
+                        let first_report = reported_clone.get(global_idx).is_some_and(|flag| {
+                            !flag.swap(true, std::sync::atomic::Ordering::Relaxed)
+                        });
+                        let processed_count = if first_report {
+                            processed_clone.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1
+                        } else {
+                            processed_clone.load(std::sync::atomic::Ordering::Relaxed)
+                        };
+
+                        // 使用节流器减少高频更新，仅每100ms发送一次进度
+                        if throttler_clone.should_update() {
+                            let event = crate::services::BatchProgressEvent::with_index(
+                                processed_count,
+                                total_count,
+                                Some(translation.clone()),
+                                global_idx,
+                            );
+                            let _ = progress_channel_clone.send(event);
+                        }
+                    }) as Box<dyn Fn(usize, String) + Send + Sync>
+                };
+
+                if current > 0
+                    && fallback_since.is_some_and(|since| since.elapsed() >= primary_retry_cooldown)
+                {
+                    crate::app_log!(
+                        "[翻译任务] 第 {} 批重新尝试主供应商 {}",
+                        chunk_idx + 1,
+                        ai_configs[0].provider_id
+                    );
+                    current = 0;
+                    fallback_since = None;
+                }
 
-            // 使用节流器减少高频更新，仅每100ms发送一次进度
-            if throttler_clone.should_update() {
-                let global_idx = chunk_start_index + local_idx;
-                let event = crate::services::BatchProgressEvent::with_index(
-                    global_idx + 1,
-                    total_count,
-                    Some(translation.clone()),
-                    global_idx,
-                );
-                let _ = progress_channel_clone.send(event);
+                // 沿供应商链依次尝试，直到某个供应商成功
+                let mut last_error = String::new();
+                let mut succeeded = None;
+                while current < chain.len() {
+                    if chain[current].is_none() {
+                        match AITranslator::new_with_config(
+                            ai_configs[current].clone(),
+                            true,
+                            custom_prompt.as_deref(),
+                            target_language.clone(),
+                        ) {
+                            Ok(mut translator) => {
+                                translator.set_usage_context(usage_context.clone());
+                                translator.set_source_language(source_language.clone());
+                                if let Err(e) = translator.set_memory_stack(&memory_stack) {
+                                    last_error = format!("加载记忆库失败: {}", e);
+                                    current += 1;
+                                    continue;
+                                }
+                                chain[current] = Some(translator);
+                            }
+                            Err(e) => {
+                                log::error!(
+                                    "[翻译任务] 备用供应商 {} 初始化失败: {}",
+                                    ai_configs[current].provider_id,
+                                    e
+                                );
+                                last_error = format!("AI翻译器初始化失败: {}", e);
+                                current += 1;
+                                continue;
+                            }
+                        }
+                    }
+                    let Some(translator) = chain[current].as_mut() else {
+                        break;
+                    };
+
+                    // 使用 translate_batch_with_context 获取翻译和来源（记忆库按 msgctxt 查询）
+                    match translator
+                        .translate_batch_with_context(
+                            chunk.clone(),
+                            &chunk_contexts,
+                            Some(make_progress_callback()),
+                            None,
+                        )
+                        .await
+                    {
+                        Ok((translations, sources)) => {
+                            // 每批成功后立即写入记忆库：之后的批次失败或用户取消时，
+                            // 已完成（已付费）批次的学习结果不会丢失
+                            auto_save_translation_memory(translator);
+                            let provider = TranslationProvider {
+                                provider_id: translator.provider_id().to_string(),
+                                model: translator.model().to_string(),
+                            };
+                            let providers = sources
+                                .iter()
+                                .map(|source| (source == "ai").then(|| provider.clone()))
+                                .collect();
+                            succeeded = Some((
+                                translations,
+                                sources,
+                                providers,
+                                translator.batch_stats.clone(),
+                            ));
+                            break;
+                        }
+                        Err(e) => {
+                            last_error = e.to_string();
+                            if abort_token.is_cancelled() {
+                                break;
+                            }
+                            if let Some(next) = ai_configs.get(current + 1) {
+                                crate::app_log!(
+                                    "[翻译任务] 第 {} 批在 {} 上失败（{}），切换到备用供应商 {}",
+                                    chunk_idx + 1,
+                                    ai_configs[current].provider_id,
+                                    e,
+                                    next.provider_id
+                                );
+                            }
+                            current += 1;
+                        }
+                    }
+                }
+
+                if current > 0 && fallback_since.is_none() {
+                    fallback_since = Some(std::time::Instant::now());
+                }
+
+                // token_stats 是各翻译器累计值，需计算增量（失败供应商消耗的 token 也计入）
+                let mut token_delta = crate::services::TokenStats::default();
+                for (translator, prev) in chain.iter().zip(prev_token_stats.iter_mut()) {
+                    if let Some(translator) = translator {
+                        let token_stats = translator.get_token_stats().clone();
+                        token_delta.accumulate(&token_stats.delta_since(prev));
+                        *prev = token_stats;
+                    }
+                }
+
+                // batch_stats 是当前批次统计
+                let message = match succeeded {
+                    Some((translations, sources, providers, batch_stats)) => {
+                        ChunkMessage::Done(ChunkOutcome {
+                            chunk_start,
+                            translations,
+                            sources,
+                            providers,
+                            batch_stats,
+                            token_delta,
+                        })
+                    }
+                    None => ChunkMessage::Failed(last_error),
+                };
+
+                let failed = matches!(message, ChunkMessage::Failed(_));
+                let _ = outcome_tx.send(message);
+                if failed {
+                    break;
+                }
             }
         });
+    }
+    drop(outcome_tx);
 
-        // 使用 translate_batch_with_sources 获取翻译和来源
-        let (result, sources) = translator
-            .translate_batch_with_sources(chunk_vec.clone(), Some(progress_callback), None)
-            .await
-            .map_err(|e| e.to_string())?;
+    // 按完成顺序汇总统计，结果按全局索引写回任务状态
+    let mut paused: Option<crate::error::AppError> = None;
 
-        // 再次检查是否被取消（AI 请求后）
-        if cancel_token.is_cancelled() {
-            crate::app_log!("[翻译任务] 任务 #{} 已被用户取消", task_id);
-            return Err("翻译已取消".to_string());
-        }
+    loop {
+        let message = tokio::select! {
+            _ = cancel_token.cancelled() => {
+                crate::app_log!("[翻译任务] 任务 #{} 已被用户取消", task_id);
+                return Err("翻译已取消".to_string());
+            }
+            message = outcome_rx.recv() => message,
+        };
+        let Some(message) = message else {
+            break;
+        };
 
-        // 收集翻译结果和来源
-        for (translation, source) in result.iter().zip(sources.iter()) {
-            translations.push(translation.clone());
-            translation_sources.push(source.clone()); // 收集来源
-            global_index += 1;
-        }
+        let outcome = match message {
+            ChunkMessage::Done(outcome) => outcome,
+            ChunkMessage::Paused(e) => {
+                // 只记录第一个超限原因，等待进行中的批次完成
+                paused.get_or_insert(e);
+                continue;
+            }
+            ChunkMessage::Failed(e) => {
+                // 中止其余批次（JoinSet 在返回时 drop，会终止未完成的 worker；
+                // 已完成批次的记忆已在 worker 中写入存储）
+                abort_token.cancel();
+                return Err(e);
+            }
+        };
 
-        // 每批发送一次统计事件（batch_stats 是当前批次增量，token_stats 需计算增量）
-        let batch_stats = &translator.batch_stats;
-        let token_stats = translator.get_token_stats();
+        job.batch_stats.accumulate(&outcome.batch_stats);
+        job.token_stats.accumulate(&outcome.token_delta);
 
-        // 累加批次统计
-        total_tm_hits += batch_stats.tm_hits;
-        total_deduplicated += batch_stats.deduplicated;
-        total_ai_translated += batch_stats.ai_translated;
-        total_tm_learned += batch_stats.tm_learned;
+        // 记录花费并发送预算预警
+        let warnings = budget_tracker.lock().record_batch(outcome.token_delta.cost);
+        emit_budget_warnings(&app, warnings);
 
+        // 每批发送一次统计事件（均为增量）
         let stats_event = BatchStatsEvent {
-            tm_hits: batch_stats.tm_hits,
-            deduplicated: batch_stats.deduplicated,
-            ai_translated: batch_stats.ai_translated,
+            tm_hits: outcome.batch_stats.tm_hits,
+            deduplicated: outcome.batch_stats.deduplicated,
+            ai_translated: outcome.batch_stats.ai_translated,
+            cache_hits: outcome.batch_stats.cache_hits,
             token_stats: TokenStatsEvent {
-                // 发送 Token 增量，而非累计值
-                prompt_tokens: (token_stats.input_tokens - prev_token_input) as usize,
-                completion_tokens: (token_stats.output_tokens - prev_token_output) as usize,
-                total_tokens: (token_stats.total_tokens - prev_token_total) as usize,
-                cost: token_stats.cost - prev_token_cost,
+                prompt_tokens: outcome.token_delta.input_tokens as usize,
+                completion_tokens: outcome.token_delta.output_tokens as usize,
+                total_tokens: outcome.token_delta.total_tokens as usize,
+                reasoning_tokens: outcome.token_delta.reasoning_tokens as usize,
+                cost: outcome.token_delta.cost,
             },
         };
         let _ = stats_channel.send(stats_event);
 
-        // 保存当前累计值，用于下一批计算增量
-        prev_token_input = token_stats.input_tokens;
-        prev_token_output = token_stats.output_tokens;
-        prev_token_total = token_stats.total_tokens;
-        prev_token_cost = token_stats.cost;
+        let entries = outcome
+            .translations
+            .into_iter()
+            .zip(outcome.sources)
+            .zip(outcome.providers);
+        for (offset, ((translation, source), provider)) in entries.enumerate() {
+            if let Some(&global_idx) = pending.get(outcome.chunk_start + offset) {
+                let (provider_id, model) = provider
+                    .map(|p| (Some(p.provider_id), Some(p.model)))
+                    .unwrap_or_default();
+                job.entries[global_idx] = Some(PausedEntry {
+                    translation,
+                    source,
+                    provider_id,
+                    model,
+                });
+            }
+        }
     }
 
-    // 保存翻译记忆库
-    auto_save_translation_memory(&translator);
+    // 再次检查是否被取消（AI 请求后）
+    if cancel_token.is_cancelled() {
+        crate::app_log!("[翻译任务] 任务 #{} 已被用户取消", task_id);
+        return Err("翻译已取消".to_string());
+    }
 
-    crate::app_log!("[翻译任务] 任务 #{} 完成", task_id);
+    // 等待 worker 退出（各批学习到的记忆已在批次完成时写入存储）
+    while let Some(joined) = workers.join_next().await {
+        if let Err(e) = joined {
+            log::error!("[翻译任务] worker 异常退出: {}", e);
+        }
+    }
+
+    // 预算超限：保存任务状态，调整预算后可恢复
+    if let Some(e) = paused {
+        job.reason = Some(e.to_string());
+        budget::save_paused_job(&job)?;
+        crate::app_log!(
+            "[翻译任务] 任务 #{} 已暂停（{}/{} 已完成）: {}",
+            task_id,
+            job.completed_count(),
+            total_count,
+            e
+        );
+        return Err(e.into());
+    }
 
-    // 返回最终结果（使用累加的统计，而不是最后一个批次的统计）
-    let token_stats = translator.get_token_stats().clone();
+    // 收集翻译结果和来源（按原文顺序）
+    let mut translations = Vec::with_capacity(total_count);
+    let mut translation_sources = Vec::with_capacity(total_count);
+    let mut translation_providers = Vec::with_capacity(total_count);
+    for (idx, entry) in job.entries.into_iter().enumerate() {
+        let entry = entry.ok_or_else(|| format!("第 {} 条翻译未完成", idx + 1))?;
+        translations.push(entry.translation);
+        translation_sources.push(entry.source);
+        translation_providers.push(match (entry.provider_id, entry.model) {
+            (Some(provider_id), Some(model)) => Some(TranslationProvider { provider_id, model }),
+            _ => None,
+        });
+    }
+
+    // 恢复的任务完成后删除暂停状态
+    if let Err(e) = budget::remove_paused_job(&job.id) {
+        log::error!("[翻译任务] 删除暂停任务 {} 失败: {}", job.id, e);
+    }
+
+    crate::app_log!("[翻译任务] 任务 #{} 完成", task_id);
 
     // TaskGuard 会在 drop 时自动完成任务
     Ok(BatchResultWithTaskId {
         task_id,
         translations,
         translation_sources, // 返回翻译来源
+        translation_providers,
         stats: TranslationStats {
-            total: texts.len(),                 // 使用总数，而不是 batch_stats.total
-            tm_hits: total_tm_hits,             // 使用累加值
-            deduplicated: total_deduplicated,   // 使用累加值
-            ai_translated: total_ai_translated, // 使用累加值
-            token_stats,
-            tm_learned: total_tm_learned, // 使用累加值
+            total: total_count,
+            tm_hits: job.batch_stats.tm_hits,
+            deduplicated: job.batch_stats.deduplicated,
+            ai_translated: job.batch_stats.ai_translated,
+            token_stats: job.token_stats,
+            tm_learned: job.batch_stats.tm_learned,
+            cache_hits: job.batch_stats.cache_hits,
         },
     })
 }
+
+/// 待处理批次：(批次序号, 在待翻译列表中的起始位置, 文本, msgctxt)
+type ChunkItem = (usize, usize, Vec<String>, Vec<Option<String>>);
+
+/// 单个批次的翻译结果（并发 worker -> 汇总）
+struct ChunkOutcome {
+    /// 在待翻译列表中的起始位置
+    chunk_start: usize,
+    translations: Vec<String>,
+    sources: Vec<String>,
+    providers: Vec<Option<TranslationProvider>>,
+    batch_stats: crate::services::translation_stats::BatchStats,
+    token_delta: crate::services::TokenStats,
+}
+
+/// worker 发往汇总循环的消息
+enum ChunkMessage {
+    Done(ChunkOutcome),
+    /// 全部供应商均失败，任务中止
+    Failed(String),
+    /// 预算超限，任务暂停
+    Paused(crate::error::AppError),
+}
+
+/// 预算超限的提示（`skipped` 说明因此未执行的操作）
+fn budget_error_message(error: crate::error::AppError, skipped: &str) -> String {
+    match error {
+        crate::error::AppError::BudgetExceeded {
+            scope,
+            spent,
+            limit,
+            ..
+        } => format!(
+            "预算超限: {}花费 {:.4} 将超过上限 {:.4}，{}",
+            scope, spent, limit, skipped
+        ),
+        e => e.to_string(),
+    }
+}
+
+/// 记录并发送预算预警（`budget:warning`）
+fn emit_budget_warnings<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    warnings: Vec<budget::BudgetWarningEvent>,
+) {
+    for warning in warnings {
+        crate::app_log!(
+            "[预算] {}花费 {:.4} 已达到上限 {:.4} 的 {:.0}%",
+            warning.scope,
+            warning.spent,
+            warning.limit,
+            warning.threshold * 100.0
+        );
+        let _ = app.emit("budget:warning", warning);
+    }
+}
@@ -50,6 +50,17 @@ pub enum AppError {
     #[error("验证错误: {0}")]
     Validation(String),
 
+    /// 预算超限（任务已暂停，调整预算后可通过 job_id 恢复）
+    #[error(
+        "预算超限: {scope}花费 {spent:.4} 将超过上限 {limit:.4}，任务 {job_id} 已暂停，调整预算后可恢复"
+    )]
+    BudgetExceeded {
+        scope: String,
+        spent: f64,
+        limit: f64,
+        job_id: String,
+    },
+
     /// 通用错误（catch-all）
     #[error("错误: {0}")]
     Generic(String),
@@ -215,4 +226,19 @@ mod tests {
         let err = AppError::Config("配置文件损坏".to_string());
         assert!(!err.is_retryable());
     }
+
+    #[test]
+    fn test_budget_exceeded_message() {
+        let err = AppError::BudgetExceeded {
+            scope: "今日".to_string(),
+            spent: 1.25,
+            limit: 1.0,
+            job_id: "job-1".to_string(),
+        };
+        assert!(!err.is_retryable());
+        let msg = err.to_string();
+        assert!(msg.contains("预算超限"));
+        assert!(msg.contains("今日"));
+        assert!(msg.contains("job-1"));
+    }
 }
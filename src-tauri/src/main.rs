@@ -40,10 +40,34 @@ fn main() {
             parse_po_file,
             translate_entry,
             translate_batch_with_channel, // Tauri 2.x: Channel API (统一翻译入口)
+            resume_translation_job,
+            list_paused_translation_jobs,
+            discard_paused_translation_job,
+            get_spending_summary,
+            get_currency_settings,
+            reload_currency_rates,
+            get_response_cache_stats,
+            clear_response_cache,
+            query_usage,
+            export_usage_csv,
             get_translation_memory,
             get_builtin_phrases,
             merge_builtin_phrases,
             save_translation_memory,
+            approve_tm_segments,
+            get_segment_memory_stats,
+            import_tmx,
+            export_tmx,
+            import_translated_catalogs,
+            list_translation_memories,
+            create_translation_memory,
+            delete_translation_memory,
+            search_translation_memory,
+            replace_in_translation_memory,
+            cleanup_translation_memory,
+            find_translation_memory_duplicates,
+            delete_translation_memory_entries,
+            get_translation_memory_usage,
             open_file_dialog,
             save_file_dialog,
             save_po_file,
@@ -67,6 +91,7 @@ fn main() {
             update_ai_config,
             remove_ai_config,
             set_active_ai_config,
+            set_fallback_ai_configs,
             test_ai_connection,
             // 系统提示词管理 (Phase 3)
             get_system_prompt,
@@ -98,6 +123,7 @@ fn main() {
             get_provider_models,
             get_model_info,
             estimate_translation_cost,
+            estimate_translation_tokens,
             calculate_precise_cost,
             // 动态 AI 供应商 API (Phase 1 重构)
             get_all_providers,
@@ -0,0 +1,320 @@
+/**
+ * 自适应批次大小
+ *
+ * - 每批条数由模型的 `context_window` / `max_output_tokens` 与逐条 token 估算共同决定
+ * - 输出 token 按目标语言的膨胀系数估算（非拉丁文字通常消耗更多 token）
+ * - 系统提示词与对话历史占用的输入 token 从预算中扣除
+ * - 记忆库参考译文只使用本批原文剩余的输入预算（且不超过预算的 1/4）
+ * - 插件未声明模型信息时使用保守的默认窗口
+ * - 指定分词器时按 BPE 精确计数，否则按字符类别估算
+ */
+use super::ModelInfo;
+use super::tokenizer::TokenizerKind;
+use std::ops::Range;
+
+/// 单批最多条数（条数过多时模型更容易漏行/合并行）
+pub const MAX_BATCH_ITEMS: usize = 100;
+
+/// 未知模型的保守默认值
+const DEFAULT_CONTEXT_WINDOW: usize = 8192;
+const DEFAULT_MAX_OUTPUT_TOKENS: usize = 2048;
+
+/// 每条文本的固定开销（序号、分隔符、换行）
+const PER_ITEM_OVERHEAD: usize = 4;
+
+/// 提示词模板本身的开销（指令行、消息封装）
+const PROMPT_TEMPLATE_OVERHEAD: usize = 64;
+
+/// 只使用预算的 80%，为估算误差留余量
+const SAFETY_RATIO: f64 = 0.8;
+
+/// 参考译文最多占用的输入预算比例
+const REFERENCE_BUDGET_RATIO: f64 = 0.25;
+
+/// 估算文本的 token 数
+///
+/// CJK 字符约 1 token/字，ASCII 约 4 字符/token，其他文字（西里尔、阿拉伯等）约 2 字符/token
+pub fn estimate_text_tokens(text: &str) -> usize {
+    let weight: f64 = text
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii() {
+                0.25
+            } else if is_wide_char(ch) {
+                1.0
+            } else {
+                0.5
+            }
+        })
+        .sum();
+    weight.ceil() as usize
+}
+
+fn is_wide_char(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1100..=0x11FF     // 谚文字母
+        | 0x2E80..=0x9FFF   // CJK 部首、假名、CJK 统一汉字等
+        | 0xAC00..=0xD7AF   // 谚文音节
+        | 0xF900..=0xFAFF   // CJK 兼容汉字
+        | 0xFF00..=0xFFEF   // 全角字符
+        | 0x20000..=0x2FA1F // CJK 扩展区
+    )
+}
+
+/// 译文相对原文的 token 膨胀系数（以英文原文为基准）
+pub fn output_token_ratio(target_language: Option<&str>) -> f64 {
+    let lang = target_language.unwrap_or_default();
+    let primary = lang.split(['-', '_']).next().unwrap_or_default();
+    match primary {
+        "en" => 1.0,
+        "fr" | "de" | "es" | "pt" | "it" | "nl" => 1.3,
+        "zh" => 1.5,
+        "ja" | "ko" | "vi" => 1.8,
+        "ru" | "uk" | "ar" | "he" => 2.0,
+        "th" => 3.0,
+        _ => 1.5,
+    }
+}
+
+/// 批次大小计算器
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchSizer {
+    context_window: usize,
+    max_output_tokens: usize,
+    output_ratio: f64,
+    max_items: usize,
+    tokenizer: Option<TokenizerKind>,
+}
+
+impl BatchSizer {
+    pub fn new(
+        context_window: usize,
+        max_output_tokens: usize,
+        target_language: Option<&str>,
+    ) -> Self {
+        Self {
+            context_window: context_window.max(1),
+            max_output_tokens: max_output_tokens.max(1),
+            output_ratio: output_token_ratio(target_language),
+            max_items: MAX_BATCH_ITEMS,
+            tokenizer: None,
+        }
+    }
+
+    /// 根据模型信息创建（未知模型使用保守默认值）
+    pub fn for_model(model: Option<&ModelInfo>, target_language: Option<&str>) -> Self {
+        match model {
+            Some(info) => Self::new(info.context_window, info.max_output_tokens, target_language),
+            None => Self::new(
+                DEFAULT_CONTEXT_WINDOW,
+                DEFAULT_MAX_OUTPUT_TOKENS,
+                target_language,
+            ),
+        }
+    }
+
+    /// 限制单批最多条数
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items.clamp(1, MAX_BATCH_ITEMS);
+        self
+    }
+
+    /// 按用户设置的 `max_tokens` 收紧输出预留（避免整批译文超出上限被截断）
+    pub fn with_output_limit(mut self, max_tokens: Option<u32>) -> Self {
+        if let Some(limit) = max_tokens {
+            self.max_output_tokens = self.max_output_tokens.min(limit as usize).max(1);
+        }
+        self
+    }
+
+    /// 使用分词器精确计算每条文本的 token
+    pub fn with_tokenizer(mut self, tokenizer: TokenizerKind) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        match self.tokenizer {
+            Some(tokenizer) => tokenizer.count(text),
+            None => estimate_text_tokens(text),
+        }
+    }
+
+    /// 为输出预留的 token（不超过上下文窗口的一半）
+    fn output_reserve(&self) -> usize {
+        self.max_output_tokens.min(self.context_window / 2).max(1)
+    }
+
+    /// 可用于原文的输入 token 预算（已扣除系统提示词/历史等开销）
+    pub fn input_budget(&self, prompt_overhead: usize) -> usize {
+        let available = self
+            .context_window
+            .saturating_sub(self.output_reserve())
+            .saturating_sub(prompt_overhead)
+            .saturating_sub(PROMPT_TEMPLATE_OVERHEAD);
+        (available as f64 * SAFETY_RATIO) as usize
+    }
+
+    /// 可用于译文的输出 token 预算
+    pub fn output_budget(&self) -> usize {
+        (self.output_reserve() as f64 * SAFETY_RATIO) as usize
+    }
+
+    /// 本批原文之外可用于参考译文的输入 token
+    pub fn reference_budget(&self, texts: &[String], prompt_overhead: usize) -> usize {
+        let input_budget = self.input_budget(prompt_overhead);
+        let texts_tokens: usize = texts
+            .iter()
+            .map(|text| self.count_tokens(text) + PER_ITEM_OVERHEAD)
+            .sum();
+        input_budget
+            .saturating_sub(texts_tokens)
+            .min((input_budget as f64 * REFERENCE_BUDGET_RATIO) as usize)
+    }
+
+    /// 对话历史是否已挤占过多输入预算（超过一半时应清空历史）
+    pub fn history_too_large(&self, prompt_overhead: usize) -> bool {
+        self.input_budget(prompt_overhead) < self.input_budget(0) / 2
+    }
+
+    /// 从 `texts` 开头取下一批的条数（至少 1 条）
+    pub fn next_batch_len(&self, texts: &[String], prompt_overhead: usize) -> usize {
+        let input_budget = self.input_budget(prompt_overhead);
+        let output_budget = self.output_budget();
+
+        let mut input_used = 0;
+        let mut output_used = 0;
+        let mut count = 0;
+        for text in texts.iter().take(self.max_items) {
+            let tokens = self.count_tokens(text);
+            let input = tokens + PER_ITEM_OVERHEAD;
+            let output = (tokens as f64 * self.output_ratio).ceil() as usize + PER_ITEM_OVERHEAD;
+            if count > 0
+                && (input_used + input > input_budget || output_used + output > output_budget)
+            {
+                break;
+            }
+            input_used += input;
+            output_used += output;
+            count += 1;
+        }
+        count.max(1).min(texts.len())
+    }
+
+    /// 把全部文本划分为连续批次
+    pub fn plan(&self, texts: &[String], prompt_overhead: usize) -> Vec<Range<usize>> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < texts.len() {
+            let len = self.next_batch_len(&texts[start..], prompt_overhead);
+            batches.push(start..start + len);
+            start += len;
+        }
+        batches
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn labels(count: usize, text: &str) -> Vec<String> {
+        (0..count).map(|_| text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_estimate_text_tokens_by_script() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("Open File"), 3);
+        assert_eq!(estimate_text_tokens("打开文件"), 4);
+        assert_eq!(estimate_text_tokens("Открыть"), 4);
+        assert!(output_token_ratio(Some("zh-Hans")) > output_token_ratio(Some("en")));
+        assert_eq!(
+            output_token_ratio(Some("pt_BR")),
+            output_token_ratio(Some("pt"))
+        );
+    }
+
+    #[test]
+    fn test_large_model_batches_many_short_labels() {
+        let sizer = BatchSizer::new(128_000, 16_384, Some("zh-Hans"));
+        let texts = labels(250, "Save");
+        let plan = sizer.plan(&texts, 500);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0], 0..MAX_BATCH_ITEMS);
+        assert_eq!(plan[2], 200..250);
+    }
+
+    #[test]
+    fn test_small_model_splits_long_texts() {
+        let long_text =
+            "This tooltip explains the behaviour of the selected component. ".repeat(20);
+        let texts = labels(20, &long_text);
+
+        let small = BatchSizer::new(8192, 2048, Some("zh-Hans"));
+        let large = BatchSizer::new(128_000, 16_384, Some("zh-Hans"));
+        let small_len = small.next_batch_len(&texts, 500);
+        let large_len = large.next_batch_len(&texts, 500);
+        assert!(small_len < large_len);
+        assert!(small_len >= 1);
+
+        // 单条超出预算时仍至少发送 1 条
+        let huge = labels(3, &"word ".repeat(20_000));
+        assert_eq!(small.next_batch_len(&huge, 0), 1);
+        assert_eq!(small.plan(&huge, 0).len(), 3);
+    }
+
+    #[test]
+    fn test_prompt_overhead_and_item_cap() {
+        let sizer = BatchSizer::new(8192, 2048, Some("en"));
+        assert!(sizer.input_budget(4000) < sizer.input_budget(0));
+        assert!(!sizer.history_too_large(500));
+        assert!(sizer.history_too_large(4000));
+
+        let capped = sizer.with_max_items(5);
+        assert_eq!(capped.next_batch_len(&labels(20, "OK"), 0), 5);
+    }
+
+    #[test]
+    fn test_output_limit_shrinks_batches() {
+        let sizer = BatchSizer::new(128_000, 8192, Some("zh-Hans"));
+        let texts = labels(200, &"Open the selected file in a new window".repeat(3));
+        let limited = sizer.with_output_limit(Some(512));
+        assert!(limited.output_budget() < sizer.output_budget());
+        assert!(limited.next_batch_len(&texts, 0) < sizer.next_batch_len(&texts, 0));
+        // 高于模型上限的设置不放宽预留
+        assert_eq!(sizer.with_output_limit(Some(100_000)), sizer);
+        assert_eq!(sizer.with_output_limit(None), sizer);
+    }
+
+    #[test]
+    fn test_reference_budget_uses_leftover_input() {
+        let sizer = BatchSizer::new(8192, 2048, Some("zh-Hans"));
+        let cap = (sizer.input_budget(500) as f64 * REFERENCE_BUDGET_RATIO) as usize;
+        // 短批次：参考译文最多占预算的 1/4
+        assert_eq!(sizer.reference_budget(&labels(5, "Save"), 500), cap);
+
+        // 原文占满输入预算时不再注入参考
+        let long_text = "word ".repeat(2_000);
+        assert_eq!(sizer.reference_budget(&labels(3, &long_text), 500), 0);
+
+        // 原文与参考合计不超过输入预算
+        let texts = labels(1, &"word ".repeat(3_500));
+        let leftover = sizer.reference_budget(&texts, 500);
+        assert!(leftover < cap);
+        assert!(
+            estimate_text_tokens(&texts[0]) + PER_ITEM_OVERHEAD + leftover
+                <= sizer.input_budget(500)
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_counts_used_for_budget() {
+        let texts = labels(40, &"打开文件失败，请检查路径。".repeat(10));
+        let estimated = BatchSizer::new(8192, 2048, Some("en"));
+        let exact = estimated.with_tokenizer(TokenizerKind::O200kBase);
+        // 字符估算按 1 token/字计算中文，比 o200k 的实际计数保守，精确计数可放入更多条目
+        assert!(exact.next_batch_len(&texts, 0) > estimated.next_batch_len(&texts, 0));
+    }
+}
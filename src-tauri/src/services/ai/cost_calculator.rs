@@ -1,4 +1,7 @@
+use super::batch_sizing::{BatchSizer, output_token_ratio};
 use super::model_info::ModelInfo;
+use super::tokenizer::TokenizerKind;
+use crate::services::prompt_builder;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ts-rs")]
@@ -15,22 +18,31 @@ pub struct CostBreakdown {
     /// 输入 token 数量（包含缓存）
     pub input_tokens: usize,
 
-    /// 输出 token 数量
+    /// 输出 token 数量（包含推理）
     pub output_tokens: usize,
 
+    /// 推理 token 数量（推理模型的思考过程）
+    pub reasoning_tokens: usize,
+
     /// 缓存写入 token 数量
     pub cache_write_tokens: usize,
 
     /// 缓存读取 token 数量
     pub cache_read_tokens: usize,
 
-    // ========== 成本明细（USD）==========
+    // ========== 成本明细（`currency`）==========
+    /// 成本货币（与模型价格相同，即用户选择的显示货币）
+    pub currency: String,
+
     /// 常规输入成本
     pub input_cost: f64,
 
-    /// 输出成本
+    /// 输出成本（包含推理）
     pub output_cost: f64,
 
+    /// 推理成本（按输出价格计费，已计入 `output_cost`）
+    pub reasoning_cost: f64,
+
     /// 缓存写入成本
     pub cache_write_cost: f64,
 
@@ -41,13 +53,56 @@ pub struct CostBreakdown {
     pub total_cost: f64,
 
     // ========== 节省信息 ==========
-    /// 缓存节省的金额（USD）
+    /// 缓存节省的金额
     pub cache_savings: f64,
 
     /// 缓存命中率（百分比，如 30.5 表示30.5%）
     pub cache_hit_rate: f64,
 }
 
+impl CostBreakdown {
+    /// 标注输出中的推理 token（按输出价格拆出推理成本，总成本不变）
+    pub fn with_reasoning_tokens(mut self, reasoning_tokens: usize) -> Self {
+        self.reasoning_tokens = reasoning_tokens.min(self.output_tokens);
+        self.reasoning_cost = if self.output_tokens > 0 {
+            self.output_cost * self.reasoning_tokens as f64 / self.output_tokens as f64
+        } else {
+            0.0
+        };
+        self
+    }
+}
+
+/// 翻译任务的预估（开始前计算）
+///
+/// 输入 token 由分词器精确计算（系统提示词 + 每批用户提示词 + 消息封装），
+/// 输出 token 按目标语言的膨胀系数估算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TranslationEstimate {
+    /// 使用的分词器
+    pub tokenizer: TokenizerKind,
+
+    /// 预计批次数
+    pub batches: usize,
+
+    /// 系统提示词 token 数（每批都会发送）
+    pub system_prompt_tokens: usize,
+
+    /// 全部批次的输入 token 总数（精确值，不含对话历史）
+    pub input_tokens: usize,
+
+    /// 预计输出 token 数
+    pub estimated_output_tokens: usize,
+
+    /// 预计成本
+    pub estimated_cost: f64,
+
+    /// 成本货币
+    pub currency: String,
+}
+
 /// 成本计算器
 ///
 /// 参考：Roo-Code 的成本计算逻辑
@@ -91,7 +146,7 @@ impl CostCalculator {
             .saturating_sub(cache_write_tokens)
             .saturating_sub(cache_read_tokens);
 
-        // 计算各部分成本（USD per million tokens）
+        // 计算各部分成本（模型货币 per million tokens）
         let input_cost = (uncached_input as f64 / 1_000_000.0) * model.input_price;
         let output_cost = (output_tokens as f64 / 1_000_000.0) * model.output_price;
         let cache_write_cost = (cache_write_tokens as f64 / 1_000_000.0)
@@ -118,10 +173,13 @@ impl CostCalculator {
         CostBreakdown {
             input_tokens,
             output_tokens,
+            reasoning_tokens: 0,
             cache_write_tokens,
             cache_read_tokens,
+            currency: model.currency.clone(),
             input_cost,
             output_cost,
+            reasoning_cost: 0.0,
             cache_write_cost,
             cache_read_cost,
             total_cost: input_cost + output_cost + cache_write_cost + cache_read_cost,
@@ -130,32 +188,113 @@ impl CostCalculator {
         }
     }
 
-    /// 估算批量翻译成本
+    /// 估算批量翻译成本（只计原文，不含提示词；需要精确预估时用 `estimate_translation`）
     ///
-    /// 基于字符数估算 token 数量
     /// 规则：
-    /// - 4 字符 ≈ 1 token（英文/中文混合经验值）
-    /// - 翻译场景：输出 token ≈ 输入 token
+    /// - 输入 token 用模型声明的分词器计数
+    /// - 输出 token = 输入 token × 目标语言膨胀系数
     ///
     /// 参数：
-    /// - `total_chars`: 总字符数
     /// - `cache_hit_rate`: 预期缓存命中率（0.0-1.0）
-    pub fn estimate_batch_cost(model: &ModelInfo, total_chars: usize, cache_hit_rate: f64) -> f64 {
-        let estimated_input_tokens = (total_chars / 4) as f64;
-        let estimated_output_tokens = estimated_input_tokens; // 翻译场景输入输出大致相等
+    pub fn estimate_batch_cost(
+        model: &ModelInfo,
+        tokenizer: TokenizerKind,
+        texts: &[String],
+        target_language: Option<&str>,
+        cache_hit_rate: f64,
+    ) -> f64 {
+        let estimated_input_tokens: usize = texts.iter().map(|text| tokenizer.count(text)).sum();
+        let estimated_output_tokens =
+            (estimated_input_tokens as f64 * output_token_ratio(target_language)).ceil() as usize;
+
+        Self::estimate_cost_from_tokens(
+            model,
+            estimated_input_tokens,
+            estimated_output_tokens,
+            cache_hit_rate,
+        )
+    }
+
+    /// 按 token 数估算成本
+    ///
+    /// 参数：
+    /// - `cache_hit_rate`: 输入 token 中预期命中缓存的比例（0.0-1.0）
+    pub fn estimate_cost_from_tokens(
+        model: &ModelInfo,
+        input_tokens: usize,
+        output_tokens: usize,
+        cache_hit_rate: f64,
+    ) -> f64 {
+        let input_tokens = input_tokens as f64;
 
         // 考虑缓存
-        let cache_read_tokens = estimated_input_tokens * cache_hit_rate;
-        let normal_input_tokens = estimated_input_tokens - cache_read_tokens;
+        let cache_read_tokens = input_tokens * cache_hit_rate;
+        let normal_input_tokens = input_tokens - cache_read_tokens;
 
         let input_cost = (normal_input_tokens / 1_000_000.0) * model.input_price;
-        let output_cost = (estimated_output_tokens / 1_000_000.0) * model.output_price;
+        let output_cost = (output_tokens as f64 / 1_000_000.0) * model.output_price;
         let cache_read_cost = (cache_read_tokens / 1_000_000.0)
             * model.cache_reads_price.unwrap_or(model.input_price);
 
         input_cost + output_cost + cache_read_cost
     }
 
+    /// 翻译任务开始前的精确预估
+    ///
+    /// 按与实际翻译相同的方式分批并构建提示词，用模型声明的分词器计数：
+    /// - 每批输入 = 系统提示词 + 用户提示词 + 消息封装开销
+    /// - 输出 = 原文 token × 目标语言膨胀系数 + 每行序号开销
+    pub fn estimate_translation(
+        model: &ModelInfo,
+        tokenizer: TokenizerKind,
+        system_prompt: &str,
+        texts: &[String],
+        target_language: Option<&str>,
+        cache_hit_rate: f64,
+    ) -> TranslationEstimate {
+        let system_prompt_tokens = tokenizer.count(system_prompt);
+        let sizer = BatchSizer::for_model(Some(model), target_language).with_tokenizer(tokenizer);
+        let batches = sizer.plan(texts, system_prompt_tokens);
+
+        let input_tokens = batches
+            .iter()
+            .map(|range| {
+                let user_prompt = prompt_builder::build_translation_prompt(
+                    &texts[range.clone()],
+                    target_language,
+                );
+                tokenizer
+                    .count_messages([("system", system_prompt), ("user", user_prompt.as_str())])
+            })
+            .sum();
+
+        let output_ratio = output_token_ratio(target_language);
+        let estimated_output_tokens = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                // 序号前缀（"12. "）与换行
+                let numbering = tokenizer.count(&format!("{}. ", i + 1)) + 1;
+                (tokenizer.count(text) as f64 * output_ratio).ceil() as usize + numbering
+            })
+            .sum();
+
+        TranslationEstimate {
+            tokenizer,
+            batches: batches.len(),
+            system_prompt_tokens,
+            input_tokens,
+            estimated_output_tokens,
+            estimated_cost: Self::estimate_cost_from_tokens(
+                model,
+                input_tokens,
+                estimated_output_tokens,
+                cache_hit_rate,
+            ),
+            currency: model.currency.clone(),
+        }
+    }
+
     /// 简化成本计算（向后兼容）
     ///
     /// 不考虑缓存，只计算基本的输入输出成本
@@ -178,6 +317,7 @@ mod tests {
             provider: "OpenAI".to_string(),
             context_window: 128000,
             max_output_tokens: 16384,
+            currency: "USD".to_string(),
             input_price: 0.15,                // $0.15 per 1M
             output_price: 0.60,               // $0.60 per 1M
             cache_reads_price: Some(0.075),   // 50% off
@@ -189,6 +329,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reasoning_cost_is_part_of_output() {
+        let model = create_test_model();
+
+        // 500 输出中 400 为推理：推理成本 = (400/1M)*0.60，总成本不变
+        let breakdown =
+            CostCalculator::calculate_openai(&model, 1000, 500, 0, 0).with_reasoning_tokens(400);
+        assert_eq!(breakdown.reasoning_tokens, 400);
+        assert!((breakdown.reasoning_cost - 0.00024).abs() < 1e-10);
+        assert!((breakdown.total_cost - 0.00045).abs() < 1e-10);
+
+        // 推理 token 不超过输出 token
+        let capped =
+            CostCalculator::calculate_openai(&model, 1000, 100, 0, 0).with_reasoning_tokens(400);
+        assert_eq!(capped.reasoning_tokens, 100);
+        assert!((capped.reasoning_cost - capped.output_cost).abs() < 1e-12);
+    }
+
     #[test]
     fn test_calculate_openai_no_cache() {
         let model = create_test_model();
@@ -230,19 +388,70 @@ mod tests {
     #[test]
     fn test_estimate_batch_cost() {
         let model = create_test_model();
+        let texts = vec!["hello world".to_string(); 1000];
+
+        // "hello world" 为 2 个 token：输入 2000，英文目标语言输出 2000
+        // - 正常输入: 1400 tokens (70%)：(1400/1M)*0.15 = 0.00021
+        // - 缓存读取: 600 tokens (30%)：(600/1M)*0.075 = 0.000045
+        // - 输出: (2000/1M)*0.60 = 0.0012
+        // 总计: 0.001455
+        let cost = CostCalculator::estimate_batch_cost(
+            &model,
+            TokenizerKind::Cl100kBase,
+            &texts,
+            Some("en"),
+            0.3,
+        );
+        assert!((cost - 0.001455).abs() < 0.000001);
+
+        // 中文译文按膨胀系数计入更多输出
+        let zh = CostCalculator::estimate_batch_cost(
+            &model,
+            TokenizerKind::Cl100kBase,
+            &texts,
+            Some("zh-Hans"),
+            0.3,
+        );
+        assert!(zh > cost);
+    }
 
-        // 测试：10000 字符，30% 缓存命中率
-        // 估算：10000/4 = 2500 tokens input
-        // - 正常输入: 1750 tokens (70%)
-        // - 缓存读取: 750 tokens (30%)
-        // - 输出: 2500 tokens (100%)
-        // 成本：
-        // - 正常输入: (1750/1M)*0.15 = 0.0002625
-        // - 缓存读取: (750/1M)*0.075 = 0.00005625
-        // - 输出: (2500/1M)*0.60 = 0.0015
-        // 总计: 0.00181875
-        let cost = CostCalculator::estimate_batch_cost(&model, 10000, 0.3);
-        assert!((cost - 0.00181875).abs() < 0.000001);
+    #[test]
+    fn test_estimate_translation_counts_system_prompt() {
+        let model = create_test_model();
+        let texts: Vec<String> = ["打开文件", "保存更改", "Close"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let system_prompt = prompt_builder::build_system_prompt(None, None);
+
+        let estimate = CostCalculator::estimate_translation(
+            &model,
+            TokenizerKind::O200kBase,
+            &system_prompt,
+            &texts,
+            Some("en"),
+            0.0,
+        );
+
+        assert_eq!(estimate.batches, 1);
+        assert_eq!(
+            estimate.system_prompt_tokens,
+            TokenizerKind::O200kBase.count(&system_prompt)
+        );
+        let user_prompt = prompt_builder::build_translation_prompt(&texts, Some("en"));
+        assert_eq!(
+            estimate.input_tokens,
+            TokenizerKind::O200kBase
+                .count_messages([("system", system_prompt.as_str()), ("user", &user_prompt)])
+        );
+        assert!(estimate.estimated_output_tokens > 0);
+        let expected_cost = CostCalculator::estimate_cost_from_tokens(
+            &model,
+            estimate.input_tokens,
+            estimate.estimated_output_tokens,
+            0.0,
+        );
+        assert!((estimate.estimated_cost - expected_cost).abs() < 1e-12);
     }
 
     #[test]
@@ -0,0 +1,257 @@
+/**
+ * 价格单位与货币换算
+ *
+ * - 插件在 `[provider]` 声明价格货币（`price_currency`）与计价单位（`price_unit`）
+ * - 加载模型时统一换算为「用户选择的货币 / 每百万 token」，之后所有成本均为该货币
+ * - 汇率表离线内置，可在 `data/currency_rates.toml` 中覆盖或补充（1 USD = ? 目标货币）
+ */
+use crate::error::AppError;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 默认货币（插件未声明、用户未选择时）
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// 内置汇率（1 USD = ? 目标货币），仅用于成本展示，可由用户覆盖
+const BUILTIN_RATES: &[(&str, f64)] = &[
+    ("USD", 1.0),
+    ("CNY", 7.2),
+    ("EUR", 0.92),
+    ("GBP", 0.79),
+    ("JPY", 150.0),
+    ("KRW", 1350.0),
+    ("HKD", 7.8),
+    ("TWD", 32.0),
+    ("SGD", 1.35),
+];
+
+/// 价格计价单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum PriceUnit {
+    /// 每百万 token
+    #[default]
+    #[serde(rename = "per_1m_tokens")]
+    PerMillionTokens,
+    /// 每千 token
+    #[serde(rename = "per_1k_tokens")]
+    PerThousandTokens,
+}
+
+impl PriceUnit {
+    /// 换算为每百万 token 的价格
+    pub fn to_per_million(self, price: f64) -> f64 {
+        match self {
+            Self::PerMillionTokens => price,
+            Self::PerThousandTokens => price * 1000.0,
+        }
+    }
+}
+
+/// 货币代码格式检查（ISO 4217：3 位大写字母）
+pub fn is_valid_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// 汇率表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyTable {
+    /// 货币代码 -> 1 USD 可兑换的数量
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl CurrencyTable {
+    /// 内置汇率表
+    pub fn builtin() -> Self {
+        Self {
+            rates: BUILTIN_RATES
+                .iter()
+                .map(|(code, rate)| (code.to_string(), *rate))
+                .collect(),
+        }
+    }
+
+    /// 内置汇率表 + 用户文件覆盖（文件不存在时只用内置值）
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let mut table = Self::builtin();
+        if !path.exists() {
+            return Ok(table);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let overrides: CurrencyTable = toml::from_str(&content)
+            .map_err(|e| AppError::config(format!("汇率表格式错误: {}", e)))?;
+        for (code, rate) in overrides.rates {
+            if !is_valid_currency_code(&code) {
+                return Err(AppError::config(format!("无效的货币代码: {}", code)));
+            }
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(AppError::config(format!("{} 的汇率必须为正数", code)));
+            }
+            table.rates.insert(code, rate);
+        }
+        if table.rates.get(DEFAULT_CURRENCY) != Some(&1.0) {
+            return Err(AppError::config("USD 汇率必须为 1"));
+        }
+        Ok(table)
+    }
+
+    /// 1 USD 可兑换的数量
+    pub fn rate(&self, code: &str) -> Option<f64> {
+        self.rates.get(code).copied()
+    }
+
+    /// 金额换算
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Result<f64, AppError> {
+        if from == to {
+            return Ok(amount);
+        }
+        let from_rate = self
+            .rate(from)
+            .ok_or_else(|| AppError::config(format!("汇率表中没有货币: {}", from)))?;
+        let to_rate = self
+            .rate(to)
+            .ok_or_else(|| AppError::config(format!("汇率表中没有货币: {}", to)))?;
+        Ok(amount / from_rate * to_rate)
+    }
+}
+
+fn rates_path() -> PathBuf {
+    crate::utils::paths::app_data_dir()
+        .map(|dir| dir.join("currency_rates.toml"))
+        .unwrap_or_else(|_| PathBuf::from("currency_rates.toml"))
+}
+
+static TABLE: once_cell::sync::Lazy<RwLock<CurrencyTable>> = once_cell::sync::Lazy::new(|| {
+    let table = CurrencyTable::load(&rates_path()).unwrap_or_else(|e| {
+        log::error!("[汇率] 加载汇率表失败，使用内置汇率: {}", e);
+        CurrencyTable::builtin()
+    });
+    RwLock::new(table)
+});
+
+static DISPLAY_CURRENCY: RwLock<Option<String>> = RwLock::new(None);
+
+/// 当前汇率表
+pub fn currency_table() -> CurrencyTable {
+    TABLE.read().clone()
+}
+
+/// 重新读取用户汇率文件
+pub fn reload_currency_table() -> Result<CurrencyTable, AppError> {
+    let table = CurrencyTable::load(&rates_path())?;
+    *TABLE.write() = table.clone();
+    Ok(table)
+}
+
+/// 用户选择的显示货币（所有成本以此货币报告）
+pub fn display_currency() -> String {
+    DISPLAY_CURRENCY
+        .read()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_CURRENCY.to_string())
+}
+
+/// 设置显示货币（随配置加载/更新同步；汇率表中没有的货币回退到 USD）
+pub fn set_display_currency(code: &str) {
+    let code = if TABLE.read().rate(code).is_some() {
+        code.to_string()
+    } else {
+        log::error!(
+            "[汇率] 汇率表中没有货币 {}，使用 {}",
+            code,
+            DEFAULT_CURRENCY
+        );
+        DEFAULT_CURRENCY.to_string()
+    };
+    *DISPLAY_CURRENCY.write() = Some(code);
+}
+
+/// 把插件声明的价格换算为「显示货币 / 每百万 token」
+pub fn normalize_price(price: f64, unit: PriceUnit, currency: &str) -> f64 {
+    let per_million = unit.to_per_million(price);
+    let display = display_currency();
+    TABLE
+        .read()
+        .convert(per_million, currency, &display)
+        .unwrap_or_else(|e| {
+            log::error!("[汇率] 价格换算失败，按原值使用: {}", e);
+            per_million
+        })
+}
+
+/// 用户汇率文件内容（用于设置页展示与编辑）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct CurrencySettings {
+    pub currency: String,
+    /// 货币代码 -> 1 USD 可兑换的数量
+    pub rates: BTreeMap<String, f64>,
+    /// 用户汇率文件路径
+    pub rates_file: String,
+}
+
+pub fn currency_settings() -> CurrencySettings {
+    CurrencySettings {
+        currency: display_currency(),
+        rates: currency_table().rates,
+        rates_file: rates_path().to_string_lossy().to_string(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_unit_conversion() {
+        assert_eq!(PriceUnit::PerMillionTokens.to_per_million(0.15), 0.15);
+        assert!((PriceUnit::PerThousandTokens.to_per_million(0.012) - 12.0).abs() < 1e-9);
+        let unit: PriceUnit = serde_json::from_str("\"per_1k_tokens\"").unwrap();
+        assert_eq!(unit, PriceUnit::PerThousandTokens);
+    }
+
+    #[test]
+    fn test_convert_between_currencies() {
+        let table = CurrencyTable::builtin();
+        assert!((table.convert(1.0, "USD", "CNY").unwrap() - 7.2).abs() < 1e-9);
+        assert!((table.convert(7.2, "CNY", "USD").unwrap() - 1.0).abs() < 1e-9);
+        assert!((table.convert(7.2, "CNY", "EUR").unwrap() - 0.92).abs() < 1e-9);
+        assert!(table.convert(1.0, "USD", "XYZ").is_err());
+    }
+
+    #[test]
+    fn test_user_rates_override_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("currency_rates.toml");
+        std::fs::write(&path, "[rates]\nCNY = 7.0\nCHF = 0.88\n").unwrap();
+
+        let table = CurrencyTable::load(&path).unwrap();
+        assert_eq!(table.rate("CNY"), Some(7.0));
+        assert_eq!(table.rate("CHF"), Some(0.88));
+        assert_eq!(table.rate("EUR"), Some(0.92));
+
+        std::fs::write(&path, "[rates]\ncny = 7.0\n").unwrap();
+        assert!(CurrencyTable::load(&path).is_err());
+        std::fs::write(&path, "[rates]\nCNY = -1.0\n").unwrap();
+        assert!(CurrencyTable::load(&path).is_err());
+        std::fs::write(&path, "[rates]\nUSD = 2.0\n").unwrap();
+        assert!(CurrencyTable::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_currency_code_format() {
+        assert!(is_valid_currency_code("USD"));
+        assert!(!is_valid_currency_code("usd"));
+        assert!(!is_valid_currency_code("US"));
+        assert!(!is_valid_currency_code("RMB1"));
+    }
+}
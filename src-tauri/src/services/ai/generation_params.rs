@@ -0,0 +1,254 @@
+/**
+ * 生成参数（temperature / top_p / max_tokens / extra_body）
+ *
+ * - 取值优先级：AIConfig > 插件模型默认值 > 内置翻译默认值
+ * - 按插件声明的模型能力校验：模型不接受的参数、超出范围的取值直接报错
+ * - 未设置或模型不接受的参数不写入请求体；`extra_body` 合并到请求体顶层
+ */
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 翻译任务的默认 temperature（偏确定性输出）
+pub const DEFAULT_TRANSLATION_TEMPERATURE: f32 = 0.2;
+
+/// temperature 的默认上限（OpenAI 兼容接口为 0~2）
+pub const DEFAULT_MAX_TEMPERATURE: f32 = 2.0;
+
+/// 由翻译器控制、不允许通过 `extra_body` 覆盖的请求体字段
+pub const RESERVED_BODY_KEYS: &[&str] = &[
+    "model",
+    "messages",
+    "stream",
+    "stream_options",
+    "response_format",
+    "temperature",
+    "top_p",
+    "max_tokens",
+];
+
+/// 生成参数（AIConfig 与插件模型默认值共用；插件 TOML 中使用 snake_case 键名）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    #[serde(alias = "top_p")]
+    pub top_p: Option<f32>,
+    /// 单次请求的最大输出 token
+    #[serde(alias = "max_tokens")]
+    pub max_tokens: Option<u32>,
+    /// 原样合并到请求体顶层的额外字段（如 `enable_thinking`、`reasoning_effort`）
+    #[serde(alias = "extra_body")]
+    #[cfg_attr(feature = "ts-rs", ts(type = "Record<string, unknown>"))]
+    pub extra_body: Map<String, Value>,
+}
+
+impl GenerationParams {
+    /// 逐项覆盖：`overrides` 中已设置的值优先，`extra_body` 按键合并
+    pub fn merged_with(&self, overrides: &GenerationParams) -> GenerationParams {
+        let mut extra_body = self.extra_body.clone();
+        for (key, value) in &overrides.extra_body {
+            extra_body.insert(key.clone(), value.clone());
+        }
+        GenerationParams {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+            extra_body,
+        }
+    }
+}
+
+/// 模型声明的参数能力与默认值
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelParameterProfile {
+    pub supports_temperature: bool,
+    pub supports_top_p: bool,
+    pub max_temperature: f32,
+    /// 模型的最大输出 token（未知模型不限制）
+    pub max_output_tokens: Option<u32>,
+    pub defaults: GenerationParams,
+}
+
+impl Default for ModelParameterProfile {
+    fn default() -> Self {
+        Self {
+            supports_temperature: true,
+            supports_top_p: true,
+            max_temperature: DEFAULT_MAX_TEMPERATURE,
+            max_output_tokens: None,
+            defaults: GenerationParams::default(),
+        }
+    }
+}
+
+impl ModelParameterProfile {
+    /// 校验参数是否被模型接受且取值有效
+    pub fn validate(&self, params: &GenerationParams) -> Result<()> {
+        if let Some(temperature) = params.temperature {
+            if !self.supports_temperature {
+                anyhow::bail!("该模型不支持设置 temperature");
+            }
+            if !temperature.is_finite() || !(0.0..=self.max_temperature).contains(&temperature) {
+                anyhow::bail!(
+                    "temperature 必须在 0 ~ {} 之间: {}",
+                    self.max_temperature,
+                    temperature
+                );
+            }
+        }
+        if let Some(top_p) = params.top_p {
+            if !self.supports_top_p {
+                anyhow::bail!("该模型不支持设置 top_p");
+            }
+            if !top_p.is_finite() || top_p <= 0.0 || top_p > 1.0 {
+                anyhow::bail!("top_p 必须在 (0, 1] 之间: {}", top_p);
+            }
+        }
+        if let Some(max_tokens) = params.max_tokens {
+            if max_tokens == 0 {
+                anyhow::bail!("max_tokens 必须大于 0");
+            }
+            if let Some(limit) = self.max_output_tokens
+                && max_tokens > limit
+            {
+                anyhow::bail!("max_tokens 超过模型的最大输出 {}: {}", limit, max_tokens);
+            }
+        }
+        if let Some(key) = params
+            .extra_body
+            .keys()
+            .find(|key| RESERVED_BODY_KEYS.contains(&key.as_str()))
+        {
+            anyhow::bail!("extra_body 不能包含保留字段: {}", key);
+        }
+        Ok(())
+    }
+
+    /// 合并配置与模型默认值并校验，得到实际发送的参数
+    ///
+    /// 模型接受 temperature 但两处都未设置时使用 [`DEFAULT_TRANSLATION_TEMPERATURE`]
+    pub fn resolve(&self, overrides: &GenerationParams) -> Result<GenerationParams> {
+        self.validate(overrides)?;
+        let mut params = self.defaults.merged_with(overrides);
+        if self.supports_temperature && params.temperature.is_none() {
+            params.temperature = Some(DEFAULT_TRANSLATION_TEMPERATURE.min(self.max_temperature));
+        }
+        self.validate(&params)?;
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_priority_and_default_temperature() {
+        let profile = ModelParameterProfile {
+            max_output_tokens: Some(8192),
+            defaults: GenerationParams {
+                top_p: Some(0.9),
+                max_tokens: Some(4096),
+                extra_body: Map::from_iter([("enable_thinking".to_string(), json!(true))]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let resolved = profile.resolve(&GenerationParams::default()).unwrap();
+        assert_eq!(resolved.temperature, Some(DEFAULT_TRANSLATION_TEMPERATURE));
+        assert_eq!(resolved.top_p, Some(0.9));
+        assert_eq!(resolved.max_tokens, Some(4096));
+
+        let overrides = GenerationParams {
+            temperature: Some(0.7),
+            max_tokens: Some(1024),
+            extra_body: Map::from_iter([("enable_thinking".to_string(), json!(false))]),
+            ..Default::default()
+        };
+        let resolved = profile.resolve(&overrides).unwrap();
+        assert_eq!(resolved.temperature, Some(0.7));
+        assert_eq!(resolved.top_p, Some(0.9));
+        assert_eq!(resolved.max_tokens, Some(1024));
+        assert_eq!(resolved.extra_body["enable_thinking"], json!(false));
+    }
+
+    #[test]
+    fn test_validate_against_capabilities() {
+        let reasoning = ModelParameterProfile {
+            supports_temperature: false,
+            supports_top_p: false,
+            max_output_tokens: Some(1000),
+            ..Default::default()
+        };
+        // 不接受 temperature 的模型不发送默认值
+        assert_eq!(
+            reasoning
+                .resolve(&GenerationParams::default())
+                .unwrap()
+                .temperature,
+            None
+        );
+
+        let reject = |params: GenerationParams| reasoning.resolve(&params).is_err();
+        assert!(reject(GenerationParams {
+            temperature: Some(0.2),
+            ..Default::default()
+        }));
+        assert!(reject(GenerationParams {
+            top_p: Some(0.5),
+            ..Default::default()
+        }));
+        assert!(reject(GenerationParams {
+            max_tokens: Some(1001),
+            ..Default::default()
+        }));
+        assert!(reject(GenerationParams {
+            extra_body: Map::from_iter([("messages".to_string(), json!([]))]),
+            ..Default::default()
+        }));
+
+        let standard = ModelParameterProfile::default();
+        assert!(
+            standard
+                .validate(&GenerationParams {
+                    temperature: Some(2.5),
+                    ..Default::default()
+                })
+                .is_err()
+        );
+        assert!(
+            standard
+                .validate(&GenerationParams {
+                    top_p: Some(0.0),
+                    ..Default::default()
+                })
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_snake_and_camel_case() {
+        let from_toml: GenerationParams = toml::from_str(
+            "temperature = 0.3\ntop_p = 0.8\nmax_tokens = 2048\n[extra_body]\nreasoning_effort = \"low\"\n",
+        )
+        .unwrap();
+        let from_json: GenerationParams = serde_json::from_value(json!({
+            "temperature": 0.3,
+            "topP": 0.8,
+            "maxTokens": 2048,
+            "extraBody": { "reasoning_effort": "low" },
+        }))
+        .unwrap();
+        assert_eq!(from_toml, from_json);
+        assert_eq!(from_json.extra_body["reasoning_effort"], "low");
+    }
+}
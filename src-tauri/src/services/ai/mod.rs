@@ -1,10 +1,19 @@
 // AI 供应商架构模块
 
+pub mod batch_sizing;
 pub mod cost_calculator;
+pub mod currency;
+pub mod generation_params;
 pub mod model_info;
 pub mod models;
 pub mod provider;
 pub mod providers;
+pub mod rate_limiter;
+pub mod reasoning;
+pub mod request_template;
+pub mod streaming;
+pub mod structured_output;
+pub mod tokenizer;
 
 // Phase 3: 插件化架构
 pub mod plugin_config;
@@ -12,8 +21,10 @@ pub mod plugin_loader;
 
 // 重新导出核心类型
 pub use cost_calculator::{CostBreakdown, CostCalculator};
+pub use generation_params::GenerationParams;
 pub use model_info::ModelInfo;
 pub use provider::ProviderInfo; // 只导出对外公开的类型
+pub use tokenizer::TokenizerKind;
 
 // 不再导出 register_all_providers（仅在 init.rs 中使用）
 // pub use providers::register_all_providers;
@@ -28,14 +28,18 @@ pub struct ModelInfo {
     /// 最大输出 token 数（如 16384）
     pub max_output_tokens: usize,
 
-    // ========== 定价（USD per million tokens）==========
+    // ========== 定价（currency per million tokens）==========
     //
-    // 重要：所有价格单位统一为 USD per million tokens
-    // 示例：0.15 = $0.15/1M = $0.00015/1K
-    /// 输入价格（USD per 1M tokens）
+    // 重要：所有价格单位统一为「`currency` / 每百万 token」
+    // 插件声明的价格在加载时按汇率表换算为用户选择的显示货币
+    // 示例：currency = "USD" 时 0.15 = $0.15/1M = $0.00015/1K
+    /// 价格货币（ISO 4217，即用户选择的显示货币）
+    pub currency: String,
+
+    /// 输入价格（per 1M tokens）
     pub input_price: f64,
 
-    /// 输出价格（USD per 1M tokens）
+    /// 输出价格（per 1M tokens）
     pub output_price: f64,
 
     /// 缓存读取价格（可选，通常是输入价格的10%）
@@ -69,12 +73,16 @@ impl ModelInfo {
         input_cost + output_cost
     }
 
-    /// 获取价格显示文本（USD per 1M）
+    /// 获取价格显示文本（per 1M）
     ///
-    /// 示例：`"$0.15/M input · $0.60/M output"`
+    /// 示例：`"$0.15/M input · $0.60/M output"`、`"CNY 1.08/M input · CNY 4.32/M output"`
     pub fn price_display(&self) -> String {
+        let symbol = match self.currency.as_str() {
+            "USD" => "$".to_string(),
+            code => format!("{} ", code),
+        };
         format!(
-            "${:.2}/M input · ${:.2}/M output",
+            "{symbol}{:.2}/M input · {symbol}{:.2}/M output",
             self.input_price, self.output_price
         )
     }
@@ -100,6 +108,7 @@ mod tests {
             provider: "OpenAI".to_string(),
             context_window: 128000,
             max_output_tokens: 16384,
+            currency: "USD".to_string(),
             input_price: 0.15,
             output_price: 0.60,
             cache_reads_price: Some(0.075),
@@ -126,6 +135,14 @@ mod tests {
         let model = create_test_model();
         let display = model.price_display();
         assert_eq!(display, "$0.15/M input · $0.60/M output");
+
+        let cny = ModelInfo {
+            currency: "CNY".to_string(),
+            input_price: 1.08,
+            output_price: 4.32,
+            ..model
+        };
+        assert_eq!(cny.price_display(), "CNY 1.08/M input · CNY 4.32/M output");
     }
 
     #[test]
@@ -8,6 +8,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use super::currency::{self, PriceUnit};
+use super::generation_params::{DEFAULT_MAX_TEMPERATURE, GenerationParams, ModelParameterProfile};
+use super::request_template::RequestTemplate;
+use super::tokenizer::TokenizerKind;
+
 /// 插件配置的根结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginConfig {
@@ -15,6 +20,9 @@ pub struct PluginConfig {
     pub provider: ProviderConfig,
     #[serde(default)]
     pub models: ModelOverrides,
+    /// 声明式请求模板（缺省为 OpenAI 兼容协议）
+    #[serde(default)]
+    pub request: RequestTemplate,
 }
 
 /// 插件元数据
@@ -57,6 +65,15 @@ pub struct ProviderConfig {
     /// 是否支持图像输入
     #[serde(default)]
     pub supports_images: bool,
+    /// 默认分词器（模型未声明时使用，缺省为 cl100k_base）
+    #[serde(default)]
+    pub tokenizer: Option<TokenizerKind>,
+    /// 模型价格的货币（ISO 4217 代码，缺省为 USD）
+    #[serde(default = "default_price_currency")]
+    pub price_currency: String,
+    /// 模型价格的计价单位（缺省为每百万 token）
+    #[serde(default)]
+    pub price_unit: PriceUnit,
     /// 模型列表（完整定义）
     #[serde(default)]
     pub models: Vec<ModelPluginConfig>,
@@ -76,9 +93,9 @@ pub struct ModelPluginConfig {
     pub context_window: usize,
     /// 最大输出 token 数
     pub max_output_tokens: usize,
-    /// 输入价格（CNY per 1K tokens）
+    /// 输入价格（货币与单位见 `price_currency` / `price_unit`）
     pub input_price: f64,
-    /// 输出价格（CNY per 1K tokens）  
+    /// 输出价格
     pub output_price: f64,
     /// 缓存读取价格（可选）
     #[serde(default)]
@@ -92,6 +109,35 @@ pub struct ModelPluginConfig {
     /// 模型描述
     #[serde(default)]
     pub description: Option<String>,
+    /// 每分钟请求数上限（可选）
+    #[serde(default)]
+    pub rpm: Option<u32>,
+    /// 每分钟 token 数上限（可选）
+    #[serde(default)]
+    pub tpm: Option<u32>,
+    /// 分词器（可选，覆盖供应商默认值）
+    #[serde(default)]
+    pub tokenizer: Option<TokenizerKind>,
+    /// 是否接受 temperature（部分推理模型不接受，缺省为 true）
+    #[serde(default = "default_true")]
+    pub supports_temperature: bool,
+    /// 是否接受 top_p（缺省为 true）
+    #[serde(default = "default_true")]
+    pub supports_top_p: bool,
+    /// temperature 上限（缺省为 2.0）
+    #[serde(default)]
+    pub max_temperature: Option<f32>,
+    /// 模型默认生成参数（`[provider.models.defaults]`）
+    #[serde(default)]
+    pub defaults: GenerationParams,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_price_currency() -> String {
+    currency::DEFAULT_CURRENCY.to_string()
 }
 
 /// 模型配置覆盖
@@ -173,6 +219,49 @@ impl PluginConfig {
             anyhow::bail!("默认模型不能为空");
         }
 
+        // 验证价格货币（必须能换算为用户选择的货币）
+        let price_currency = &self.provider.price_currency;
+        if !currency::is_valid_currency_code(price_currency) {
+            anyhow::bail!("价格货币必须是 3 位大写 ISO 4217 代码: {}", price_currency);
+        }
+        if currency::currency_table().rate(price_currency).is_none() {
+            anyhow::bail!(
+                "汇率表中没有价格货币 {}，请在 currency_rates.toml 中补充",
+                price_currency
+            );
+        }
+
+        // 验证模型限流与价格配置
+        for model in &self.provider.models {
+            if model.rpm == Some(0) || model.tpm == Some(0) {
+                anyhow::bail!("模型 {} 的 rpm/tpm 必须大于 0", model.id);
+            }
+            let prices = [
+                model.input_price,
+                model.output_price,
+                model.cache_reads_price,
+                model.cache_writes_price,
+            ];
+            if prices
+                .iter()
+                .any(|price| !price.is_finite() || *price < 0.0)
+            {
+                anyhow::bail!("模型 {} 的价格必须为非负数", model.id);
+            }
+            if let Some(max_temperature) = model.max_temperature
+                && (!max_temperature.is_finite() || max_temperature <= 0.0)
+            {
+                anyhow::bail!("模型 {} 的 max_temperature 必须大于 0", model.id);
+            }
+            let profile = self.parameter_profile_for(&model.id);
+            profile
+                .validate(&profile.defaults)
+                .with_context(|| format!("模型 {} 的默认生成参数无效", model.id))?;
+        }
+
+        // 验证请求模板
+        self.request.validate().context("请求模板无效")?;
+
         Ok(())
     }
 
@@ -194,6 +283,33 @@ impl PluginConfig {
         plugin_major == supported_major
     }
 
+    /// 模型使用的分词器：模型声明 > 供应商声明 > cl100k_base
+    pub fn tokenizer_for(&self, model_id: &str) -> TokenizerKind {
+        self.provider
+            .models
+            .iter()
+            .find(|model| model.id == model_id)
+            .and_then(|model| model.tokenizer)
+            .or(self.provider.tokenizer)
+            .unwrap_or_default()
+    }
+
+    /// 模型的参数能力与默认值（未声明的模型不限制）
+    pub fn parameter_profile_for(&self, model_id: &str) -> ModelParameterProfile {
+        self.provider
+            .models
+            .iter()
+            .find(|model| model.id == model_id)
+            .map(|model| ModelParameterProfile {
+                supports_temperature: model.supports_temperature,
+                supports_top_p: model.supports_top_p,
+                max_temperature: model.max_temperature.unwrap_or(DEFAULT_MAX_TEMPERATURE),
+                max_output_tokens: u32::try_from(model.max_output_tokens).ok(),
+                defaults: model.defaults.clone(),
+            })
+            .unwrap_or_default()
+    }
+
     /// 获取插件的完整标识（id@version）
     pub fn full_id(&self) -> String {
         format!("{}@{}", self.plugin.id, self.plugin.version)
@@ -294,10 +410,14 @@ mod tests {
                 default_model: "test-model".to_string(),
                 supports_cache: true,
                 supports_images: false,
+                tokenizer: None,
+                price_currency: "USD".to_string(),
+                price_unit: PriceUnit::default(),
                 models: vec![],
                 extra_config: HashMap::new(),
             },
             models: ModelOverrides::default(),
+            request: RequestTemplate::default(),
         }
     }
 
@@ -340,6 +460,313 @@ supports_images = false
         assert_eq!(config.plugin.id, "test_provider");
         assert_eq!(config.provider.display_name, "Test Provider");
         assert!(config.provider.supports_cache);
+        // 未声明 [request] 时使用 OpenAI 兼容模板
+        assert_eq!(config.request.endpoint, "/chat/completions");
+    }
+
+    #[test]
+    fn test_model_rate_limits() {
+        let toml_content = r#"
+[plugin]
+name = "Test Provider"
+id = "test_provider"
+version = "1.0.0"
+api_version = "1.0"
+
+[provider]
+display_name = "Test Provider"
+default_url = "https://api.test.com/v1"
+default_model = "test-model"
+
+[[provider.models]]
+id = "test-model"
+name = "Test Model"
+context_window = 128000
+max_output_tokens = 4096
+input_price = 1.0
+output_price = 2.0
+rpm = 60
+tpm = 100000
+"#;
+
+        let config = PluginConfig::from_toml(toml_content).unwrap();
+        let model = &config.provider.models[0];
+        assert_eq!(model.rpm, Some(60));
+        assert_eq!(model.tpm, Some(100000));
+
+        // 0 表示配置错误
+        let invalid = toml_content.replace("rpm = 60", "rpm = 0");
+        assert!(PluginConfig::from_toml(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_model_tokenizer_declaration() {
+        let toml_content = r#"
+[plugin]
+name = "Test Provider"
+id = "test_provider"
+version = "1.0.0"
+api_version = "1.0"
+
+[provider]
+display_name = "Test Provider"
+default_url = "https://api.test.com/v1"
+default_model = "test-model"
+tokenizer = "o200k_base"
+
+[[provider.models]]
+id = "test-model"
+name = "Test Model"
+context_window = 128000
+max_output_tokens = 4096
+input_price = 1.0
+output_price = 2.0
+
+[[provider.models]]
+id = "legacy-model"
+name = "Legacy Model"
+context_window = 16000
+max_output_tokens = 4096
+input_price = 1.0
+output_price = 2.0
+tokenizer = "cl100k_base"
+"#;
+
+        let config = PluginConfig::from_toml(toml_content).unwrap();
+        assert_eq!(config.tokenizer_for("test-model"), TokenizerKind::O200kBase);
+        assert_eq!(
+            config.tokenizer_for("legacy-model"),
+            TokenizerKind::Cl100kBase
+        );
+        // 未知模型沿用供应商默认值
+        assert_eq!(config.tokenizer_for("unknown"), TokenizerKind::O200kBase);
+        assert_eq!(
+            create_test_config().tokenizer_for("test-model"),
+            TokenizerKind::Cl100kBase
+        );
+
+        // 未知分词器名称视为配置错误
+        let invalid = toml_content.replace("\"o200k_base\"", "\"p50k_base\"");
+        assert!(PluginConfig::from_toml(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_model_generation_defaults_and_capabilities() {
+        let toml_content = r#"
+[plugin]
+name = "Test Provider"
+id = "test_provider"
+version = "1.0.0"
+api_version = "1.0"
+
+[provider]
+display_name = "Test Provider"
+default_url = "https://api.test.com/v1"
+default_model = "chat-model"
+
+[[provider.models]]
+id = "chat-model"
+name = "Chat Model"
+context_window = 128000
+max_output_tokens = 8192
+input_price = 1.0
+output_price = 2.0
+
+[provider.models.defaults]
+temperature = 0.3
+max_tokens = 4096
+
+[[provider.models]]
+id = "reasoning-model"
+name = "Reasoning Model"
+context_window = 128000
+max_output_tokens = 32768
+input_price = 1.0
+output_price = 2.0
+supports_temperature = false
+supports_top_p = false
+
+[provider.models.defaults.extra_body]
+reasoning_effort = "low"
+"#;
+
+        let config = PluginConfig::from_toml(toml_content).unwrap();
+        let chat = config.parameter_profile_for("chat-model");
+        assert!(chat.supports_temperature);
+        assert_eq!(chat.max_output_tokens, Some(8192));
+        assert_eq!(chat.defaults.temperature, Some(0.3));
+        assert_eq!(chat.defaults.max_tokens, Some(4096));
+
+        let reasoning = config.parameter_profile_for("reasoning-model");
+        assert!(!reasoning.supports_temperature);
+        assert!(!reasoning.supports_top_p);
+        assert_eq!(reasoning.defaults.extra_body["reasoning_effort"], "low");
+
+        // 未声明的模型不限制
+        assert_eq!(
+            config.parameter_profile_for("unknown"),
+            ModelParameterProfile::default()
+        );
+
+        // 默认值必须符合模型能力
+        let invalid = toml_content.replace("max_tokens = 4096", "max_tokens = 10000");
+        assert!(PluginConfig::from_toml(&invalid).is_err());
+        let invalid = toml_content.replace(
+            "supports_top_p = false",
+            "supports_top_p = false\n[provider.models.defaults]\ntemperature = 0.5",
+        );
+        assert!(PluginConfig::from_toml(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_claude_example_sends_configured_max_tokens() {
+        use crate::services::ai::request_template::TemplateVars;
+
+        let config = PluginConfig::from_toml(include_str!(
+            "../../../../example-plugins/claude-ai/plugin.toml"
+        ))
+        .unwrap();
+        let model = &config.provider.default_model;
+        let defaults = config.parameter_profile_for(model).defaults;
+        assert_eq!(defaults.max_tokens, Some(8192));
+
+        let vars = |max_tokens: Option<u32>| {
+            TemplateVars::new(model, serde_json::json!([]), 0.2).with_generation(
+                &defaults.merged_with(&GenerationParams {
+                    max_tokens,
+                    ..Default::default()
+                }),
+            )
+        };
+        assert_eq!(config.request.render_body(&vars(None))["max_tokens"], 8192);
+        assert_eq!(
+            config.request.render_body(&vars(Some(1024)))["max_tokens"],
+            1024
+        );
+    }
+
+    #[test]
+    fn test_claude_example_counts_cache_reads_like_streaming() {
+        use crate::services::ai::streaming::StreamAccumulator;
+
+        let config = PluginConfig::from_toml(include_str!(
+            "../../../../example-plugins/claude-ai/plugin.toml"
+        ))
+        .unwrap();
+        let response = serde_json::json!({
+            "content": [{"type": "text", "text": "1. 你好"}],
+            "usage": {"input_tokens": 100, "cache_read_input_tokens": 40, "output_tokens": 20}
+        });
+        let usage = config.request.extract_usage(&response).unwrap();
+        assert_eq!(usage.prompt_tokens, 140);
+        assert_eq!(usage.cached_tokens, 40);
+        assert_eq!(usage.total_tokens, 160);
+
+        let mut acc = StreamAccumulator::new(config.request.stream);
+        for event in [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":100,"cache_read_input_tokens":40,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"1. 你好"}}"#,
+            r#"{"type":"message_delta","usage":{"output_tokens":20}}"#,
+            r#"{"type":"message_stop"}"#,
+        ] {
+            acc.apply(event).unwrap();
+        }
+        assert_eq!(acc.finish().usage, Some(usage));
+    }
+
+    #[test]
+    fn test_price_currency_and_unit_validation() {
+        let toml_content = r#"
+[plugin]
+name = "Test Provider"
+id = "test_provider"
+version = "1.0.0"
+api_version = "1.0"
+
+[provider]
+display_name = "Test Provider"
+default_url = "https://api.test.com/v1"
+default_model = "test-model"
+price_currency = "CNY"
+price_unit = "per_1k_tokens"
+
+[[provider.models]]
+id = "test-model"
+name = "Test Model"
+context_window = 128000
+max_output_tokens = 4096
+input_price = 0.008
+output_price = 0.016
+"#;
+
+        let config = PluginConfig::from_toml(toml_content).unwrap();
+        assert_eq!(config.provider.price_currency, "CNY");
+        assert_eq!(config.provider.price_unit, PriceUnit::PerThousandTokens);
+
+        // 未声明时为 USD / 每百万 token
+        let defaults = create_test_config();
+        assert_eq!(defaults.provider.price_currency, "USD");
+        assert_eq!(defaults.provider.price_unit, PriceUnit::PerMillionTokens);
+
+        let lowercase = toml_content.replace("\"CNY\"", "\"cny\"");
+        assert!(PluginConfig::from_toml(&lowercase).is_err());
+        let unknown = toml_content.replace("\"CNY\"", "\"XYZ\"");
+        assert!(PluginConfig::from_toml(&unknown).is_err());
+        let bad_unit = toml_content.replace("per_1k_tokens", "per_token");
+        assert!(PluginConfig::from_toml(&bad_unit).is_err());
+        let negative = toml_content.replace("input_price = 0.008", "input_price = -0.008");
+        assert!(PluginConfig::from_toml(&negative).is_err());
+    }
+
+    #[test]
+    fn test_toml_parsing_with_request_template() {
+        let toml_content = r#"
+[plugin]
+name = "Test Provider"
+id = "test_provider"
+version = "1.0.0"
+api_version = "1.0"
+description = "Test provider plugin"
+author = "Test Author"
+
+[provider]
+display_name = "Test Provider"
+default_url = "https://api.test.com/v1"
+default_model = "test-model"
+supports_cache = false
+supports_images = false
+
+[request]
+endpoint = "/messages"
+content_path = "/content/0/text"
+
+[request.auth]
+type = "header"
+name = "x-api-key"
+
+[request.headers]
+anthropic-version = "2023-06-01"
+
+[request.body]
+model = "{{model}}"
+messages = "{{chat_messages}}"
+"#;
+
+        let config = PluginConfig::from_toml(toml_content).unwrap();
+        assert_eq!(config.request.endpoint, "/messages");
+        assert_eq!(config.request.content_path, "/content/0/text");
+        assert_eq!(
+            config
+                .request
+                .headers
+                .get("anthropic-version")
+                .map(String::as_str),
+            Some("2023-06-01")
+        );
+
+        // 未知占位符应在校验时被拒绝
+        let invalid = toml_content.replace("{{chat_messages}}", "{{unknown}}");
+        assert!(PluginConfig::from_toml(&invalid).is_err());
     }
 
     #[test]
@@ -247,7 +247,12 @@ impl DynamicAIProvider {
 }
 
 use super::ModelInfo;
+use super::currency;
+use super::generation_params::ModelParameterProfile;
 use super::provider::AIProvider;
+use super::rate_limiter::RateLimit;
+use super::request_template::RequestTemplate;
+use super::tokenizer::TokenizerKind;
 
 impl AIProvider for DynamicAIProvider {
     fn id(&self) -> &'static str {
@@ -269,36 +274,66 @@ impl AIProvider for DynamicAIProvider {
     }
 
     fn get_models(&self) -> Vec<ModelInfo> {
-        // 从插件配置动态生成模型列表
-        self.config
-            .provider
+        // 从插件配置动态生成模型列表，价格换算为「显示货币 / 每百万 token」
+        let provider = &self.config.provider;
+        let price = |value: f64| {
+            currency::normalize_price(value, provider.price_unit, &provider.price_currency)
+        };
+        let display_currency = currency::display_currency();
+        provider
             .models
             .iter()
             .map(|model_config| ModelInfo {
                 id: model_config.id.clone(),
                 name: model_config.name.clone(),
-                provider: self.config.provider.display_name.clone(),
+                provider: provider.display_name.clone(),
                 context_window: model_config.context_window,
                 max_output_tokens: model_config.max_output_tokens,
-                input_price: model_config.input_price,
-                output_price: model_config.output_price,
+                currency: display_currency.clone(),
+                input_price: price(model_config.input_price),
+                output_price: price(model_config.output_price),
                 cache_reads_price: if model_config.cache_reads_price == 0.0 {
                     None
                 } else {
-                    Some(model_config.cache_reads_price)
+                    Some(price(model_config.cache_reads_price))
                 },
                 cache_writes_price: if model_config.cache_writes_price == 0.0 {
                     None
                 } else {
-                    Some(model_config.cache_writes_price)
+                    Some(price(model_config.cache_writes_price))
                 },
-                supports_cache: self.config.provider.supports_cache,
-                supports_images: self.config.provider.supports_images,
+                supports_cache: provider.supports_cache,
+                supports_images: provider.supports_images,
                 recommended: model_config.recommended,
                 description: model_config.description.clone(),
             })
             .collect()
     }
+
+    fn request_template(&self) -> RequestTemplate {
+        self.config.request.clone()
+    }
+
+    fn rate_limit(&self, model_id: &str) -> RateLimit {
+        self.config
+            .provider
+            .models
+            .iter()
+            .find(|model| model.id == model_id)
+            .map(|model| RateLimit {
+                rpm: model.rpm,
+                tpm: model.tpm,
+            })
+            .unwrap_or_default()
+    }
+
+    fn tokenizer(&self, model_id: &str) -> TokenizerKind {
+        self.config.tokenizer_for(model_id)
+    }
+
+    fn parameter_profile(&self, model_id: &str) -> ModelParameterProfile {
+        self.config.parameter_profile_for(model_id)
+    }
 }
 
 /// 全局插件加载器实例
@@ -343,6 +378,18 @@ pub fn load_all_plugins() -> Result<usize> {
     with_global_plugin_loader(|loader| loader.load_all_plugins())
 }
 
+/// 内置的 mock 供应商配置（与 `example-plugins/mock/plugin.toml` 相同）
+#[cfg(test)]
+const MOCK_PLUGIN_TOML: &str = include_str!("../../../../example-plugins/mock/plugin.toml");
+
+/// 注册内置的 mock 供应商（离线测试用，不依赖插件目录）
+#[cfg(test)]
+pub fn register_mock_provider() -> Result<()> {
+    let config = PluginConfig::from_toml(MOCK_PLUGIN_TOML).context("mock 插件配置无效")?;
+    with_global_registry_mut(|registry| registry.register(DynamicAIProvider::new(config)))
+        .context("注册 mock 供应商失败")
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used, clippy::clone_on_ref_ptr)]
 mod tests {
@@ -3,6 +3,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::ModelInfo;
+use super::generation_params::ModelParameterProfile;
+use super::rate_limiter::RateLimit;
+use super::request_template::RequestTemplate;
+use super::tokenizer::TokenizerKind;
 
 #[cfg(feature = "ts-rs")]
 use ts_rs::TS;
@@ -32,6 +36,26 @@ pub trait AIProvider: Send + Sync {
         self.get_models().into_iter().find(|m| m.id == model_id)
     }
 
+    /// 请求模板（认证、端点、请求体及响应字段路径），默认 OpenAI 兼容
+    fn request_template(&self) -> RequestTemplate {
+        RequestTemplate::default()
+    }
+
+    /// 模型的限流配置（rpm/tpm），默认不限
+    fn rate_limit(&self, _model_id: &str) -> RateLimit {
+        RateLimit::default()
+    }
+
+    /// 模型使用的分词器，默认 cl100k_base
+    fn tokenizer(&self, _model_id: &str) -> TokenizerKind {
+        TokenizerKind::default()
+    }
+
+    /// 模型的生成参数能力与默认值，默认不限制
+    fn parameter_profile(&self, _model_id: &str) -> ModelParameterProfile {
+        ModelParameterProfile::default()
+    }
+
     /// 供应商是否支持该模型
     fn supports_model(&self, model_id: &str) -> bool {
         self.get_model_info(model_id).is_some()
@@ -204,6 +228,7 @@ mod tests {
                 provider: self.display_name.to_string(),
                 context_window: 4096,
                 max_output_tokens: 2048,
+                currency: "USD".to_string(),
                 input_price: 0.01,
                 output_price: 0.02,
                 cache_reads_price: Some(0.001),
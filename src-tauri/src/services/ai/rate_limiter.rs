@@ -0,0 +1,311 @@
+/**
+ * 供应商限流与重试策略
+ *
+ * - 每个 (供应商, 模型) 共享一个限流器，所有并发翻译任务共用
+ * - 令牌桶：每分钟请求数（rpm）和每分钟 token 数（tpm），在 plugin.toml 的模型定义中声明
+ * - 收到 429 时按 `Retry-After` 暂停该限流器上的所有请求
+ * - 状态码分类：401/403 不重试，408/429/5xx 可重试
+ */
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 单个模型的限流配置（未声明表示不限）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// 每分钟请求数
+    pub rpm: Option<u32>,
+    /// 每分钟 token 数
+    pub tpm: Option<u32>,
+}
+
+/// 令牌桶（容量 = 每分钟额度，匀速补充）
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32, now: Instant) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// 获取 `amount` 所需的等待时间（超过容量的请求按满桶计算，避免永远等待）
+    fn wait_time(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        let amount = amount.min(self.capacity);
+        if self.available >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.available) / self.refill_per_sec)
+        }
+    }
+
+    /// 扣减额度（允许为负，用于按实际用量修正）
+    fn consume(&mut self, amount: f64) {
+        self.available -= amount;
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    limit: RateLimit,
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+    /// 429 后的全局暂停截止时间
+    paused_until: Option<Instant>,
+}
+
+impl LimiterState {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            requests: limit.rpm.map(|rpm| TokenBucket::per_minute(rpm, now)),
+            tokens: limit.tpm.map(|tpm| TokenBucket::per_minute(tpm, now)),
+            paused_until: None,
+        }
+    }
+
+    /// 尝试获取额度：成功返回 None，否则返回需要等待的时间
+    fn try_acquire(&mut self, tokens: u32, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.paused_until {
+            if until > now {
+                return Some(until - now);
+            }
+            self.paused_until = None;
+        }
+
+        let request_wait = self
+            .requests
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.wait_time(1.0, now));
+        let token_wait = self.tokens.as_mut().map_or(Duration::ZERO, |bucket| {
+            bucket.wait_time(f64::from(tokens), now)
+        });
+        let wait = request_wait.max(token_wait);
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+
+        if let Some(bucket) = self.requests.as_mut() {
+            bucket.consume(1.0);
+        }
+        if let Some(bucket) = self.tokens.as_mut() {
+            bucket.consume(f64::from(tokens));
+        }
+        None
+    }
+}
+
+/// 供应商限流器（线程安全，可在并发任务间共享）
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            state: Mutex::new(LimiterState::new(limit, Instant::now())),
+        }
+    }
+
+    /// 当前限流配置
+    pub fn limit(&self) -> RateLimit {
+        self.state.lock().limit
+    }
+
+    /// 等待直到可以发送一个预计消耗 `estimated_tokens` 的请求
+    pub async fn acquire(&self, estimated_tokens: u32) {
+        loop {
+            let wait = self
+                .state
+                .lock()
+                .try_acquire(estimated_tokens, Instant::now());
+            match wait {
+                None => return,
+                Some(wait) => {
+                    crate::app_log!("[限流] 等待 {:.1} 秒后发送请求", wait.as_secs_f64());
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+
+    /// 按实际用量修正 token 桶（实际 > 预估时补扣，反之退还）
+    pub fn reconcile(&self, estimated_tokens: u32, actual_tokens: u32) {
+        let mut state = self.state.lock();
+        if let Some(bucket) = state.tokens.as_mut() {
+            bucket.consume(f64::from(actual_tokens) - f64::from(estimated_tokens));
+        }
+    }
+
+    /// 暂停所有共享此限流器的请求（收到 429 + Retry-After 时）
+    pub fn pause_for(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.state.lock();
+        if state.paused_until.is_none_or(|current| current < until) {
+            state.paused_until = Some(until);
+        }
+    }
+}
+
+type LimiterKey = (String, String);
+
+static SHARED_LIMITERS: once_cell::sync::Lazy<Mutex<HashMap<LimiterKey, Arc<RateLimiter>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 获取 (供应商, 模型) 共享的限流器；配置变化时（如插件更新）重建
+pub fn shared_limiter(provider_id: &str, model_id: &str, limit: RateLimit) -> Arc<RateLimiter> {
+    let mut limiters = SHARED_LIMITERS.lock();
+    let key = (provider_id.to_string(), model_id.to_string());
+    match limiters.get(&key) {
+        Some(limiter) if limiter.limit() == limit => Arc::clone(limiter),
+        _ => {
+            let limiter = Arc::new(RateLimiter::new(limit));
+            limiters.insert(key, Arc::clone(&limiter));
+            limiter
+        }
+    }
+}
+
+/// 状态码是否可重试（408/429/5xx）
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500..=599)
+}
+
+/// 解析 `Retry-After` 头（秒数或 HTTP 日期）
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// 指数退避：1s, 2s, 4s ...（上限 30s）
+pub fn backoff_delay(attempt: usize) -> Duration {
+    let exp = u32::try_from(attempt).unwrap_or(u32::MAX).min(5);
+    Duration::from_secs(2u64.pow(exp).min(30))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_bucket_waits_when_exhausted() {
+        let now = Instant::now();
+        let mut state = LimiterState::new(
+            RateLimit {
+                rpm: Some(2),
+                tpm: None,
+            },
+            now,
+        );
+
+        assert!(state.try_acquire(0, now).is_none());
+        assert!(state.try_acquire(0, now).is_none());
+        // 第三个请求需等待约 30 秒（2 rpm 每 30 秒补充 1 个）
+        let wait = state.try_acquire(0, now).unwrap();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+        // 时间推进后可继续发送
+        assert!(
+            state
+                .try_acquire(0, now + Duration::from_secs(30))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_token_bucket_limits_large_requests() {
+        let now = Instant::now();
+        let mut state = LimiterState::new(
+            RateLimit {
+                rpm: None,
+                tpm: Some(6000),
+            },
+            now,
+        );
+
+        assert!(state.try_acquire(5000, now).is_none());
+        // 剩余 1000，需要 2000 -> 等待 1000 / 100 = 10 秒
+        let wait = state.try_acquire(2000, now).unwrap();
+        assert!((wait.as_secs_f64() - 10.0).abs() < 0.01);
+        // 超过容量的请求按满桶处理，不会永远等待
+        assert!(
+            state
+                .try_acquire(100_000, now + Duration::from_secs(60))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_pause_blocks_until_deadline() {
+        let limiter = RateLimiter::new(RateLimit::default());
+        limiter.pause_for(Duration::from_secs(5));
+        let wait = limiter.state.lock().try_acquire(0, Instant::now());
+        assert!(wait.is_some_and(|w| w > Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_shared_limiter_is_shared_per_provider_model() {
+        let limit = RateLimit {
+            rpm: Some(10),
+            tpm: None,
+        };
+        let a = shared_limiter("rate_test", "model-a", limit);
+        let b = shared_limiter("rate_test", "model-a", limit);
+        let c = shared_limiter("rate_test", "model-b", limit);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+
+        // 配置变化时重建
+        let d = shared_limiter("rate_test", "model-a", RateLimit::default());
+        assert!(!Arc::ptr_eq(&a, &d));
+    }
+
+    #[test]
+    fn test_status_classification_and_retry_after() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(401));
+        assert!(!is_retryable_status(403));
+        assert!(!is_retryable_status(400));
+
+        assert_eq!(parse_retry_after("12"), Some(Duration::from_secs(12)));
+        assert_eq!(parse_retry_after("0.5"), Some(Duration::from_millis(500)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+
+        assert_eq!(backoff_delay(0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2), Duration::from_secs(4));
+        assert_eq!(backoff_delay(10), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,248 @@
+/**
+ * 推理模型输出处理
+ *
+ * - 推理内容有两种来源：独立的 `reasoning_content` 字段，或混在正文中的 `<think>...</think>` 块
+ * - `ChatReply`：把回答与推理内容分开，推理内容不参与译文解析、对话历史与响应缓存
+ * - `ThinkTagFilter`：流式输出时过滤 `<think>` 块，避免推理中的序号行被当作译文上屏
+ */
+use super::request_template::ResponseUsage;
+use super::tokenizer::TokenizerKind;
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// 一次聊天请求的结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatReply {
+    /// 去除推理内容后的回答
+    pub content: String,
+    /// 推理过程（没有时为 None）
+    pub reasoning: Option<String>,
+    pub usage: Option<ResponseUsage>,
+}
+
+impl ChatReply {
+    /// 从原始回复构建：剥离正文中的 `<think>` 块，并与独立的推理字段合并
+    pub fn new(
+        raw_content: &str,
+        reasoning_field: Option<String>,
+        usage: Option<ResponseUsage>,
+    ) -> Self {
+        let (content, inline) = split_reasoning(raw_content);
+        let reasoning = [reasoning_field, inline]
+            .into_iter()
+            .flatten()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>();
+        Self {
+            content,
+            reasoning: (!reasoning.is_empty()).then(|| reasoning.join("\n\n")),
+            usage,
+        }
+    }
+
+    /// 供应商未返回推理 token 时按分词器估算（不超过输出 token）
+    pub fn fill_reasoning_tokens(&mut self, tokenizer: TokenizerKind) {
+        let (Some(usage), Some(reasoning)) = (self.usage.as_mut(), self.reasoning.as_deref())
+        else {
+            return;
+        };
+        if usage.reasoning_tokens == 0 {
+            let estimated = u32::try_from(tokenizer.count(reasoning)).unwrap_or(u32::MAX);
+            usage.reasoning_tokens = estimated.min(usage.completion_tokens);
+        }
+    }
+}
+
+/// 拆分回答与 `<think>` 推理块，返回 (回答, 推理内容)
+///
+/// - 未闭合的 `<think>`（输出被截断）之后的内容都视为推理
+/// - 只有 `</think>` 没有开始标签（部分推理服务会省略）时，结束标签之前都视为推理
+/// - 不含标签时原样返回
+pub fn split_reasoning(content: &str) -> (String, Option<String>) {
+    if !content.contains(THINK_OPEN) && !content.contains(THINK_CLOSE) {
+        return (content.to_string(), None);
+    }
+
+    let mut answer = String::new();
+    let mut reasoning: Vec<&str> = Vec::new();
+    let mut rest = content;
+
+    if let Some(end) = rest.find(THINK_CLOSE)
+        && !rest[..end].contains(THINK_OPEN)
+    {
+        reasoning.push(&rest[..end]);
+        rest = &rest[end + THINK_CLOSE.len()..];
+    }
+
+    while let Some(start) = rest.find(THINK_OPEN) {
+        answer.push_str(&rest[..start]);
+        let inner = &rest[start + THINK_OPEN.len()..];
+        match inner.find(THINK_CLOSE) {
+            Some(end) => {
+                reasoning.push(&inner[..end]);
+                rest = &inner[end + THINK_CLOSE.len()..];
+            }
+            None => {
+                reasoning.push(inner);
+                rest = "";
+            }
+        }
+    }
+    answer.push_str(rest);
+
+    let reasoning = reasoning
+        .iter()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (
+        answer.trim().to_string(),
+        (!reasoning.is_empty()).then_some(reasoning),
+    )
+}
+
+/// 流式 `<think>` 过滤器：支持标签被拆分到多个数据块
+///
+/// 省略开始标签的推理输出无法在流中提前识别，最终结果仍以 [`split_reasoning`] 为准
+#[derive(Debug, Default)]
+pub struct ThinkTagFilter {
+    pending: String,
+    in_think: bool,
+}
+
+impl ThinkTagFilter {
+    /// 追加增量文本，返回其中属于回答的部分
+    pub fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+
+        let mut answer = String::new();
+        loop {
+            let tag = if self.in_think {
+                THINK_CLOSE
+            } else {
+                THINK_OPEN
+            };
+            if let Some(pos) = self.pending.find(tag) {
+                if !self.in_think {
+                    answer.push_str(&self.pending[..pos]);
+                }
+                self.pending.drain(..pos + tag.len());
+                self.in_think = !self.in_think;
+                continue;
+            }
+
+            // 末尾可能是被拆开的标签前缀，留到下一块再判断
+            let keep = (1..tag.len())
+                .rev()
+                .find(|&n| self.pending.ends_with(&tag[..n]))
+                .unwrap_or(0);
+            let ready = self.pending.len() - keep;
+            if !self.in_think {
+                answer.push_str(&self.pending[..ready]);
+            }
+            self.pending.drain(..ready);
+            return answer;
+        }
+    }
+
+    /// 流结束：返回残留的回答文本
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        if self.in_think { String::new() } else { rest }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reasoning_variants() {
+        assert_eq!(
+            split_reasoning("1. 你好\n2. 世界"),
+            ("1. 你好\n2. 世界".to_string(), None)
+        );
+        assert_eq!(
+            split_reasoning("<think>\n1. Hello 是问候\n</think>\n\n1. 你好"),
+            ("1. 你好".to_string(), Some("1. Hello 是问候".to_string()))
+        );
+        // 省略开始标签
+        assert_eq!(
+            split_reasoning("先分析原文</think>1. 你好"),
+            ("1. 你好".to_string(), Some("先分析原文".to_string()))
+        );
+        // 输出在推理中被截断
+        assert_eq!(
+            split_reasoning("<think>还在思考"),
+            (String::new(), Some("还在思考".to_string()))
+        );
+        // 空推理块（如 Qwen3 关闭思考时）
+        assert_eq!(
+            split_reasoning("<think>\n\n</think>\n\n1. 你好"),
+            ("1. 你好".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_chat_reply_merges_and_estimates_reasoning() {
+        let usage = ResponseUsage {
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            total_tokens: 150,
+            ..Default::default()
+        };
+        let mut reply = ChatReply::new(
+            "<think>字段外的推理</think>1. 你好",
+            Some("字段中的推理".to_string()),
+            Some(usage),
+        );
+        assert_eq!(reply.content, "1. 你好");
+        assert_eq!(
+            reply.reasoning.as_deref(),
+            Some("字段中的推理\n\n字段外的推理")
+        );
+
+        reply.fill_reasoning_tokens(TokenizerKind::Cl100kBase);
+        let reasoning_tokens = reply.usage.unwrap().reasoning_tokens;
+        assert!(reasoning_tokens > 0 && reasoning_tokens <= 50);
+
+        // 供应商已返回推理 token 时不覆盖
+        let mut reported = ChatReply::new(
+            "1. 你好",
+            Some("推理".to_string()),
+            Some(ResponseUsage {
+                reasoning_tokens: 7,
+                ..usage
+            }),
+        );
+        reported.fill_reasoning_tokens(TokenizerKind::Cl100kBase);
+        assert_eq!(reported.usage.unwrap().reasoning_tokens, 7);
+    }
+
+    #[test]
+    fn test_think_filter_handles_split_tags() {
+        let mut filter = ThinkTagFilter::default();
+        let mut answer = String::new();
+        for chunk in [
+            "<thi",
+            "nk>1. 推理行\n",
+            "</th",
+            "ink>\n1. 你",
+            "好\n2. <",
+            "b>世界",
+        ] {
+            answer.push_str(&filter.push(chunk));
+        }
+        answer.push_str(&filter.finish());
+        assert_eq!(answer, "\n1. 你好\n2. <b>世界");
+
+        // 未闭合的推理块不输出
+        let mut filter = ThinkTagFilter::default();
+        assert_eq!(filter.push("<think>1. 推理"), "");
+        assert_eq!(filter.finish(), "");
+    }
+}
@@ -0,0 +1,928 @@
+/**
+ * 声明式 HTTP 请求模板
+ *
+ * 插件可在 plugin.toml 的 `[request]` 段声明线协议：认证方式、端点路径、
+ * 请求体模板、响应字段的 JSON Pointer 路径以及额外请求头。
+ * 未声明时使用 OpenAI 兼容协议，新供应商只需一个 TOML 文件即可接入。
+ *
+ * `stream` 声明 SSE 流式协议（openai / anthropic），用于逐行渐进式返回译文。
+ * `structured_output` 声明结构化输出方式，批量翻译改用 id→text 的 JSON 协议。
+ *
+ * 整个值就是占位符且变量未设置（如模型不接受的 temperature）时，该字段从请求体中省略；
+ * 生成参数中的 `extra_body` 在渲染后合并到请求体顶层。
+ */
+use anyhow::Result;
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::generation_params::GenerationParams;
+
+/// 模板中可用的占位符（`{{name}}`）
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "model",
+    "messages",
+    "chat_messages",
+    "system_prompt",
+    "user_prompt",
+    "temperature",
+    "top_p",
+    "max_tokens",
+];
+
+/// 认证方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <api_key>`
+    #[default]
+    Bearer,
+    /// 自定义请求头，如 `x-api-key: <api_key>`
+    Header {
+        name: String,
+        #[serde(default)]
+        prefix: String,
+    },
+    /// URL 查询参数，如 `?key=<api_key>`
+    Query { name: String },
+    /// 无需认证（本地服务）
+    None,
+}
+
+/// SSE 流式协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamProtocol {
+    /// 不支持流式，等待完整响应
+    #[default]
+    None,
+    /// OpenAI 兼容：`choices[0].delta.content`，末尾 usage 块 + `[DONE]`
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Anthropic Messages：`content_block_delta` / `message_delta` 事件
+    Anthropic,
+}
+
+/// 结构化输出方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutput {
+    /// 按序号逐行返回（默认）
+    #[default]
+    None,
+    /// `response_format: { type: "json_schema" }`，按 schema 严格约束
+    JsonSchema,
+    /// `response_format: { type: "json_object" }`，仅保证返回 JSON
+    JsonObject,
+}
+
+/// Token 用量字段的 JSON Pointer 路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsagePaths {
+    #[serde(default = "default_prompt_tokens_path")]
+    pub prompt_tokens: String,
+    #[serde(default = "default_completion_tokens_path")]
+    pub completion_tokens: String,
+    /// 总 token 路径（缺省时按输入 + 输出计算）
+    #[serde(default)]
+    pub total_tokens: Option<String>,
+    /// 缓存命中的输入 token 路径
+    #[serde(default)]
+    pub cached_tokens: Option<String>,
+    /// 缓存命中的 token 是否已计入 `prompt_tokens`（OpenAI 语义为是；Anthropic 的
+    /// `cache_read_input_tokens` 单独计数，设为 false 时会加到输入 token 上）
+    #[serde(default = "default_true")]
+    pub cached_included: bool,
+    /// 推理 token 路径
+    #[serde(default)]
+    pub reasoning_tokens: Option<String>,
+    /// 推理 token 是否已计入 `completion_tokens`（OpenAI 语义为是；Gemini 的
+    /// `thoughtsTokenCount` 单独计数，设为 false 时会加到输出 token 上）
+    #[serde(default = "default_true")]
+    pub reasoning_included: bool,
+}
+
+impl Default for UsagePaths {
+    fn default() -> Self {
+        Self {
+            prompt_tokens: default_prompt_tokens_path(),
+            completion_tokens: default_completion_tokens_path(),
+            total_tokens: Some("/usage/total_tokens".to_string()),
+            cached_tokens: Some("/usage/prompt_tokens_details/cached_tokens".to_string()),
+            cached_included: true,
+            reasoning_tokens: Some("/usage/completion_tokens_details/reasoning_tokens".to_string()),
+            reasoning_included: true,
+        }
+    }
+}
+
+/// 请求模板（对应 plugin.toml 的 `[request]` 段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestTemplate {
+    /// 认证方式
+    #[serde(default)]
+    pub auth: AuthScheme,
+    /// 端点路径（拼接在 base_url 之后，支持占位符）
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+    /// 额外请求头
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 请求体模板（字符串值中的 `{{name}}` 会被替换）
+    #[serde(default = "default_body")]
+    pub body: Value,
+    /// 译文内容的 JSON Pointer 路径
+    #[serde(default = "default_content_path")]
+    pub content_path: String,
+    /// 推理内容的 JSON Pointer 路径（DeepSeek / Qwen / Kimi 的 `reasoning_content`）
+    #[serde(default = "default_reasoning_path")]
+    pub reasoning_path: Option<String>,
+    /// Token 用量路径
+    #[serde(default)]
+    pub usage: UsagePaths,
+    /// 流式协议（自定义 `[request]` 时默认不启用）
+    #[serde(default)]
+    pub stream: StreamProtocol,
+    /// 结构化输出方式
+    #[serde(default)]
+    pub structured_output: StructuredOutput,
+}
+
+impl Default for RequestTemplate {
+    fn default() -> Self {
+        Self {
+            auth: AuthScheme::default(),
+            endpoint: default_endpoint(),
+            headers: HashMap::new(),
+            body: default_body(),
+            content_path: default_content_path(),
+            reasoning_path: default_reasoning_path(),
+            usage: UsagePaths::default(),
+            stream: StreamProtocol::OpenAi,
+            structured_output: StructuredOutput::None,
+        }
+    }
+}
+
+fn default_endpoint() -> String {
+    "/chat/completions".to_string()
+}
+
+fn default_content_path() -> String {
+    "/choices/0/message/content".to_string()
+}
+
+fn default_reasoning_path() -> Option<String> {
+    Some("/choices/0/message/reasoning_content".to_string())
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_prompt_tokens_path() -> String {
+    "/usage/prompt_tokens".to_string()
+}
+
+fn default_completion_tokens_path() -> String {
+    "/usage/completion_tokens".to_string()
+}
+
+fn default_body() -> Value {
+    serde_json::json!({
+        "model": "{{model}}",
+        "messages": "{{messages}}",
+        "temperature": "{{temperature}}",
+        "top_p": "{{top_p}}",
+        "max_tokens": "{{max_tokens}}",
+    })
+}
+
+/// 从响应中提取的 token 用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    /// 缓存命中的输入 token（包含在 prompt_tokens 中）
+    pub cached_tokens: u32,
+    /// 推理 token（包含在 completion_tokens 中）
+    pub reasoning_tokens: u32,
+}
+
+/// 渲染模板所需的变量
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars {
+    values: HashMap<&'static str, Value>,
+    extra_body: Map<String, Value>,
+}
+
+impl TemplateVars {
+    /// 从对话消息构建模板变量
+    ///
+    /// `messages` 为 `[{role, content}]` 数组；`chat_messages` 为去掉 system 的部分，
+    /// `system_prompt` / `user_prompt` 分别取第一条 system 消息和最后一条 user 消息。
+    pub fn new(model: &str, messages: Value, temperature: f32) -> Self {
+        let all = messages.as_array().cloned().unwrap_or_default();
+        let role_of = |m: &Value| m["role"].as_str().unwrap_or_default().to_string();
+
+        let system_prompt = all
+            .iter()
+            .find(|m| role_of(m) == "system")
+            .and_then(|m| m["content"].as_str())
+            .unwrap_or_default()
+            .to_string();
+        let user_prompt = all
+            .iter()
+            .rev()
+            .find(|m| role_of(m) == "user")
+            .and_then(|m| m["content"].as_str())
+            .unwrap_or_default()
+            .to_string();
+        let chat_messages: Vec<Value> = all
+            .iter()
+            .filter(|m| role_of(m) != "system")
+            .cloned()
+            .collect();
+
+        let mut values = HashMap::new();
+        values.insert("model", Value::String(model.to_string()));
+        values.insert("messages", messages);
+        values.insert("chat_messages", Value::Array(chat_messages));
+        values.insert("system_prompt", Value::String(system_prompt));
+        values.insert("user_prompt", Value::String(user_prompt));
+        values.insert("temperature", f32_value(temperature));
+
+        Self {
+            values,
+            extra_body: Map::new(),
+        }
+    }
+
+    /// 使用实际生成参数：未设置的 temperature / top_p / max_tokens 不写入请求体
+    pub fn with_generation(mut self, params: &GenerationParams) -> Self {
+        let mut set = |name: &'static str, value: Option<Value>| match value {
+            Some(value) => {
+                self.values.insert(name, value);
+            }
+            None => {
+                self.values.remove(name);
+            }
+        };
+        set("temperature", params.temperature.map(f32_value));
+        set("top_p", params.top_p.map(f32_value));
+        set("max_tokens", params.max_tokens.map(Value::from));
+        self.extra_body = params.extra_body.clone();
+        self
+    }
+
+    /// 获取变量值
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+impl RequestTemplate {
+    /// 验证模板的有效性
+    pub fn validate(&self) -> Result<()> {
+        if !self.endpoint.is_empty() && !self.endpoint.starts_with('/') {
+            anyhow::bail!("请求端点必须以 / 开头: {}", self.endpoint);
+        }
+
+        match &self.auth {
+            AuthScheme::Header { name, .. } | AuthScheme::Query { name } if name.is_empty() => {
+                anyhow::bail!("认证参数名不能为空");
+            }
+            _ => {}
+        }
+
+        let mut pointers = vec![
+            &self.content_path,
+            &self.usage.prompt_tokens,
+            &self.usage.completion_tokens,
+        ];
+        if let Some(total) = &self.usage.total_tokens {
+            pointers.push(total);
+        }
+        if let Some(cached) = &self.usage.cached_tokens {
+            pointers.push(cached);
+        }
+        if let Some(reasoning) = &self.usage.reasoning_tokens {
+            pointers.push(reasoning);
+        }
+        if let Some(reasoning) = &self.reasoning_path {
+            pointers.push(reasoning);
+        }
+        for pointer in pointers {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                anyhow::bail!("JSON Pointer 必须以 / 开头: {}", pointer);
+            }
+        }
+        if self.content_path.is_empty() {
+            anyhow::bail!("content_path 不能为空");
+        }
+
+        for (name, value) in &self.headers {
+            if name.trim().is_empty() {
+                anyhow::bail!("请求头名称不能为空");
+            }
+            check_placeholders(value)?;
+        }
+        check_placeholders(&self.endpoint)?;
+        check_value_placeholders(&self.body)?;
+
+        Ok(())
+    }
+
+    /// 拼接请求 URL
+    ///
+    /// base_url 已经以端点结尾时（如插件的 default_url 写了完整地址）不再重复拼接
+    pub fn build_url(&self, base_url: &str, vars: &TemplateVars) -> String {
+        let base = base_url.trim_end_matches('/');
+        let endpoint = render_str(&self.endpoint, vars);
+        if endpoint.is_empty() || base.ends_with(endpoint.as_str()) {
+            base.to_string()
+        } else {
+            format!("{}{}", base, endpoint)
+        }
+    }
+
+    /// 渲染请求体（合并 `extra_body`）
+    pub fn render_body(&self, vars: &TemplateVars) -> Value {
+        let mut body = render_value(&self.body, vars);
+        if let Value::Object(map) = &mut body {
+            for (key, value) in &vars.extra_body {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+        body
+    }
+
+    /// 是否支持流式响应
+    pub fn supports_stream(&self) -> bool {
+        self.stream != StreamProtocol::None
+    }
+
+    /// 渲染流式请求体（按协议注入 `stream` 等字段）
+    pub fn render_stream_body(&self, vars: &TemplateVars) -> Value {
+        let mut body = self.render_body(vars);
+        if let Value::Object(map) = &mut body {
+            match self.stream {
+                StreamProtocol::None => {}
+                StreamProtocol::OpenAi => {
+                    map.insert("stream".to_string(), Value::Bool(true));
+                    // 要求在最后一个数据块中返回 usage
+                    map.insert(
+                        "stream_options".to_string(),
+                        serde_json::json!({ "include_usage": true }),
+                    );
+                }
+                StreamProtocol::Anthropic => {
+                    map.insert("stream".to_string(), Value::Bool(true));
+                }
+            }
+        }
+        body
+    }
+
+    /// 是否声明了结构化输出
+    pub fn supports_structured_output(&self) -> bool {
+        self.structured_output != StructuredOutput::None
+    }
+
+    /// 渲染结构化输出请求体（注入 `response_format`）
+    pub fn render_structured_body(&self, vars: &TemplateVars, schema: &Value) -> Value {
+        let mut body = self.render_body(vars);
+        if let Value::Object(map) = &mut body {
+            let response_format = match self.structured_output {
+                StructuredOutput::None => None,
+                StructuredOutput::JsonSchema => Some(serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "translations",
+                        "strict": true,
+                        "schema": schema,
+                    },
+                })),
+                StructuredOutput::JsonObject => Some(serde_json::json!({ "type": "json_object" })),
+            };
+            if let Some(response_format) = response_format {
+                map.insert("response_format".to_string(), response_format);
+            }
+        }
+        body
+    }
+
+    /// 构建结构化输出请求
+    pub fn build_structured_request(
+        &self,
+        client: &HttpClient,
+        base_url: &str,
+        api_key: &str,
+        vars: &TemplateVars,
+        schema: &Value,
+    ) -> reqwest::RequestBuilder {
+        self.request_with_body(
+            client,
+            base_url,
+            api_key,
+            vars,
+            &self.render_structured_body(vars, schema),
+        )
+    }
+
+    /// 构建完整的 HTTP 请求（URL、认证、请求头、请求体）
+    pub fn build_request(
+        &self,
+        client: &HttpClient,
+        base_url: &str,
+        api_key: &str,
+        vars: &TemplateVars,
+    ) -> reqwest::RequestBuilder {
+        self.request_with_body(client, base_url, api_key, vars, &self.render_body(vars))
+    }
+
+    /// 构建流式请求（模板不支持流式时等同于 `build_request`）
+    pub fn build_stream_request(
+        &self,
+        client: &HttpClient,
+        base_url: &str,
+        api_key: &str,
+        vars: &TemplateVars,
+    ) -> reqwest::RequestBuilder {
+        if !self.supports_stream() {
+            return self.build_request(client, base_url, api_key, vars);
+        }
+        self.request_with_body(
+            client,
+            base_url,
+            api_key,
+            vars,
+            &self.render_stream_body(vars),
+        )
+        .header("Accept", "text/event-stream")
+    }
+
+    fn request_with_body(
+        &self,
+        client: &HttpClient,
+        base_url: &str,
+        api_key: &str,
+        vars: &TemplateVars,
+        body: &Value,
+    ) -> reqwest::RequestBuilder {
+        let mut builder = client
+            .post(self.build_url(base_url, vars))
+            .header("Content-Type", "application/json");
+
+        builder = match &self.auth {
+            AuthScheme::Bearer => builder.header("Authorization", format!("Bearer {}", api_key)),
+            AuthScheme::Header { name, prefix } => {
+                builder.header(name.as_str(), format!("{}{}", prefix, api_key))
+            }
+            AuthScheme::Query { name } => builder.query(&[(name.as_str(), api_key)]),
+            AuthScheme::None => builder,
+        };
+
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), render_str(value, vars));
+        }
+
+        builder.json(body)
+    }
+
+    /// 提取响应内容
+    pub fn extract_content(&self, response: &Value) -> Option<String> {
+        response
+            .pointer(&self.content_path)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+
+    /// 提取推理内容（字段不存在或为空时返回 None）
+    pub fn extract_reasoning(&self, response: &Value) -> Option<String> {
+        response
+            .pointer(self.reasoning_path.as_deref()?)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_string())
+    }
+
+    /// 提取 token 用量（找不到输入 token 字段时返回 None）
+    pub fn extract_usage(&self, response: &Value) -> Option<ResponseUsage> {
+        let read = |pointer: &str| {
+            response
+                .pointer(pointer)
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+        };
+
+        let mut prompt_tokens = read(&self.usage.prompt_tokens)?;
+        let cached_tokens = self
+            .usage
+            .cached_tokens
+            .as_deref()
+            .and_then(read)
+            .unwrap_or(0);
+        // 统一为 OpenAI 语义（输入包含缓存读取，与流式读取一致）
+        if !self.usage.cached_included {
+            prompt_tokens += cached_tokens;
+        }
+        let mut completion_tokens = read(&self.usage.completion_tokens).unwrap_or(0);
+        let reasoning_tokens = self
+            .usage
+            .reasoning_tokens
+            .as_deref()
+            .and_then(read)
+            .unwrap_or(0);
+        // 统一为 OpenAI 语义（输出包含推理）
+        if !self.usage.reasoning_included {
+            completion_tokens += reasoning_tokens;
+        }
+        let total_tokens = self
+            .usage
+            .total_tokens
+            .as_deref()
+            .and_then(read)
+            .unwrap_or(prompt_tokens + completion_tokens);
+
+        Some(ResponseUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+            cached_tokens: cached_tokens.min(prompt_tokens),
+            reasoning_tokens: reasoning_tokens.min(completion_tokens),
+        })
+    }
+}
+
+/// 检查字符串中的占位符是否都已定义
+fn check_placeholders(text: &str) -> Result<()> {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            anyhow::bail!("占位符缺少结束符 }}: {}", text);
+        };
+        let name = after[..end].trim();
+        if !TEMPLATE_VARIABLES.contains(&name) {
+            anyhow::bail!(
+                "未知的模板占位符: {{{{{}}}}}（可用: {}）",
+                name,
+                TEMPLATE_VARIABLES.join(", ")
+            );
+        }
+        rest = &after[end + 2..];
+    }
+    Ok(())
+}
+
+fn check_value_placeholders(value: &Value) -> Result<()> {
+    match value {
+        Value::String(s) => check_placeholders(s),
+        Value::Array(items) => items.iter().try_for_each(check_value_placeholders),
+        Value::Object(map) => map.values().try_for_each(check_value_placeholders),
+        _ => Ok(()),
+    }
+}
+
+/// 若字符串恰好是单个占位符，返回变量名
+fn whole_placeholder(text: &str) -> Option<&str> {
+    text.strip_prefix("{{")
+        .and_then(|s| s.strip_suffix("}}"))
+        .map(str::trim)
+        .filter(|name| !name.contains("{{") && !name.contains("}}"))
+}
+
+fn render_str(text: &str, vars: &TemplateVars) -> String {
+    let mut output = text.to_string();
+    for name in TEMPLATE_VARIABLES {
+        let placeholder = format!("{{{{{}}}}}", name);
+        if output.contains(&placeholder) {
+            let replacement = match vars.get(name) {
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            output = output.replace(&placeholder, &replacement);
+        }
+    }
+    output
+}
+
+/// f32 按最短十进制表示写入 JSON（避免 0.2 变成 0.20000000298023224）
+fn f32_value(value: f32) -> Value {
+    let decimal = value.to_string().parse::<f64>().unwrap_or(f64::from(value));
+    serde_json::json!(decimal)
+}
+
+/// 值恰好是单个占位符且变量未设置（对象中的该字段应省略）
+fn is_unset_placeholder(value: &Value, vars: &TemplateVars) -> bool {
+    value
+        .as_str()
+        .and_then(whole_placeholder)
+        .is_some_and(|name| vars.get(name).is_none_or(Value::is_null))
+}
+
+fn render_value(value: &Value, vars: &TemplateVars) -> Value {
+    match value {
+        Value::String(s) => {
+            // 整个字符串就是占位符时保留变量原始类型（数组、数字等）
+            if let Some(var) = whole_placeholder(s).and_then(|name| vars.get(name)) {
+                return var.clone();
+            }
+            Value::String(render_str(s, vars))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, vars)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, v)| !is_unset_placeholder(v, vars))
+                .map(|(k, v)| (k.clone(), render_value(v, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn sample_vars() -> TemplateVars {
+        let messages = serde_json::json!([
+            {"role": "system", "content": "你是翻译"},
+            {"role": "user", "content": "1. Hello"},
+        ]);
+        TemplateVars::new("test-model", messages, 0.3)
+    }
+
+    #[test]
+    fn test_default_template_is_openai_compatible() {
+        let template = RequestTemplate::default();
+        assert!(template.validate().is_ok());
+
+        let body = template.render_body(&sample_vars());
+        assert_eq!(body["model"], "test-model");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 2);
+        assert!((body["temperature"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_generation_params_omit_unset_and_merge_extra_body() {
+        let template = RequestTemplate::default();
+
+        // 未设置的 top_p / max_tokens 不出现在请求体中
+        let body = template.render_body(&sample_vars());
+        assert!(body.get("top_p").is_none());
+        assert!(body.get("max_tokens").is_none());
+
+        let params = GenerationParams {
+            temperature: None,
+            top_p: Some(0.9),
+            max_tokens: Some(2048),
+            extra_body: Map::from_iter([
+                ("enable_thinking".to_string(), serde_json::json!(false)),
+                ("reasoning_effort".to_string(), serde_json::json!("low")),
+            ]),
+        };
+        let vars = sample_vars().with_generation(&params);
+        let body = template.render_stream_body(&vars);
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["top_p"], 0.9);
+        assert_eq!(body["max_tokens"], 2048);
+        assert_eq!(body["enable_thinking"], false);
+        assert_eq!(body["reasoning_effort"], "low");
+        assert_eq!(body["stream"], true);
+
+        // 嵌套位置的占位符同样省略（如 Gemini 的 generationConfig）
+        let gemini: RequestTemplate = toml::from_str(
+            r#"
+[body]
+contents = "{{chat_messages}}"
+generationConfig = { temperature = "{{temperature}}", maxOutputTokens = "{{max_tokens}}" }
+"#,
+        )
+        .unwrap();
+        let body = gemini.render_body(&vars);
+        assert_eq!(
+            body["generationConfig"],
+            serde_json::json!({ "maxOutputTokens": 2048 })
+        );
+    }
+
+    #[test]
+    fn test_stream_body_per_protocol() {
+        let vars = sample_vars();
+
+        let openai = RequestTemplate::default();
+        assert!(openai.supports_stream());
+        let body = openai.render_stream_body(&vars);
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+
+        let anthropic = RequestTemplate {
+            stream: StreamProtocol::Anthropic,
+            ..RequestTemplate::default()
+        };
+        let body = anthropic.render_stream_body(&vars);
+        assert_eq!(body["stream"], true);
+        assert!(body.get("stream_options").is_none());
+
+        // 自定义 [request] 段未声明 stream 时不启用流式
+        let custom: RequestTemplate = toml::from_str(r#"endpoint = "/generate""#).unwrap();
+        assert!(!custom.supports_stream());
+        assert!(custom.render_stream_body(&vars).get("stream").is_none());
+    }
+
+    #[test]
+    fn test_structured_body_injects_response_format() {
+        let vars = sample_vars();
+        let schema = serde_json::json!({"type": "object"});
+
+        let template = RequestTemplate {
+            structured_output: StructuredOutput::JsonSchema,
+            ..RequestTemplate::default()
+        };
+        assert!(template.supports_structured_output());
+        let body = template.render_structured_body(&vars, &schema);
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["schema"], schema);
+
+        let template = RequestTemplate {
+            structured_output: StructuredOutput::JsonObject,
+            ..RequestTemplate::default()
+        };
+        let body = template.render_structured_body(&vars, &schema);
+        assert_eq!(body["response_format"]["type"], "json_object");
+
+        let template = RequestTemplate::default();
+        assert!(!template.supports_structured_output());
+        let body = template.render_structured_body(&vars, &schema);
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_build_url_avoids_duplicate_endpoint() {
+        let template = RequestTemplate::default();
+        let vars = sample_vars();
+
+        assert_eq!(
+            template.build_url("https://api.example.com/v1/", &vars),
+            "https://api.example.com/v1/chat/completions"
+        );
+        assert_eq!(
+            template.build_url("https://api.example.com/v1/chat/completions", &vars),
+            "https://api.example.com/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_render_partial_placeholders() {
+        let template: RequestTemplate = toml::from_str(
+            r#"
+endpoint = "/models/{{model}}:generateContent"
+content_path = "/candidates/0/content/parts/0/text"
+
+[auth]
+type = "query"
+name = "key"
+
+[body]
+contents = [{ parts = [{ text = "{{user_prompt}}" }] }]
+systemInstruction = { parts = [{ text = "{{system_prompt}}" }] }
+"#,
+        )
+        .unwrap();
+        assert!(template.validate().is_ok());
+
+        let vars = sample_vars();
+        assert_eq!(
+            template.build_url("https://g.example.com/v1beta", &vars),
+            "https://g.example.com/v1beta/models/test-model:generateContent"
+        );
+
+        let body = template.render_body(&vars);
+        assert_eq!(body["contents"][0]["parts"][0]["text"], "1. Hello");
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "你是翻译");
+    }
+
+    #[test]
+    fn test_chat_messages_excludes_system() {
+        let vars = sample_vars();
+        let chat = vars.get("chat_messages").unwrap().as_array().unwrap();
+        assert_eq!(chat.len(), 1);
+        assert_eq!(chat[0]["role"], "user");
+    }
+
+    #[test]
+    fn test_extract_content_and_usage() {
+        let template: RequestTemplate = toml::from_str(
+            r#"
+content_path = "/content/0/text"
+
+[usage]
+prompt_tokens = "/usage/input_tokens"
+completion_tokens = "/usage/output_tokens"
+"#,
+        )
+        .unwrap();
+
+        let response = serde_json::json!({
+            "content": [{"type": "text", "text": "1. 你好"}],
+            "usage": {"input_tokens": 12, "output_tokens": 5}
+        });
+
+        assert_eq!(
+            template.extract_content(&response).as_deref(),
+            Some("1. 你好")
+        );
+        let usage = template.extract_usage(&response).unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.completion_tokens, 5);
+        assert_eq!(usage.total_tokens, 17);
+        assert_eq!(usage.cached_tokens, 0);
+    }
+
+    #[test]
+    fn test_extract_cached_tokens_by_default() {
+        let response = serde_json::json!({
+            "usage": {
+                "prompt_tokens": 1500,
+                "completion_tokens": 500,
+                "total_tokens": 2000,
+                "prompt_tokens_details": {"cached_tokens": 300}
+            }
+        });
+        let usage = RequestTemplate::default().extract_usage(&response).unwrap();
+        assert_eq!(usage.prompt_tokens, 1500);
+        assert_eq!(usage.cached_tokens, 300);
+    }
+
+    #[test]
+    fn test_extract_reasoning_and_tokens() {
+        let response = serde_json::json!({
+            "choices": [{"message": {"content": "1. 你好", "reasoning_content": "先看原文"}}],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 80,
+                "completion_tokens_details": {"reasoning_tokens": 60}
+            }
+        });
+        let template = RequestTemplate::default();
+        assert_eq!(
+            template.extract_reasoning(&response).as_deref(),
+            Some("先看原文")
+        );
+        let usage = template.extract_usage(&response).unwrap();
+        assert_eq!(usage.completion_tokens, 80);
+        assert_eq!(usage.reasoning_tokens, 60);
+
+        // Gemini：思考 token 不计入 candidatesTokenCount
+        let gemini: RequestTemplate = toml::from_str(
+            r#"
+content_path = "/candidates/0/content/parts/0/text"
+
+[usage]
+prompt_tokens = "/usageMetadata/promptTokenCount"
+completion_tokens = "/usageMetadata/candidatesTokenCount"
+total_tokens = "/usageMetadata/totalTokenCount"
+reasoning_tokens = "/usageMetadata/thoughtsTokenCount"
+reasoning_included = false
+"#,
+        )
+        .unwrap();
+        let response = serde_json::json!({
+            "usageMetadata": {
+                "promptTokenCount": 10,
+                "candidatesTokenCount": 5,
+                "thoughtsTokenCount": 20,
+                "totalTokenCount": 35
+            }
+        });
+        let usage = gemini.extract_usage(&response).unwrap();
+        assert_eq!(usage.completion_tokens, 25);
+        assert_eq!(usage.reasoning_tokens, 20);
+        assert_eq!(usage.total_tokens, 35);
+        assert_eq!(gemini.extract_reasoning(&response), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_placeholder() {
+        let template = RequestTemplate {
+            body: serde_json::json!({"model": "{{modle}}"}),
+            ..RequestTemplate::default()
+        };
+        assert!(template.validate().is_err());
+
+        let template = RequestTemplate {
+            auth: AuthScheme::Header {
+                name: String::new(),
+                prefix: String::new(),
+            },
+            ..RequestTemplate::default()
+        };
+        assert!(template.validate().is_err());
+    }
+}
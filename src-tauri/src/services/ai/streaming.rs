@@ -0,0 +1,420 @@
+/**
+ * SSE 流式响应解析
+ *
+ * - `SseDecoder`：把字节块拆分为 SSE `data:` 负载（支持跨块拼接）
+ * - `StreamAccumulator`：按协议累积增量文本、推理内容与最终 token 用量
+ * - `NumberedLineParser`：增量提取带序号的译文行，按行首序号定位到批内条目
+ */
+use super::reasoning::ChatReply;
+use super::request_template::{ResponseUsage, StreamProtocol};
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashSet;
+
+// 与 AITranslator::parse_translations 使用相同的序号格式（额外捕获序号）
+#[allow(clippy::unwrap_used)]
+static NUMBER_PREFIX_REGEX: Lazy<regex::Regex> =
+    Lazy::new(|| regex::Regex::new(r"^(\d+)[\.\)、:\s]+(.+)$").unwrap());
+
+/// 判断响应是否为 SSE 流
+pub fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"))
+}
+
+/// SSE 解码器：按行缓冲字节，空行分隔事件
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    pending: Vec<String>,
+}
+
+impl SseDecoder {
+    /// 输入一个字节块，返回已完整的事件数据
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.process_line(line.trim_end_matches(['\r', '\n']), &mut events);
+        }
+        events
+    }
+
+    /// 流结束：处理残留的半行和未分隔的事件
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&self.buffer).to_string();
+            self.buffer.clear();
+            self.process_line(line.trim_end_matches('\r'), &mut events);
+        }
+        self.dispatch(&mut events);
+        events
+    }
+
+    fn process_line(&mut self, line: &str, events: &mut Vec<String>) {
+        if line.is_empty() {
+            self.dispatch(events);
+        } else if let Some(data) = line.strip_prefix("data:") {
+            self.pending
+                .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+        }
+        // event:/id:/retry: 以及 ":" 注释行无需处理（Anthropic 的事件类型也在 data 中）
+    }
+
+    fn dispatch(&mut self, events: &mut Vec<String>) {
+        if !self.pending.is_empty() {
+            events.push(self.pending.join("\n"));
+            self.pending.clear();
+        }
+    }
+}
+
+/// 按协议累积流式响应
+#[derive(Debug)]
+pub struct StreamAccumulator {
+    protocol: StreamProtocol,
+    content: String,
+    reasoning: String,
+    usage: Option<ResponseUsage>,
+    done: bool,
+}
+
+impl StreamAccumulator {
+    pub fn new(protocol: StreamProtocol) -> Self {
+        Self {
+            protocol,
+            content: String::new(),
+            reasoning: String::new(),
+            usage: None,
+            done: false,
+        }
+    }
+
+    /// 处理一条事件数据，返回本次新增的文本
+    pub fn apply(&mut self, data: &str) -> Result<Option<String>, AppError> {
+        let data = data.trim();
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(None);
+        }
+
+        // 心跳或非 JSON 数据直接忽略
+        let Ok(json) = serde_json::from_str::<Value>(data) else {
+            return Ok(None);
+        };
+
+        if json.get("error").is_some_and(|e| !e.is_null()) {
+            let message = json["error"]["message"]
+                .as_str()
+                .or_else(|| json["error"].as_str())
+                .unwrap_or("未知错误");
+            return Err(AppError::translation(
+                format!("流式响应错误: {}", message),
+                true,
+            ));
+        }
+
+        let delta = match self.protocol {
+            StreamProtocol::None => None,
+            StreamProtocol::OpenAi => self.apply_openai(&json),
+            StreamProtocol::Anthropic => self.apply_anthropic(&json),
+        };
+
+        if let Some(ref text) = delta {
+            self.content.push_str(text);
+        }
+        Ok(delta)
+    }
+
+    fn apply_openai(&mut self, json: &Value) -> Option<String> {
+        // include_usage 时最后一个数据块携带 usage（choices 为空）
+        if let Some(prompt_tokens) = json.pointer("/usage/prompt_tokens").and_then(Value::as_u64) {
+            let completion_tokens = json
+                .pointer("/usage/completion_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let total_tokens = json
+                .pointer("/usage/total_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(prompt_tokens + completion_tokens);
+            let cached_tokens = json
+                .pointer("/usage/prompt_tokens_details/cached_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+                .min(prompt_tokens);
+            let reasoning_tokens = json
+                .pointer("/usage/completion_tokens_details/reasoning_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+                .min(completion_tokens);
+            self.usage = Some(ResponseUsage {
+                prompt_tokens: prompt_tokens as u32,
+                completion_tokens: completion_tokens as u32,
+                total_tokens: total_tokens as u32,
+                cached_tokens: cached_tokens as u32,
+                reasoning_tokens: reasoning_tokens as u32,
+            });
+        }
+
+        if let Some(reasoning) = json
+            .pointer("/choices/0/delta/reasoning_content")
+            .and_then(Value::as_str)
+        {
+            self.reasoning.push_str(reasoning);
+        }
+
+        json.pointer("/choices/0/delta/content")
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    }
+
+    fn apply_anthropic(&mut self, json: &Value) -> Option<String> {
+        let read = |pointer: &str| json.pointer(pointer).and_then(Value::as_u64);
+
+        match json["type"].as_str() {
+            Some("message_start") => {
+                let usage = self.usage.get_or_insert_with(ResponseUsage::default);
+                // input_tokens 不含缓存读取，统一为 OpenAI 语义（输入包含缓存）
+                usage.cached_tokens =
+                    read("/message/usage/cache_read_input_tokens").unwrap_or(0) as u32;
+                usage.prompt_tokens =
+                    read("/message/usage/input_tokens").unwrap_or(0) as u32 + usage.cached_tokens;
+                usage.completion_tokens = read("/message/usage/output_tokens").unwrap_or(0) as u32;
+                usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+                None
+            }
+            Some("content_block_delta") => {
+                // 扩展思考的 thinking_delta 单独累积
+                if let Some(thinking) = json.pointer("/delta/thinking").and_then(Value::as_str) {
+                    self.reasoning.push_str(thinking);
+                    return None;
+                }
+                json.pointer("/delta/text")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string())
+            }
+            Some("message_delta") => {
+                // output_tokens 为累计值
+                if let Some(output_tokens) = read("/usage/output_tokens") {
+                    let usage = self.usage.get_or_insert_with(ResponseUsage::default);
+                    usage.completion_tokens = output_tokens as u32;
+                    usage.total_tokens = usage.prompt_tokens + usage.completion_tokens;
+                }
+                None
+            }
+            Some("message_stop") => {
+                self.done = true;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// 是否已收到结束标记
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// 返回完整回复（推理内容与回答分开）
+    pub fn finish(self) -> ChatReply {
+        let reasoning = Some(self.reasoning).filter(|r| !r.trim().is_empty());
+        ChatReply::new(&self.content, reasoning, self.usage)
+    }
+}
+
+/// 增量序号行解析器：每收到一整行带序号的译文即返回 `(位置, 译文)`
+///
+/// 位置取自行首序号（`3.` 对应位置 2），不按出现顺序计数；序号为 0 或重复的行忽略，
+/// 超出批次范围的序号由调用方过滤
+#[derive(Debug, Default)]
+pub struct NumberedLineParser {
+    buffer: String,
+    seen: HashSet<usize>,
+}
+
+impl NumberedLineParser {
+    /// 追加增量文本，返回新完成的序号行
+    pub fn push(&mut self, delta: &str) -> Vec<(usize, String)> {
+        self.buffer.push_str(delta);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=pos).collect();
+            if let Some(parsed) = self.parse_line(&line) {
+                lines.push(parsed);
+            }
+        }
+        lines
+    }
+
+    /// 流结束：处理最后一行（通常没有换行符）
+    pub fn finish(&mut self) -> Vec<(usize, String)> {
+        let line = std::mem::take(&mut self.buffer);
+        self.parse_line(&line).into_iter().collect()
+    }
+
+    fn parse_line(&mut self, line: &str) -> Option<(usize, String)> {
+        let captures = NUMBER_PREFIX_REGEX.captures(line.trim())?;
+        let position = captures
+            .get(1)?
+            .as_str()
+            .parse::<usize>()
+            .ok()?
+            .checked_sub(1)?;
+        if !self.seen.insert(position) {
+            return None;
+        }
+        let translation = captures.get(2)?.as_str().trim().to_string();
+        Some((position, translation))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_decoder_handles_split_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.feed(b"data: {\"a\":").is_empty());
+        assert!(decoder.feed(b"1}\n").is_empty());
+        let events = decoder.feed(b"\n: keep-alive\n\ndata: [DONE]\n\n");
+        assert_eq!(events, vec!["{\"a\":1}".to_string(), "[DONE]".to_string()]);
+
+        // 多字节字符被拆分到两个块中
+        let bytes = "data: 你好\n\n".as_bytes();
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.feed(&bytes[..8]).is_empty());
+        assert_eq!(decoder.feed(&bytes[8..]), vec!["你好".to_string()]);
+
+        // 缺少结尾空行时由 finish 输出
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.feed(b"event: message_stop\ndata: {}").is_empty());
+        assert_eq!(decoder.finish(), vec!["{}".to_string()]);
+    }
+
+    #[test]
+    fn test_openai_accumulator_reads_final_usage() {
+        let mut acc = StreamAccumulator::new(StreamProtocol::OpenAi);
+        let chunks = [
+            r#"{"choices":[{"delta":{"role":"assistant","content":""}}]}"#,
+            r#"{"choices":[{"delta":{"content":"1. 你"}}]}"#,
+            r#"{"choices":[{"delta":{"content":"好\n2. 世界"}}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":20,"completion_tokens":6,"total_tokens":26}}"#,
+            "[DONE]",
+        ];
+        for chunk in chunks {
+            acc.apply(chunk).unwrap();
+        }
+        assert!(acc.is_done());
+
+        let reply = acc.finish();
+        assert_eq!(reply.content, "1. 你好\n2. 世界");
+        let usage = reply.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 20);
+        assert_eq!(usage.completion_tokens, 6);
+        assert_eq!(usage.total_tokens, 26);
+    }
+
+    #[test]
+    fn test_anthropic_accumulator() {
+        let mut acc = StreamAccumulator::new(StreamProtocol::Anthropic);
+        let events = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":30,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"1. Hola"}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":8}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        for event in events {
+            acc.apply(event).unwrap();
+        }
+        assert!(acc.is_done());
+
+        let reply = acc.finish();
+        assert_eq!(reply.content, "1. Hola");
+        let usage = reply.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 30);
+        assert_eq!(usage.completion_tokens, 8);
+        assert_eq!(usage.total_tokens, 38);
+    }
+
+    #[test]
+    fn test_accumulators_separate_reasoning() {
+        let mut acc = StreamAccumulator::new(StreamProtocol::OpenAi);
+        let chunks = [
+            r#"{"choices":[{"delta":{"reasoning_content":"先看"}}]}"#,
+            r#"{"choices":[{"delta":{"reasoning_content":"原文"}}]}"#,
+            r#"{"choices":[{"delta":{"content":"1. 你好"}}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":20,"completion_tokens":30,"completion_tokens_details":{"reasoning_tokens":25}}}"#,
+        ];
+        let deltas: Vec<_> = chunks
+            .iter()
+            .filter_map(|chunk| acc.apply(chunk).unwrap())
+            .collect();
+        assert_eq!(deltas, vec!["1. 你好".to_string()]);
+        let reply = acc.finish();
+        assert_eq!(reply.content, "1. 你好");
+        assert_eq!(reply.reasoning.as_deref(), Some("先看原文"));
+        assert_eq!(reply.usage.unwrap().reasoning_tokens, 25);
+
+        let mut acc = StreamAccumulator::new(StreamProtocol::Anthropic);
+        let events = [
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"分析"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"1. Hola"}}"#,
+        ];
+        for event in events {
+            acc.apply(event).unwrap();
+        }
+        let reply = acc.finish();
+        assert_eq!(reply.content, "1. Hola");
+        assert_eq!(reply.reasoning.as_deref(), Some("分析"));
+    }
+
+    #[test]
+    fn test_stream_error_event() {
+        let mut acc = StreamAccumulator::new(StreamProtocol::Anthropic);
+        let result = acc.apply(
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_numbered_line_parser_incremental() {
+        let mut parser = NumberedLineParser::default();
+        assert!(parser.push("1. 你").is_empty());
+        assert_eq!(parser.push("好\n2"), vec![(0, "你好".to_string())]);
+        assert_eq!(
+            parser.push(". 世界\n\n3) 再见"),
+            vec![(1, "世界".to_string())]
+        );
+        assert_eq!(parser.finish(), vec![(2, "再见".to_string())]);
+        assert!(parser.finish().is_empty());
+    }
+
+    #[test]
+    fn test_numbered_line_parser_maps_by_number() {
+        let mut parser = NumberedLineParser::default();
+        // 乱序、跳号、重复与 0 号
+        let lines = parser.push("2. 乙\n1. 甲\n0. 无效\n2. 重复\n5. 额外\n");
+        assert_eq!(
+            lines,
+            vec![
+                (1, "乙".to_string()),
+                (0, "甲".to_string()),
+                (4, "额外".to_string())
+            ]
+        );
+    }
+}
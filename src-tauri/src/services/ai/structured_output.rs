@@ -0,0 +1,104 @@
+/**
+ * 结构化输出（JSON）翻译协议
+ *
+ * 请求携带 `{"1": "原文", "2": "原文"}`，响应按相同的 id 返回译文，
+ * 不再依赖逐行序号解析，多行原文、合并/拆分行都不会导致整批失败。
+ */
+use crate::error::AppError;
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 构建 id→原文 的请求负载（保持顺序）
+pub fn build_payload(items: &[(String, &str)]) -> String {
+    let map: IndexMap<&str, &str> = items
+        .iter()
+        .map(|(id, text)| (id.as_str(), *text))
+        .collect();
+    serde_json::to_string_pretty(&map).unwrap_or_default()
+}
+
+/// 构建严格 JSON Schema：每个 id 都是必填的字符串字段
+pub fn build_schema(ids: &[String]) -> Value {
+    let properties: serde_json::Map<String, Value> = ids
+        .iter()
+        .map(|id| (id.clone(), serde_json::json!({ "type": "string" })))
+        .collect();
+
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": ids,
+        "additionalProperties": false,
+    })
+}
+
+/// 解析模型返回的 id→译文 对象
+///
+/// 兼容 Markdown 代码块包裹和前后多余文字；非字符串的值视为缺失
+pub fn parse_response(content: &str) -> Result<HashMap<String, String>, AppError> {
+    let start = content.find('{');
+    let end = content.rfind('}');
+    let json_text = match (start, end) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => {
+            return Err(AppError::parse(format!(
+                "结构化翻译结果不是 JSON 对象: {}",
+                content
+            )));
+        }
+    };
+
+    let object: serde_json::Map<String, Value> = serde_json::from_str(json_text)
+        .map_err(|e| AppError::parse(format!("无法解析结构化翻译结果: {}", e)))?;
+
+    Ok(object
+        .into_iter()
+        .filter_map(|(id, value)| match value {
+            Value::String(text) => Some((id.trim().to_string(), text)),
+            _ => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_keeps_order_and_multiline_text() {
+        let items = vec![
+            ("1".to_string(), "Hello"),
+            ("2".to_string(), "Line one\nLine two"),
+        ];
+        let payload = build_payload(&items);
+        let parsed: IndexMap<String, String> = serde_json::from_str(&payload).unwrap();
+        assert_eq!(parsed.keys().collect::<Vec<_>>(), vec!["1", "2"]);
+        assert_eq!(parsed["2"], "Line one\nLine two");
+    }
+
+    #[test]
+    fn test_schema_requires_all_ids() {
+        let schema = build_schema(&["1".to_string(), "2".to_string()]);
+        assert_eq!(schema["required"], serde_json::json!(["1", "2"]));
+        assert_eq!(schema["properties"]["2"]["type"], "string");
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_parse_response_with_code_fence_and_missing_ids() {
+        let content = "```json\n{\"1\": \"你好\", \"3\": \"第一行\\n第二行\", \"4\": null}\n```";
+        let map = parse_response(content).unwrap();
+        assert_eq!(map.get("1").map(String::as_str), Some("你好"));
+        assert_eq!(map.get("3").map(String::as_str), Some("第一行\n第二行"));
+        assert!(!map.contains_key("2"));
+        assert!(!map.contains_key("4"));
+    }
+
+    #[test]
+    fn test_parse_response_rejects_non_json() {
+        assert!(parse_response("1. 你好\n2. 世界").is_err());
+        assert!(parse_response("{not json}").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+/**
+ * 离线 BPE 分词器
+ *
+ * - 内置 cl100k_base / o200k_base 词表（tiktoken 兼容），无需联网即可精确计算 token 数
+ * - 插件可在 `[provider]` 或单个模型上声明 `tokenizer`，未声明时使用 cl100k_base
+ * - 聊天消息按 OpenAI 的计数规则附加每条消息的封装开销
+ */
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 每条聊天消息的固定开销（角色标记与分隔符）
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// 回复引导的固定开销（`<|start|>assistant<|message|>`）
+const TOKENS_PER_REPLY: usize = 3;
+
+/// 模型使用的分词器
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum TokenizerKind {
+    /// GPT-3.5 / GPT-4 / 多数 OpenAI 兼容模型
+    #[default]
+    #[serde(rename = "cl100k_base")]
+    Cl100kBase,
+    /// GPT-4o / o 系列
+    #[serde(rename = "o200k_base")]
+    O200kBase,
+}
+
+impl TokenizerKind {
+    fn bpe(self) -> &'static CoreBPE {
+        match self {
+            Self::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Self::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        }
+    }
+
+    /// 文本的 token 数（特殊标记按普通文本计）
+    pub fn count(self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        self.bpe().encode_ordinary(text).len()
+    }
+
+    /// 一组聊天消息（角色, 内容）作为请求输入时的 token 数
+    pub fn count_messages<'a>(
+        self,
+        messages: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> usize {
+        messages
+            .into_iter()
+            .map(|(role, content)| TOKENS_PER_MESSAGE + self.count(role) + self.count(content))
+            .sum::<usize>()
+            + TOKENS_PER_REPLY
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_known_strings() {
+        assert_eq!(TokenizerKind::Cl100kBase.count(""), 0);
+        assert_eq!(TokenizerKind::Cl100kBase.count("hello world"), 2);
+        assert_eq!(TokenizerKind::O200kBase.count("hello world"), 2);
+        // 特殊标记按普通文本计，不会被识别为控制标记
+        assert!(TokenizerKind::Cl100kBase.count("<|endoftext|>") > 1);
+    }
+
+    #[test]
+    fn test_cjk_counts_differ_from_character_heuristic() {
+        let text = "打开文件失败，请检查路径是否正确";
+        let cl100k = TokenizerKind::Cl100kBase.count(text);
+        let o200k = TokenizerKind::O200kBase.count(text);
+        // 4 字符/token 的经验值会严重低估中文
+        assert!(cl100k > text.chars().count() / 4 * 2);
+        // o200k 对中文的压缩率更高
+        assert!(o200k <= cl100k);
+    }
+
+    #[test]
+    fn test_message_overhead() {
+        let kind = TokenizerKind::Cl100kBase;
+        let tokens =
+            kind.count_messages([("system", "You are a translator."), ("user", "hello world")]);
+        let content = kind.count("system")
+            + kind.count("You are a translator.")
+            + kind.count("user")
+            + kind.count("hello world");
+        assert_eq!(tokens, content + 2 * TOKENS_PER_MESSAGE + TOKENS_PER_REPLY);
+    }
+
+    #[test]
+    fn test_serde_names() {
+        let kind: TokenizerKind = serde_json::from_str("\"o200k_base\"").unwrap();
+        assert_eq!(kind, TokenizerKind::O200kBase);
+        assert_eq!(
+            serde_json::to_string(&TokenizerKind::Cl100kBase).unwrap(),
+            "\"cl100k_base\""
+        );
+    }
+}
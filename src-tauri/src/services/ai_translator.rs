@@ -38,15 +38,31 @@
 //! - `translation_stats`: 统计模块，记录 token 使用和成本
 
 use crate::error::AppError;
-use crate::services::term_library::TermLibrary;
-use crate::services::translation_memory::TranslationMemory;
+use crate::services::fuzzy_match::{self, FuzzyMatch, FuzzyMatchConfig};
+use crate::services::segment_memory::{ReviewStatus, SegmentEntry, SegmentMemory};
+use crate::services::tm_registry::{DEFAULT_MEMORY, MemoryStack};
+use crate::services::tm_store;
+use crate::services::translation_memory::{
+    CONTEXT_FALLBACK_PENALTY, TmHit, TmKey, TranslationMemory,
+};
+use crate::services::usage_ledger::{self, UsageContext, UsageRecord};
 // 使用新的提示词和统计模块
+use crate::services::ai::TokenizerKind;
+use crate::services::ai::batch_sizing::BatchSizer;
+use crate::services::ai::generation_params::{GenerationParams, ModelParameterProfile};
+use crate::services::ai::rate_limiter::{self, RateLimiter};
+use crate::services::ai::reasoning::{ChatReply, ThinkTagFilter};
+use crate::services::ai::request_template::{RequestTemplate, ResponseUsage, TemplateVars};
+use crate::services::ai::streaming::{
+    NumberedLineParser, SseDecoder, StreamAccumulator, is_event_stream,
+};
 use crate::services::prompt_builder;
+use crate::services::response_cache::{self, CacheKeyParts};
 use crate::services::translation_stats::{BatchStats, TokenStats};
-use crate::utils::common::is_simple_phrase;
-use crate::utils::paths::get_translation_memory_path;
+use crate::utils::common::{is_simple_phrase, truncate_chars};
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[cfg(feature = "ts-rs")]
 use ts_rs::TS;
@@ -54,6 +70,26 @@ use ts_rs::TS;
 // ========== 重新导出类型 ==========
 pub use crate::services::prompt_builder::DEFAULT_SYSTEM_PROMPT;
 
+/// 未配置时的单次请求超时（含读取完整响应；超时视为网络错误并重试）
+const DEFAULT_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// 配置的单次请求超时（`AppConfig.timeout_seconds`）
+static REQUEST_TIMEOUT: parking_lot::RwLock<Option<std::time::Duration>> =
+    parking_lot::RwLock::new(None);
+
+/// 应用请求超时配置（随配置加载/更新同步；0 表示使用默认值）
+pub fn configure_request_timeout(seconds: u64) {
+    *REQUEST_TIMEOUT.write() = (seconds > 0).then(|| std::time::Duration::from_secs(seconds));
+}
+
+/// 当前请求超时（新建的翻译器读取）
+fn current_request_timeout() -> std::time::Duration {
+    REQUEST_TIMEOUT.read().unwrap_or(DEFAULT_REQUEST_TIMEOUT)
+}
+
+/// 单批提示词中最多注入的记忆库参考条目数
+const MAX_PROMPT_REFERENCES: usize = 20;
+
 // ========== Phase 1: AI 供应商配置系统 ==========
 
 // ========== 废弃代码已移除 ==========
@@ -102,6 +138,7 @@ pub struct ProxyConfig {
 /// - `base_url`: 可选的自定义 API 地址（默认使用供应商的默认地址）
 /// - `model`: 可选的自定义模型名称（默认使用供应商的默认模型）
 /// - `proxy`: 可选的代理配置
+/// - `generation`: 生成参数（temperature / top_p / max_tokens / extra_body）
 ///
 /// # 示例
 ///
@@ -118,6 +155,10 @@ pub struct ProxyConfig {
 ///         port: 7890,
 ///         enabled: true,
 ///     }),
+///     generation: GenerationParams {
+///         temperature: Some(0.2),
+///         ..Default::default()
+///     },
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,38 +172,25 @@ pub struct AIConfig {
     pub base_url: Option<String>, // 可选的自定义URL
     pub model: Option<String>,    // 可选的自定义模型
     pub proxy: Option<ProxyConfig>,
+    /// 生成参数（覆盖插件中的模型默认值）
+    #[serde(default)]
+    pub generation: GenerationParams,
 }
 
 // ========== Chat API 数据结构 ==========
+// 请求体与响应解析由供应商的 RequestTemplate 决定（见 services::ai::request_template）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ChatMessage {
     role: String,
     content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    temperature: f32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-    usage: Option<Usage>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Choice {
-    message: ChatMessage,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Usage {
-    prompt_tokens: u32,
-    completion_tokens: u32,
-    total_tokens: u32,
+/// 只读的参考记忆库（查询与命中归属用，不写回）
+#[derive(Debug, Clone)]
+struct ReferenceMemory {
+    name: String,
+    tm: TranslationMemory,
+    segments: SegmentMemory,
 }
 
 /// AI 翻译器
@@ -183,7 +211,8 @@ struct Usage {
 /// - `conversation_history`: 对话历史（用于上下文翻译）
 /// - `token_stats`: token 使用统计
 /// - `batch_stats`: 批量翻译统计
-/// - `tm`: 翻译记忆库（可选）
+/// - `tm`: 翻译记忆库（可选，可写的项目记忆库）
+/// - `references`: 只读参考记忆库（按优先级排列）
 /// - `target_language`: 目标语言（可选）
 ///
 /// # 示例
@@ -212,18 +241,30 @@ pub struct AITranslator {
     model: String,
     provider_id: String, // 插件化：使用 provider_id 字符串
     provider_info: Option<crate::services::ai::ProviderInfo>, // 缓存供应商信息
+    request_template: RequestTemplate, // 插件声明的线协议
+    rate_limiter: Arc<RateLimiter>, // 与同供应商/模型的任务共享
+    tokenizer: TokenizerKind, // 插件声明的分词器（用于精确计算 token）
     system_prompt: String,
+    generation: GenerationParams, // 实际发送的生成参数（参与请求体与响应缓存键）
+    request_timeout: std::time::Duration,
     conversation_history: Vec<ChatMessage>,
     #[allow(dead_code)]
     max_history_tokens: usize,
     token_stats: TokenStats,
+    last_reasoning: Option<String>, // 最近一次请求的推理过程（写入提示词日志）
     #[allow(dead_code)]
     use_tm: bool,
     tm: Option<TranslationMemory>,
+    segments: Option<SegmentMemory>, // 句段级记忆库（与 tm 同时启用）
+    memory_name: String,             // tm/segments 所属的记忆库（学习结果写回该库）
+    references: Vec<ReferenceMemory>, // 只读参考记忆库（优先级低于项目记忆库）
+    fuzzy_match: FuzzyMatchConfig,   // 记忆库模糊匹配（创建时取自全局配置）
     // Phase 5: 目标语言（用于生成翻译提示词）
     target_language: Option<String>,
+    source_language: Option<String>, // 源语言（参与记忆库键，未设置时与旧键通用）
     // 统计信息
     pub batch_stats: BatchStats,
+    usage_context: UsageContext, // 写入用量台账的文件/项目归属
 }
 
 impl AITranslator {
@@ -270,8 +311,9 @@ impl AITranslator {
         let base_url = base_url.unwrap_or_else(|| "https://api.moonshot.cn/v1".to_string());
 
         // 加载术语库并构建系统提示词
-        let term_library_path = crate::utils::paths::get_term_library_path();
-        let term_library = TermLibrary::load_from_file(&term_library_path).ok();
+        let term_library = tm_store::shared()
+            .and_then(|store| store.load_term_library())
+            .ok();
 
         // 调试日志：检查术语库状态
         if let Some(ref lib) = term_library {
@@ -285,19 +327,21 @@ impl AITranslator {
                 }
             );
         } else {
-            crate::app_log!("[AITranslator] 术语库文件不存在或加载失败");
+            crate::app_log!("[AITranslator] 术语库加载失败");
         }
 
         let system_prompt =
             prompt_builder::build_system_prompt(custom_system_prompt, term_library.as_ref());
 
-        // 从文件加载TM（合并内置短语和已保存的翻译）
-        let tm = if use_tm {
-            Some(TranslationMemory::new_from_file(
-                get_translation_memory_path(),
-            )?)
+        // TM（短语表与句段库）按需查询嵌入式存储，不整表加载
+        let (tm, segments) = if use_tm {
+            let store = tm_store::shared()?;
+            (
+                Some(TranslationMemory::open(Arc::clone(&store))?),
+                Some(SegmentMemory::open(store)),
+            )
         } else {
-            None
+            (None, None)
         };
 
         Ok(Self {
@@ -307,14 +351,28 @@ impl AITranslator {
             model: "moonshot-v1-auto".to_string(),
             provider_id: "moonshot".to_string(), // 插件化：默认使用 Moonshot
             provider_info: None,                 // 延迟加载
+            request_template: RequestTemplate::default(),
+            rate_limiter: Self::get_rate_limiter("moonshot", "moonshot-v1-auto"),
+            tokenizer: Self::get_tokenizer("moonshot", "moonshot-v1-auto"),
             system_prompt,
+            generation: ModelParameterProfile::default()
+                .resolve(&GenerationParams::default())
+                .map_err(|e| AppError::config(e.to_string()))?,
+            request_timeout: current_request_timeout(),
             conversation_history: Vec::new(),
             max_history_tokens: 2000,
             token_stats: TokenStats::default(),
+            last_reasoning: None,
             use_tm,
             tm,
+            segments,
+            memory_name: DEFAULT_MEMORY.to_string(),
+            references: Vec::new(),
+            fuzzy_match: fuzzy_match::current(),
             target_language, // Phase 5: 目标语言
+            source_language: None,
             batch_stats: BatchStats::default(),
+            usage_context: UsageContext::default(),
         })
     }
 
@@ -342,6 +400,7 @@ impl AITranslator {
     ///     base_url: None,
     ///     model: None,
     ///     proxy: None,
+    ///     generation: Default::default(),
     /// };
     ///
     /// let translator = AITranslator::new_with_config(
@@ -355,6 +414,7 @@ impl AITranslator {
     /// # 错误
     ///
     /// - 代理配置无效时返回错误
+    /// - 生成参数不符合模型能力时返回错误
     /// - 加载翻译记忆库失败时返回错误
     /// - 加载术语库失败时会记录日志但不返回错误
     pub fn new_with_config(
@@ -366,8 +426,9 @@ impl AITranslator {
         // 构建HTTP客户端（支持代理）
         let client = Self::build_client_with_proxy(config.proxy.clone())?;
 
-        // 从插件系统获取供应商信息
+        // 从插件系统获取供应商信息和请求模板
         let provider_info = Self::get_provider_info(&config.provider_id)?;
+        let request_template = Self::get_request_template(&config.provider_id)?;
 
         // 使用自定义URL或默认URL
         let base_url = config
@@ -379,9 +440,14 @@ impl AITranslator {
             .model
             .unwrap_or_else(|| provider_info.default_model.clone());
 
+        // 合并配置与模型默认的生成参数，并按模型能力校验
+        let generation =
+            Self::resolve_generation_params(&config.provider_id, &model, &config.generation)?;
+
         // 加载术语库并构建系统提示词
-        let term_library_path = crate::utils::paths::get_term_library_path();
-        let term_library = TermLibrary::load_from_file(&term_library_path).ok();
+        let term_library = tm_store::shared()
+            .and_then(|store| store.load_term_library())
+            .ok();
 
         // 调试日志：检查术语库状态
         if let Some(ref lib) = term_library {
@@ -395,19 +461,21 @@ impl AITranslator {
                 }
             );
         } else {
-            crate::app_log!("[AITranslator] 术语库文件不存在或加载失败");
+            crate::app_log!("[AITranslator] 术语库加载失败");
         }
 
         let system_prompt =
             prompt_builder::build_system_prompt(custom_system_prompt, term_library.as_ref());
 
-        // 从文件加载TM
-        let tm = if use_tm {
-            Some(TranslationMemory::new_from_file(
-                get_translation_memory_path(),
-            )?)
+        // TM（短语表与句段库）按需查询嵌入式存储，不整表加载
+        let (tm, segments) = if use_tm {
+            let store = tm_store::shared()?;
+            (
+                Some(TranslationMemory::open(Arc::clone(&store))?),
+                Some(SegmentMemory::open(store)),
+            )
         } else {
-            None
+            (None, None)
         };
 
         crate::app_log!(
@@ -421,6 +489,9 @@ impl AITranslator {
             }
         );
 
+        let rate_limiter = Self::get_rate_limiter(&config.provider_id, &model);
+        let tokenizer = Self::get_tokenizer(&config.provider_id, &model);
+
         Ok(Self {
             client,
             api_key: config.api_key,
@@ -428,14 +499,26 @@ impl AITranslator {
             model,
             provider_id: config.provider_id.clone(),
             provider_info: Some(provider_info), // 缓存 provider 信息
+            rate_limiter,
+            tokenizer,
+            request_template,
             system_prompt,
+            generation,
+            request_timeout: current_request_timeout(),
             conversation_history: Vec::new(),
             max_history_tokens: 2000,
             token_stats: TokenStats::default(),
+            last_reasoning: None,
             use_tm,
             tm,
+            segments,
+            memory_name: DEFAULT_MEMORY.to_string(),
+            references: Vec::new(),
+            fuzzy_match: fuzzy_match::current(),
             target_language, // Phase 5: 目标语言
+            source_language: None,
             batch_stats: BatchStats::default(),
+            usage_context: UsageContext::default(),
         })
     }
 
@@ -450,6 +533,189 @@ impl AITranslator {
         })
     }
 
+    /// 从插件系统获取供应商的请求模板
+    fn get_request_template(provider_id: &str) -> Result<RequestTemplate, AppError> {
+        use crate::services::ai::provider::with_global_registry;
+
+        with_global_registry(|registry| {
+            registry
+                .get_provider(provider_id)
+                .map(|provider| provider.request_template())
+                .ok_or_else(|| AppError::plugin(format!("未找到供应商: {}", provider_id)))
+        })
+    }
+
+    /// 获取 (供应商, 模型) 共享的限流器（限流配置来自插件的模型定义）
+    fn get_rate_limiter(provider_id: &str, model: &str) -> Arc<RateLimiter> {
+        use crate::services::ai::provider::with_global_registry;
+
+        let limit = with_global_registry(|registry| {
+            registry
+                .get_provider(provider_id)
+                .map(|provider| provider.rate_limit(model))
+                .unwrap_or_default()
+        });
+        rate_limiter::shared_limiter(provider_id, model, limit)
+    }
+
+    /// 按插件声明的模型能力合并并校验生成参数
+    pub fn resolve_generation_params(
+        provider_id: &str,
+        model: &str,
+        overrides: &GenerationParams,
+    ) -> Result<GenerationParams, AppError> {
+        use crate::services::ai::provider::with_global_registry;
+
+        let profile = with_global_registry(|registry| {
+            registry
+                .get_provider(provider_id)
+                .map(|provider| provider.parameter_profile(model))
+                .unwrap_or_default()
+        });
+        profile
+            .resolve(overrides)
+            .map_err(|e| AppError::config(format!("模型 {} 的生成参数无效: {}", model, e)))
+    }
+
+    /// 从插件系统获取模型声明的分词器（未找到供应商时使用默认分词器）
+    fn get_tokenizer(provider_id: &str, model: &str) -> TokenizerKind {
+        use crate::services::ai::provider::with_global_registry;
+
+        with_global_registry(|registry| {
+            registry
+                .get_provider(provider_id)
+                .map(|provider| provider.tokenizer(model))
+                .unwrap_or_default()
+        })
+    }
+
+    /// 消息数组作为请求输入的精确 token 数
+    fn count_message_tokens(&self, messages: &[ChatMessage]) -> usize {
+        self.tokenizer.count_messages(
+            messages
+                .iter()
+                .map(|msg| (msg.role.as_str(), msg.content.as_str())),
+        )
+    }
+
+    /// 按请求模板构建聊天请求（URL、认证、请求头、请求体）
+    fn build_chat_request(
+        &self,
+        messages: &[ChatMessage],
+        stream: bool,
+        response_schema: Option<&serde_json::Value>,
+    ) -> reqwest::RequestBuilder {
+        let messages = serde_json::to_value(messages).unwrap_or_default();
+        let vars = TemplateVars::new(&self.model, messages, 0.0).with_generation(&self.generation);
+        let template = &self.request_template;
+        if let Some(schema) = response_schema {
+            template.build_structured_request(
+                &self.client,
+                &self.base_url,
+                &self.api_key,
+                &vars,
+                schema,
+            )
+        } else if stream {
+            template.build_stream_request(&self.client, &self.base_url, &self.api_key, &vars)
+        } else {
+            template.build_request(&self.client, &self.base_url, &self.api_key, &vars)
+        }
+    }
+
+    /// 读取 SSE 流式响应，每完成一行带序号的译文即回调 `on_line(位置, 译文)`
+    ///
+    /// 逐行结果是临时的：重试或拆分后的请求按同一位置重新上报覆盖，最终以完整解析的结果为准
+    async fn read_event_stream(
+        &self,
+        mut response: reqwest::Response,
+        on_line: Option<&(dyn Fn(usize, &str) + Send + Sync)>,
+    ) -> Result<ChatReply, AppError> {
+        let mut decoder = SseDecoder::default();
+        let mut accumulator = StreamAccumulator::new(self.request_template.stream);
+        let mut think_filter = ThinkTagFilter::default();
+        let mut line_parser = NumberedLineParser::default();
+
+        // 正文中的 <think> 块不上屏
+        let mut emit = |delta: &str, line_parser: &mut NumberedLineParser| {
+            if let Some(callback) = on_line {
+                for (position, line) in line_parser.push(&think_filter.push(delta)) {
+                    callback(position, &line);
+                }
+            }
+        };
+
+        while let Some(chunk) = response.chunk().await? {
+            for data in decoder.feed(&chunk) {
+                if let Some(delta) = accumulator.apply(&data)? {
+                    emit(&delta, &mut line_parser);
+                }
+            }
+            if accumulator.is_done() {
+                break;
+            }
+        }
+        for data in decoder.finish() {
+            if let Some(delta) = accumulator.apply(&data)? {
+                emit(&delta, &mut line_parser);
+            }
+        }
+        if let Some(callback) = on_line {
+            let mut lines = line_parser.push(&think_filter.finish());
+            lines.extend(line_parser.finish());
+            for (position, line) in lines {
+                callback(position, &line);
+            }
+        }
+
+        let reply = accumulator.finish();
+        Self::ensure_answer(&reply)?;
+        crate::app_log!(
+            "[API响应] 流式完成, {} 字符, tokens: {}",
+            reply.content.chars().count(),
+            reply.usage.map(|u| u.total_tokens).unwrap_or(0)
+        );
+        Ok(reply)
+    }
+
+    /// 回答为空时报错（推理模型可能在思考阶段就用完了 max_tokens）
+    fn ensure_answer(reply: &ChatReply) -> Result<(), AppError> {
+        if !reply.content.trim().is_empty() {
+            return Ok(());
+        }
+        let message = if reply.reasoning.is_some() {
+            "AI响应只有推理过程、没有译文（可能已达到 max_tokens 上限）"
+        } else {
+            "AI响应为空"
+        };
+        Err(AppError::translation(message, false))
+    }
+
+    /// 按请求模板解析响应，分离推理内容与回答
+    fn parse_chat_response(&self, response: &serde_json::Value) -> Result<ChatReply, AppError> {
+        let content = self
+            .request_template
+            .extract_content(response)
+            .ok_or_else(|| {
+                AppError::translation(
+                    format!(
+                        "AI响应为空: 未找到字段 {}",
+                        self.request_template.content_path
+                    ),
+                    false,
+                )
+            })?;
+        let reply = ChatReply::new(
+            &content,
+            self.request_template.extract_reasoning(response),
+            self.request_template.extract_usage(response),
+        );
+        if reply.reasoning.is_some() {
+            Self::ensure_answer(&reply)?;
+        }
+        Ok(reply)
+    }
+
     /// 获取供应商显示名称（带缓存）
     fn get_provider_display_name(&self) -> String {
         if let Some(ref info) = self.provider_info {
@@ -542,7 +808,7 @@ impl AITranslator {
         progress_callback: Option<Box<dyn Fn(usize, String) + Send + Sync>>,
         stats_callback: Option<Box<dyn Fn(BatchStats, TokenStats) + Send + Sync>>,
     ) -> Result<Vec<String>, AppError> {
-        self.translate_batch_internal(texts, progress_callback, Some(stats_callback), None)
+        self.translate_batch_internal(texts, progress_callback, Some(stats_callback), None, &[])
             .await
     }
 
@@ -592,6 +858,25 @@ impl AITranslator {
         texts: Vec<String>,
         progress_callback: Option<Box<dyn Fn(usize, String) + Send + Sync>>,
         stats_callback: Option<Option<Box<dyn Fn(BatchStats, TokenStats) + Send + Sync>>>,
+    ) -> Result<(Vec<String>, Vec<String>), AppError> {
+        self.translate_batch_with_context(texts, &[], progress_callback, stats_callback)
+            .await
+    }
+
+    /// 带 msgctxt 翻译并返回每个条目的来源
+    ///
+    /// `contexts` 与 `texts` 按下标对应（可短于 `texts`）；记忆库按上下文查询和学习
+    #[tracing::instrument(
+        name = "translate_batch_with_context",
+        skip(self, contexts, progress_callback, stats_callback),
+        fields(text_count = texts.len())
+    )]
+    pub async fn translate_batch_with_context(
+        &mut self,
+        texts: Vec<String>,
+        contexts: &[Option<String>],
+        progress_callback: Option<Box<dyn Fn(usize, String) + Send + Sync>>,
+        stats_callback: Option<Option<Box<dyn Fn(BatchStats, TokenStats) + Send + Sync>>>,
     ) -> Result<(Vec<String>, Vec<String>), AppError> {
         if texts.is_empty() {
             return Ok((Vec::new(), Vec::new()));
@@ -601,7 +886,13 @@ impl AITranslator {
         let mut sources = vec![String::from("unknown"); texts.len()];
 
         let translations = self
-            .translate_batch_internal(texts, progress_callback, stats_callback, Some(&mut sources))
+            .translate_batch_internal(
+                texts,
+                progress_callback,
+                stats_callback,
+                Some(&mut sources),
+                contexts,
+            )
             .await?;
         Ok((translations, sources))
     }
@@ -612,6 +903,7 @@ impl AITranslator {
         progress_callback: Option<Box<dyn Fn(usize, String) + Send + Sync>>,
         stats_callback: Option<Option<Box<dyn Fn(BatchStats, TokenStats) + Send + Sync>>>,
         mut sources: Option<&mut Vec<String>>, // 可选的来源跟踪
+        contexts: &[Option<String>],           // 每条文本的 msgctxt（可为空）
     ) -> Result<Vec<String>, AppError> {
         if texts.is_empty() {
             return Ok(Vec::new());
@@ -630,56 +922,46 @@ impl AITranslator {
         self.batch_stats.deduplicated = 0;
         self.batch_stats.ai_translated = 0;
         self.batch_stats.tm_learned = 0;
+        self.batch_stats.cache_hits = 0;
 
         // Step 1: 使用翻译记忆库进行预翻译 + 去重（保持顺序）
         let mut result = vec![String::new(); texts.len()];
         let mut untranslated_indices = Vec::new();
 
+        // 按 (上下文, 原文) 去重：同一原文在不同 msgctxt 下可能有不同译法，分别请求
         let mut unique_texts_ordered: Vec<String> = Vec::new();
-        let mut unique_text_to_indices: std::collections::HashMap<String, Vec<usize>> =
+        let mut unique_contexts: Vec<Option<String>> = Vec::new();
+        let mut unique_indices: Vec<Vec<usize>> = Vec::new();
+        let mut unique_positions: std::collections::HashMap<(Option<&str>, &str), usize> =
             std::collections::HashMap::new();
 
-        if let Some(ref mut tm) = self.tm {
-            for (i, text) in texts.iter().enumerate() {
-                // 修复：传入目标语言，避免跨语言命中
-                if let Some(translation) = tm.get_translation(text, self.target_language.as_deref())
-                {
-                    // TM命中
-                    result[i] = translation.clone();
-                    self.batch_stats.tm_hits += 1;
-                    // 记录来源为TM
-                    if let Some(ref mut sources_vec) = sources {
-                        sources_vec[i] = String::from("tm");
-                    }
-                    // 按顺序上报TM命中进度
-                    if let Some(ref callback) = progress_callback {
-                        callback(i, translation.clone());
-                    }
-                } else {
-                    // TM未命中，记录到去重map
-                    untranslated_indices.push(i);
-
-                    if !unique_text_to_indices.contains_key(text) {
-                        unique_texts_ordered.push(text.clone());
-                    }
-                    unique_text_to_indices
-                        .entry(text.clone())
-                        .or_insert_with(Vec::new)
-                        .push(i);
+        for (i, text) in texts.iter().enumerate() {
+            let context = contexts.get(i).and_then(|context| context.as_deref());
+            if let Some((translation, source)) = self.reuse_from_memory(text, context) {
+                // 记忆库命中（短语表、句段库或达到阈值的模糊匹配）
+                result[i] = translation.clone();
+                self.batch_stats.tm_hits += 1;
+                if let Some(ref mut sources_vec) = sources {
+                    sources_vec[i] = source;
                 }
-            }
-        } else {
-            // 没有TM，直接去重
-            for (i, text) in texts.iter().enumerate() {
+                // 按顺序上报TM命中进度
+                if let Some(ref callback) = progress_callback {
+                    callback(i, translation);
+                }
+            } else {
+                // TM未命中（或未启用TM），记录到去重map
                 untranslated_indices.push(i);
 
-                if !unique_text_to_indices.contains_key(text) {
-                    unique_texts_ordered.push(text.clone());
-                }
-                unique_text_to_indices
-                    .entry(text.clone())
-                    .or_insert_with(Vec::new)
-                    .push(i);
+                let context = context.filter(|context| !context.is_empty());
+                let position = *unique_positions
+                    .entry((context, text.as_str()))
+                    .or_insert_with(|| {
+                        unique_texts_ordered.push(text.clone());
+                        unique_contexts.push(context.map(str::to_string));
+                        unique_indices.push(Vec::new());
+                        unique_indices.len() - 1
+                    });
+                unique_indices[position].push(i);
             }
         }
 
@@ -707,15 +989,46 @@ impl AITranslator {
                 self.batch_stats.deduplicated
             );
 
-            const BATCH_SIZE: usize = 25;
-            let mut ai_translations = Vec::new();
-            let total_batches = (unique_list.len() + BATCH_SIZE - 1) / BATCH_SIZE;
+            // 按模型上下文窗口与 token 估算动态分批；数量不匹配的批次对半拆分重试
+            let sizer = self.batch_sizer();
+            let mut ai_translations: Vec<Option<String>> = vec![None; unique_list.len()];
+            // 已通过流式上报的译文（按去重后的位置），避免完成后重复上报
+            let streamed_lines: parking_lot::Mutex<std::collections::HashMap<usize, String>> =
+                parking_lot::Mutex::new(std::collections::HashMap::new());
+            let estimated_batches = sizer
+                .plan(&unique_list, self.prompt_overhead_tokens())
+                .len();
+            // 拆分后待重试的批次（后进先出，保证按顺序完成）
+            let mut split_batches: Vec<std::ops::Range<usize>> = Vec::new();
+            let mut next_start = 0;
+            let mut batch_idx = 0;
+
+            loop {
+                let range = match split_batches.pop() {
+                    Some(range) => range,
+                    None if next_start < unique_list.len() => {
+                        // 对话历史挤占过多上下文时清空，保证本批有足够预算
+                        if sizer.history_too_large(self.prompt_overhead_tokens()) {
+                            crate::app_log!("[分批翻译] 对话历史过长，清空后继续");
+                            self.clear_conversation_history();
+                        }
+                        let len = sizer.next_batch_len(
+                            &unique_list[next_start..],
+                            self.prompt_overhead_tokens(),
+                        );
+                        let range = next_start..next_start + len;
+                        next_start += len;
+                        range
+                    }
+                    None => break,
+                };
+                let chunk = &unique_list[range.clone()];
+                let chunk_contexts = &unique_contexts[range.clone()];
 
-            for (batch_idx, chunk) in unique_list.chunks(BATCH_SIZE).enumerate() {
                 crate::app_log!(
-                    "[分批翻译] 批次 {}/{}, 当前批{}条",
+                    "[分批翻译] 批次 {}/~{}, 当前批{}条",
                     batch_idx + 1,
-                    total_batches,
+                    estimated_batches,
                     chunk.len()
                 );
 
@@ -723,10 +1036,8 @@ impl AITranslator {
                     let sample_size = std::cmp::min(3, chunk.len());
                     let sample_texts: Vec<String> =
                         chunk.iter().take(sample_size).cloned().collect();
-                    let user_prompt = prompt_builder::build_translation_prompt(
-                        &sample_texts,
-                        self.target_language.as_deref(),
-                    );
+                    let user_prompt =
+                        self.build_user_prompt_in_context(&sample_texts, chunk_contexts);
 
                     // 构建提示词日志（只显示实际发送给AI的内容，不包括API参数）
                     let full_prompt = format!(
@@ -737,19 +1048,58 @@ impl AITranslator {
 
                     let metadata = serde_json::json!({
                         "batch_index": batch_idx + 1,
-                        "total_batches": total_batches,
+                        "total_batches": estimated_batches,
                         "batch_size": chunk.len(),
                         "sample_size": sample_size,
                         "total_items": chunk.len(),
                         "sample_texts": sample_texts,
                         "model": self.model,
-                        "temperature": 1.0,
+                        "temperature": self.generation.temperature,
                         "provider": self.get_provider_display_name(),
                     });
                     crate::services::log_prompt("批量翻译", full_prompt, Some(metadata));
                 }
 
-                let batch_translations = self.translate_with_ai(chunk.to_vec()).await?;
+                // 流式逐行上报：批内位置 -> 去重文本 -> 原始索引
+                let chunk_offset = range.start;
+                let on_line = |position: usize, line: &str| {
+                    if position >= chunk.len() {
+                        return;
+                    }
+                    let unique_pos = chunk_offset + position;
+                    streamed_lines.lock().insert(unique_pos, line.to_string());
+                    if let Some(callback) = &progress_callback {
+                        for &idx in &unique_indices[unique_pos] {
+                            callback(idx, line.to_string());
+                        }
+                    }
+                };
+                let on_line_ref: &(dyn Fn(usize, &str) + Send + Sync) = &on_line;
+
+                let batch_translations = match self
+                    .translate_chunk(
+                        chunk.to_vec(),
+                        chunk_contexts,
+                        progress_callback.as_ref().map(|_| on_line_ref),
+                    )
+                    .await
+                {
+                    Ok(batch_translations) => batch_translations,
+                    // 数量不匹配（漏行、合并行、输出被截断）：对半拆分后重试
+                    Err(AppError::Parse(msg)) if chunk.len() > 1 => {
+                        let mid = range.start + chunk.len() / 2;
+                        crate::app_log!(
+                            "[分批翻译] {}，拆分为 {} + {} 条重试",
+                            msg,
+                            mid - range.start,
+                            range.end - mid
+                        );
+                        split_batches.push(mid..range.end);
+                        split_batches.push(range.start..mid);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
 
                 if batch_idx == 0 {
                     let logs = crate::services::get_prompt_logs();
@@ -771,10 +1121,20 @@ impl AITranslator {
                             }
                             crate::services::update_prompt_response(last_idx, response);
                         }
+                        if let Some(reasoning) = self.last_reasoning() {
+                            crate::services::update_prompt_reasoning(
+                                last_idx,
+                                reasoning.to_string(),
+                            );
+                        }
                     }
                 }
 
-                ai_translations.extend(batch_translations);
+                for (slot, translation) in ai_translations[range].iter_mut().zip(batch_translations)
+                {
+                    *slot = Some(translation);
+                }
+                batch_idx += 1;
 
                 if let Some(ref stats_cb_opt) = stats_callback {
                     if let Some(stats_cb) = stats_cb_opt {
@@ -785,32 +1145,50 @@ impl AITranslator {
                 }
             }
 
-            self.batch_stats.ai_translated = unique_list.len();
+            let ai_translations: Vec<String> = ai_translations
+                .into_iter()
+                .zip(&unique_list)
+                .map(|(translation, text)| {
+                    translation
+                        .ok_or_else(|| AppError::translation(format!("缺少译文: {}", text), false))
+                })
+                .collect::<Result<_, _>>()?;
+
+            // 命中响应缓存的条目单独计数，不算作付费的 AI 调用
+            self.batch_stats.ai_translated = unique_list
+                .len()
+                .saturating_sub(self.batch_stats.cache_hits);
 
             // Step 3: 将翻译结果分发到所有对应的索引（按原始顺序）
             // 收集所有 (index, translation) 对并排序，确保按顺序上报
             let mut updates: Vec<(usize, String)> = Vec::new();
-            for (unique_text, translation) in unique_list.iter().zip(ai_translations.iter()) {
-                if let Some(indices) = unique_text_to_indices.get(unique_text) {
-                    for (local_idx, &idx) in indices.iter().enumerate() {
-                        if idx >= result.len() {
-                            crate::app_log!(
-                                "[翻译] 索引越界: idx={}, result.len={}",
-                                idx,
-                                result.len()
-                            );
-                            continue;
-                        }
-                        result[idx] = translation.clone();
-                        // 记录来源：第一个是AI翻译，其余是去重
-                        if let Some(ref mut sources_vec) = sources {
-                            sources_vec[idx] = if local_idx == 0 {
-                                String::from("ai")
-                            } else {
-                                String::from("dedup")
-                            };
-                        }
-                        // 收集更新，稍后按顺序上报
+            let streamed_lines = streamed_lines.into_inner();
+            for (unique_pos, (indices, translation)) in unique_indices
+                .iter()
+                .zip(ai_translations.iter())
+                .enumerate()
+            {
+                let already_streamed = streamed_lines.get(&unique_pos) == Some(translation);
+                for (local_idx, &idx) in indices.iter().enumerate() {
+                    if idx >= result.len() {
+                        crate::app_log!(
+                            "[翻译] 索引越界: idx={}, result.len={}",
+                            idx,
+                            result.len()
+                        );
+                        continue;
+                    }
+                    result[idx] = translation.clone();
+                    // 记录来源：第一个是AI翻译，其余是去重
+                    if let Some(ref mut sources_vec) = sources {
+                        sources_vec[idx] = if local_idx == 0 {
+                            String::from("ai")
+                        } else {
+                            String::from("dedup")
+                        };
+                    }
+                    // 收集更新，稍后按顺序上报（流式已上报且一致的跳过）
+                    if !already_streamed {
                         updates.push((idx, translation.clone()));
                     }
                 }
@@ -823,45 +1201,23 @@ impl AITranslator {
                 }
             }
 
-            // Step 4: 更新翻译记忆库（每个unique文本只学习一次）
-            // 只检查当前记忆库，不检查代码内置词库（用户清空后内置词库不参与）
-            if let Some(ref mut tm) = self.tm {
-                for (unique_text, translation) in unique_list.iter().zip(ai_translations.iter()) {
-                    if is_simple_phrase(unique_text) && translation.len() <= 50 {
-                        // 修复：构造带语言的键来检查是否已存在
-                        let check_key = if let Some(lang) = self.target_language.as_deref() {
-                            format!("{}|{}", unique_text, lang)
-                        } else {
-                            unique_text.clone()
-                        };
-
-                        if !tm.memory.contains_key(&check_key) {
-                            // 修复：保存时记录目标语言
-                            tm.add_translation(
-                                unique_text.clone(),
-                                translation.clone(),
-                                self.target_language.as_deref(),
-                            );
-                            self.batch_stats.tm_learned += 1;
-                            crate::app_log!(
-                                "[TM学习] {} -> {} ({})",
-                                unique_text,
-                                translation,
-                                self.target_language.as_deref().unwrap_or("无语言")
-                            );
-                        } else {
-                            crate::app_log!("[TM跳过] {} (已在记忆库)", unique_text);
-                        }
-                    }
-                }
+            // Step 4: 更新翻译记忆库（每个 (上下文, 原文) 学习一次，译文是带该上下文请求得到的）
+            let provider = format!("{}/{}", self.provider_id, self.model);
+            for ((unique_text, context), translation) in unique_list
+                .iter()
+                .zip(&unique_contexts)
+                .zip(ai_translations.iter())
+            {
+                self.learn_translation(unique_text, translation, context.as_deref(), &provider);
             }
         }
 
         crate::app_log!(
-            "[统计] 总{}条 | TM命中{}条 | 去重节省{}条 | AI翻译{}条 | 学习{}条",
+            "[统计] 总{}条 | TM命中{}条 | 去重节省{}条 | 缓存命中{}条 | AI翻译{}条 | 学习{}条",
             self.batch_stats.total,
             self.batch_stats.tm_hits,
             self.batch_stats.deduplicated,
+            self.batch_stats.cache_hits,
             self.batch_stats.ai_translated,
             self.batch_stats.tm_learned
         );
@@ -927,72 +1283,10 @@ impl AITranslator {
             msgs
         };
 
-        // 发送请求（带重试机制）
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages,
-            temperature: 1.0,
-        };
-
-        // 最多重试3次，指数退避策略
-        let max_retries = 3;
-        let mut chat_response: Option<ChatResponse> = None;
-        let mut last_error: Option<AppError> = None;
-
-        for retry in 0..max_retries {
-            match self
-                .client
-                .post(&format!("{}/chat/completions", self.base_url))
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("Content-Type", "application/json")
-                .json(&request)
-                .send()
-                .await
-            {
-                Ok(response) => match response.json().await {
-                    Ok(parsed) => {
-                        chat_response = Some(parsed);
-                        break;
-                    }
-                    Err(_e) => {
-                        last_error = Some(AppError::translation("解析响应体失败", true));
-                        if retry < max_retries - 1 {
-                            let delay = 2u64.pow(retry as u32);
-                            crate::app_log!(
-                                "[重试] 解析响应失败，{}秒后重试 ({}/{})",
-                                delay,
-                                retry + 1,
-                                max_retries
-                            );
-                            tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
-                        }
-                    }
-                },
-                Err(e) => {
-                    last_error = Some(AppError::from(e));
-                    if retry < max_retries - 1 {
-                        let delay = 2u64.pow(retry as u32);
-                        crate::app_log!(
-                            "[重试] 请求失败，{}秒后重试 ({}/{})",
-                            delay,
-                            retry + 1,
-                            max_retries
-                        );
-                        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
-                    }
-                }
-            }
-        }
-
-        let chat_response = chat_response.ok_or_else(|| {
-            last_error.unwrap_or_else(|| AppError::translation("未知错误", false))
-        })?;
-
-        let assistant_response = chat_response
-            .choices
-            .first()
-            .and_then(|choice| Some(choice.message.content.clone()))
-            .ok_or_else(|| AppError::translation("AI响应为空", false))?;
+        let reply = self.send_chat(&messages, None, None).await?;
+        self.record_usage(reply.usage)?;
+        self.last_reasoning = reply.reasoning;
+        let assistant_response = reply.content;
 
         // 更新对话历史（如果需要）
         self.update_conversation_history(&user_prompt, &assistant_response);
@@ -1011,6 +1305,26 @@ impl AITranslator {
         )
     )]
     pub async fn translate_with_ai(&mut self, texts: Vec<String>) -> Result<Vec<String>, AppError> {
+        self.translate_with_ai_streaming(texts, None).await
+    }
+
+    /// 翻译一批文本；传入 `on_line` 且供应商支持流式时使用 SSE，
+    /// 每收到一行带序号的译文即按行首序号回调 `(批内位置, 译文)`，最终结果仍以完整解析为准
+    pub async fn translate_with_ai_streaming(
+        &mut self,
+        texts: Vec<String>,
+        on_line: Option<&(dyn Fn(usize, &str) + Send + Sync)>,
+    ) -> Result<Vec<String>, AppError> {
+        self.translate_chunk(texts, &[], on_line).await
+    }
+
+    /// 翻译一批文本，`contexts` 为对应的 msgctxt（随提示词发送给模型，用于区分词义）
+    async fn translate_chunk(
+        &mut self,
+        texts: Vec<String>,
+        contexts: &[Option<String>],
+        on_line: Option<&(dyn Fn(usize, &str) + Send + Sync)>,
+    ) -> Result<Vec<String>, AppError> {
         // 测试模拟：仅在测试构建中生效，生产构建完全移除此分支
         #[cfg(test)]
         if self.api_key == "test_key" {
@@ -1018,11 +1332,198 @@ impl AITranslator {
             return Ok(texts);
         }
 
-        let user_prompt =
-            prompt_builder::build_translation_prompt(&texts, self.target_language.as_deref());
+        // 插件声明了结构化输出时使用 id→text JSON 协议
+        if self.request_template.supports_structured_output() {
+            return self.translate_with_ai_structured(texts, contexts).await;
+        }
 
-        // 构建消息数组
-        let messages = if self.conversation_history.is_empty() {
+        let user_prompt = self.build_user_prompt_in_context(&texts, contexts);
+        let messages = self.build_messages(&user_prompt);
+        // 序号超出本批的行（模型多输出的内容）不上报
+        let batch_len = texts.len();
+        let in_batch = move |position: usize, line: &str| {
+            if let Some(callback) = on_line.filter(|_| position < batch_len) {
+                callback(position, line);
+            }
+        };
+        let streamed_line = on_line.map(|_| &in_batch as &(dyn Fn(usize, &str) + Send + Sync));
+
+        let cache_key = self.response_cache_key(&user_prompt);
+        self.last_reasoning = None;
+        if let Some(content) = response_cache::lookup(&cache_key) {
+            match self.parse_translations(&content, &texts) {
+                Ok(translations) => {
+                    crate::app_log!("[响应缓存] 命中，跳过 {} 条的 API 请求", texts.len());
+                    self.batch_stats.cache_hits += texts.len();
+                    // 命中时没有流式输出，按解析结果补发逐行回调
+                    if let Some(callback) = on_line {
+                        for (position, translation) in translations.iter().enumerate() {
+                            callback(position, translation);
+                        }
+                    }
+                    self.update_conversation_history(&user_prompt, &content);
+                    return Ok(translations);
+                }
+                // 无法解析的缓存条目删除后改为实际请求
+                Err(e) => {
+                    crate::app_log!("[响应缓存] 缓存的响应无法解析，已删除: {}", e);
+                    response_cache::invalidate(&cache_key);
+                }
+            }
+        }
+
+        let reply = self.send_chat(&messages, streamed_line, None).await?;
+        self.record_usage(reply.usage)?;
+        self.last_reasoning = reply.reasoning;
+        let assistant_response = reply.content;
+
+        // 解析成功后才写入对话历史与缓存（数量不符的响应不能污染拆分重试的上下文）
+        let translations = self.parse_translations(&assistant_response, &texts)?;
+        self.update_conversation_history(&user_prompt, &assistant_response);
+        response_cache::store(
+            &cache_key,
+            &self.provider_id,
+            &self.model,
+            &assistant_response,
+        );
+
+        Ok(translations)
+    }
+
+    /// 结构化输出模式：按 id→text JSON 请求整批，缺失的 id 逐条重试
+    async fn translate_with_ai_structured(
+        &mut self,
+        texts: Vec<String>,
+        contexts: &[Option<String>],
+    ) -> Result<Vec<String>, AppError> {
+        let mut results: Vec<Option<String>> = vec![None; texts.len()];
+        let all_indices: Vec<usize> = (0..texts.len()).collect();
+        self.request_structured(&texts, contexts, &all_indices, &mut results)
+            .await?;
+
+        let missing: Vec<usize> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_none())
+            .map(|(i, _)| i)
+            .collect();
+        if !missing.is_empty() {
+            crate::app_log!(
+                "[结构化输出] 缺少 {}/{} 条译文，逐条重试",
+                missing.len(),
+                texts.len()
+            );
+        }
+        for idx in missing {
+            self.request_structured(&texts, contexts, &[idx], &mut results)
+                .await?;
+        }
+
+        let mut translations = Vec::with_capacity(texts.len());
+        for (i, translation) in results.into_iter().enumerate() {
+            translations.push(translation.ok_or_else(|| {
+                AppError::parse(format!("AI未返回第 {} 条的译文: {}", i + 1, texts[i]))
+            })?);
+        }
+        self.check_special_chars(&mut translations, &texts);
+
+        Ok(translations)
+    }
+
+    /// 发送一次结构化输出请求，把返回的译文按 id 写入 `results`
+    async fn request_structured(
+        &mut self,
+        texts: &[String],
+        contexts: &[Option<String>],
+        indices: &[usize],
+        results: &mut [Option<String>],
+    ) -> Result<(), AppError> {
+        use crate::services::ai::structured_output;
+
+        // id 使用原始序号（从 1 开始），单条重试时保持不变
+        let items: Vec<(String, &str)> = indices
+            .iter()
+            .map(|&i| ((i + 1).to_string(), texts[i].as_str()))
+            .collect();
+        let ids: Vec<String> = items.iter().map(|(id, _)| id.clone()).collect();
+        let item_contexts: Vec<(String, &str)> = ids
+            .iter()
+            .zip(indices)
+            .filter_map(|(id, &i)| Some((id.clone(), contexts.get(i)?.as_deref()?)))
+            .collect();
+
+        let payload = structured_output::build_payload(&items);
+        let user_prompt = prompt_builder::build_json_translation_prompt_with_context(
+            &payload,
+            &item_contexts,
+            self.target_language.as_deref(),
+        );
+        let messages = self.build_messages(&user_prompt);
+        let schema = structured_output::build_schema(&ids);
+
+        let cache_key = self.response_cache_key(&user_prompt);
+        self.last_reasoning = None;
+        // 无法解析的缓存条目删除后改为实际请求
+        let cached = response_cache::lookup(&cache_key).and_then(|content| {
+            match structured_output::parse_response(&content) {
+                Ok(translated) => Some((content, translated)),
+                Err(e) => {
+                    crate::app_log!("[响应缓存] 缓存的响应无法解析，已删除: {}", e);
+                    response_cache::invalidate(&cache_key);
+                    None
+                }
+            }
+        });
+        let from_cache = cached.is_some();
+        let (content, translated) = match cached {
+            Some(hit) => hit,
+            None => {
+                let reply = self.send_chat(&messages, None, Some(&schema)).await?;
+                self.record_usage(reply.usage)?;
+                self.last_reasoning = reply.reasoning;
+                match structured_output::parse_response(&reply.content) {
+                    Ok(translated) => (reply.content, translated),
+                    // 整批解析失败时全部视为缺失，交由逐条重试
+                    Err(e) if indices.len() > 1 => {
+                        crate::app_log!("[结构化输出] {}，改为逐条重试", e);
+                        return Ok(());
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        self.update_conversation_history(&user_prompt, &content);
+        if !from_cache {
+            response_cache::store(&cache_key, &self.provider_id, &self.model, &content);
+        }
+
+        for (id, &i) in ids.iter().zip(indices) {
+            if let Some(translation) = translated.get(id).filter(|t| !t.trim().is_empty()) {
+                results[i] = Some(translation.trim().to_string());
+                if from_cache {
+                    self.batch_stats.cache_hits += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 响应缓存键：hash(供应商, 模型, 系统提示词, 用户提示词, 生成参数)
+    fn response_cache_key(&self, user_prompt: &str) -> String {
+        CacheKeyParts {
+            provider: &self.provider_id,
+            model: &self.model,
+            system_prompt: &self.system_prompt,
+            user_prompt,
+            generation: &self.generation,
+        }
+        .hash()
+    }
+
+    /// 构建消息数组（首轮带系统提示词，后续沿用对话历史）
+    fn build_messages(&self, user_prompt: &str) -> Vec<ChatMessage> {
+        if self.conversation_history.is_empty() {
             vec![
                 ChatMessage {
                     role: "system".to_string(),
@@ -1030,233 +1531,308 @@ impl AITranslator {
                 },
                 ChatMessage {
                     role: "user".to_string(),
-                    content: user_prompt.clone(),
+                    content: user_prompt.to_string(),
                 },
             ]
         } else {
             let mut msgs = self.conversation_history.clone();
             msgs.push(ChatMessage {
                 role: "user".to_string(),
-                content: user_prompt.clone(),
+                content: user_prompt.to_string(),
             });
             msgs
-        };
-
-        // 发送请求（带重试机制）
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages,
-            temperature: 1.0,
-        };
+        }
+    }
 
-        // 最多重试3次，指数退避策略
-        let max_retries = 3;
-        let mut chat_response: Option<ChatResponse> = None;
-        let mut last_error: Option<AppError> = None;
-
-        for retry in 0..max_retries {
-            match self
-                .client
-                .post(&format!("{}/chat/completions", self.base_url))
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("Content-Type", "application/json")
-                .json(&request)
-                .send()
+    /// 发送聊天请求（带重试），返回去除推理内容后的回复与 token 用量
+    ///
+    /// `response_schema` 不为空时按插件声明的结构化输出方式约束响应格式
+    async fn send_chat(
+        &self,
+        messages: &[ChatMessage],
+        on_line: Option<&(dyn Fn(usize, &str) + Send + Sync)>,
+        response_schema: Option<&serde_json::Value>,
+    ) -> Result<ChatReply, AppError> {
+        // 最多尝试3次：仅对网络错误、408/429/5xx 重试，401/403 等立即失败
+        const MAX_ATTEMPTS: usize = 3;
+
+        // 结构化输出需要完整 JSON，不使用流式
+        let stream = on_line.is_some()
+            && response_schema.is_none()
+            && self.request_template.supports_stream();
+
+        // tpm 预扣：按分词器计算提示词 token，响应后按实际用量修正
+        let estimated_tokens =
+            u32::try_from(self.count_message_tokens(messages)).unwrap_or(u32::MAX);
+
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.acquire(estimated_tokens).await;
+
+            let (error, retry_after) = match self
+                .attempt_chat(messages, stream, on_line, response_schema)
                 .await
             {
-                Ok(response) => {
-                    let status = response.status();
-                    // 先获取原始文本用于调试
-                    match response.text().await {
-                        Ok(body_text) => {
-                            // 简化日志：只记录关键信息
-                            if !status.is_success() {
-                                // 错误时记录完整响应
-                                crate::app_log!(
-                                    "[API错误] 状态码: {}, 响应: {}",
-                                    status,
-                                    &body_text
-                                );
-                            } else {
-                                // 成功时提取关键内容
-                                let summary = if let Ok(json) =
-                                    serde_json::from_str::<serde_json::Value>(&body_text)
-                                {
-                                    // 提取 AI 返回的实际内容
-                                    if let Some(content) =
-                                        json["choices"][0]["message"]["content"].as_str()
-                                    {
-                                        format!("内容: \"{}\"", content)
-                                    } else {
-                                        format!(
-                                            "tokens: {}, cost: 参考usage字段",
-                                            json["usage"]["total_tokens"].as_u64().unwrap_or(0)
-                                        )
-                                    }
-                                } else {
-                                    // JSON 解析失败，显示前100字符
-                                    if body_text.len() > 100 {
-                                        format!(
-                                            "{}... ({} 字符)",
-                                            &body_text[..100],
-                                            body_text.len()
-                                        )
-                                    } else {
-                                        body_text.clone()
-                                    }
-                                };
-                                crate::app_log!("[API响应] {} OK, {}", status.as_u16(), summary);
-                            }
-
-                            // 检查是否是错误响应
-                            if !status.is_success() {
-                                // 尝试解析通用错误格式
-                                let error_msg = if let Ok(error_json) =
-                                    serde_json::from_str::<serde_json::Value>(&body_text)
-                                {
-                                    // 提取错误信息
-                                    let extracted_msg = error_json["error"]["message"]
-                                        .as_str()
-                                        .or_else(|| error_json["message"].as_str())
-                                        .or_else(|| error_json["error"].as_str())
-                                        .unwrap_or("API请求失败");
-
-                                    // 根据状态码和错误信息生成友好提示
-                                    match status.as_u16() {
-                                        401 => format!("API Key无效或已过期: {}", extracted_msg),
-                                        403 => format!("API访问被拒绝: {}", extracted_msg),
-                                        429 => {
-                                            // 429 可能是频率超限，也可能是余额不足
-                                            if extracted_msg.contains("余额")
-                                                || extracted_msg.contains("资源包")
-                                            {
-                                                format!("账户余额不足: {}", extracted_msg)
-                                            } else {
-                                                format!("API请求频率超限: {}", extracted_msg)
-                                            }
-                                        }
-                                        500..=599 => format!("AI服务器错误: {}", extracted_msg),
-                                        _ => format!(
-                                            "API请求失败({}): {}",
-                                            status.as_u16(),
-                                            extracted_msg
-                                        ),
-                                    }
-                                } else {
-                                    format!("API请求失败({}): {}", status.as_u16(), body_text)
-                                };
-
-                                crate::app_log!("[错误] {}", error_msg);
-                                last_error = Some(AppError::translation(error_msg, false));
-                                break; // 错误响应不重试
-                            }
-
-                            // 尝试解析为ChatResponse
-                            match serde_json::from_str::<ChatResponse>(&body_text) {
-                                Ok(parsed) => {
-                                    chat_response = Some(parsed);
-                                    break;
-                                }
-                                Err(e) => {
-                                    let error_msg = format!(
-                                        "无法解析AI响应格式 (模型: {}): {}\n响应内容: {}",
-                                        self.model,
-                                        e,
-                                        if body_text.len() > 500 {
-                                            format!("{}...(已截断)", &body_text[..500])
-                                        } else {
-                                            body_text.clone()
-                                        }
-                                    );
-                                    crate::app_log!("[错误] {}", error_msg);
-                                    last_error = Some(AppError::translation(error_msg, false));
-                                    break; // 格式错误不重试
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let error_msg = format!("读取响应体失败: {}", e);
-                            crate::app_log!("[错误] {}", error_msg);
-                            last_error = Some(AppError::translation(error_msg, false));
-
-                            if retry < max_retries - 1 {
-                                let delay_secs = 2_u64.pow(retry as u32);
-                                tokio::time::sleep(tokio::time::Duration::from_secs(delay_secs))
-                                    .await;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    last_error = Some(e.into());
-                    if retry < max_retries - 1 {
-                        let delay_secs = 2_u64.pow(retry as u32); // 1s, 2s, 4s
-                        crate::app_log!(
-                            "[重试] 网络请求失败，{}秒后重试 ({}/{})",
-                            delay_secs,
-                            retry + 1,
-                            max_retries
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(delay_secs)).await;
+                Ok(mut reply) => {
+                    if let Some(usage) = reply.usage {
+                        self.rate_limiter
+                            .reconcile(estimated_tokens, usage.total_tokens);
                     }
+                    reply.fill_reasoning_tokens(self.tokenizer);
+                    return Ok(reply);
                 }
+                Err(failure) => failure,
+            };
+
+            crate::app_log!("[错误] {}", error);
+            attempt += 1;
+            if !error.is_retryable() || attempt >= MAX_ATTEMPTS {
+                return Err(error);
             }
+
+            // 有 Retry-After 时暂停同供应商的所有请求，否则指数退避
+            let delay = match retry_after {
+                Some(retry_after) => {
+                    self.rate_limiter.pause_for(retry_after);
+                    retry_after
+                }
+                None => rate_limiter::backoff_delay(attempt - 1),
+            };
+            crate::app_log!(
+                "[重试] {:.1}秒后重试 ({}/{})",
+                delay.as_secs_f64(),
+                attempt,
+                MAX_ATTEMPTS - 1
+            );
+            tokio::time::sleep(delay).await;
         }
+    }
 
-        let chat_response = chat_response.ok_or_else(|| {
-            last_error.unwrap_or_else(|| {
-                AppError::translation(format!("翻译请求失败，已重试{}次", max_retries), false)
-            })
+    /// 发送一次聊天请求；失败时返回 (错误, Retry-After)
+    ///
+    /// 错误的 `is_retryable()` 决定是否重试
+    async fn attempt_chat(
+        &self,
+        messages: &[ChatMessage],
+        stream: bool,
+        on_line: Option<&(dyn Fn(usize, &str) + Send + Sync)>,
+        response_schema: Option<&serde_json::Value>,
+    ) -> Result<ChatReply, (AppError, Option<std::time::Duration>)> {
+        let response = self
+            .build_chat_request(messages, stream, response_schema)
+            .timeout(self.request_timeout)
+            .send()
+            .await
+            .map_err(|e| (AppError::from(e), None))?;
+
+        let status = response.status();
+        if status.is_success() && is_event_stream(&response) {
+            return self
+                .read_event_stream(response, on_line)
+                .await
+                .map_err(|e| (e, None));
+        }
+
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(rate_limiter::parse_retry_after);
+
+        // 非流式响应：先获取原始文本用于调试
+        let body_text = response.text().await.map_err(|e| {
+            (
+                AppError::translation(format!("读取响应体失败: {}", e), true),
+                None,
+            )
         })?;
 
+        if !status.is_success() {
+            // 错误时记录完整响应
+            crate::app_log!("[API错误] 状态码: {}, 响应: {}", status, &body_text);
+            return Err((
+                Self::classify_error_response(status.as_u16(), &body_text),
+                retry_after,
+            ));
+        }
+
+        // 成功时提取关键内容
+        let summary = if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body_text) {
+            // 提取 AI 返回的实际内容
+            if let Some(content) = self.request_template.extract_content(&json) {
+                format!("内容: \"{}\"", content)
+            } else {
+                format!(
+                    "tokens: {}, cost: 参考usage字段",
+                    json["usage"]["total_tokens"].as_u64().unwrap_or(0)
+                )
+            }
+        } else if let Some(head) = truncate_chars(&body_text, 100) {
+            // JSON 解析失败，显示前100字符
+            format!("{}... ({} 字符)", head, body_text.chars().count())
+        } else {
+            body_text.clone()
+        };
+        crate::app_log!("[API响应] {} OK, {}", status.as_u16(), summary);
+
+        // 按请求模板解析响应（格式错误不重试）
+        let parsed = serde_json::from_str::<serde_json::Value>(&body_text).map_err(|e| {
+            let error_msg = format!(
+                "无法解析AI响应格式 (模型: {}): {}\n响应内容: {}",
+                self.model,
+                e,
+                match truncate_chars(&body_text, 500) {
+                    Some(head) => format!("{}...(已截断)", head),
+                    None => body_text.clone(),
+                }
+            );
+            (AppError::translation(error_msg, false), None)
+        })?;
+        self.parse_chat_response(&parsed).map_err(|e| (e, None))
+    }
+
+    /// 按状态码和错误信息生成友好提示，并标记是否可重试
+    ///
+    /// 401/403 不重试；429（余额不足除外）、408、5xx 可重试
+    fn classify_error_response(status: u16, body_text: &str) -> AppError {
+        let Ok(error_json) = serde_json::from_str::<serde_json::Value>(body_text) else {
+            return AppError::translation(
+                format!("API请求失败({}): {}", status, body_text),
+                rate_limiter::is_retryable_status(status),
+            );
+        };
+
+        // 提取错误信息
+        let extracted_msg = error_json["error"]["message"]
+            .as_str()
+            .or_else(|| error_json["message"].as_str())
+            .or_else(|| error_json["error"].as_str())
+            .unwrap_or("API请求失败");
+
+        let (error_msg, retryable) = match status {
+            401 => (format!("API Key无效或已过期: {}", extracted_msg), false),
+            403 => (format!("API访问被拒绝: {}", extracted_msg), false),
+            429 => {
+                // 429 可能是频率超限，也可能是余额不足（余额不足重试无意义）
+                if extracted_msg.contains("余额")
+                    || extracted_msg.contains("资源包")
+                    || extracted_msg.contains("insufficient_quota")
+                {
+                    (format!("账户余额不足: {}", extracted_msg), false)
+                } else {
+                    (format!("API请求频率超限: {}", extracted_msg), true)
+                }
+            }
+            500..=599 => (format!("AI服务器错误: {}", extracted_msg), true),
+            _ => (
+                format!("API请求失败({}): {}", status, extracted_msg),
+                rate_limiter::is_retryable_status(status),
+            ),
+        };
+        AppError::translation(error_msg, retryable)
+    }
+
+    /// 累加 token 用量与成本
+    fn record_usage(&mut self, usage: Option<ResponseUsage>) -> Result<(), AppError> {
         // 更新token统计（使用新架构精确计算）
-        if let Some(usage) = chat_response.usage {
-            self.token_stats.input_tokens += usage.prompt_tokens;
-            self.token_stats.output_tokens += usage.completion_tokens;
-            self.token_stats.total_tokens += usage.total_tokens;
-
-            // 使用 ModelInfo 计算精确成本
-            // Fail Fast 架构设计：多AI供应商架构要求强制 ModelInfo 存在
-            // 模型不存在 = 配置错误，应立即返回错误（见 docs/Architecture.md:195）
-            let model_info = {
-                use crate::services::ai::provider::with_global_registry;
-                with_global_registry(|registry| {
-                    registry
-                        .get_provider(&self.provider_id)
-                        .and_then(|provider| provider.get_model_info(&self.model))
-                        .ok_or_else(|| {
-                            AppError::plugin(format!(
-                                "模型信息不存在: provider={}, model={}. 请检查插件系统中的模型定义",
-                                self.provider_id, self.model
-                            ))
-                        })
-                })?
-            };
+        let Some(usage) = usage else {
+            return Ok(());
+        };
+        self.token_stats.input_tokens += usage.prompt_tokens;
+        self.token_stats.output_tokens += usage.completion_tokens;
+        self.token_stats.total_tokens += usage.total_tokens;
+        self.token_stats.reasoning_tokens += usage.reasoning_tokens;
+
+        // 使用 ModelInfo 计算精确成本
+        // Fail Fast 架构设计：多AI供应商架构要求强制 ModelInfo 存在
+        // 模型不存在 = 配置错误，应立即返回错误（见 docs/Architecture.md:195）
+        let model_info = self.get_model_info().ok_or_else(|| {
+            AppError::plugin(format!(
+                "模型信息不存在: provider={}, model={}. 请检查插件系统中的模型定义",
+                self.provider_id, self.model
+            ))
+        })?;
 
-            use crate::services::ai::CostCalculator;
-            let breakdown = CostCalculator::calculate_openai(
-                &model_info,
-                usage.prompt_tokens as usize,
-                usage.completion_tokens as usize,
-                0, // TODO: 支持从 API 响应中提取缓存 token
-                0,
+        use crate::services::ai::CostCalculator;
+        let breakdown = CostCalculator::calculate_openai(
+            &model_info,
+            usage.prompt_tokens as usize,
+            usage.completion_tokens as usize,
+            0,
+            usage.cached_tokens as usize,
+        )
+        .with_reasoning_tokens(usage.reasoning_tokens as usize);
+        self.token_stats.cost += breakdown.total_cost;
+        if breakdown.reasoning_tokens > 0 {
+            crate::app_log!(
+                "[推理] {} tokens, 成本 {:.6} {}",
+                breakdown.reasoning_tokens,
+                breakdown.reasoning_cost,
+                breakdown.currency
             );
-            self.token_stats.cost += breakdown.total_cost;
         }
 
-        let assistant_response = chat_response
-            .choices
-            .first()
-            .map(|choice| &choice.message.content)
-            .ok_or_else(|| AppError::translation("AI响应为空", false))?;
+        // 追加到用量台账（写入失败不影响翻译）
+        let record = UsageRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            provider: self.provider_id.clone(),
+            model: self.model.clone(),
+            file: self.usage_context.file.clone(),
+            project: self.usage_context.project.clone(),
+            input_tokens: usage.prompt_tokens as u64,
+            output_tokens: usage.completion_tokens as u64,
+            cached_tokens: usage.cached_tokens as u64,
+            cost: breakdown.total_cost,
+            currency: breakdown.currency.clone(),
+        };
+        if let Err(e) = usage_ledger::append(&record) {
+            log::error!("[用量台账] 写入失败: {}", e);
+        }
+        Ok(())
+    }
 
-        // 更新对话历史
-        self.update_conversation_history(&user_prompt, assistant_response);
+    /// 从插件系统获取当前模型的信息
+    fn get_model_info(&self) -> Option<crate::services::ai::ModelInfo> {
+        use crate::services::ai::provider::with_global_registry;
+        with_global_registry(|registry| {
+            registry
+                .get_provider(&self.provider_id)
+                .and_then(|provider| provider.get_model_info(&self.model))
+        })
+    }
 
-        // 解析翻译结果
-        let translations = self.parse_translations(assistant_response, &texts)?;
+    /// 按当前模型的上下文窗口、分词器与目标语言创建批次大小计算器
+    pub fn batch_sizer(&self) -> BatchSizer {
+        BatchSizer::for_model(
+            self.get_model_info().as_ref(),
+            self.target_language.as_deref(),
+        )
+        .with_output_limit(self.generation.max_tokens)
+        .with_tokenizer(self.tokenizer)
+    }
 
-        Ok(translations)
+    /// 按当前模型预估翻译这些文本的花费（与 `TokenStats.cost` 同一货币，模型信息缺失时为 None）
+    pub fn estimate_cost(&self, texts: &[String]) -> Option<f64> {
+        use crate::services::ai::CostCalculator;
+        let model_info = self.get_model_info()?;
+        let estimate = CostCalculator::estimate_translation(
+            &model_info,
+            self.tokenizer,
+            &self.system_prompt,
+            texts,
+            self.target_language.as_deref(),
+            0.0,
+        );
+        Some(estimate.estimated_cost)
+    }
+
+    /// 系统提示词与对话历史占用的输入 token
+    pub fn prompt_overhead_tokens(&self) -> usize {
+        if self.conversation_history.is_empty() {
+            self.tokenizer.count(&self.system_prompt)
+        } else {
+            self.count_message_tokens(&self.conversation_history)
+        }
     }
 
     /// 获取当前使用的系统提示词（用于日志记录）
@@ -1265,10 +1841,246 @@ impl AITranslator {
         &self.system_prompt
     }
 
-    /// 构建用户提示词（包装方法，用于向后兼容）
-    #[inline]
+    /// 构建用户提示词（附带记忆库中的相似条目作为参考译文，按模型上下文窗口限制 token）
     pub fn build_user_prompt(&self, texts: &[String]) -> String {
-        prompt_builder::build_translation_prompt(texts, self.target_language.as_deref())
+        self.build_user_prompt_in_context(texts, &[])
+    }
+
+    /// 构建用户提示词，并按序号列出各条的 msgctxt
+    fn build_user_prompt_in_context(
+        &self,
+        texts: &[String],
+        contexts: &[Option<String>],
+    ) -> String {
+        let candidates = self.fuzzy_references(texts);
+        let budget = self
+            .batch_sizer()
+            .reference_budget(texts, self.prompt_overhead_tokens());
+        let references =
+            prompt_builder::fit_references(&candidates, budget, |text| self.tokenizer.count(text));
+        if references.len() < candidates.len() {
+            crate::app_log!(
+                "[参考译文] 超出 token 预算（{}），注入 {}/{} 条",
+                budget,
+                references.len(),
+                candidates.len()
+            );
+        }
+        prompt_builder::build_translation_prompt_with_context(
+            texts,
+            contexts,
+            self.target_language.as_deref(),
+            &references,
+        )
+    }
+
+    /// 记忆库中与本批原文相似、但未达到自动套用阈值的条目（按原文去重）
+    ///
+    /// 先取每条原文的最佳匹配，再取次佳匹配，预算有限时每条原文都尽量有一条参考
+    fn fuzzy_references(&self, texts: &[String]) -> Vec<FuzzyMatch> {
+        let per_text: Vec<Vec<FuzzyMatch>> = texts
+            .iter()
+            .map(|text| {
+                self.fuzzy_candidates(
+                    text,
+                    self.fuzzy_match.top_k,
+                    self.fuzzy_match.min_score,
+                    false,
+                )
+                .into_iter()
+                .map(|(candidate, _)| candidate)
+                .filter(|candidate| !self.fuzzy_match.should_auto_apply(candidate.score))
+                .collect()
+            })
+            .collect();
+        let depth = per_text.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut seen = std::collections::HashSet::new();
+        let mut references = Vec::new();
+        for rank in 0..depth {
+            for candidate in per_text
+                .iter()
+                .filter_map(|candidates| candidates.get(rank))
+            {
+                if !seen.insert(candidate.source.as_str()) {
+                    continue;
+                }
+                references.push(candidate.clone());
+                if references.len() >= MAX_PROMPT_REFERENCES {
+                    return references;
+                }
+            }
+        }
+        references
+    }
+
+    /// 在各记忆库的短语表与句段库中模糊查询，合并后按匹配度取前 `top_k` 条（同一原文只保留一条）
+    ///
+    /// 返回 (匹配, 记忆库序号)：0 为项目记忆库，其后为参考记忆库；同分时优先级高的记忆库在前。
+    /// `approved_only` 时句段库只查已审校的条目（自动套用），否则包含未审校的机器译文（提示词参考）
+    fn fuzzy_candidates(
+        &self,
+        text: &str,
+        top_k: usize,
+        min_score: u8,
+        approved_only: bool,
+    ) -> Vec<(FuzzyMatch, usize)> {
+        let Some(lang) = self.target_language.as_deref() else {
+            return Vec::new();
+        };
+        if !self.fuzzy_match.enabled {
+            return Vec::new();
+        }
+
+        let layers = std::iter::once((self.tm.as_ref(), self.segments.as_ref())).chain(
+            self.references
+                .iter()
+                .map(|reference| (Some(&reference.tm), Some(&reference.segments))),
+        );
+        let mut candidates: Vec<(FuzzyMatch, usize)> = Vec::new();
+        for (layer, (tm, segments)) in layers.enumerate() {
+            // 句段库在前：同分时优先使用完整句段
+            candidates.extend(
+                segments
+                    .into_iter()
+                    .flat_map(|segments| {
+                        segments.fuzzy_matches(text, lang, top_k, min_score, approved_only)
+                    })
+                    .chain(
+                        tm.into_iter()
+                            .flat_map(|tm| tm.fuzzy_matches(text, lang, top_k, min_score)),
+                    )
+                    .map(|candidate| (candidate, layer)),
+            );
+        }
+        // 原文相同却未被精确查询命中的，只能是其他上下文/源语言下的条目，按回退扣分
+        for (candidate, _) in &mut candidates {
+            candidate.score = candidate.score.min(100 - CONTEXT_FALLBACK_PENALTY);
+        }
+        candidates.sort_by_key(|(candidate, _)| std::cmp::Reverse(candidate.score));
+        let mut seen = std::collections::HashSet::new();
+        candidates.retain(|(candidate, _)| seen.insert(candidate.source.clone()));
+        candidates.truncate(top_k);
+        candidates
+    }
+
+    /// 命中来源：默认记忆库为 `tm`/`fuzzy`，命名记忆库附带名称（如 `tm:client-a`）
+    fn memory_source(&self, kind: &str, layer: usize) -> String {
+        let name = match layer {
+            0 => self.memory_name.as_str(),
+            _ => self.references[layer - 1].name.as_str(),
+        };
+        if name == DEFAULT_MEMORY {
+            kind.to_string()
+        } else {
+            format!("{}:{}", kind, name)
+        }
+    }
+
+    /// 从记忆库复用译文：短语表/句段库精确匹配（上下文一致优先）→ 达到阈值的模糊匹配
+    ///
+    /// 项目记忆库与参考记忆库中取匹配度最高者，同分时优先级高的记忆库优先。
+    /// 返回 (译文, 来源)。完全匹配来源为 `tm`；退回到无上下文条目（扣分）或模糊匹配
+    /// 需达到自动套用阈值，来源为 `fuzzy`；来自命名记忆库时附带名称
+    fn reuse_from_memory(&mut self, text: &str, context: Option<&str>) -> Option<(String, String)> {
+        let raw_lang = self.target_language.as_deref();
+        let query = TmKey::new(text, raw_lang)
+            .with_source_lang(self.source_language.as_deref())
+            .with_context(context);
+        // 修复：键中带目标语言，避免跨语言命中
+        let tm = self.tm.as_mut()?;
+        let mut best =
+            lookup_exact(tm, self.segments.as_mut(), &query, raw_lang).map(|hit| (hit, 0));
+        for (index, reference) in self.references.iter_mut().enumerate() {
+            let Some(hit) = lookup_exact(
+                &mut reference.tm,
+                Some(&mut reference.segments),
+                &query,
+                raw_lang,
+            ) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|(best, _)| hit.score > best.score) {
+                best = Some((hit, index + 1));
+            }
+        }
+
+        if let Some((hit, layer)) = best {
+            if hit.score >= 100 {
+                return Some((hit.target, self.memory_source("tm", layer)));
+            }
+            if self.fuzzy_match.should_auto_apply(hit.score) {
+                crate::app_log!(
+                    "[TM上下文回退] {}% '{}' -> '{}'",
+                    hit.score,
+                    text,
+                    hit.target
+                );
+                return Some((hit.target, self.memory_source("fuzzy", layer)));
+            }
+        }
+
+        let (fuzzy, layer) = self
+            .fuzzy_candidates(text, 1, self.fuzzy_match.auto_apply_threshold, true)
+            .into_iter()
+            .find(|(candidate, _)| self.fuzzy_match.should_auto_apply(candidate.score))?;
+        // 模糊匹配达到自动套用阈值：直接使用，来源标记为 fuzzy 供复核
+        crate::app_log!(
+            "[TM模糊] {}% '{}' -> '{}'",
+            fuzzy.score,
+            fuzzy.source,
+            fuzzy.target
+        );
+        Some((fuzzy.target, self.memory_source("fuzzy", layer)))
+    }
+
+    /// 把 AI 译文记入记忆库：简单短语写入短语表，其余句段写入句段库（未审校）
+    ///
+    /// 只检查当前记忆库，不检查代码内置词库（用户清空后内置词库不参与）
+    fn learn_translation(
+        &mut self,
+        text: &str,
+        translation: &str,
+        context: Option<&str>,
+        provider: &str,
+    ) {
+        let target_lang = self.target_language.as_deref();
+        let source_lang = self.source_language.as_deref();
+        if is_simple_phrase(text) && translation.len() <= 50 {
+            let Some(tm) = self.tm.as_mut() else {
+                return;
+            };
+            let key = TmKey::new(text, target_lang)
+                .with_source_lang(source_lang)
+                .with_context(context);
+            if tm.contains(&key) {
+                crate::app_log!("[TM跳过] {} (已在记忆库)", text);
+                return;
+            }
+            tm.insert(&key, translation.to_string());
+            self.batch_stats.tm_learned += 1;
+            crate::app_log!(
+                "[TM学习] {} -> {} ({})",
+                text,
+                translation,
+                target_lang.unwrap_or("无语言")
+            );
+        } else if let (Some(segments), Some(lang)) = (self.segments.as_mut(), target_lang) {
+            let entry = SegmentEntry::new(text, translation, lang, ReviewStatus::Unreviewed)
+                .with_source_lang(source_lang)
+                .with_context(context.map(str::to_string))
+                .with_file(self.usage_context.file.clone())
+                .with_provider(Some(provider.to_string()));
+            if segments.record(entry) {
+                self.batch_stats.tm_learned += 1;
+                crate::app_log!("[TM句段学习] {} -> {} ({})", text, translation, lang);
+            }
+        }
+    }
+
+    /// 替换模糊匹配配置（测试或调用方需要覆盖全局配置时）
+    pub fn set_fuzzy_match_config(&mut self, config: FuzzyMatchConfig) {
+        self.fuzzy_match = config;
     }
 
     fn update_conversation_history(&mut self, user_prompt: &str, assistant_response: &str) {
@@ -1354,7 +2166,13 @@ impl AITranslator {
             )));
         }
 
-        // 验证特殊字符保留
+        self.check_special_chars(&mut translations, original_texts);
+
+        Ok(translations)
+    }
+
+    /// 校验并修正特殊字符（结尾换行符、占位符数量）
+    fn check_special_chars(&self, translations: &mut [String], original_texts: &[String]) {
         for (i, translation) in translations.iter_mut().enumerate() {
             let original = &original_texts[i];
 
@@ -1377,8 +2195,6 @@ impl AITranslator {
                 );
             }
         }
-
-        Ok(translations)
     }
 
     fn count_placeholders(&self, text: &str) -> usize {
@@ -1407,10 +2223,86 @@ impl AITranslator {
 }
 
 impl AITranslator {
+    pub fn provider_id(&self) -> &str {
+        &self.provider_id
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// 实际发送的生成参数（已合并模型默认值）
+    pub fn generation_params(&self) -> &GenerationParams {
+        &self.generation
+    }
+
+    /// 设置单次请求超时
+    pub fn set_request_timeout(&mut self, timeout: std::time::Duration) {
+        self.request_timeout = timeout;
+    }
+
+    /// 设置后续 API 调用在用量台账中的文件/项目归属
+    pub fn set_usage_context(&mut self, context: UsageContext) {
+        self.usage_context = context;
+    }
+
+    /// 设置记忆库组合：可写的项目记忆库与按优先级排列的只读参考记忆库
+    ///
+    /// 组合由调用方打开一次后在各翻译器间共用；未启用记忆库时只记录名称
+    pub fn set_memory_stack(&mut self, stack: &MemoryStack) -> Result<(), AppError> {
+        if !self.use_tm {
+            self.memory_name = stack.project.name.clone();
+            return Ok(());
+        }
+        if stack.project.name != self.memory_name {
+            let store = &stack.project.store;
+            self.tm = Some(TranslationMemory::open(Arc::clone(store))?);
+            self.segments = Some(SegmentMemory::open(Arc::clone(store)));
+            self.memory_name = stack.project.name.clone();
+        }
+
+        self.references = stack
+            .references
+            .iter()
+            .map(|reference| {
+                Ok(ReferenceMemory {
+                    name: reference.name.clone(),
+                    tm: TranslationMemory::open(Arc::clone(&reference.store))?,
+                    segments: SegmentMemory::open(Arc::clone(&reference.store)),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        crate::app_log!(
+            "[TM] 项目记忆库: {}，参考记忆库: [{}]",
+            self.memory_name,
+            self.references
+                .iter()
+                .map(|reference| reference.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Ok(())
+    }
+
+    /// 可写的项目记忆库名称（学习结果保存到该库）
+    pub fn memory_name(&self) -> &str {
+        &self.memory_name
+    }
+
+    /// 设置源语言（写入和查询记忆库键时使用）
+    pub fn set_source_language(&mut self, source_language: Option<String>) {
+        self.source_language = source_language.filter(|lang| !lang.trim().is_empty());
+    }
+
     pub fn get_token_stats(&self) -> &TokenStats {
         &self.token_stats
     }
 
+    /// 最近一次请求的推理过程（非推理模型或命中缓存时为 None）
+    pub fn last_reasoning(&self) -> Option<&str> {
+        self.last_reasoning.as_deref()
+    }
+
     pub fn reset_stats(&mut self) {
         self.token_stats = TokenStats::default();
     }
@@ -1426,4 +2318,53 @@ impl AITranslator {
     pub fn get_translation_memory_mut(&mut self) -> Option<&mut TranslationMemory> {
         self.tm.as_mut()
     }
+
+    pub fn get_segment_memory(&self) -> Option<&SegmentMemory> {
+        self.segments.as_ref()
+    }
+
+    pub fn get_segment_memory_mut(&mut self) -> Option<&mut SegmentMemory> {
+        self.segments.as_mut()
+    }
+}
+
+/// 在一个记忆库（短语表 + 句段库）中精确查询
+///
+/// 取匹配度较高者，同分时短语表优先；采用句段时记录其命中
+fn lookup_exact(
+    tm: &mut TranslationMemory,
+    segments: Option<&mut SegmentMemory>,
+    query: &TmKey,
+    raw_lang: Option<&str>,
+) -> Option<TmHit> {
+    let phrase = tm.lookup(query, raw_lang);
+    let Some(segments) = segments.filter(|_| raw_lang.is_some()) else {
+        return phrase;
+    };
+    // 只复用已审校的句段；未审校的机器译文只作为提示词参考
+    let segment = segments.lookup_approved(query).map(|(entry, score)| {
+        crate::app_log!(
+            "[TM句段] 命中已审校句段（{}%）: {} -> {}",
+            score,
+            query.source,
+            entry.target
+        );
+        let key = entry.tm_key();
+        let hit = TmHit {
+            target: entry.target,
+            score,
+        };
+        (key, hit)
+    });
+    match segment {
+        Some((key, hit))
+            if phrase
+                .as_ref()
+                .is_none_or(|phrase| hit.score > phrase.score) =>
+        {
+            segments.record_hit(&key);
+            Some(hit)
+        }
+        _ => phrase,
+    }
 }
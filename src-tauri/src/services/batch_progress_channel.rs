@@ -94,6 +94,8 @@ pub struct BatchStatsEvent {
     pub deduplicated: usize,
     /// AI翻译数量
     pub ai_translated: usize,
+    /// 响应缓存命中数（未付费）
+    pub cache_hits: usize,
     /// Token统计
     pub token_stats: TokenStatsEvent,
 }
@@ -105,7 +107,9 @@ pub struct TokenStatsEvent {
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
     pub total_tokens: usize,
-    /// 费用（USD）
+    /// 推理 token（已计入 completion_tokens）
+    pub reasoning_tokens: usize,
+    /// 费用（配置的显示货币）
     pub cost: f64,
 }
 
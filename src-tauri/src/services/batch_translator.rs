@@ -9,6 +9,7 @@
 //! - 翻译记忆库（TM）集成
 //! - 详细的翻译报告和统计
 //! - 进度回调支持
+//! - 花费预算：每个文件开始前检查，超限时停止（已写入的文件保留，重新运行即从剩余文件继续）
 //!
 //! # 使用示例
 //!
@@ -40,10 +41,12 @@
 
 use crate::commands::POEntry;
 use crate::error::AppError;
+use crate::services::budget::{self, BudgetTracker};
+use crate::services::tm_store;
 use crate::services::translation_stats::TokenStats;
+use crate::services::usage_ledger::UsageContext;
 use crate::services::{AITranslator, POParser, TranslationMemory};
 use crate::utils::common::is_simple_phrase;
-use crate::utils::paths::get_translation_memory_path;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -144,12 +147,14 @@ pub struct TranslationMemoryStats {
 /// - `translator`: AI 翻译器
 /// - `translation_memory`: 翻译记忆库
 /// - `reports`: 翻译报告列表
+/// - `budget`: 花费预算跟踪
 #[derive(Debug, Clone)]
 pub struct BatchTranslator {
     parser: POParser,
     translator: AITranslator,
     translation_memory: TranslationMemory,
     reports: Vec<TranslationReport>,
+    budget: BudgetTracker,
 }
 
 impl BatchTranslator {
@@ -177,20 +182,31 @@ impl BatchTranslator {
 
         // Phase 3: 从当前配置获取自定义系统提示词
         use crate::services::ConfigDraft;
-        let custom_prompt = ConfigDraft::new(None)
-            .ok()
+        let draft = ConfigDraft::new(None).ok();
+        let custom_prompt = draft
+            .as_ref()
             .and_then(|draft| draft.data().system_prompt.clone());
+        let budget_config = draft
+            .map(|draft| draft.data().budget.clone())
+            .unwrap_or_default();
 
         // Phase 5: 批处理翻译器暂不支持目标语言（可在后续扩展）
         let translator =
             AITranslator::new(api_key, base_url, true, custom_prompt.as_deref(), None)?;
-        let translation_memory = TranslationMemory::new();
+        let store = tm_store::shared()?;
+        let translation_memory = TranslationMemory::open(store)?;
 
         Ok(Self {
             parser,
             translator,
             translation_memory,
             reports: Vec::new(),
+            budget: BudgetTracker::new(
+                budget_config,
+                uuid::Uuid::new_v4().to_string(),
+                0.0,
+                budget::shared_ledger(),
+            ),
         })
     }
 
@@ -215,7 +231,37 @@ impl BatchTranslator {
                 );
             }
 
-            match self.translate_po_file(file_path).await {
+            // 预算超限时停止：已完成的文件已写回磁盘，重新运行时只会翻译剩余条目
+            if let Err(e) = self.budget.check_next_batch() {
+                log::error!(
+                    "[预算] 目录翻译在 {}/{} 个文件处暂停: {}",
+                    index,
+                    total_files,
+                    e
+                );
+                self.reports = reports.clone();
+                self.generate_summary_report(&reports)?;
+                return Err(e);
+            }
+
+            self.translator.set_usage_context(UsageContext {
+                file: Some(file_path.to_string_lossy().to_string()),
+                project: Some(directory.to_string_lossy().to_string()),
+            });
+            let cost_before = self.translator.get_token_stats().cost;
+            let result = self.translate_po_file(file_path).await;
+            let cost = self.translator.get_token_stats().cost - cost_before;
+            for warning in self.budget.record_batch(cost) {
+                crate::app_log!(
+                    "[预算] {}花费 {:.4} 已达到上限 {:.4} 的 {:.0}%",
+                    warning.scope,
+                    warning.spent,
+                    warning.limit,
+                    warning.threshold * 100.0
+                );
+            }
+
+            match result {
                 Ok(report) => {
                     reports.push(report);
                     if let Some(ref callback) = progress_callback {
@@ -344,12 +390,9 @@ impl BatchTranslator {
         self.parser
             .write_file(file_path.to_string_lossy().to_string(), &updated_entries)?;
 
-        // 保存翻译记忆库到文件
-        let memory_path = get_translation_memory_path().to_string_lossy().to_string();
-        if let Some(parent) = std::path::Path::new(&memory_path).parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        self.translation_memory.save_to_file(memory_path)?;
+        // 新学习的条目增量写入记忆库
+        let store = tm_store::shared()?;
+        self.translation_memory.save_to_store(&store)?;
 
         // 获取token统计
         let token_stats = self.translator.get_token_stats().clone();
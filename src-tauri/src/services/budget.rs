@@ -0,0 +1,816 @@
+/**
+ * 翻译花费预算
+ *
+ * - 单任务上限：每批开始前按「已花费 + 预计花费」检查（花费来自 `TokenStats.cost`）；
+ *   第一批按开始前的预估花费检查，之后按已完成批次的平均花费
+ * - 每日/每月上限：花费由用量台账（`usage_ledger.jsonl`）按日期汇总，所有任务与单条翻译共享；
+ *   启动时汇总本月记录，之后每追加一条用量记录同步计入，不再单独持久化
+ * - 花费达到配置的阈值比例时产生预警（每个任务内每个范围/阈值只提醒一次）
+ * - 超出上限时任务暂停，已完成的译文与剩余条目保存到 `data/paused_jobs/<id>.json`，调整预算后可恢复
+ */
+use crate::error::AppError;
+use crate::services::ai::currency;
+use crate::services::translation_stats::{BatchStats, TokenStats};
+use crate::services::usage_ledger::{self, UsageRecord};
+use chrono::{Datelike, Local, NaiveDate};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 预算配置（金额为用户选择的显示货币，与 `TokenStats.cost` 一致；未设置表示不限）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct BudgetConfig {
+    /// 单个翻译任务的花费上限
+    #[serde(default)]
+    pub max_job_cost: Option<f64>,
+    /// 每日花费上限
+    #[serde(default)]
+    pub daily_cap: Option<f64>,
+    /// 每月花费上限
+    #[serde(default)]
+    pub monthly_cap: Option<f64>,
+    /// 预警阈值（上限的比例，如 0.8 表示花费达到 80% 时提醒）
+    #[serde(default = "default_warning_thresholds")]
+    pub warning_thresholds: Vec<f64>,
+}
+
+fn default_warning_thresholds() -> Vec<f64> {
+    vec![0.5, 0.8]
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_job_cost: None,
+            daily_cap: None,
+            monthly_cap: None,
+            warning_thresholds: default_warning_thresholds(),
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// 把上限金额换算为另一种货币（显示货币变更时调用，与花费台账保持一致）
+    pub fn convert_currency(&mut self, from: &str, to: &str) -> Result<(), AppError> {
+        let factor = currency::currency_table().convert(1.0, from, to)?;
+        for cap in [
+            &mut self.max_job_cost,
+            &mut self.daily_cap,
+            &mut self.monthly_cap,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *cap *= factor;
+        }
+        Ok(())
+    }
+}
+
+/// 预算范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum BudgetScope {
+    Job,
+    Daily,
+    Monthly,
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BudgetScope::Job => "单任务",
+            BudgetScope::Daily => "今日",
+            BudgetScope::Monthly => "本月",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 预算预警事件（`budget:warning`）
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct BudgetWarningEvent {
+    pub job_id: String,
+    pub scope: BudgetScope,
+    /// 触发的阈值比例
+    pub threshold: f64,
+    pub spent: f64,
+    pub limit: f64,
+}
+
+// ========== 花费台账 ==========
+
+/// 按日期累计的花费（由用量台账汇总，所有任务共享）
+#[derive(Debug, Default)]
+pub struct SpendingLedger {
+    /// 金额货币（显示货币变更时整体换算）
+    currency: Option<String>,
+    /// 日期 -> 当日花费
+    days: BTreeMap<NaiveDate, f64>,
+}
+
+impl SpendingLedger {
+    /// 汇总用量记录（换算为 `currency`）
+    pub fn from_records(records: &[UsageRecord], currency: &str) -> Self {
+        let mut ledger = Self {
+            currency: Some(currency.to_string()),
+            ..Default::default()
+        };
+        for record in records {
+            ledger.add_usage(record);
+        }
+        ledger
+    }
+
+    /// 计入一条用量记录（按记录的货币换算，汇率表中没有时保持原值）
+    pub fn add_usage(&mut self, record: &UsageRecord) {
+        let Some(date) = record.date() else {
+            return;
+        };
+        let target = self
+            .currency
+            .clone()
+            .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+        let cost = currency::currency_table()
+            .convert(record.cost, &record.currency, &target)
+            .unwrap_or_else(|e| {
+                log::error!("[预算] 用量记录货币换算失败: {}", e);
+                record.cost
+            });
+        self.record(date, cost);
+    }
+
+    /// 记录一笔花费
+    pub fn record(&mut self, date: NaiveDate, cost: f64) {
+        if cost > 0.0 {
+            *self.days.entry(date).or_insert(0.0) += cost;
+        }
+    }
+
+    /// 把已记录的金额换算为指定货币（显示货币变更后调用）
+    pub fn ensure_currency(&mut self, target: &str) {
+        let from = self
+            .currency
+            .clone()
+            .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+        if from == target {
+            self.currency = Some(from);
+            return;
+        }
+        match currency::currency_table().convert(1.0, &from, target) {
+            Ok(factor) => {
+                for cost in self.days.values_mut() {
+                    *cost *= factor;
+                }
+                self.currency = Some(target.to_string());
+            }
+            Err(e) => log::error!("[预算] 花费台账货币换算失败: {}", e),
+        }
+    }
+
+    /// 某日花费
+    pub fn spent_on(&self, date: NaiveDate) -> f64 {
+        self.days.get(&date).copied().unwrap_or(0.0)
+    }
+
+    /// 某日所在月份的累计花费
+    pub fn spent_in_month(&self, date: NaiveDate) -> f64 {
+        self.days
+            .iter()
+            .filter(|(day, _)| day.year() == date.year() && day.month() == date.month())
+            .map(|(_, cost)| cost)
+            .sum()
+    }
+}
+
+/// 汇总用量台账中本月的记录
+fn load_shared_ledger() -> Arc<Mutex<SpendingLedger>> {
+    let month_start = Local::now().date_naive().with_day(1);
+    let records = usage_ledger::load(month_start, None).unwrap_or_else(|e| {
+        log::error!("[预算] 读取用量台账失败，使用空台账: {}", e);
+        Vec::new()
+    });
+    Arc::new(Mutex::new(SpendingLedger::from_records(
+        &records,
+        &currency::display_currency(),
+    )))
+}
+
+/// 全局花费台账（数据目录变化时按新目录下的用量台账重新汇总）
+pub fn shared_ledger() -> Arc<Mutex<SpendingLedger>> {
+    type SharedLedger = (PathBuf, Arc<Mutex<SpendingLedger>>);
+    static SHARED_LEDGER: Mutex<Option<SharedLedger>> = Mutex::new(None);
+
+    let path = usage_ledger::ledger_path();
+    let mut shared = SHARED_LEDGER.lock();
+    if let Some((loaded, ledger)) = shared.as_ref()
+        && *loaded == path
+    {
+        return Arc::clone(ledger);
+    }
+
+    let ledger = load_shared_ledger();
+    *shared = Some((path, Arc::clone(&ledger)));
+    ledger
+}
+
+/// 今日与本月花费
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct SpendingSummary {
+    pub currency: String,
+    pub today: f64,
+    pub this_month: f64,
+    pub daily_cap: Option<f64>,
+    pub monthly_cap: Option<f64>,
+}
+
+/// 汇总当前花费
+pub fn spending_summary(config: &BudgetConfig) -> SpendingSummary {
+    let today = Local::now().date_naive();
+    let ledger = shared_ledger();
+    let mut ledger = ledger.lock();
+    let display = currency::display_currency();
+    ledger.ensure_currency(&display);
+    SpendingSummary {
+        currency: display,
+        today: ledger.spent_on(today),
+        this_month: ledger.spent_in_month(today),
+        daily_cap: config.daily_cap,
+        monthly_cap: config.monthly_cap,
+    }
+}
+
+// ========== 任务预算跟踪 ==========
+
+/// 单个任务的预算跟踪器（并发 worker 共享，外部加锁）
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    config: BudgetConfig,
+    job_id: String,
+    ledger: Arc<Mutex<SpendingLedger>>,
+    /// 本任务已花费（恢复的任务包含暂停前的花费）
+    job_spent: f64,
+    /// 本次运行中已完成的批次及其花费（用于预计下一批花费）
+    completed_batches: usize,
+    run_spent: f64,
+    /// 开始前预估的单批花费（尚无已完成批次时使用）
+    preflight_batch_cost: f64,
+    /// 已放行但尚未完成的批次
+    in_flight: usize,
+    warned: HashSet<(BudgetScope, u64)>,
+}
+
+impl BudgetTracker {
+    pub fn new(
+        config: BudgetConfig,
+        job_id: impl Into<String>,
+        job_spent: f64,
+        ledger: Arc<Mutex<SpendingLedger>>,
+    ) -> Self {
+        Self {
+            config,
+            job_id: job_id.into(),
+            ledger,
+            job_spent,
+            completed_batches: 0,
+            run_spent: 0.0,
+            preflight_batch_cost: 0.0,
+            in_flight: 0,
+            warned: HashSet::new(),
+        }
+    }
+
+    /// 设置开始前预估的单批花费（第一批据此检查上限）
+    pub fn with_estimated_batch_cost(mut self, cost: f64) -> Self {
+        self.preflight_batch_cost = cost.max(0.0);
+        self
+    }
+
+    pub fn job_spent(&self) -> f64 {
+        self.job_spent
+    }
+
+    /// 预计单批花费（本次运行已完成批次的平均值，尚无数据时取开始前的预估）
+    fn estimated_batch_cost(&self) -> f64 {
+        if self.completed_batches == 0 {
+            self.preflight_batch_cost
+        } else {
+            self.run_spent / self.completed_batches as f64
+        }
+    }
+
+    /// 下一批开始前检查预算；通过时占用一个进行中名额
+    pub fn check_next_batch(&mut self) -> Result<(), AppError> {
+        self.check_next_batch_on(Local::now().date_naive())
+    }
+
+    fn check_next_batch_on(&mut self, today: NaiveDate) -> Result<(), AppError> {
+        // 计入已放行但未完成的批次
+        let pending = (self.in_flight + 1) as f64 * self.estimated_batch_cost();
+        let (daily_spent, monthly_spent) = {
+            let mut ledger = self.ledger.lock();
+            ledger.ensure_currency(&currency::display_currency());
+            (ledger.spent_on(today), ledger.spent_in_month(today))
+        };
+
+        let checks = [
+            (BudgetScope::Job, self.job_spent, self.config.max_job_cost),
+            (BudgetScope::Daily, daily_spent, self.config.daily_cap),
+            (BudgetScope::Monthly, monthly_spent, self.config.monthly_cap),
+        ];
+        for (scope, spent, limit) in checks {
+            let Some(limit) = limit else {
+                continue;
+            };
+            let projected = spent + pending;
+            // 已达上限，或预计本批会超出上限
+            if spent >= limit || projected > limit {
+                return Err(AppError::BudgetExceeded {
+                    scope: scope.to_string(),
+                    spent: projected,
+                    limit,
+                    job_id: self.job_id.clone(),
+                });
+            }
+        }
+
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    /// 放弃已放行的批次（未实际发送）
+    pub fn release_batch(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+    }
+
+    /// 记录一批的实际花费，返回新触发的预警
+    pub fn record_batch(&mut self, cost: f64) -> Vec<BudgetWarningEvent> {
+        self.record_batch_on(Local::now().date_naive(), cost)
+    }
+
+    fn record_batch_on(&mut self, today: NaiveDate, cost: f64) -> Vec<BudgetWarningEvent> {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.completed_batches += 1;
+        self.run_spent += cost;
+        self.job_spent += cost;
+
+        // 本批花费已随用量记录写入台账
+        let (daily_spent, monthly_spent) = {
+            let mut ledger = self.ledger.lock();
+            ledger.ensure_currency(&currency::display_currency());
+            (ledger.spent_on(today), ledger.spent_in_month(today))
+        };
+
+        let checks = [
+            (BudgetScope::Job, self.job_spent, self.config.max_job_cost),
+            (BudgetScope::Daily, daily_spent, self.config.daily_cap),
+            (BudgetScope::Monthly, monthly_spent, self.config.monthly_cap),
+        ];
+        let mut warnings = Vec::new();
+        for (scope, spent, limit) in checks {
+            let Some(limit) = limit.filter(|limit| *limit > 0.0) else {
+                continue;
+            };
+            // 同一范围一次只提醒已越过的最高阈值
+            let crossed = self
+                .config
+                .warning_thresholds
+                .iter()
+                .copied()
+                .filter(|threshold| spent >= limit * threshold)
+                .fold(None, |max: Option<f64>, t| {
+                    Some(max.map_or(t, |m| m.max(t)))
+                });
+            let Some(threshold) = crossed else {
+                continue;
+            };
+            if self.warned.insert((scope, threshold.to_bits())) {
+                warnings.push(BudgetWarningEvent {
+                    job_id: self.job_id.clone(),
+                    scope,
+                    threshold,
+                    spent,
+                    limit,
+                });
+            }
+        }
+        warnings
+    }
+}
+
+// ========== 可恢复的暂停任务 ==========
+
+/// 已完成条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausedEntry {
+    pub translation: String,
+    pub source: String,
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// 翻译任务状态（暂停时写入磁盘，恢复时继续翻译未完成的条目）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausedJob {
+    pub id: String,
+    pub created_at: String,
+    pub target_language: Option<String>,
+    /// 翻译的文件（用于用量台账归属）
+    #[serde(default)]
+    pub file_path: Option<String>,
+    pub texts: Vec<String>,
+    /// 与 texts 对应的 msgctxt（可为空或短于 texts）
+    #[serde(default)]
+    pub contexts: Vec<Option<String>>,
+    /// 源语言（参与记忆库键）
+    #[serde(default)]
+    pub source_language: Option<String>,
+    /// 可写的项目记忆库（None 为默认记忆库）
+    #[serde(default)]
+    pub project_memory: Option<String>,
+    /// 只读参考记忆库（按优先级排列）
+    #[serde(default)]
+    pub reference_memories: Vec<String>,
+    /// 与 texts 一一对应，None 表示尚未翻译
+    pub entries: Vec<Option<PausedEntry>>,
+    pub batch_stats: BatchStats,
+    pub token_stats: TokenStats,
+    /// 暂停原因
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl PausedJob {
+    /// 新任务
+    pub fn new(texts: Vec<String>, target_language: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            target_language,
+            file_path: None,
+            entries: vec![None; texts.len()],
+            texts,
+            contexts: Vec::new(),
+            source_language: None,
+            project_memory: None,
+            reference_memories: Vec::new(),
+            batch_stats: BatchStats::default(),
+            token_stats: TokenStats::default(),
+            reason: None,
+        }
+    }
+
+    /// 未翻译条目的索引
+    pub fn pending_indices(&self) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn completed_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+}
+
+/// 暂停任务概要（用于列表展示）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct PausedJobSummary {
+    pub id: String,
+    pub created_at: String,
+    pub target_language: Option<String>,
+    pub total: usize,
+    pub completed: usize,
+    pub cost: f64,
+    pub reason: Option<String>,
+}
+
+impl From<&PausedJob> for PausedJobSummary {
+    fn from(job: &PausedJob) -> Self {
+        Self {
+            id: job.id.clone(),
+            created_at: job.created_at.clone(),
+            target_language: job.target_language.clone(),
+            total: job.texts.len(),
+            completed: job.completed_count(),
+            cost: job.token_stats.cost,
+            reason: job.reason.clone(),
+        }
+    }
+}
+
+fn paused_jobs_dir() -> PathBuf {
+    crate::utils::paths::app_data_dir()
+        .map(|dir| dir.join("paused_jobs"))
+        .unwrap_or_else(|_| PathBuf::from("paused_jobs"))
+}
+
+/// 任务 ID 只允许 UUID 字符，避免路径穿越
+fn paused_job_path(dir: &Path, id: &str) -> Result<PathBuf, AppError> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(AppError::validation(format!("无效的任务 ID: {}", id)));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+pub fn save_paused_job(job: &PausedJob) -> Result<(), AppError> {
+    save_paused_job_in(&paused_jobs_dir(), job)
+}
+
+pub fn load_paused_job(id: &str) -> Result<PausedJob, AppError> {
+    load_paused_job_in(&paused_jobs_dir(), id)
+}
+
+pub fn remove_paused_job(id: &str) -> Result<(), AppError> {
+    remove_paused_job_in(&paused_jobs_dir(), id)
+}
+
+pub fn list_paused_jobs() -> Result<Vec<PausedJobSummary>, AppError> {
+    list_paused_jobs_in(&paused_jobs_dir())
+}
+
+fn save_paused_job_in(dir: &Path, job: &PausedJob) -> Result<(), AppError> {
+    let path = paused_job_path(dir, &job.id)?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, serde_json::to_string(job)?)?;
+    Ok(())
+}
+
+fn load_paused_job_in(dir: &Path, id: &str) -> Result<PausedJob, AppError> {
+    let path = paused_job_path(dir, id)?;
+    if !path.exists() {
+        return Err(AppError::validation(format!("暂停的任务不存在: {}", id)));
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn remove_paused_job_in(dir: &Path, id: &str) -> Result<(), AppError> {
+    let path = paused_job_path(dir, id)?;
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn list_paused_jobs_in(dir: &Path) -> Result<Vec<PausedJobSummary>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut jobs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            match std::fs::read_to_string(&path)
+                .map_err(AppError::from)
+                .and_then(|content| Ok(serde_json::from_str::<PausedJob>(&content)?))
+            {
+                Ok(job) => jobs.push(PausedJobSummary::from(&job)),
+                Err(e) => log::error!("[预算] 读取暂停任务失败 {}: {}", path.display(), e),
+            }
+        }
+    }
+    jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(jobs)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::services::tests::test_env::TestDataDir;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn memory_ledger() -> Arc<Mutex<SpendingLedger>> {
+        Arc::new(Mutex::new(SpendingLedger::default()))
+    }
+
+    fn usage(day: NaiveDate, cost: f64, currency: &str) -> UsageRecord {
+        let timestamp = day
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .to_rfc3339();
+        UsageRecord {
+            timestamp,
+            provider: "openai".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            file: None,
+            project: None,
+            input_tokens: 100,
+            output_tokens: 50,
+            cached_tokens: 0,
+            cost,
+            currency: currency.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ledger_sums_usage_records_by_day_and_month() {
+        let records = [
+            usage(date(2026, 10, 17), 1.0, "USD"),
+            usage(date(2026, 10, 18), 0.5, "USD"),
+            usage(date(2026, 10, 18), 1.8, "CNY"),
+            usage(date(2026, 9, 30), 4.0, "USD"),
+        ];
+        let ledger = SpendingLedger::from_records(&records, "USD");
+        assert!((ledger.spent_on(date(2026, 10, 18)) - 0.75).abs() < 1e-9);
+        assert!((ledger.spent_in_month(date(2026, 10, 1)) - 1.75).abs() < 1e-9);
+        assert!((ledger.spent_in_month(date(2026, 9, 1)) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shared_ledger_follows_usage_ledger() {
+        let _data = TestDataDir::new();
+        let today = Local::now().date_naive();
+        usage_ledger::append(&usage(today, 0.25, "USD")).unwrap();
+        usage_ledger::append(&usage(today, 0.5, "USD")).unwrap();
+        let display = currency::display_currency();
+        let expected = currency::currency_table()
+            .convert(0.75, "USD", &display)
+            .unwrap();
+
+        let ledger = shared_ledger();
+        let mut ledger = ledger.lock();
+        ledger.ensure_currency(&display);
+        // 首次使用时汇总台账文件，之后追加的记录只计入一次
+        assert!((ledger.spent_on(today) - expected).abs() < 1e-9);
+        let reloaded =
+            SpendingLedger::from_records(&usage_ledger::load(Some(today), None).unwrap(), &display);
+        assert!((reloaded.spent_on(today) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ledger_converts_on_currency_change() {
+        let mut ledger = SpendingLedger::default();
+        ledger.ensure_currency("USD");
+        ledger.record(date(2026, 10, 18), 1.0);
+        ledger.ensure_currency("CNY");
+        assert!((ledger.spent_on(date(2026, 10, 18)) - 7.2).abs() < 1e-9);
+        // 未知货币时保持原值
+        ledger.ensure_currency("XYZ");
+        assert!((ledger.spent_on(date(2026, 10, 18)) - 7.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_caps_convert_on_currency_change() {
+        let mut config = BudgetConfig {
+            max_job_cost: Some(0.5),
+            daily_cap: Some(1.0),
+            ..Default::default()
+        };
+        config.convert_currency("USD", "CNY").unwrap();
+        assert!((config.max_job_cost.unwrap() - 3.6).abs() < 1e-9);
+        assert!((config.daily_cap.unwrap() - 7.2).abs() < 1e-9);
+        assert_eq!(config.monthly_cap, None);
+        assert!(config.convert_currency("CNY", "XYZ").is_err());
+        assert!((config.daily_cap.unwrap() - 7.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_job_cap_pauses_before_projected_overrun() {
+        let config = BudgetConfig {
+            max_job_cost: Some(1.0),
+            ..Default::default()
+        };
+        let mut tracker = BudgetTracker::new(config, "job", 0.0, memory_ledger());
+        let today = date(2026, 10, 18);
+
+        // 第一批没有历史数据，直接放行
+        tracker.check_next_batch_on(today).unwrap();
+        tracker.record_batch_on(today, 0.4);
+        tracker.check_next_batch_on(today).unwrap();
+        tracker.record_batch_on(today, 0.4);
+
+        // 已花费 0.8，预计下一批 0.4 -> 超出 1.0
+        let err = tracker.check_next_batch_on(today).unwrap_err();
+        assert!(matches!(err, AppError::BudgetExceeded { .. }));
+        assert!(err.to_string().contains("job"));
+        assert!((tracker.job_spent() - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_first_batch_checked_against_preflight_estimate() {
+        let config = BudgetConfig {
+            max_job_cost: Some(1.0),
+            ..Default::default()
+        };
+        let today = date(2026, 10, 18);
+        let mut tracker = BudgetTracker::new(config.clone(), "job", 0.0, memory_ledger())
+            .with_estimated_batch_cost(1.5);
+        assert!(tracker.check_next_batch_on(today).is_err());
+
+        let mut tracker =
+            BudgetTracker::new(config, "job", 0.0, memory_ledger()).with_estimated_batch_cost(0.6);
+        tracker.check_next_batch_on(today).unwrap();
+        // 第二个并发批次计入第一批的预估
+        assert!(tracker.check_next_batch_on(today).is_err());
+        // 完成后改用实际平均花费
+        tracker.record_batch_on(today, 0.2);
+        tracker.check_next_batch_on(today).unwrap();
+    }
+
+    #[test]
+    fn test_daily_cap_counts_in_flight_batches_and_other_jobs() {
+        let ledger = memory_ledger();
+        let today = date(2026, 10, 18);
+        ledger.lock().record(today, 2.0);
+
+        let config = BudgetConfig {
+            daily_cap: Some(3.0),
+            ..Default::default()
+        };
+        let mut tracker = BudgetTracker::new(config, "job", 0.0, Arc::clone(&ledger));
+        tracker.check_next_batch_on(today).unwrap();
+        // 实际花费随用量记录计入台账
+        ledger.lock().record(today, 0.3);
+        tracker.record_batch_on(today, 0.3);
+
+        // 2.3 已花费，两个并发批次各预计 0.3
+        tracker.check_next_batch_on(today).unwrap();
+        tracker.check_next_batch_on(today).unwrap();
+        assert!(tracker.check_next_batch_on(today).is_err());
+
+        // 次日重新计算
+        tracker.release_batch();
+        tracker.release_batch();
+        assert!(tracker.check_next_batch_on(date(2026, 10, 19)).is_ok());
+    }
+
+    #[test]
+    fn test_warnings_fire_once_per_threshold() {
+        let config = BudgetConfig {
+            max_job_cost: Some(10.0),
+            warning_thresholds: vec![0.5, 0.8],
+            ..Default::default()
+        };
+        let mut tracker = BudgetTracker::new(config, "job", 0.0, memory_ledger());
+        let today = date(2026, 10, 18);
+
+        assert!(tracker.record_batch_on(today, 4.0).is_empty());
+        let warnings = tracker.record_batch_on(today, 1.5);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].scope, BudgetScope::Job);
+        assert_eq!(warnings[0].threshold, 0.5);
+        assert!(tracker.record_batch_on(today, 0.1).is_empty());
+
+        let warnings = tracker.record_batch_on(today, 3.0);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].threshold, 0.8);
+    }
+
+    #[test]
+    fn test_paused_job_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut job = PausedJob::new(
+            vec!["Open".to_string(), "Close".to_string()],
+            Some("zh-Hans".to_string()),
+        );
+        job.entries[0] = Some(PausedEntry {
+            translation: "打开".to_string(),
+            source: "ai".to_string(),
+            provider_id: Some("openai".to_string()),
+            model: Some("gpt-4o-mini".to_string()),
+        });
+        job.reason = Some("预算超限".to_string());
+        save_paused_job_in(dir.path(), &job).unwrap();
+
+        let loaded = load_paused_job_in(dir.path(), &job.id).unwrap();
+        assert_eq!(loaded.pending_indices(), vec![1]);
+        assert_eq!(loaded.completed_count(), 1);
+
+        let jobs = list_paused_jobs_in(dir.path()).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].total, 2);
+
+        remove_paused_job_in(dir.path(), &job.id).unwrap();
+        assert!(list_paused_jobs_in(dir.path()).unwrap().is_empty());
+        assert!(load_paused_job_in(dir.path(), "../config").is_err());
+    }
+}
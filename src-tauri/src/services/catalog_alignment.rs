@@ -0,0 +1,627 @@
+/**
+ * 对齐已翻译的历史文件，批量生成记忆库
+ *
+ * - PO：原文与译文在同一文件中，读取 `msgctxt`/`msgid`/`msgstr`，跳过 fuzzy 与未翻译条目；
+ *   译文与原文相同（常见于回退到源语言的目录）视为未翻译，与记忆库清理规则一致；
+ *   目标语言依次取头部 `Language:`、文件名（`de.po`）、所在目录（`de/LC_MESSAGES/app.po`）
+ * - JSON：文件中只有译文，按语言与源语言文件配对（`en.json` ↔ `de.json`，`en/app.json` ↔ `de/app.json`），
+ *   嵌套对象按 `a.b.c` 展平后按键对齐；也可直接指定源文件/译文文件对
+ * - 同一 (源语言, 目标语言, 上下文, 原文) 出现不同译文，或与记忆库已有译文不同时记入冲突报告，不写入
+ * - 简单短语写入短语表，其余写入句段库并标记为已审校（与 TMX 导入规则一致）
+ */
+use crate::services::po_parser::POParser;
+use crate::services::segment_memory::{ReviewStatus, SegmentEntry};
+use crate::services::tm_store::TmStore;
+use crate::services::tmx::{looks_like_lang, memory_lang, normalize_bcp47};
+use crate::services::translation_memory::TmKey;
+use crate::utils::common::is_simple_phrase;
+use anyhow::{Result, anyhow};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 对齐得到的一条译文对
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignedPair {
+    pub source: String,
+    pub target: String,
+    pub context: Option<String>,
+    /// 目标语言（记忆库标准化后）
+    pub target_lang: String,
+    /// 来源文件
+    pub file: String,
+}
+
+/// 显式指定的源文件/译文文件对（用于不含原文的格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct CatalogFilePair {
+    pub source: String,
+    pub target: String,
+    /// 目标语言，未指定时按文件名/目录推断
+    #[serde(default)]
+    pub target_language: Option<String>,
+}
+
+/// 冲突中的一种译文及其出处
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct ConflictVariant {
+    pub target: String,
+    pub files: Vec<String>,
+}
+
+/// 同一原文的冲突译文（均未写入记忆库）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct CatalogConflict {
+    pub source: String,
+    pub context: Option<String>,
+    pub target_lang: String,
+    /// 记忆库中已有的译文
+    pub existing: Option<String>,
+    pub variants: Vec<ConflictVariant>,
+}
+
+/// 对齐导入结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct CatalogImportReport {
+    /// 读取的文件数
+    pub files: usize,
+    /// 跳过的文件及原因
+    pub skipped_files: Vec<String>,
+    /// 对齐得到的译文对数量（含重复）
+    pub pairs: usize,
+    /// 写入短语表的条数
+    pub phrases: usize,
+    /// 写入句段库的条数
+    pub segments: usize,
+    /// 与记忆库已有译文相同而未改动的条数
+    pub unchanged: usize,
+    /// 跳过的 fuzzy 条目
+    pub fuzzy: usize,
+    /// 跳过的未翻译条目
+    pub untranslated: usize,
+    /// 跳过的译文与原文相同的条目
+    pub same_as_source: usize,
+    pub conflicts: Vec<CatalogConflict>,
+}
+
+/// 扫描目录（递归）中的 PO 与 JSON 文件并对齐
+///
+/// `source_lang` 用于给 JSON 译文文件找源语言文件；`default_target_lang` 在无法推断目标语言时使用
+pub fn collect_from_directory(
+    dir: &Path,
+    source_lang: Option<&str>,
+    default_target_lang: Option<&str>,
+    report: &mut CatalogImportReport,
+) -> Result<Vec<AlignedPair>> {
+    let mut files = Vec::new();
+    walk(dir, &mut files)?;
+
+    let source_lang = source_lang.map(memory_lang);
+    let mut pairs = Vec::new();
+    for path in files {
+        let result = match extension(&path).as_str() {
+            "po" => read_po(&path, default_target_lang, report),
+            "json" => {
+                let Some(source_lang) = source_lang.as_deref() else {
+                    skip(report, &path, "未指定源语言，无法配对 JSON 文件");
+                    continue;
+                };
+                let Some((source_path, target_lang)) = json_partner(&path, source_lang) else {
+                    skip(report, &path, "未找到对应的源语言文件");
+                    continue;
+                };
+                if source_path == path {
+                    continue; // 源语言文件本身
+                }
+                read_json_pair(&source_path, &path, &target_lang, report)
+            }
+            _ => continue,
+        };
+        match result {
+            Ok(aligned) => {
+                report.files += 1;
+                pairs.extend(aligned);
+            }
+            Err(e) => skip(report, &path, &e.to_string()),
+        }
+    }
+    Ok(pairs)
+}
+
+/// 对齐显式指定的文件对
+pub fn collect_from_pairs(
+    file_pairs: &[CatalogFilePair],
+    default_target_lang: Option<&str>,
+    report: &mut CatalogImportReport,
+) -> Vec<AlignedPair> {
+    let mut pairs = Vec::new();
+    for file_pair in file_pairs {
+        let target_path = Path::new(&file_pair.target);
+        let target_lang = file_pair
+            .target_language
+            .as_deref()
+            .map(memory_lang)
+            .or_else(|| lang_of_path(target_path))
+            .or_else(|| default_target_lang.map(memory_lang));
+        let Some(target_lang) = target_lang else {
+            skip(report, target_path, "无法确定目标语言");
+            continue;
+        };
+        match read_json_pair(
+            Path::new(&file_pair.source),
+            target_path,
+            &target_lang,
+            report,
+        ) {
+            Ok(aligned) => {
+                report.files += 1;
+                pairs.extend(aligned);
+            }
+            Err(e) => skip(report, target_path, &e.to_string()),
+        }
+    }
+    pairs
+}
+
+/// 把对齐结果写入存储；有冲突的原文只记入报告
+pub fn import_pairs(
+    pairs: Vec<AlignedPair>,
+    source_lang: Option<&str>,
+    store: &TmStore,
+    report: &mut CatalogImportReport,
+) -> Result<()> {
+    report.pairs += pairs.len();
+
+    // 按记忆库键分组，合并相同译文的出处
+    let mut groups: IndexMap<String, (TmKey, Vec<ConflictVariant>)> = IndexMap::new();
+    for pair in pairs {
+        let key = TmKey::new(&pair.source, Some(&pair.target_lang))
+            .with_source_lang(source_lang)
+            .with_context(pair.context.as_deref());
+        let (_, variants) = groups
+            .entry(key.encode())
+            .or_insert_with(|| (key, Vec::new()));
+        match variants.iter_mut().find(|v| v.target == pair.target) {
+            Some(variant) if variant.files.contains(&pair.file) => {}
+            Some(variant) => variant.files.push(pair.file),
+            None => variants.push(ConflictVariant {
+                target: pair.target,
+                files: vec![pair.file],
+            }),
+        }
+    }
+
+    let mut phrases: Vec<(String, String)> = Vec::new();
+    let mut segments = Vec::new();
+    for (encoded, (key, mut variants)) in groups {
+        let existing = existing_translation(store, &key)?;
+        if variants.len() == 1 && existing.as_ref() == Some(&variants[0].target) {
+            report.unchanged += 1;
+            continue;
+        }
+        if variants.len() > 1 || existing.is_some() {
+            report.conflicts.push(CatalogConflict {
+                source: key.source,
+                context: key.context,
+                target_lang: key.target_lang.unwrap_or_default(),
+                existing,
+                variants,
+            });
+            continue;
+        }
+
+        let ConflictVariant { target, mut files } = variants.remove(0);
+        if key.context.is_none() && is_simple_phrase(&key.source) && target.len() <= 50 {
+            phrases.push((encoded, target));
+            continue;
+        }
+        let target_lang = key.target_lang.as_deref().unwrap_or_default();
+        segments.push(
+            SegmentEntry::new(&key.source, &target, target_lang, ReviewStatus::Approved)
+                .with_source_lang(key.source_lang.as_deref())
+                .with_context(key.context.clone())
+                .with_file(Some(files.remove(0))),
+        );
+    }
+
+    report.phrases += store.upsert_pinned_phrases(
+        phrases
+            .iter()
+            .map(|(key, target)| (key.as_str(), target.as_str())),
+    )?;
+    report.segments += store.upsert_segments(&segments)?;
+    crate::app_log!(
+        "[TM对齐] {} 个文件，{} 对译文：短语 {} 条，句段 {} 条，未变 {} 条，冲突 {} 条",
+        report.files,
+        report.pairs,
+        report.phrases,
+        report.segments,
+        report.unchanged,
+        report.conflicts.len()
+    );
+    Ok(())
+}
+
+/// 记忆库中与 `key` 同上下文的已有译文（同时查未记录源语言的旧条目）
+fn existing_translation(store: &TmStore, key: &TmKey) -> Result<Option<String>> {
+    for (candidate, score) in key.lookup_candidates(None) {
+        // 候选按上下文一致优先排列，不考虑退回到无上下文的条目
+        if score < 100 {
+            break;
+        }
+        if let Some(target) = store.phrase(&candidate)? {
+            return Ok(Some(target));
+        }
+        let candidate = TmKey::parse(&candidate);
+        if candidate.target_lang.is_some()
+            && let Some(entry) = store.segment(&candidate)?
+        {
+            return Ok(Some(entry.target));
+        }
+    }
+    Ok(None)
+}
+
+/// 读取已翻译的 PO 文件
+fn read_po(
+    path: &Path,
+    default_target_lang: Option<&str>,
+    report: &mut CatalogImportReport,
+) -> Result<Vec<AlignedPair>> {
+    let content = fs::read_to_string(path)?;
+    let target_lang = po_header_language(&content)
+        .map(|lang| memory_lang(&lang))
+        .or_else(|| lang_of_path(path))
+        .or_else(|| default_target_lang.map(memory_lang))
+        .ok_or_else(|| anyhow!("无法确定目标语言"))?;
+
+    let file = path.to_string_lossy().to_string();
+    let mut pairs = Vec::new();
+    for entry in POParser::new()?.parse_content(&content)? {
+        if is_fuzzy(&entry.comments) {
+            report.fuzzy += 1;
+        } else if entry.msgstr.trim().is_empty() {
+            report.untranslated += 1;
+        } else if entry.msgstr.trim() == entry.msgid.trim() {
+            report.same_as_source += 1;
+        } else {
+            pairs.push(AlignedPair {
+                source: entry.msgid,
+                target: entry.msgstr,
+                context: (!entry.msgctxt.is_empty()).then_some(entry.msgctxt),
+                target_lang: target_lang.clone(),
+                file: file.clone(),
+            });
+        }
+    }
+    Ok(pairs)
+}
+
+/// 按键对齐源语言 JSON 与译文 JSON
+fn read_json_pair(
+    source_path: &Path,
+    target_path: &Path,
+    target_lang: &str,
+    report: &mut CatalogImportReport,
+) -> Result<Vec<AlignedPair>> {
+    let source = read_json_strings(source_path)?;
+    let target = read_json_strings(target_path)?;
+    let file = target_path.to_string_lossy().to_string();
+
+    let mut pairs = Vec::new();
+    for (key, source_text) in source {
+        match target.get(&key) {
+            Some(target_text) if target_text.trim() == source_text.trim() => {
+                report.same_as_source += 1;
+            }
+            Some(target_text) if !target_text.trim().is_empty() && !source_text.is_empty() => {
+                pairs.push(AlignedPair {
+                    source: source_text,
+                    target: target_text.clone(),
+                    context: None,
+                    target_lang: target_lang.to_string(),
+                    file: file.clone(),
+                });
+            }
+            _ => report.untranslated += 1,
+        }
+    }
+    Ok(pairs)
+}
+
+/// 读取 JSON 中的全部字符串值，嵌套对象与数组按 `a.b.0` 展平
+fn read_json_strings(path: &Path) -> Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| anyhow!("JSON 解析失败: {}", e))?;
+    let mut strings = BTreeMap::new();
+    flatten_json(&value, String::new(), &mut strings);
+    Ok(strings)
+}
+
+fn flatten_json(value: &serde_json::Value, prefix: String, out: &mut BTreeMap<String, String>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        serde_json::Value::String(text) => {
+            out.insert(prefix, text.clone());
+        }
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                flatten_json(value, join(key), out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten_json(value, join(&i.to_string()), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 为 JSON 译文文件找源语言文件：同目录下的 `<源语言>.json`，或同名文件在 `<源语言>/` 目录中
+fn json_partner(path: &Path, source_lang: &str) -> Option<(PathBuf, String)> {
+    let dir = path.parent()?;
+    if let Some(target_lang) = path
+        .file_stem()
+        .and_then(|stem| lang_of(&stem.to_string_lossy()))
+        && let Some(source_path) = find_child(dir, source_lang, true)
+    {
+        return Some((source_path, target_lang));
+    }
+
+    let target_lang = lang_of(&dir.file_name()?.to_string_lossy())?;
+    let source_dir = find_child(dir.parent()?, source_lang, false)?;
+    let source_path = source_dir.join(path.file_name()?);
+    source_path.is_file().then_some((source_path, target_lang))
+}
+
+/// 在 `dir` 下找名称（文件取主名）对应语言为 `lang` 的文件或目录
+fn find_child(dir: &Path, lang: &str, file: bool) -> Option<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+    entries.into_iter().find(|path| {
+        let name = if file {
+            if !path.is_file() || extension(path) != "json" {
+                return false;
+            }
+            path.file_stem()
+        } else {
+            if !path.is_dir() {
+                return false;
+            }
+            path.file_name()
+        };
+        name.and_then(|name| lang_of(&name.to_string_lossy()))
+            .as_deref()
+            == Some(lang)
+    })
+}
+
+/// 由文件名或所在目录推断语言（`de.po`、`de/app.json`、`de/LC_MESSAGES/app.po`）
+fn lang_of_path(path: &Path) -> Option<String> {
+    if let Some(lang) = path
+        .file_stem()
+        .and_then(|stem| lang_of(&stem.to_string_lossy()))
+    {
+        return Some(lang);
+    }
+    path.ancestors()
+        .skip(1)
+        .filter_map(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy())
+        .find(|name| name != "LC_MESSAGES")
+        .and_then(|name| lang_of(&name))
+}
+
+/// 形如语言代码的名称转为记忆库语言代码（主语言限两个字母，避免把 `app` 之类的名称当作语言）
+fn lang_of(name: &str) -> Option<String> {
+    let tag = normalize_bcp47(name);
+    let primary_len = tag.split('-').next().map_or(0, str::len);
+    (primary_len == 2 && looks_like_lang(&tag)).then(|| memory_lang(name))
+}
+
+/// PO 头部的 `Language:` 字段
+fn po_header_language(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .find_map(|line| line.strip_prefix("Language:"))
+        .map(|value| value.trim_end_matches("\\n").trim().to_string())
+        .filter(|lang| !lang.is_empty())
+}
+
+/// `#, fuzzy` 标记（解析器保存的注释不含开头的 `#`）
+fn is_fuzzy(comments: &[String]) -> bool {
+    comments.iter().any(|comment| {
+        comment
+            .strip_prefix(',')
+            .is_some_and(|flags| flags.split(',').any(|flag| flag.trim() == "fuzzy"))
+    })
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn skip(report: &mut CatalogImportReport, path: &Path, reason: &str) {
+    report
+        .skipped_files
+        .push(format!("{}: {}", path.display(), reason));
+}
+
+/// 递归收集文件（按路径排序，结果稳定）
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const DE_PO: &str = r#"msgid ""
+msgstr ""
+"Language: de\n"
+
+msgid "Open"
+msgstr "Öffnen"
+
+#, fuzzy
+msgid "Close"
+msgstr "Schließen"
+
+msgctxt "menu"
+msgid "File"
+msgstr "Datei"
+
+msgid "Save all documents before closing the window."
+msgstr "Alle Dokumente vor dem Schließen des Fensters speichern."
+
+msgid "Untranslated"
+msgstr ""
+
+msgid "OK"
+msgstr "OK"
+"#;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_po_and_json_alignment() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "po/app.po", DE_PO);
+        write(
+            dir.path(),
+            "json/en.json",
+            r#"{"menu": {"open": "Open", "quit": "Quit"}}"#,
+        );
+        write(
+            dir.path(),
+            "json/fr.json",
+            r#"{"menu": {"open": "Ouvrir", "quit": "Quit"}}"#,
+        );
+        write(dir.path(), "nested/en/app.json", r#"{"title": "Settings"}"#);
+        write(dir.path(), "nested/ja/app.json", r#"{"title": "設定"}"#);
+        write(dir.path(), "orphan/zh-CN.json", r#"{"title": "设置"}"#);
+
+        let mut report = CatalogImportReport::default();
+        let pairs = collect_from_directory(dir.path(), Some("en"), None, &mut report).unwrap();
+        assert_eq!(report.files, 3);
+        assert_eq!((report.fuzzy, report.untranslated), (1, 1));
+        // 回退到原文的译文不导入
+        assert_eq!(report.same_as_source, 2);
+        assert_eq!(report.skipped_files.len(), 1);
+        assert!(report.skipped_files[0].contains("zh-CN.json"));
+
+        let find = |source: &str, lang: &str| {
+            pairs
+                .iter()
+                .find(|p| p.source == source && p.target_lang == lang)
+                .map(|p| (p.target.as_str(), p.context.as_deref()))
+        };
+        assert_eq!(find("Open", "de"), Some(("Öffnen", None)));
+        assert_eq!(find("File", "de"), Some(("Datei", Some("menu"))));
+        assert_eq!(find("Close", "de"), None);
+        assert_eq!(find("Open", "fr"), Some(("Ouvrir", None)));
+        assert_eq!(find("Quit", "fr"), None);
+        assert_eq!(find("OK", "de"), None);
+        assert_eq!(find("Settings", "ja"), Some(("設定", None)));
+    }
+
+    #[test]
+    fn test_conflicts_are_reported_not_imported() {
+        let store = TmStore::open_in_memory().unwrap();
+        let existing = TmKey::new("Cancel", Some("de")).with_source_lang(Some("en"));
+        store
+            .upsert_phrases([(existing.encode().as_str(), "Abbruch")])
+            .unwrap();
+
+        let pair = |source: &str, target: &str, file: &str| AlignedPair {
+            source: source.to_string(),
+            target: target.to_string(),
+            context: None,
+            target_lang: "de".to_string(),
+            file: file.to_string(),
+        };
+        let pairs = vec![
+            pair("Open", "Öffnen", "a.po"),
+            pair("Open", "Öffnen", "b.po"),
+            pair("Save", "Speichern", "a.po"),
+            pair("Save", "Sichern", "b.po"),
+            pair("Cancel", "Abbrechen", "a.po"),
+        ];
+        let mut report = CatalogImportReport::default();
+        import_pairs(pairs, Some("en"), &store, &mut report).unwrap();
+
+        assert_eq!((report.pairs, report.phrases), (5, 1));
+        assert_eq!(report.conflicts.len(), 2);
+        let save = &report.conflicts[0];
+        assert_eq!(save.source, "Save");
+        assert_eq!(save.variants.len(), 2);
+        let cancel = &report.conflicts[1];
+        assert_eq!(cancel.existing.as_deref(), Some("Abbruch"));
+
+        // 冲突的原文不覆盖已有译文，也不写入
+        assert_eq!(
+            store.phrase(&existing.encode()).unwrap().as_deref(),
+            Some("Abbruch")
+        );
+        let save_key = TmKey::new("Save", Some("de")).with_source_lang(Some("en"));
+        assert_eq!(store.phrase(&save_key.encode()).unwrap(), None);
+
+        // 再次导入相同内容：已有条目计为未变
+        let mut again = CatalogImportReport::default();
+        import_pairs(
+            vec![pair("Open", "Öffnen", "a.po")],
+            Some("en"),
+            &store,
+            &mut again,
+        )
+        .unwrap();
+        assert_eq!((again.unchanged, again.phrases), (1, 0));
+    }
+}
@@ -21,7 +21,12 @@ use std::sync::Arc;
 use tokio::sync::OnceCell;
 use tracing::instrument;
 
-use crate::services::ai_translator::AIConfig;
+use crate::services::ai::currency;
+use crate::services::ai_translator::{self, AIConfig};
+use crate::services::budget::BudgetConfig;
+use crate::services::fuzzy_match::{self, FuzzyMatchConfig};
+use crate::services::response_cache::{self, ResponseCacheConfig};
+use crate::services::tm_maintenance::{self, TmCapacityConfig};
 use crate::utils::paths;
 
 #[cfg(feature = "ts-rs")]
@@ -55,11 +60,33 @@ pub struct AppConfig {
     pub batch_size: usize,
     pub max_concurrent: usize,
     pub timeout_seconds: u64,
+    /// 按模型上下文窗口和文本长度自动决定每批条数（关闭时使用固定的 `batch_size`）
+    #[serde(default = "default_adaptive_batch_size")]
+    pub adaptive_batch_size: bool,
 
     #[serde(default)]
     pub ai_configs: Vec<AIConfig>,
     #[serde(default)]
     pub active_config_index: Option<usize>,
+    /// 备用供应商链（`ai_configs` 索引，按顺序尝试）：当前配置重试耗尽后依次切换
+    #[serde(default)]
+    pub fallback_config_indices: Vec<usize>,
+
+    /// 花费预算（单任务 / 每日 / 每月上限与预警阈值）
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// 成本显示货币（ISO 4217 代码），插件价格按汇率表换算为该货币
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// AI 响应磁盘缓存（相同请求不再重复付费）
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    /// 翻译记忆库模糊匹配（自动套用阈值与提示词参考）
+    #[serde(default)]
+    pub fuzzy_match: FuzzyMatchConfig,
+    /// 记忆库容量（超出时按 LRU/LFU 淘汰，默认不限）
+    #[serde(default)]
+    pub tm_capacity: TmCapacityConfig,
 
     #[serde(default)]
     pub system_prompt: Option<String>,
@@ -82,6 +109,14 @@ pub struct AppConfig {
     pub last_modified: Option<String>,
 }
 
+fn default_adaptive_batch_size() -> bool {
+    true
+}
+
+fn default_currency() -> String {
+    currency::DEFAULT_CURRENCY.to_string()
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         let default_tm_path = Self::get_default_tm_path();
@@ -98,8 +133,15 @@ impl Default for AppConfig {
             batch_size: 10,
             max_concurrent: 3,
             timeout_seconds: 30,
+            adaptive_batch_size: true,
             ai_configs: Vec::new(),
             active_config_index: None,
+            fallback_config_indices: Vec::new(),
+            budget: BudgetConfig::default(),
+            currency: default_currency(),
+            response_cache: ResponseCacheConfig::default(),
+            fuzzy_match: FuzzyMatchConfig::default(),
+            tm_capacity: TmCapacityConfig::default(),
             system_prompt: None,
             theme_mode: None,
             language: None,
@@ -134,6 +176,17 @@ impl AppConfig {
             .and_then(|index| self.ai_configs.get(index))
     }
 
+    /// 按尝试顺序返回供应商链：启用的配置在前，随后是备用配置（忽略越界和重复的索引）
+    pub fn get_ai_config_chain(&self) -> Vec<&AIConfig> {
+        let mut seen = std::collections::HashSet::new();
+        self.active_config_index
+            .into_iter()
+            .chain(self.fallback_config_indices.iter().copied())
+            .filter(|index| seen.insert(*index))
+            .filter_map(|index| self.ai_configs.get(index))
+            .collect()
+    }
+
     pub fn get_active_ai_config_mut(&mut self) -> Option<&mut AIConfig> {
         if let Some(index) = self.active_config_index {
             self.ai_configs.get_mut(index)
@@ -177,6 +230,31 @@ impl AppConfig {
             }
         }
 
+        self.fallback_config_indices
+            .retain(|&fallback| fallback != index);
+        for fallback in &mut self.fallback_config_indices {
+            if *fallback > index {
+                *fallback -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 设置备用供应商链（按顺序尝试；去掉重复与当前启用的索引）
+    pub fn set_fallback_ai_configs(&mut self, indices: Vec<usize>) -> Result<()> {
+        if let Some(index) = indices
+            .iter()
+            .find(|index| **index >= self.ai_configs.len())
+        {
+            return Err(anyhow!("配置索引超出范围: {}", index));
+        }
+        let mut seen = std::collections::HashSet::new();
+        let active = self.active_config_index;
+        self.fallback_config_indices = indices
+            .into_iter()
+            .filter(|index| Some(*index) != active && seen.insert(*index))
+            .collect();
         Ok(())
     }
 
@@ -435,6 +513,11 @@ impl ConfigDraft {
         }
 
         let secrets_path = Self::get_secrets_path(&config_path);
+        currency::set_display_currency(&config.currency);
+        ai_translator::configure_request_timeout(config.timeout_seconds);
+        response_cache::configure(&config.response_cache);
+        fuzzy_match::configure(&config.fuzzy_match);
+        tm_maintenance::configure(&config.tm_capacity);
 
         let instance = Self {
             secrets_path: Arc::new(secrets_path),
@@ -577,6 +660,7 @@ impl ConfigDraft {
                         base_url: legacy_config.base_url,
                         model: legacy_config.model,
                         proxy: legacy_config.proxy,
+                        generation: Default::default(),
                     };
                     new_config.ai_configs.push(new_config_item);
                 }
@@ -729,6 +813,11 @@ impl ConfigDraft {
         log::info!("[apply] config.apply() 返回，有草稿: {}", applied.is_some());
         if applied.is_some() {
             let new_config = self.config.clone_data();
+            currency::set_display_currency(&new_config.currency);
+            ai_translator::configure_request_timeout(new_config.timeout_seconds);
+            response_cache::configure(&new_config.response_cache);
+            fuzzy_match::configure(&new_config.fuzzy_match);
+            tm_maintenance::configure(&new_config.tm_capacity);
 
             // 保存到磁盘（使用最新正式配置，避免将旧值写回）
             log::info!("[apply] 准备调用 save_to_disk_with_config");
@@ -774,6 +863,11 @@ impl ConfigDraft {
 
         // 发送事件
         let config_clone = self.config.clone_data();
+        currency::set_display_currency(&config_clone.currency);
+        ai_translator::configure_request_timeout(config_clone.timeout_seconds);
+        response_cache::configure(&config_clone.response_cache);
+        fuzzy_match::configure(&config_clone.fuzzy_match);
+        tm_maintenance::configure(&config_clone.tm_capacity);
         tokio::spawn(async move {
             if let Err(e) = Self::emit_config_updated(&config_clone) {
                 log::warn!("发送配置更新事件失败: {}", e);
@@ -971,6 +1065,7 @@ mod tests {
                     base_url: Some("https://api.openai.com/v1".to_string()),
                     model: Some("gpt-4o-mini".to_string()),
                     proxy: None,
+                    generation: Default::default(),
                 }];
                 config.active_config_index = Some(0);
             })
@@ -1008,6 +1103,7 @@ mod tests {
                     base_url: None,
                     model: Some("gpt-4o-mini".to_string()),
                     proxy: None,
+                    generation: Default::default(),
                 });
                 config.active_config_index = Some(0);
             })
@@ -1043,6 +1139,7 @@ mod tests {
                     base_url: Some("https://api.openai.com/v1".to_string()),
                     model: Some("gpt-4o-mini".to_string()),
                     proxy: None,
+                    generation: Default::default(),
                 });
                 config.active_config_index = Some(0);
             })
@@ -1127,6 +1224,53 @@ mod tests {
         assert_eq!(loaded.data().active_config_index, Some(0));
     }
 
+    #[test]
+    fn test_ai_config_chain_order_and_removal() {
+        let provider = |id: &str| AIConfig {
+            provider_id: id.to_string(),
+            api_key: "key".to_string(),
+            base_url: None,
+            model: None,
+            proxy: None,
+            generation: Default::default(),
+        };
+        let mut config = AppConfig {
+            ai_configs: vec![
+                provider("openai"),
+                provider("deepseek"),
+                provider("moonshot"),
+            ],
+            active_config_index: Some(1),
+            // 重复的启用索引和越界索引会被忽略
+            fallback_config_indices: vec![1, 2, 9, 0],
+            ..Default::default()
+        };
+
+        let chain: Vec<&str> = config
+            .get_ai_config_chain()
+            .iter()
+            .map(|c| c.provider_id.as_str())
+            .collect();
+        assert_eq!(chain, vec!["deepseek", "moonshot", "openai"]);
+
+        // 删除配置后备用索引同步调整
+        config.remove_ai_config(0).unwrap();
+        assert_eq!(config.fallback_config_indices, vec![0, 1, 8]);
+        let chain: Vec<&str> = config
+            .get_ai_config_chain()
+            .iter()
+            .map(|c| c.provider_id.as_str())
+            .collect();
+        assert_eq!(chain, vec!["deepseek", "moonshot"]);
+
+        // 设置备用链：越界时报错，重复与启用中的索引被去掉
+        assert!(config.set_fallback_ai_configs(vec![0, 5]).is_err());
+        config.set_fallback_ai_configs(vec![1, 0, 1]).unwrap();
+        assert_eq!(config.fallback_config_indices, vec![1]);
+        config.set_fallback_ai_configs(Vec::new()).unwrap();
+        assert!(config.fallback_config_indices.is_empty());
+    }
+
     #[derive(Debug, Clone, PartialEq)]
     struct TestConfig {
         value: i32,
@@ -0,0 +1,322 @@
+/**
+ * 翻译记忆库模糊匹配
+ *
+ * - 字符三元组倒排索引召回候选，再按 Levenshtein 编辑距离计算匹配度（百分比）
+ * - 匹配度达到自动套用阈值的直接作为译文（来源标记为 `fuzzy`，需要复核）
+ * - 低于阈值但不低于最低匹配度的作为参考译文注入批量翻译提示词
+ * - 比较前统一小写并合并空白；仅大小写或空白不同的匹配最高为 99%
+ */
+use crate::services::translation_memory::CONTEXT_SEPARATOR;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 每次查询参与编辑距离计算的候选上限（按共享三元组数排序）
+const MAX_CANDIDATES: usize = 50;
+
+/// 模糊匹配配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct FuzzyMatchConfig {
+    pub enabled: bool,
+    /// 自动套用阈值（%），达到该匹配度的直接作为译文并标记为模糊匹配；100 表示不自动套用
+    pub auto_apply_threshold: u8,
+    /// 最低匹配度（%），低于该值的不作为参考
+    pub min_score: u8,
+    /// 每条原文最多注入的参考条目数
+    pub top_k: usize,
+}
+
+impl Default for FuzzyMatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_apply_threshold: 95,
+            min_score: 70,
+            top_k: 2,
+        }
+    }
+}
+
+impl FuzzyMatchConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.auto_apply_threshold > 100 || self.min_score > 100 {
+            anyhow::bail!("匹配度必须在 0 ~ 100 之间");
+        }
+        if self.min_score > self.auto_apply_threshold {
+            anyhow::bail!(
+                "最低匹配度 {} 不能高于自动套用阈值 {}",
+                self.min_score,
+                self.auto_apply_threshold
+            );
+        }
+        Ok(())
+    }
+
+    /// 该匹配度是否自动套用
+    pub fn should_auto_apply(&self, score: u8) -> bool {
+        self.enabled && self.auto_apply_threshold < 100 && score >= self.auto_apply_threshold
+    }
+}
+
+/// 一条模糊匹配结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct FuzzyMatch {
+    pub source: String,
+    pub target: String,
+    /// 匹配度（%）
+    pub score: u8,
+}
+
+/// 索引中的一条原文
+#[derive(Debug, Clone)]
+struct IndexedSource {
+    /// 记忆库键（`[上下文\u{4}]原文|[源语言>]语言`）
+    key: String,
+    /// 原文在键中的位置（字节）
+    source_range: std::ops::Range<usize>,
+    normalized: Vec<char>,
+}
+
+/// 字符三元组倒排索引
+#[derive(Debug, Clone, Default)]
+pub struct FuzzyIndex {
+    sources: Vec<IndexedSource>,
+    grams: HashMap<String, Vec<usize>>,
+}
+
+impl FuzzyIndex {
+    /// 从记忆库键构建索引（只索引原文部分，上下文不参与匹配；不带语言的旧键不参与模糊匹配）
+    pub fn build<'a>(keys: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut index = Self::default();
+        for key in keys {
+            let Some((body, _lang)) = key.rsplit_once('|') else {
+                continue;
+            };
+            let start = body
+                .find(CONTEXT_SEPARATOR)
+                .map_or(0, |pos| pos + CONTEXT_SEPARATOR.len_utf8());
+            let source = &body[start..];
+            let normalized = normalize(source);
+            if normalized.is_empty() {
+                continue;
+            }
+            let id = index.sources.len();
+            for gram in trigrams(&normalized) {
+                let ids = index.grams.entry(gram).or_default();
+                if ids.last() != Some(&id) {
+                    ids.push(id);
+                }
+            }
+            index.sources.push(IndexedSource {
+                key: key.clone(),
+                source_range: start..body.len(),
+                normalized,
+            });
+        }
+        index
+    }
+
+    /// 已索引的键数量
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// 查询匹配度不低于 `min_score` 的前 `top_k` 条，返回 (记忆库键, 匹配度)
+    ///
+    /// `key_filter` 用于限定目标语言等条件
+    pub fn search(
+        &self,
+        text: &str,
+        top_k: usize,
+        min_score: u8,
+        key_filter: impl Fn(&str) -> bool,
+    ) -> Vec<(&str, u8)> {
+        let query = normalize(text);
+        if query.is_empty() || top_k == 0 {
+            return Vec::new();
+        }
+
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for gram in trigrams(&query) {
+            for &id in self.grams.get(&gram).into_iter().flatten() {
+                *shared.entry(id).or_default() += 1;
+            }
+        }
+        let mut candidates: Vec<(usize, usize)> = shared.into_iter().collect();
+        candidates.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let max_distance_ratio = 1.0 - f64::from(min_score) / 100.0;
+        let mut matches: Vec<(&str, u8)> = candidates
+            .into_iter()
+            .map(|(id, _)| &self.sources[id])
+            .filter(|entry| key_filter(&entry.key))
+            // 长度差本身已超出允许的编辑距离时跳过
+            .filter(|entry| {
+                let longest = entry.normalized.len().max(query.len()) as f64;
+                entry.normalized.len().abs_diff(query.len()) as f64 <= longest * max_distance_ratio
+            })
+            .take(MAX_CANDIDATES)
+            .filter_map(|entry| {
+                let mut score = similarity(&query, &entry.normalized);
+                if score == 100 && &entry.key[entry.source_range.clone()] != text {
+                    score = 99;
+                }
+                (score >= min_score).then_some((entry.key.as_str(), score))
+            })
+            .collect();
+        matches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        matches.truncate(top_k);
+        matches
+    }
+}
+
+/// 匹配度（%）：1 - 编辑距离 / 较长文本长度，向下取整
+pub fn similarity(a: &[char], b: &[char]) -> u8 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 100;
+    }
+    let distance = levenshtein(a, b);
+    ((longest - distance) * 100 / longest) as u8
+}
+
+/// 字符级 Levenshtein 编辑距离（单行滚动数组）
+pub fn levenshtein(a: &[char], b: &[char]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// 小写并把连续空白合并为一个空格
+fn normalize(text: &str) -> Vec<char> {
+    let lowered = text.to_lowercase();
+    let mut chars = Vec::with_capacity(lowered.len());
+    for word in lowered.split_whitespace() {
+        if !chars.is_empty() {
+            chars.push(' ');
+        }
+        chars.extend(word.chars());
+    }
+    chars
+}
+
+/// 字符三元组（不足三个字符时整体作为一个 gram）
+fn trigrams(chars: &[char]) -> Vec<String> {
+    if chars.len() < 3 {
+        return vec![chars.iter().collect()];
+    }
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+static CONFIG: RwLock<Option<FuzzyMatchConfig>> = RwLock::new(None);
+
+/// 应用配置（随配置加载/更新同步）
+pub fn configure(config: &FuzzyMatchConfig) {
+    *CONFIG.write() = Some(config.clone());
+}
+
+/// 当前配置（新建的翻译器读取）
+pub fn current() -> FuzzyMatchConfig {
+    CONFIG.read().clone().unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn test_levenshtein_and_similarity() {
+        assert_eq!(levenshtein(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(levenshtein(&chars(""), &chars("abc")), 3);
+        assert_eq!(levenshtein(&chars("删除项目"), &chars("删除所选项目")), 2);
+        // 21 个字符中差 1 个
+        assert_eq!(
+            similarity(
+                &chars("delete selected item"),
+                &chars("delete selected items")
+            ),
+            95
+        );
+        assert_eq!(similarity(&chars("abc"), &chars("xyz")), 0);
+    }
+
+    #[test]
+    fn test_index_returns_top_k_above_min_score() {
+        let keys: Vec<String> = [
+            "Delete selected items|zh-Hans",
+            "Delete all items|zh-Hans",
+            "Delete selected items|ja",
+            "Open file|zh-Hans",
+            "Legacy key without lang",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let index = FuzzyIndex::build(&keys);
+        assert_eq!(index.len(), 4);
+
+        let zh = |key: &str| key.ends_with("|zh-Hans");
+        let matches = index.search("Delete selected item", 3, 60, zh);
+        assert_eq!(
+            matches,
+            vec![
+                ("Delete selected items|zh-Hans", 95),
+                ("Delete all items|zh-Hans", 60),
+            ]
+        );
+        assert_eq!(index.search("Delete selected item", 1, 60, zh).len(), 1);
+        assert!(index.search("Completely different", 3, 60, zh).is_empty());
+
+        // 仅大小写不同不算完全匹配
+        assert_eq!(
+            index.search("open FILE", 1, 60, zh),
+            vec![("Open file|zh-Hans", 99)]
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(FuzzyMatchConfig::default().validate().is_ok());
+        let inverted = FuzzyMatchConfig {
+            min_score: 90,
+            auto_apply_threshold: 80,
+            ..Default::default()
+        };
+        assert!(inverted.validate().is_err());
+
+        let never = FuzzyMatchConfig {
+            auto_apply_threshold: 100,
+            ..Default::default()
+        };
+        assert!(!never.should_auto_apply(100));
+        assert!(FuzzyMatchConfig::default().should_auto_apply(95));
+    }
+}
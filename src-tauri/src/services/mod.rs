@@ -5,15 +5,25 @@
 // 核心服务模块
 pub mod ai_translator;
 pub mod batch_translator;
+pub mod budget;
 pub mod config_draft;
 pub mod po_parser;
+pub mod response_cache;
 pub mod translation_stats;
 pub mod translation_task;
+pub mod usage_ledger;
 
 // AI 和翻译相关
 pub mod ai;
+pub mod catalog_alignment;
+pub mod fuzzy_match;
 pub mod language_detector;
 pub mod prompt_builder;
+pub mod segment_memory;
+pub mod tm_maintenance;
+pub mod tm_registry;
+pub mod tm_store;
+pub mod tmx;
 pub mod translation_memory;
 
 // 文件和数据处理
@@ -47,7 +57,8 @@ pub use po_parser::POParser;
 // 翻译记忆和术语
 pub use prompt_logger::{
     clear_prompt_logs, format_prompt_logs, get_prompt_logs, init_prompt_logger, log_prompt,
-    update_prompt_response,
+    update_prompt_reasoning, update_prompt_response,
 };
+pub use segment_memory::SegmentMemory;
 pub use term_library::TermLibrary;
 pub use translation_memory::TranslationMemory;
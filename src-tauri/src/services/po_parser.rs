@@ -193,7 +193,7 @@ impl POParser {
         }
     }
 
-    fn parse_content(&self, content: &str) -> Result<Vec<POEntry>> {
+    pub(crate) fn parse_content(&self, content: &str) -> Result<Vec<POEntry>> {
         let mut entries = Vec::new();
         let mut current_entry = POEntry {
             comments: Vec::new(),
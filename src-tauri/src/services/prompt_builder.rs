@@ -2,6 +2,7 @@
 //!
 //! 负责构建翻译系统提示词和用户提示词
 
+use crate::services::fuzzy_match::FuzzyMatch;
 use crate::services::term_library::TermLibrary;
 
 /// 默认系统提示词
@@ -13,6 +14,12 @@ pub const DEFAULT_SYSTEM_PROMPT: &str = r"专业游戏本地化翻译。
 4. 保留所有特殊符号: |、{}、%%、[]、()、\n、\t、{0}、{1}等
 5. 特殊表达: in-place→原地, by value→按值, True/False保持原样";
 
+/// 参考译文段落的标题行
+const REFERENCE_HEADER: &str = "参考译文（记忆库中的相似条目，保持用词一致，不要输出）:\n";
+
+/// 上下文段落的标题行
+const CONTEXT_HEADER: &str = "上下文（msgctxt，用于区分同一原文的不同含义，不要输出）:\n";
+
 /// 构建系统提示词
 ///
 /// # 参数
@@ -50,8 +57,152 @@ pub fn build_system_prompt(
 /// # 返回
 /// 格式化的用户提示词字符串
 pub fn build_translation_prompt(texts: &[String], target_language: Option<&str>) -> String {
-    // 根据目标语言生成提示词
-    let target_lang_instruction = match target_language {
+    build_translation_prompt_with_references(texts, target_language, &[])
+}
+
+/// 构建带参考译文的翻译用户提示词
+///
+/// # 参数
+/// - `texts`: 待翻译的文本列表
+/// - `target_language`: 目标语言代码
+/// - `references`: 记忆库中的相似条目（未达到自动套用阈值），仅供模型参考用词与风格
+///
+/// # 返回
+/// 参考译文在前、待翻译文本在后的用户提示词；参考行不带序号，避免与译文行混淆
+pub fn build_translation_prompt_with_references(
+    texts: &[String],
+    target_language: Option<&str>,
+    references: &[FuzzyMatch],
+) -> String {
+    build_translation_prompt_with_context(texts, &[], target_language, references)
+}
+
+/// 构建带参考译文与上下文的翻译用户提示词
+///
+/// # 参数
+/// - `texts`: 待翻译的文本列表
+/// - `contexts`: 与 `texts` 一一对应的 msgctxt（可以更短；为空的条目不列出）
+/// - `target_language`: 目标语言代码
+/// - `references`: 记忆库中的相似条目
+///
+/// # 返回
+/// 参考译文、上下文（按序号列出）在前，待翻译文本在后的用户提示词
+pub fn build_translation_prompt_with_context(
+    texts: &[String],
+    contexts: &[Option<String>],
+    target_language: Option<&str>,
+    references: &[FuzzyMatch],
+) -> String {
+    let target_lang_instruction = target_language_name(target_language);
+
+    let mut prompt = String::new();
+    if !references.is_empty() {
+        prompt.push_str(REFERENCE_HEADER);
+        for reference in references {
+            prompt.push_str(&reference_line(reference));
+        }
+        prompt.push('\n');
+    }
+    prompt.push_str(&context_section(contexts.iter().enumerate().filter_map(
+        |(i, context)| Some(((i + 1).to_string(), context.as_deref()?)),
+    )));
+
+    // 精简提示词：移除冗余说明和空行
+    prompt.push_str(&format!(
+        "翻译为{}（每行一条，带序号）:\n",
+        target_lang_instruction
+    ));
+    for (i, text) in texts.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", i + 1, text));
+    }
+    prompt
+}
+
+/// 按顺序挑选能放入 token 预算的参考译文
+///
+/// # 参数
+/// - `references`: 候选参考译文（按优先顺序排列）
+/// - `token_budget`: 参考译文段落可用的 token（含标题行）
+/// - `count_tokens`: token 计数函数（分词器或估算）
+///
+/// # 返回
+/// 放得下的参考译文；放不下的条目跳过，继续尝试后面较短的条目
+pub fn fit_references(
+    references: &[FuzzyMatch],
+    token_budget: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Vec<FuzzyMatch> {
+    let mut used = count_tokens(REFERENCE_HEADER) + 1;
+    let mut fitted = Vec::new();
+    for reference in references {
+        let tokens = count_tokens(&reference_line(reference));
+        if used + tokens <= token_budget {
+            used += tokens;
+            fitted.push(reference.clone());
+        }
+    }
+    fitted
+}
+
+fn reference_line(reference: &FuzzyMatch) -> String {
+    format!(
+        "- {} => {} ({}%)\n",
+        reference.source, reference.target, reference.score
+    )
+}
+
+/// 构建结构化输出（JSON）模式的翻译提示词
+///
+/// # 参数
+/// - `payload`: id→原文 的 JSON 对象字符串
+/// - `target_language`: 目标语言代码
+///
+/// # 返回
+/// 要求模型返回相同键、值为译文的 JSON 对象
+pub fn build_json_translation_prompt(payload: &str, target_language: Option<&str>) -> String {
+    build_json_translation_prompt_with_context(payload, &[], target_language)
+}
+
+/// 构建带上下文的结构化输出（JSON）模式翻译提示词
+///
+/// # 参数
+/// - `payload`: id→原文 的 JSON 对象字符串
+/// - `contexts`: (id, msgctxt) 列表，只需列出有上下文的条目
+/// - `target_language`: 目标语言代码
+pub fn build_json_translation_prompt_with_context(
+    payload: &str,
+    contexts: &[(String, &str)],
+    target_language: Option<&str>,
+) -> String {
+    format!(
+        "{}翻译为{}。输入为 JSON 对象（键为编号，值为原文），返回相同键的 JSON 对象，值为译文，不要输出其他内容:\n{}",
+        context_section(contexts.iter().map(|(id, context)| (id.clone(), *context))),
+        target_language_name(target_language),
+        payload
+    )
+}
+
+/// 上下文段落：每行 `编号: msgctxt`；没有上下文时为空
+fn context_section<'a>(contexts: impl IntoIterator<Item = (String, &'a str)>) -> String {
+    let mut section = String::new();
+    for (id, context) in contexts {
+        if context.is_empty() {
+            continue;
+        }
+        if section.is_empty() {
+            section.push_str(CONTEXT_HEADER);
+        }
+        section.push_str(&format!("{}: {}\n", id, context));
+    }
+    if !section.is_empty() {
+        section.push('\n');
+    }
+    section
+}
+
+/// 目标语言代码 -> 提示词中的语言名称
+fn target_language_name(target_language: Option<&str>) -> &str {
+    match target_language {
         Some("zh-Hans") => "简体中文",
         Some("zh-Hant") => "繁体中文",
         Some("en") => "English",
@@ -68,14 +219,7 @@ pub fn build_translation_prompt(texts: &[String], target_language: Option<&str>)
         Some("vi") => "Tiếng Việt",
         Some(lang) => lang,
         None => "目标语言", // 默认（未指定语言）
-    };
-
-    // 精简提示词：移除冗余说明和空行
-    let mut prompt = format!("翻译为{}（每行一条，带序号）:\n", target_lang_instruction);
-    for (i, text) in texts.iter().enumerate() {
-        prompt.push_str(&format!("{}. {}\n", i + 1, text));
     }
-    prompt
 }
 
 #[cfg(test)]
@@ -103,4 +247,80 @@ mod tests {
         assert!(prompt.contains("1. Hello"));
         assert!(prompt.contains("2. World"));
     }
+
+    #[test]
+    fn test_build_translation_prompt_with_references() {
+        let texts = vec!["Delete selected item".to_string()];
+        let references = vec![FuzzyMatch {
+            source: "Delete all items".to_string(),
+            target: "删除所有项目".to_string(),
+            score: 75,
+        }];
+        let prompt = build_translation_prompt_with_references(&texts, Some("zh-Hans"), &references);
+        assert!(prompt.starts_with("参考译文"));
+        assert!(prompt.contains("- Delete all items => 删除所有项目 (75%)"));
+        assert!(prompt.ends_with("1. Delete selected item\n"));
+
+        // 没有参考时与普通提示词一致
+        assert_eq!(
+            build_translation_prompt_with_references(&texts, Some("zh-Hans"), &[]),
+            build_translation_prompt(&texts, Some("zh-Hans"))
+        );
+    }
+
+    #[test]
+    fn test_build_translation_prompt_with_context() {
+        let texts = vec!["Open".to_string(), "Open".to_string(), "Save".to_string()];
+        let contexts = vec![Some("verb".to_string()), Some("adjective".to_string())];
+        let prompt = build_translation_prompt_with_context(&texts, &contexts, Some("zh-Hans"), &[]);
+        assert!(prompt.starts_with(CONTEXT_HEADER));
+        assert!(prompt.contains("1: verb\n2: adjective\n\n"));
+        assert!(!prompt.contains("3:"));
+        assert!(prompt.ends_with("1. Open\n2. Open\n3. Save\n"));
+
+        let json = build_json_translation_prompt_with_context(
+            r#"{"2": "Open"}"#,
+            &[("2".to_string(), "verb")],
+            Some("ja"),
+        );
+        assert!(json.starts_with(CONTEXT_HEADER));
+        assert!(json.contains("2: verb\n"));
+        assert_eq!(
+            build_json_translation_prompt_with_context("{}", &[], None),
+            build_json_translation_prompt("{}", None)
+        );
+    }
+
+    #[test]
+    fn test_fit_references_within_budget() {
+        let reference = |source: &str, score| FuzzyMatch {
+            source: source.to_string(),
+            target: "译文".to_string(),
+            score,
+        };
+        let references = vec![
+            reference(&"Long reference ".repeat(20), 90),
+            reference("Short one", 80),
+            reference("Short two", 75),
+        ];
+        let count = |text: &str| text.chars().count();
+        let header = count(REFERENCE_HEADER) + 1;
+        let short_line = count(&reference_line(&references[1]));
+
+        // 长条目放不下时跳过，仍保留后面较短的条目
+        let fitted = fit_references(&references, header + short_line * 2, count);
+        assert_eq!(
+            fitted.iter().map(|r| r.score).collect::<Vec<_>>(),
+            vec![80, 75]
+        );
+        assert!(fit_references(&references, header, count).is_empty());
+        assert_eq!(fit_references(&references, usize::MAX, count).len(), 3);
+    }
+
+    #[test]
+    fn test_build_json_translation_prompt() {
+        let prompt = build_json_translation_prompt(r#"{"1": "Hello"}"#, Some("ja"));
+        assert!(prompt.contains("日本語"));
+        assert!(prompt.ends_with(r#"{"1": "Hello"}"#));
+    }
 }
@@ -12,6 +12,9 @@ pub struct PromptLogEntry {
     pub log_type: String, // "批量翻译" 或 "精翻"
     pub prompt: String,
     pub response: Option<String>,
+    /// 推理模型的思考过程（与响应分开显示）
+    #[serde(default)]
+    pub reasoning: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
 
@@ -45,6 +48,7 @@ pub fn log_prompt(log_type: &str, prompt: String, metadata: Option<serde_json::V
         log_type: log_type.to_string(),
         prompt,
         response: None,
+        reasoning: None,
         metadata,
     };
 
@@ -69,6 +73,16 @@ pub fn update_prompt_response(index: usize, response: String) {
     }
 }
 
+/// 更新提示词的推理过程
+pub fn update_prompt_reasoning(index: usize, reasoning: String) {
+    let mut logs = PROMPT_LOGS.lock();
+    if let Some(ref mut log_vec) = *logs {
+        if let Some(entry) = log_vec.get_mut(index) {
+            entry.reasoning = Some(reasoning);
+        }
+    }
+}
+
 /// 获取所有提示词日志
 pub fn get_prompt_logs() -> Vec<PromptLogEntry> {
     let logs = PROMPT_LOGS.lock();
@@ -127,6 +141,13 @@ pub fn format_prompt_logs() -> String {
         output.push_str(&entry.prompt);
         output.push_str("\n\n");
 
+        // 推理过程（推理模型）
+        if let Some(ref reasoning) = entry.reasoning {
+            output.push_str("【推理过程】\n");
+            output.push_str(reasoning);
+            output.push_str("\n\n");
+        }
+
         // AI 响应
         output.push_str("【AI 响应】\n");
         if let Some(ref response) = entry.response {
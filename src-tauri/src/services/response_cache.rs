@@ -0,0 +1,446 @@
+/**
+ * AI 响应磁盘缓存
+ *
+ * - 键为 hash(供应商, 模型, 系统提示词, 用户提示词, 生成参数)，相同请求不再重复付费
+ * - 每条缓存一个文件，存放在 `data/response_cache/<hash>.json`
+ * - 读取时检查有效期（TTL），写入后按总大小上限淘汰最旧的条目
+ * - 只缓存解析成功的响应，避免把格式错误的回复固化下来；命中后解析失败的条目随即删除
+ */
+use crate::error::AppError;
+use crate::services::ai::GenerationParams;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 响应缓存配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    /// 有效期（小时），0 表示不过期
+    pub ttl_hours: u64,
+    /// 缓存目录总大小上限（MB）
+    pub max_size_mb: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_hours: 24 * 7,
+            max_size_mb: 100,
+        }
+    }
+}
+
+/// 缓存键的组成部分
+#[derive(Debug, Clone, Copy)]
+pub struct CacheKeyParts<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub system_prompt: &'a str,
+    pub user_prompt: &'a str,
+    pub generation: &'a GenerationParams,
+}
+
+impl CacheKeyParts<'_> {
+    /// SHA-256 十六进制摘要（各字段带长度前缀，避免拼接歧义）
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for field in [
+            self.provider,
+            self.model,
+            self.system_prompt,
+            self.user_prompt,
+        ] {
+            hasher.update((field.len() as u64).to_le_bytes());
+            hasher.update(field.as_bytes());
+        }
+        // 生成参数（temperature / top_p / max_tokens / extra_body）影响输出，整体参与哈希
+        let generation = serde_json::to_string(self.generation).unwrap_or_default();
+        hasher.update((generation.len() as u64).to_le_bytes());
+        hasher.update(generation.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// 单条缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 写入时间（Unix 秒）
+    created_at: u64,
+    provider: String,
+    model: String,
+    content: String,
+}
+
+/// 缓存目录统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct ResponseCacheStats {
+    pub entries: u64,
+    pub size_bytes: u64,
+    pub directory: String,
+}
+
+/// 磁盘响应缓存
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    config: ResponseCacheConfig,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, config: ResponseCacheConfig) -> Self {
+        Self { dir, config }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// 读取未过期的缓存内容（过期或损坏的条目顺带删除）
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.get_at(key, now_secs())
+    }
+
+    fn get_at(&self, key: &str, now: u64) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+        let path = self.entry_path(key);
+        let content = std::fs::read_to_string(&path).ok()?;
+        let entry = match serde_json::from_str::<CacheEntry>(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::error!("[响应缓存] 条目损坏，已删除 {}: {}", key, e);
+                let _ = std::fs::remove_file(&path);
+                return None;
+            }
+        };
+        let ttl = self.config.ttl_hours.saturating_mul(3600);
+        if ttl > 0 && now.saturating_sub(entry.created_at) > ttl {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        Some(entry.content)
+    }
+
+    /// 写入缓存并按大小上限淘汰
+    pub fn put(
+        &self,
+        key: &str,
+        provider: &str,
+        model: &str,
+        content: &str,
+    ) -> Result<(), AppError> {
+        self.put_at(key, provider, model, content, now_secs())
+    }
+
+    fn put_at(
+        &self,
+        key: &str,
+        provider: &str,
+        model: &str,
+        content: &str,
+        now: u64,
+    ) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = CacheEntry {
+            created_at: now,
+            provider: provider.to_string(),
+            model: model.to_string(),
+            content: content.to_string(),
+        };
+        std::fs::write(self.entry_path(key), serde_json::to_vec(&entry)?)?;
+        self.enforce_size_limit()
+    }
+
+    /// 删除一条缓存（不存在时忽略）
+    pub fn remove(&self, key: &str) -> Result<(), AppError> {
+        match std::fs::remove_file(self.entry_path(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// 超过大小上限时按修改时间从旧到新删除
+    fn enforce_size_limit(&self) -> Result<(), AppError> {
+        let limit = self.config.max_size_mb.saturating_mul(1024 * 1024);
+        let mut files = self.list_files()?;
+        let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+        if total <= limit {
+            return Ok(());
+        }
+        files.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in files {
+            if total <= limit {
+                break;
+            }
+            std::fs::remove_file(&path)?;
+            total = total.saturating_sub(size);
+        }
+        Ok(())
+    }
+
+    /// (路径, 大小, 修改时间)
+    fn list_files(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, AppError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let metadata = std::fs::metadata(&path)?;
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            files.push((path, metadata.len(), modified));
+        }
+        Ok(files)
+    }
+
+    pub fn stats(&self) -> Result<ResponseCacheStats, AppError> {
+        let files = self.list_files()?;
+        Ok(ResponseCacheStats {
+            entries: files.len() as u64,
+            size_bytes: files.iter().map(|(_, size, _)| size).sum(),
+            directory: self.dir.to_string_lossy().to_string(),
+        })
+    }
+
+    /// 清空缓存，返回删除的条目数
+    pub fn clear(&self) -> Result<u64, AppError> {
+        let files = self.list_files()?;
+        for (path, _, _) in &files {
+            std::fs::remove_file(path)?;
+        }
+        Ok(files.len() as u64)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+// ========== 全局实例 ==========
+
+fn cache_dir() -> PathBuf {
+    crate::utils::paths::app_data_dir()
+        .map(|dir| dir.join("response_cache"))
+        .unwrap_or_else(|_| PathBuf::from("response_cache"))
+}
+
+static CONFIG: parking_lot::RwLock<Option<ResponseCacheConfig>> = parking_lot::RwLock::new(None);
+/// 串行化写入与淘汰（多个翻译器并发写入）
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 应用配置（随配置加载/更新同步）
+pub fn configure(config: &ResponseCacheConfig) {
+    *CONFIG.write() = Some(config.clone());
+}
+
+fn current_config() -> ResponseCacheConfig {
+    CONFIG.read().clone().unwrap_or_default()
+}
+
+/// 按当前配置构造的全局缓存
+pub fn shared() -> ResponseCache {
+    ResponseCache::new(cache_dir(), current_config())
+}
+
+/// 查询全局缓存
+pub fn lookup(key: &str) -> Option<String> {
+    shared().get(key)
+}
+
+/// 写入全局缓存（失败只记录日志，不影响翻译）
+pub fn store(key: &str, provider: &str, model: &str, content: &str) {
+    let _guard = WRITE_LOCK.lock();
+    if let Err(e) = shared().put(key, provider, model, content) {
+        log::error!("[响应缓存] 写入失败: {}", e);
+    }
+}
+
+/// 删除全局缓存中的一条（失败只记录日志）
+pub fn invalidate(key: &str) {
+    let _guard = WRITE_LOCK.lock();
+    if let Err(e) = shared().remove(key) {
+        log::error!("[响应缓存] 删除失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn key_parts<'a>(user_prompt: &'a str, generation: &'a GenerationParams) -> CacheKeyParts<'a> {
+        CacheKeyParts {
+            provider: "openai",
+            model: "gpt-4o-mini",
+            system_prompt: "你是翻译助手",
+            user_prompt,
+            generation,
+        }
+    }
+
+    #[test]
+    fn test_key_depends_on_every_part() {
+        let generation = GenerationParams {
+            temperature: Some(1.0),
+            ..Default::default()
+        };
+        let base = key_parts("1. Hello", &generation);
+        assert_eq!(base.hash(), key_parts("1. Hello", &generation).hash());
+        assert_eq!(base.hash().len(), 64);
+        assert_ne!(base.hash(), key_parts("1. Hello!", &generation).hash());
+        let cooler = GenerationParams {
+            temperature: Some(0.3),
+            ..Default::default()
+        };
+        assert_ne!(
+            base.hash(),
+            CacheKeyParts {
+                generation: &cooler,
+                ..base
+            }
+            .hash()
+        );
+        let capped = GenerationParams {
+            max_tokens: Some(1024),
+            ..generation.clone()
+        };
+        assert_ne!(
+            base.hash(),
+            CacheKeyParts {
+                generation: &capped,
+                ..base
+            }
+            .hash()
+        );
+        assert_ne!(
+            base.hash(),
+            CacheKeyParts {
+                model: "gpt-4o",
+                ..base
+            }
+            .hash()
+        );
+        // 字段边界不同的拼接不应碰撞
+        assert_ne!(
+            CacheKeyParts {
+                provider: "ab",
+                model: "c",
+                ..base
+            }
+            .hash(),
+            CacheKeyParts {
+                provider: "a",
+                model: "bc",
+                ..base
+            }
+            .hash()
+        );
+    }
+
+    #[test]
+    fn test_get_respects_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(
+            dir.path().to_path_buf(),
+            ResponseCacheConfig {
+                ttl_hours: 1,
+                ..Default::default()
+            },
+        );
+        cache
+            .put_at("k", "openai", "gpt-4o", "1. 你好", 1_000)
+            .unwrap();
+        assert_eq!(cache.get_at("k", 1_000 + 3600).as_deref(), Some("1. 你好"));
+        assert_eq!(cache.get_at("k", 1_000 + 3601), None);
+        // 过期条目被删除
+        assert_eq!(cache.stats().unwrap().entries, 0);
+    }
+
+    #[test]
+    fn test_size_limit_evicts_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(
+            dir.path().to_path_buf(),
+            ResponseCacheConfig {
+                max_size_mb: 1,
+                ..Default::default()
+            },
+        );
+        // 每条约 400 KB：两条在 1 MB 以内，第三条写入后超限
+        let content = "x".repeat(400 * 1024);
+        for (key, age_secs) in [("a", 20), ("b", 10)] {
+            cache.put(key, "openai", "gpt-4o", &content).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(age_secs);
+            std::fs::File::options()
+                .write(true)
+                .open(cache.entry_path(key))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        assert_eq!(cache.stats().unwrap().entries, 2);
+
+        cache.put("c", "openai", "gpt-4o", &content).unwrap();
+        assert_eq!(cache.stats().unwrap().entries, 2);
+        assert_eq!(cache.get("a"), None);
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn test_remove_deletes_single_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), ResponseCacheConfig::default());
+        cache.put("a", "openai", "gpt-4o", "x").unwrap();
+        cache.put("b", "openai", "gpt-4o", "y").unwrap();
+        cache.remove("a").unwrap();
+        cache.remove("missing").unwrap();
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b").as_deref(), Some("y"));
+    }
+
+    #[test]
+    fn test_disabled_cache_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(
+            dir.path().to_path_buf(),
+            ResponseCacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+        cache.put("a", "openai", "gpt-4o", "x").unwrap();
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.stats().unwrap().entries, 0);
+    }
+}
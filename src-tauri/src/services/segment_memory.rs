@@ -0,0 +1,646 @@
+/**
+ * 句段级翻译记忆库
+ *
+ * - 与短语表（`TranslationMemory`，仅收录 `is_simple_phrase` 短语，相当于术语快速通道）并存的第二层记忆库
+ * - 收录所有完整句段：AI 译文记为未审校，用户确认/保存后的译文记为已审校
+ * - 每条记录附带上下文（msgctxt）、来源文件、供应商/模型、时间与审校状态
+ * - 键为 (源语言, 目标语言, 上下文, 原文)：同一原文在不同 msgctxt 下分别保存，查询时上下文一致的优先
+ * - 参与精确复用与模糊匹配；已审校的译文不会被后续 AI 译文覆盖
+ * - 存于嵌入式存储的 segments 表（旧版 `segment_memory.json` 首次启动时迁移），记忆库管理界面保存短语表时不受影响
+ * - 翻译时按需查询存储（`open`），只在本地保存本次写入与读取过的句段
+ */
+use crate::services::fuzzy_match::{FuzzyIndex, FuzzyMatch};
+use crate::services::tm_store::{LookupCache, TmStore};
+use crate::services::translation_memory::{TmKey, normalize_lang_code};
+use anyhow::Result;
+use chrono::Utc;
+use indexmap::{IndexMap, IndexSet};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 审校状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum ReviewStatus {
+    /// AI 译文，尚未经人工确认
+    #[default]
+    Unreviewed,
+    /// 已由用户确认
+    Approved,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unreviewed => "unreviewed",
+            Self::Approved => "approved",
+        }
+    }
+
+    /// 解析存储中的状态（未知值视为未审校）
+    pub fn parse(value: &str) -> Self {
+        if value == "approved" {
+            Self::Approved
+        } else {
+            Self::Unreviewed
+        }
+    }
+}
+
+/// 一条句段记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct SegmentEntry {
+    pub source: String,
+    pub target: String,
+    /// 目标语言（标准化后的代码）
+    pub target_lang: String,
+    /// 源语言（标准化后的代码，旧记录为 None）
+    #[serde(default)]
+    pub source_lang: Option<String>,
+    /// 上下文（PO 的 msgctxt）
+    #[serde(default)]
+    pub context: Option<String>,
+    /// 来源文件
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 产生译文的供应商/模型（人工译文为 None）
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// 备注（TMX 的 `note`）
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 创建时间（RFC 3339）
+    pub created_at: String,
+    /// 最近更新时间（RFC 3339）
+    pub updated_at: String,
+    #[serde(default)]
+    pub review_status: ReviewStatus,
+}
+
+impl SegmentEntry {
+    pub fn new(source: &str, target: &str, target_lang: &str, review_status: ReviewStatus) -> Self {
+        let now = Utc::now().to_rfc3339();
+        Self {
+            source: source.to_string(),
+            target: target.to_string(),
+            target_lang: normalize_lang_code(target_lang).to_string(),
+            source_lang: None,
+            context: None,
+            file: None,
+            provider: None,
+            note: None,
+            created_at: now.clone(),
+            updated_at: now,
+            review_status,
+        }
+    }
+
+    pub fn with_context(mut self, context: Option<String>) -> Self {
+        self.context = context.filter(|c| !c.is_empty());
+        self
+    }
+
+    pub fn with_source_lang(mut self, source_lang: Option<&str>) -> Self {
+        self.source_lang = source_lang
+            .filter(|lang| !lang.is_empty())
+            .map(|lang| normalize_lang_code(lang).to_string());
+        self
+    }
+
+    pub fn with_file(mut self, file: Option<String>) -> Self {
+        self.file = file;
+        self
+    }
+
+    pub fn with_provider(mut self, provider: Option<String>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note.filter(|n| !n.is_empty());
+        self
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.review_status == ReviewStatus::Approved
+    }
+
+    pub fn tm_key(&self) -> TmKey {
+        TmKey::new(&self.source, Some(&self.target_lang))
+            .with_source_lang(self.source_lang.as_deref())
+            .with_context(self.context.as_deref())
+    }
+
+    fn key(&self) -> String {
+        self.tm_key().encode()
+    }
+}
+
+/// 用户确认的一条译文（前端保存文件时提交）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct SegmentApproval {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+/// 句段记忆库统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct SegmentMemoryStats {
+    pub total: usize,
+    pub approved: usize,
+    pub unreviewed: usize,
+}
+
+/// 句段级记忆库（键为 `原文|目标语言`，与短语表一致）
+#[derive(Debug, Clone, Default)]
+pub struct SegmentMemory {
+    segments: IndexMap<String, SegmentEntry>,
+    fuzzy_index: OnceCell<FuzzyIndex>, // 首次模糊查询时构建，修改后失效
+    pending: IndexSet<String>,         // 尚未写入存储的句段键
+    pending_hits: IndexMap<String, (TmKey, u32)>, // 尚未写入存储的命中次数
+    store: Option<Arc<TmStore>>,       // 按需查询的存储（此时 segments 只含本次写入/读取过的句段）
+    cache: LookupCache<Option<SegmentEntry>>, // 按需查询的有界缓存（含未命中）
+}
+
+/// 持久化格式
+#[derive(Serialize, Deserialize)]
+struct SegmentFile {
+    version: u32,
+    segments: Vec<SegmentEntry>,
+}
+
+impl SegmentMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载（文件不存在时返回空库）
+    pub fn load_from_file<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let path = file_path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let data: SegmentFile = serde_json::from_str(&content)?;
+        let memory = Self::from_entries(data.segments);
+        crate::app_log!("[TM句段] 加载句段记忆库: {} 条", memory.len());
+        Ok(memory)
+    }
+
+    /// 由已保存的句段构建（不计入待写入）
+    pub fn from_entries(entries: impl IntoIterator<Item = SegmentEntry>) -> Self {
+        let mut memory = Self::new();
+        for entry in entries {
+            memory.segments.insert(entry.key(), entry);
+        }
+        memory
+    }
+
+    /// 从嵌入式存储加载全部句段
+    pub fn load_from_store(store: &TmStore) -> Result<Self> {
+        let memory = Self::from_entries(store.load_segments()?);
+        crate::app_log!("[TM句段] 加载句段记忆库: {} 条", memory.len());
+        Ok(memory)
+    }
+
+    /// 按需查询存储中的句段库（翻译时使用，不整表加载）
+    pub fn open(store: Arc<TmStore>) -> Self {
+        Self {
+            store: Some(store),
+            ..Self::default()
+        }
+    }
+
+    /// 把加载后新增/修改的句段与命中次数增量写入存储，返回写入的句段数
+    pub fn save_to_store(&mut self, store: &TmStore) -> Result<usize> {
+        if !self.pending_hits.is_empty() {
+            store
+                .record_segment_hits(self.pending_hits.values().map(|(key, hits)| (key, *hits)))?;
+            self.pending_hits.clear();
+        }
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let count =
+            store.upsert_segments(self.pending.iter().filter_map(|key| self.segments.get(key)))?;
+        self.pending.clear();
+        crate::app_log!("[TM句段] 写入句段记忆库: {} 条", count);
+        Ok(count)
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+        let path = file_path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = SegmentFile {
+            version: 1,
+            segments: self.segments.values().cloned().collect(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&data)?)?;
+        crate::app_log!("[TM句段] 保存句段记忆库: {} 条", self.len());
+        Ok(())
+    }
+
+    /// 本地句段数（按需模式下只含本次写入/读取过的句段）
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// 按插入顺序遍历所有句段
+    pub fn iter(&self) -> impl Iterator<Item = &SegmentEntry> {
+        self.segments.values()
+    }
+
+    pub fn stats(&self) -> SegmentMemoryStats {
+        let approved = self.iter().filter(|entry| entry.is_approved()).count();
+        SegmentMemoryStats {
+            total: self.len(),
+            approved,
+            unreviewed: self.len() - approved,
+        }
+    }
+
+    /// 按完整键精确查询：先查本地句段，按需模式下再经缓存查询存储
+    pub fn get(&self, key: &TmKey) -> Option<SegmentEntry> {
+        self.entry(&key.encode(), key)
+    }
+
+    fn entry(&self, encoded: &str, key: &TmKey) -> Option<SegmentEntry> {
+        if let Some(entry) = self.segments.get(encoded) {
+            return Some(entry.clone());
+        }
+        let store = self.store.as_ref()?;
+        self.cache
+            .get_or_load(store, encoded, || store.segment(key))
+            .unwrap_or_else(|e| {
+                log::error!("[TM句段] 查询句段记忆库失败: {}", e);
+                None
+            })
+    }
+
+    /// 复用查询：上下文一致的优先（101%），其次无上下文句段（带上下文查询时扣分）
+    pub fn lookup(&self, query: &TmKey) -> Option<(SegmentEntry, u8)> {
+        self.lookup_where(query, |_| true)
+    }
+
+    /// 只在已审校的句段中复用查询（未审校的机器译文不直接套用，以免一处错译扩散到后续文件）
+    pub fn lookup_approved(&self, query: &TmKey) -> Option<(SegmentEntry, u8)> {
+        self.lookup_where(query, SegmentEntry::is_approved)
+    }
+
+    fn lookup_where(
+        &self,
+        query: &TmKey,
+        accept: impl Fn(&SegmentEntry) -> bool,
+    ) -> Option<(SegmentEntry, u8)> {
+        query
+            .lookup_candidates(None)
+            .into_iter()
+            .find_map(|(key, score)| {
+                self.entry(&key, &TmKey::parse(&key))
+                    .filter(|entry| accept(entry))
+                    .map(|entry| (entry, score))
+            })
+    }
+
+    /// 记录一次复用命中（随 `save_to_store` 写入）
+    pub fn record_hit(&mut self, key: &TmKey) {
+        let encoded = key.encode();
+        if self.entry(&encoded, key).is_some() {
+            self.pending_hits
+                .entry(encoded)
+                .or_insert_with(|| (key.clone(), 0))
+                .1 += 1;
+        }
+    }
+
+    /// 写入一条句段，返回是否为新增
+    ///
+    /// 已审校的译文不会被未审校的译文覆盖；更新时保留创建时间
+    pub fn record(&mut self, entry: SegmentEntry) -> bool {
+        if entry.source.trim().is_empty() || entry.target.trim().is_empty() {
+            return false;
+        }
+
+        let key = entry.key();
+        // 按需模式：存储中已有的句段先取到本地（保留创建时间与审校状态）
+        if !self.segments.contains_key(&key) {
+            if let Some(existing) = self.entry(&key, &entry.tm_key()) {
+                self.segments.insert(key.clone(), existing);
+                self.fuzzy_index = OnceCell::new();
+            }
+        }
+        match self.segments.get_mut(&key) {
+            Some(existing) => {
+                if existing.is_approved() && !entry.is_approved() {
+                    return false;
+                }
+                let created_at = std::mem::take(&mut existing.created_at);
+                // 新记录没有备注时保留原备注
+                let note = entry.note.clone().or_else(|| existing.note.take());
+                *existing = SegmentEntry {
+                    created_at,
+                    note,
+                    ..entry
+                };
+                self.pending.insert(key);
+                false
+            }
+            None => {
+                self.pending.insert(key.clone());
+                self.segments.insert(key, entry);
+                self.fuzzy_index = OnceCell::new();
+                true
+            }
+        }
+    }
+
+    /// 记录用户确认的译文（按各自的上下文分别保存），返回新增或状态/译文发生变化的条数
+    pub fn approve(
+        &mut self,
+        approvals: &[SegmentApproval],
+        source_lang: Option<&str>,
+        target_lang: &str,
+        file: Option<&str>,
+    ) -> usize {
+        let mut changed = 0;
+        for approval in approvals {
+            let entry = SegmentEntry::new(
+                &approval.source,
+                &approval.target,
+                target_lang,
+                ReviewStatus::Approved,
+            )
+            .with_source_lang(source_lang)
+            .with_context(approval.context.clone())
+            .with_file(file.map(str::to_string));
+            let unchanged = self
+                .get(&entry.tm_key())
+                .is_some_and(|existing| existing.is_approved() && existing.target == entry.target);
+            if unchanged || approval.source.trim().is_empty() || approval.target.trim().is_empty() {
+                continue;
+            }
+            self.record(entry);
+            changed += 1;
+        }
+        changed
+    }
+
+    /// 合并另一份句段库（并发翻译时各 worker 学习结果汇总），返回新增条数
+    pub fn merge_from(&mut self, other: &SegmentMemory) -> usize {
+        other
+            .iter()
+            .filter(|entry| self.record((*entry).clone()))
+            .count()
+    }
+
+    /// 模糊查询：返回目标语言下匹配度不低于 `min_score` 的前 `top_k` 条
+    ///
+    /// `approved_only` 时只在已审校的句段中查询（用于自动套用）
+    pub fn fuzzy_matches(
+        &self,
+        source: &str,
+        target_lang: &str,
+        top_k: usize,
+        min_score: u8,
+        approved_only: bool,
+    ) -> Vec<FuzzyMatch> {
+        let target_lang = normalize_lang_code(target_lang);
+        let index = self
+            .fuzzy_index
+            .get_or_init(|| FuzzyIndex::build(self.segments.keys()));
+
+        let mut matches = index.search(source, top_k, min_score, |key| {
+            TmKey::target_lang_of(key) == Some(target_lang)
+                && (!approved_only
+                    || self
+                        .segments
+                        .get(key)
+                        .is_some_and(SegmentEntry::is_approved))
+        });
+        // 按需模式：再查存储共用的索引（本地已有的句段以本地为准）
+        let shared = self.store.as_ref().and_then(|store| {
+            store
+                .segment_index()
+                .map_err(|e| log::error!("[TM句段] 构建模糊匹配索引失败: {}", e))
+                .ok()
+        });
+        if let Some(shared) = &shared {
+            matches.extend(shared.index.search(source, top_k, min_score, |key| {
+                TmKey::target_lang_of(key) == Some(target_lang)
+                    && !self.segments.contains_key(key)
+                    && (!approved_only || shared.is_approved(key))
+            }));
+            matches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            matches.truncate(top_k);
+        }
+
+        matches
+            .into_iter()
+            .filter_map(|(key, score)| {
+                let entry = self.entry(key, &TmKey::parse(key))?;
+                Some(FuzzyMatch {
+                    source: entry.source,
+                    target: entry.target,
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn machine(source: &str, target: &str) -> SegmentEntry {
+        SegmentEntry::new(source, target, "zh-CN", ReviewStatus::Unreviewed)
+            .with_provider(Some("mock/mock-echo".to_string()))
+    }
+
+    #[test]
+    fn test_record_keeps_approved_translation() {
+        let mut memory = SegmentMemory::new();
+        assert!(memory.record(machine("Save the current file.", "保存当前文件。")));
+        // 语言代码标准化：zh-CN 与 zh-Hans 共用
+        let key = TmKey::new("Save the current file.", Some("zh-Hans"));
+        assert_eq!(memory.get(&key).unwrap().target, "保存当前文件。");
+
+        let approvals = vec![SegmentApproval {
+            source: "Save the current file.".to_string(),
+            target: "保存当前的文件。".to_string(),
+            context: None,
+        }];
+        assert_eq!(memory.approve(&approvals, None, "zh-Hans", Some("a.po")), 1);
+        assert_eq!(memory.approve(&approvals, None, "zh-Hans", Some("a.po")), 0);
+
+        // 后续 AI 译文不覆盖已审校译文
+        assert!(!memory.record(machine("Save the current file.", "另存")));
+        let entry = memory
+            .get(&TmKey::new("Save the current file.", Some("zh-CN")))
+            .unwrap();
+        assert_eq!(entry.target, "保存当前的文件。");
+        assert_eq!(entry.review_status, ReviewStatus::Approved);
+        assert_eq!(entry.file.as_deref(), Some("a.po"));
+        assert_eq!(memory.stats().approved, 1);
+    }
+
+    #[test]
+    fn test_context_keys_prefer_in_context_match() {
+        let mut memory = SegmentMemory::new();
+        let approvals = vec![
+            SegmentApproval {
+                source: "Open the door".to_string(),
+                target: "打开门".to_string(),
+                context: Some("action".to_string()),
+            },
+            SegmentApproval {
+                source: "Open the door".to_string(),
+                target: "敞开的门".to_string(),
+                context: Some("state".to_string()),
+            },
+        ];
+        assert_eq!(
+            memory.approve(&approvals, Some("en-US"), "zh-Hans", None),
+            2
+        );
+        assert_eq!(memory.len(), 2);
+
+        let query = TmKey::new("Open the door", Some("zh-Hans")).with_source_lang(Some("en"));
+        let (entry, score) = memory
+            .lookup(&query.clone().with_context(Some("state")))
+            .unwrap();
+        assert_eq!((entry.target.as_str(), score), ("敞开的门", 101));
+
+        // 没有该上下文的句段时退回到无上下文句段并扣分
+        assert!(
+            memory
+                .lookup(&query.clone().with_context(Some("menu")))
+                .is_none()
+        );
+        memory.record(machine("Open the door", "开门"));
+        let (entry, score) = memory
+            .lookup(&query.clone().with_context(Some("menu")))
+            .unwrap();
+        assert_eq!(entry.target, "开门");
+        assert_eq!(
+            score,
+            100 - crate::services::translation_memory::CONTEXT_FALLBACK_PENALTY
+        );
+
+        // 源语言不同的句段不参与
+        let german = TmKey::new("Open the door", Some("zh-Hans"))
+            .with_source_lang(Some("de"))
+            .with_context(Some("state"));
+        assert_eq!(memory.lookup(&german).unwrap().0.target, "开门");
+        assert!(memory.lookup_approved(&german).is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_and_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("segment_memory.json");
+
+        let mut memory = SegmentMemory::new();
+        memory.record(machine("The file could not be opened.", "无法打开该文件。"));
+        memory.record(SegmentEntry::new(
+            "The file could not be opened.",
+            "Die Datei konnte nicht geöffnet werden.",
+            "de",
+            ReviewStatus::Unreviewed,
+        ));
+        memory.save_to_file(&path).unwrap();
+
+        let loaded = SegmentMemory::load_from_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        let matches =
+            loaded.fuzzy_matches("The files could not be opened.", "zh-Hans", 3, 70, false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].target, "无法打开该文件。");
+        assert!(matches[0].score >= 90);
+        // 未审校的句段不参与自动套用查询
+        assert!(
+            loaded
+                .fuzzy_matches("The files could not be opened.", "zh-Hans", 3, 70, true)
+                .is_empty()
+        );
+
+        let mut merged = SegmentMemory::new();
+        assert_eq!(merged.merge_from(&loaded), 2);
+        assert_eq!(merged.merge_from(&loaded), 0);
+    }
+
+    #[test]
+    fn test_open_keeps_stored_approval() {
+        let store = Arc::new(TmStore::open_in_memory().unwrap());
+        let approved = SegmentEntry::new(
+            "Save the current file.",
+            "保存当前文件。",
+            "zh-Hans",
+            ReviewStatus::Approved,
+        );
+        store
+            .upsert_segments([
+                &approved,
+                &machine("Close the current file.", "关闭当前文件。"),
+            ])
+            .unwrap();
+
+        let mut memory = SegmentMemory::open(Arc::clone(&store));
+        assert!(memory.is_empty());
+        let query = TmKey::new("Save the current file.", Some("zh-Hans"));
+        assert_eq!(memory.lookup_approved(&query).unwrap().1, 100);
+        // 存储中已审校的译文不会被 AI 译文覆盖
+        assert!(!memory.record(machine("Save the current file.", "保存文件。")));
+        assert_eq!(memory.get(&query).unwrap().target, "保存当前文件。");
+
+        // 自动套用只取已审校的句段
+        let all = memory.fuzzy_matches("Close the current file", "zh-Hans", 2, 80, false);
+        assert_eq!(all[0].target, "关闭当前文件。");
+        let approved_only = memory.fuzzy_matches("Close the current file", "zh-Hans", 2, 80, true);
+        assert!(approved_only.is_empty());
+
+        memory.record_hit(&query);
+        memory.save_to_store(&store).unwrap();
+        let stored = store.records().unwrap();
+        assert_eq!(
+            stored
+                .iter()
+                .find(|record| record.source == "Save the current file.")
+                .unwrap()
+                .hits,
+            1
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! 包含 `AITranslator` 的单元测试和集成测试
 
+use super::test_env::TestDataDir;
 use crate::services::ai_translator::{AIConfig, AITranslator, ProxyConfig};
 use crate::services::translation_stats::{BatchStats, TokenStats};
 
@@ -18,6 +19,7 @@ mod tests {
             base_url: Some("https://api.test.com".to_string()),
             model: Some("test-model".to_string()),
             proxy: None,
+            generation: Default::default(),
         }
     }
 
@@ -25,6 +27,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_basic() {
+        let _data = TestDataDir::new();
         let translator = AITranslator::new("test_key".to_string(), None, false, None, None);
 
         assert!(translator.is_ok());
@@ -35,6 +38,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_with_tm() {
+        let _data = TestDataDir::new();
         let translator = AITranslator::new(
             "test_key".to_string(),
             None,
@@ -51,6 +55,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_with_custom_prompt() {
+        let _data = TestDataDir::new();
         let custom_prompt = "自定义翻译提示词";
         let translator = AITranslator::new(
             "test_key".to_string(),
@@ -70,6 +75,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_with_config_basic() {
+        let _data = TestDataDir::new();
         let config = create_test_config();
         let result = AITranslator::new_with_config(config, false, None, None);
 
@@ -86,6 +92,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_with_config_with_proxy() {
+        let _data = TestDataDir::new();
         let mut config = create_test_config();
         config.proxy = Some(ProxyConfig {
             host: "127.0.0.1".to_string(),
@@ -101,6 +108,7 @@ mod tests {
 
     #[test]
     fn test_ai_translator_new_with_config_with_tm() {
+        let _data = TestDataDir::new();
         let config = create_test_config();
         let result = AITranslator::new_with_config(config, true, None, Some("zh-Hant".to_string()));
 
@@ -243,6 +251,7 @@ mod tests {
 
     #[test]
     fn test_get_token_stats() {
+        let _data = TestDataDir::new();
         let translator =
             AITranslator::new("test_key".to_string(), None, false, None, None).unwrap();
 
@@ -253,6 +262,7 @@ mod tests {
 
     #[test]
     fn test_reset_stats() {
+        let _data = TestDataDir::new();
         let mut translator =
             AITranslator::new("test_key".to_string(), None, false, None, None).unwrap();
 
@@ -265,6 +275,7 @@ mod tests {
 
     #[test]
     fn test_clear_conversation_history() {
+        let _data = TestDataDir::new();
         let mut translator =
             AITranslator::new("test_key".to_string(), None, false, None, None).unwrap();
 
@@ -275,6 +286,7 @@ mod tests {
 
     #[test]
     fn test_current_system_prompt() {
+        let _data = TestDataDir::new();
         let translator =
             AITranslator::new("test_key".to_string(), None, false, None, None).unwrap();
 
@@ -284,6 +296,7 @@ mod tests {
 
     #[test]
     fn test_build_user_prompt() {
+        let _data = TestDataDir::new();
         let translator = AITranslator::new(
             "test_key".to_string(),
             None,
@@ -303,6 +316,7 @@ mod tests {
 
     #[test]
     fn test_build_user_prompt_without_target_language() {
+        let _data = TestDataDir::new();
         let translator =
             AITranslator::new("test_key".to_string(), None, false, None, None).unwrap();
 
@@ -315,6 +329,7 @@ mod tests {
 
     #[test]
     fn test_invalid_proxy_config() {
+        let _data = TestDataDir::new();
         let mut config = create_test_config();
         // 无效的代理地址（空地址，但仍然可以创建）
         config.proxy = Some(ProxyConfig {
@@ -331,6 +346,7 @@ mod tests {
 
     #[test]
     fn test_empty_api_key() {
+        let _data = TestDataDir::new();
         let translator = AITranslator::new("".to_string(), None, false, None, None);
 
         // 空 API key 应该也能创建（实际请求时会失败）
@@ -2,6 +2,7 @@
 //!
 //! 包含 `BatchTranslator` 的基础单元测试
 
+use super::test_env::TestDataDir;
 use crate::commands::POEntry;
 use crate::services::batch_translator::{
     BatchTranslator, DeduplicationStats, TranslationMemoryStats, TranslationReport,
@@ -17,6 +18,7 @@ mod tests {
 
     #[test]
     fn test_batch_translator_new() {
+        let _data = TestDataDir::new();
         let translator = BatchTranslator::new("test_key".to_string(), None);
 
         assert!(translator.is_ok());
@@ -26,6 +28,7 @@ mod tests {
 
     #[test]
     fn test_batch_translator_new_with_base_url() {
+        let _data = TestDataDir::new();
         let translator = BatchTranslator::new(
             "test_key".to_string(),
             Some("https://api.test.com".to_string()),
@@ -36,6 +39,7 @@ mod tests {
 
     #[test]
     fn test_batch_translator_new_with_empty_api_key() {
+        let _data = TestDataDir::new();
         let translator = BatchTranslator::new("".to_string(), None);
 
         // 空 API key 应该也能创建
@@ -46,6 +50,7 @@ mod tests {
 
     #[test]
     fn test_get_reports() {
+        let _data = TestDataDir::new();
         let translator = BatchTranslator::new("test_key".to_string(), None).unwrap();
 
         let reports = translator.get_reports();
@@ -54,6 +59,7 @@ mod tests {
 
     #[test]
     fn test_get_translation_memory() {
+        let _data = TestDataDir::new();
         let translator = BatchTranslator::new("test_key".to_string(), None).unwrap();
 
         let tm = translator.get_translation_memory();
@@ -0,0 +1,931 @@
+//! mock 供应商测试模块
+//!
+//! 通过内置 mock 供应商 + 进程内桩服务器离线覆盖 `AITranslator` 的网络路径：
+//! 重试、解析错误、分批恢复、流式输出、记忆库学习、模糊匹配与统计回调
+
+use super::stub_server::{ECHO_PREFIX, StubReply, StubServer};
+use super::test_env::TestDataDir;
+use crate::commands::translator::{
+    ChannelJobSettings, auto_save_translation_memory, run_channel_job,
+};
+use crate::error::AppError;
+use crate::services::ai::GenerationParams;
+use crate::services::ai::plugin_loader::register_mock_provider;
+use crate::services::ai_translator::{AIConfig, AITranslator};
+use crate::services::budget::{BudgetConfig, PausedJob};
+use crate::services::fuzzy_match::FuzzyMatchConfig;
+use crate::services::response_cache::{self, ResponseCacheConfig};
+use crate::services::segment_memory::SegmentApproval;
+use crate::services::tm_registry;
+use crate::services::translation_memory::TmKey;
+use crate::services::translation_stats::{BatchStats, TokenStats};
+use crate::services::usage_ledger;
+use crate::services::{BatchProgressEvent, BatchStatsEvent};
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::clone_on_ref_ptr)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tauri::ipc::{Channel, InvokeResponseBody};
+
+    /// 指向桩服务器的 mock 翻译器（关闭响应缓存，保证每次都真正发请求）
+    fn mock_translator(server: &StubServer, use_tm: bool) -> AITranslator {
+        mock_translator_with(server, use_tm, GenerationParams::default()).unwrap()
+    }
+
+    fn mock_translator_with(
+        server: &StubServer,
+        use_tm: bool,
+        generation: GenerationParams,
+    ) -> Result<AITranslator, AppError> {
+        register_mock_provider().unwrap();
+        response_cache::configure(&ResponseCacheConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        let config = AIConfig {
+            provider_id: "mock".to_string(),
+            api_key: "mock-key".to_string(),
+            base_url: Some(server.url()),
+            model: None,
+            proxy: None,
+            generation,
+        };
+        AITranslator::new_with_config(config, use_tm, None, Some("zh-Hans".to_string()))
+    }
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn echoed(text: &str) -> String {
+        format!("{}{}", ECHO_PREFIX, text)
+    }
+
+    #[tokio::test]
+    async fn test_echo_round_trip_records_usage() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::echo()]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator
+            .translate_with_ai(texts(&["Open file", "Save file"]))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("Open file"), echoed("Save file")]);
+        assert_eq!(server.request_count(), 1);
+        let request = &server.requests()[0];
+        assert_eq!(request["model"], "mock-echo");
+        assert!(request["stream"].as_bool() != Some(true));
+
+        let stats = translator.get_token_stats();
+        assert_eq!(stats.input_tokens, 100);
+        assert_eq!(stats.output_tokens, 50);
+        assert!(stats.cost > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_custom_prompt_records_usage() {
+        let _data = TestDataDir::new();
+        // 精修、风格总结等自定义提示词请求同样计入统计与用量台账
+        let server = StubServer::start(vec![StubReply::content("精修后的译文")]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator
+            .translate_with_custom_user_prompt("请精修以下译文".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(result, "精修后的译文");
+        let stats = translator.get_token_stats();
+        assert_eq!(stats.input_tokens, 100);
+        assert_eq!(stats.output_tokens, 50);
+        let records = usage_ledger::load(None, None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].provider, "mock");
+        assert_eq!(records[0].input_tokens, 100);
+        assert!((records[0].cost - stats.cost).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_generation_params_reach_request_body() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let generation = GenerationParams {
+            top_p: Some(0.5),
+            max_tokens: Some(1024),
+            extra_body: serde_json::Map::from_iter([(
+                "enable_thinking".to_string(),
+                serde_json::json!(false),
+            )]),
+            ..Default::default()
+        };
+        let mut translator = mock_translator_with(&server, false, generation).unwrap();
+
+        translator.translate_with_ai(texts(&["Hi"])).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request["temperature"], 0.2);
+        assert_eq!(request["top_p"], 0.5);
+        assert_eq!(request["max_tokens"], 1024);
+        assert_eq!(request["enable_thinking"], false);
+
+        // 超过模型最大输出（mock-echo 为 4096）在创建时即报错
+        let too_long = GenerationParams {
+            max_tokens: Some(8192),
+            ..Default::default()
+        };
+        assert!(matches!(
+            mock_translator_with(&server, false, too_long),
+            Err(AppError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_count_is_parse_error() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::content("1. 只有一条")]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let err = translator
+            .translate_with_ai(texts(&["One", "Two"]))
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, AppError::Parse(_)),
+            "unexpected error: {err:?}"
+        );
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_recovers_from_malformed_count_by_splitting() {
+        let _data = TestDataDir::new();
+        // 整批数量不符后拆成两半重试，后续请求走回显
+        let server = StubServer::start(vec![StubReply::content("1. 只有一条")]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator
+            .translate_batch(texts(&["Alpha text", "Beta text"]), None)
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("Alpha text"), echoed("Beta text")]);
+        assert_eq!(server.request_count(), 3);
+        // 数量不符的响应不进入对话历史，拆分后的请求不带它
+        for request in &server.requests()[1..] {
+            assert!(!request["messages"].to_string().contains("只有一条"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_retried() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::rate_limited(0)]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator
+            .translate_with_ai(texts(&["Retry me"]))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("Retry me")]);
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_is_not_retried() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::error(401, "Invalid API key")]).await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator.translate_with_ai(texts(&["Denied"])).await;
+
+        assert!(result.is_err());
+        assert_eq!(server.request_count(), 1);
+        assert_eq!(translator.get_token_stats().total_tokens, 0);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_retried() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::delayed(
+            Duration::from_secs(2),
+            StubReply::echo(),
+        )])
+        .await;
+        let mut translator = mock_translator(&server, false);
+        translator.set_request_timeout(Duration::from_millis(200));
+
+        let result = translator
+            .translate_with_ai(texts(&["Slow"]))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("Slow")]);
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_reports_lines() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::echo()]).await;
+        let mut translator = mock_translator(&server, false);
+        let lines: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let on_line = move |idx: usize, text: &str| sink.lock().push((idx, text.to_string()));
+
+        let result = translator
+            .translate_with_ai_streaming(texts(&["First", "Second"]), Some(&on_line))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("First"), echoed("Second")]);
+        assert_eq!(server.requests()[0]["stream"], true);
+        assert_eq!(
+            *lines.lock(),
+            vec![(0, echoed("First")), (1, echoed("Second"))]
+        );
+        assert_eq!(translator.get_token_stats().output_tokens, 50);
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_replays_lines_and_drops_unparsable_entries() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, false);
+        response_cache::configure(&ResponseCacheConfig::default());
+        let lines: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let on_line = move |idx: usize, text: &str| sink.lock().push((idx, text.to_string()));
+        let expected = vec![(0, echoed("First")), (1, echoed("Second"))];
+
+        translator
+            .translate_with_ai_streaming(texts(&["First", "Second"]), Some(&on_line))
+            .await
+            .unwrap();
+        assert_eq!(*lines.lock(), expected);
+
+        // 命中缓存：不发请求，逐行回调照常触发
+        lines.lock().clear();
+        let cached = translator
+            .translate_with_ai_streaming(texts(&["First", "Second"]), Some(&on_line))
+            .await
+            .unwrap();
+        assert_eq!(cached, vec![echoed("First"), echoed("Second")]);
+        assert_eq!(server.request_count(), 1);
+        assert_eq!(*lines.lock(), expected);
+
+        // 缓存内容无法解析：删除后重新请求，并写入新的响应
+        let cache_dir = crate::utils::paths::app_data_dir()
+            .unwrap()
+            .join("response_cache");
+        let poisoned = serde_json::json!({
+            "created_at": chrono::Utc::now().timestamp(),
+            "provider": "mock",
+            "model": "mock-echo",
+            "content": "1. 只有一条",
+        });
+        for entry in std::fs::read_dir(&cache_dir).unwrap() {
+            std::fs::write(entry.unwrap().path(), poisoned.to_string()).unwrap();
+        }
+        let refreshed = translator
+            .translate_with_ai(texts(&["First", "Second"]))
+            .await
+            .unwrap();
+        assert_eq!(refreshed, vec![echoed("First"), echoed("Second")]);
+        assert_eq!(server.request_count(), 2);
+        translator
+            .translate_with_ai(texts(&["First", "Second"]))
+            .await
+            .unwrap();
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_chunks_split_mid_line() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::Stream(texts(&[
+            "1. 甲", "乙\n2. ", "丙丁",
+        ]))])
+        .await;
+        let mut translator = mock_translator(&server, false);
+        let on_line = |_: usize, _: &str| {};
+
+        let result = translator
+            .translate_with_ai_streaming(texts(&["A", "B"]), Some(&on_line))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec!["甲乙".to_string(), "丙丁".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_drops_lines_outside_batch() {
+        let _data = TestDataDir::new();
+        // 模型多输出了一行：收到的行立即上报（之后整批数量不符仍按解析错误处理），
+        // 超出本批序号的行不上报
+        let server =
+            StubServer::start(vec![StubReply::Stream(texts(&["1. 甲\n2. 乙\n3. 丙"]))]).await;
+        let mut translator = mock_translator(&server, false);
+        let lines: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let on_line = move |idx: usize, text: &str| sink.lock().push((idx, text.to_string()));
+
+        let result = translator
+            .translate_with_ai_streaming(texts(&["A", "B"]), Some(&on_line))
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            *lines.lock(),
+            vec![(0, "甲".to_string()), (1, "乙".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_retry_overwrites_streamed_lines() {
+        let _data = TestDataDir::new();
+        // 第一次流式响应少一行：已上报的行是临时的，拆分重试后按同一位置覆盖为最终译文
+        let server = StubServer::start(vec![StubReply::Stream(texts(&["1. 错译"]))]).await;
+        let mut translator = mock_translator(&server, false);
+        let progress: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = progress.clone();
+
+        let result = translator
+            .translate_batch_with_callbacks(
+                texts(&["Alpha text", "Beta text"]),
+                Some(Box::new(move |idx, text| sink.lock().push((idx, text)))),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![echoed("Alpha text"), echoed("Beta text")]);
+        assert_eq!(server.request_count(), 3);
+        let progress = progress.lock().clone();
+        assert_eq!(progress[0], (0, "错译".to_string()));
+        let latest: std::collections::HashMap<usize, String> = progress.into_iter().collect();
+        assert_eq!(latest[&0], echoed("Alpha text"));
+        assert_eq!(latest[&1], echoed("Beta text"));
+    }
+
+    #[tokio::test]
+    async fn test_think_block_is_stripped_and_counted() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::content(
+            "<think>\n1. Open 是动词\n2. Save 也是\n</think>\n\n1. 打开\n2. 保存",
+        )])
+        .await;
+        let mut translator = mock_translator(&server, false);
+
+        let result = translator
+            .translate_with_ai(texts(&["Open", "Save"]))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec!["打开".to_string(), "保存".to_string()]);
+        assert_eq!(
+            translator.last_reasoning(),
+            Some("1. Open 是动词\n2. Save 也是")
+        );
+        // 未返回推理 token 时按分词器估算，且计入输出
+        let stats = translator.get_token_stats();
+        assert!(stats.reasoning_tokens > 0 && stats.reasoning_tokens <= stats.output_tokens);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_skips_think_lines() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(vec![StubReply::Stream(texts(&[
+            "<think>1. 推",
+            "理行\n</thi",
+            "nk>\n1. 甲\n",
+            "2. 乙",
+        ]))])
+        .await;
+        let mut translator = mock_translator(&server, false);
+        let lines: Arc<Mutex<Vec<(usize, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let on_line = move |idx: usize, text: &str| sink.lock().push((idx, text.to_string()));
+
+        let result = translator
+            .translate_with_ai_streaming(texts(&["A", "B"]), Some(&on_line))
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec!["甲".to_string(), "乙".to_string()]);
+        assert_eq!(
+            *lines.lock(),
+            vec![(0, "甲".to_string()), (1, "乙".to_string())]
+        );
+        assert_eq!(translator.last_reasoning(), Some("1. 推理行"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_learns_tm_and_reports_callbacks() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        let progress: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(Vec::new()));
+        let last_stats: Arc<Mutex<Option<(BatchStats, TokenStats)>>> = Arc::new(Mutex::new(None));
+        let progress_sink = progress.clone();
+        let stats_sink = last_stats.clone();
+
+        // 重复条目只发送一次，简单短语写入记忆库
+        let result = translator
+            .translate_batch_with_callbacks(
+                texts(&["Zorblax widget", "Quuxify", "Zorblax widget"]),
+                Some(Box::new(move |idx, _| progress_sink.lock().push(idx))),
+                Some(Box::new(move |batch, tokens| {
+                    *stats_sink.lock() = Some((batch, tokens));
+                })),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                echoed("Zorblax widget"),
+                echoed("Quuxify"),
+                echoed("Zorblax widget")
+            ]
+        );
+        assert_eq!(server.request_count(), 1);
+
+        let mut indices = progress.lock().clone();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices, vec![0, 1, 2]);
+
+        let (batch, tokens) = last_stats.lock().clone().expect("未收到统计回调");
+        assert_eq!(batch.total, 3);
+        assert_eq!(batch.deduplicated, 1);
+        assert_eq!(batch.ai_translated, 2);
+        assert_eq!(batch.tm_learned, 2);
+        assert_eq!(tokens.total_tokens, 150);
+
+        let tm = translator.get_translation_memory_mut().unwrap();
+        assert_eq!(
+            tm.get_translation("Quuxify", Some("zh-Hans")),
+            Some(echoed("Quuxify"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_tm_auto_applies_and_injects_references() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        translator.set_fuzzy_match_config(FuzzyMatchConfig::default());
+        {
+            let tm = translator.get_translation_memory_mut().unwrap();
+            tm.add_translation(
+                "Zorblax the selected widgets".to_string(),
+                "Zorblax 所选部件".to_string(),
+                Some("zh-Hans"),
+            );
+            tm.add_translation(
+                "Quuxify the remote archive".to_string(),
+                "Quuxify 远程归档".to_string(),
+                Some("zh-Hans"),
+            );
+        }
+
+        // 96% 达到自动套用阈值；75% 只作为参考译文发送
+        let (result, sources) = translator
+            .translate_batch_with_sources(
+                texts(&[
+                    "Zorblax the selected widget",
+                    "Quuxify a remote archive now",
+                ]),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                "Zorblax 所选部件".to_string(),
+                echoed("Quuxify a remote archive now")
+            ]
+        );
+        assert_eq!(sources, vec!["fuzzy".to_string(), "ai".to_string()]);
+        assert_eq!(translator.batch_stats.tm_hits, 1);
+
+        let request = &server.requests()[0];
+        let prompt = request["messages"].as_array().unwrap().last().unwrap()["content"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(prompt.contains("- Quuxify the remote archive => Quuxify 远程归档 (75%)"));
+        assert!(!prompt.contains("Zorblax"));
+    }
+
+    #[tokio::test]
+    async fn test_full_sentences_learned_into_segment_tier() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        let sentence = "The Zorblax archive could not be opened because it is locked.";
+
+        let first = translator
+            .translate_batch(texts(&[sentence]), None)
+            .await
+            .unwrap();
+        assert_eq!(first, vec![echoed(sentence)]);
+        assert_eq!(translator.batch_stats.tm_learned, 1);
+
+        let entry = translator
+            .get_segment_memory()
+            .unwrap()
+            .get(&TmKey::new(sentence, Some("zh-Hans")))
+            .unwrap();
+        assert_eq!(entry.provider.as_deref(), Some("mock/mock-echo"));
+        assert!(!entry.is_approved());
+        // 完整句子不进入短语表
+        assert!(
+            translator
+                .get_translation_memory_mut()
+                .unwrap()
+                .get_translation(sentence, Some("zh-Hans"))
+                .is_none()
+        );
+
+        // 未审校的句段不直接套用，仍然请求模型
+        let (second, sources) = translator
+            .translate_batch_with_sources(texts(&[sentence]), None, None)
+            .await
+            .unwrap();
+        assert_eq!(second, first);
+        assert_eq!(sources, vec!["ai".to_string()]);
+        assert_eq!(server.request_count(), 2);
+
+        // 审校通过后直接从句段库复用，不再请求
+        let approved = translator.get_segment_memory_mut().unwrap().approve(
+            &[SegmentApproval {
+                source: sentence.to_string(),
+                target: echoed(sentence),
+                context: None,
+            }],
+            None,
+            "zh-Hans",
+            None,
+        );
+        assert_eq!(approved, 1);
+        let (third, sources) = translator
+            .translate_batch_with_sources(texts(&[sentence]), None, None)
+            .await
+            .unwrap();
+        assert_eq!(third, first);
+        assert_eq!(sources, vec!["tm".to_string()]);
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tm_prefers_in_context_entries() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        translator.set_fuzzy_match_config(FuzzyMatchConfig::default());
+        {
+            let tm = translator.get_translation_memory_mut().unwrap();
+            tm.add_translation(
+                "Zorblax".to_string(),
+                "佐布拉克斯".to_string(),
+                Some("zh-Hans"),
+            );
+            let verb = TmKey::new("Zorblax", Some("zh-Hans")).with_context(Some("verb"));
+            tm.insert(&verb, "佐布".to_string());
+        }
+
+        let contexts = vec![Some("verb".to_string()), Some("noun".to_string()), None];
+        let (translations, sources) = translator
+            .translate_batch_with_context(
+                texts(&["Zorblax", "Zorblax", "Zorblax"]),
+                &contexts,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(translations, vec!["佐布", "佐布拉克斯", "佐布拉克斯"]);
+        // 退回到无上下文条目扣分（97%），达到默认自动套用阈值，来源标记为 fuzzy
+        assert_eq!(sources, vec!["tm", "fuzzy", "tm"]);
+        assert_eq!(server.request_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_same_text_in_different_contexts_translated_separately() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        let contexts = vec![
+            Some("verb".to_string()),
+            Some("adjective".to_string()),
+            Some("verb".to_string()),
+        ];
+        let (translations, sources) = translator
+            .translate_batch_with_context(
+                texts(&["Zorblax", "Zorblax", "Zorblax"]),
+                &contexts,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(translations, vec![echoed("Zorblax"); 3]);
+        // 只有同一上下文的重复条目去重
+        assert_eq!(sources, vec!["ai", "ai", "dedup"]);
+        assert_eq!(translator.batch_stats.deduplicated, 1);
+
+        // 上下文随提示词按序号发送给模型
+        assert_eq!(server.request_count(), 1);
+        let request = &server.requests()[0];
+        let prompt = request["messages"].as_array().unwrap().last().unwrap()["content"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(prompt.contains("1: verb\n2: adjective\n"));
+        assert!(prompt.contains("1. Zorblax\n2. Zorblax\n"));
+
+        // 每个上下文各自学习，无上下文的键不写入
+        let tm = translator.get_translation_memory_mut().unwrap();
+        for context in ["verb", "adjective"] {
+            let key = TmKey::new("Zorblax", Some("zh-Hans")).with_context(Some(context));
+            assert_eq!(tm.lookup(&key, Some("zh-Hans")).unwrap().score, 101);
+        }
+        assert!(tm.get_translation("Zorblax", Some("zh-Hans")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_memory_stack_priority_and_attribution() {
+        let _data = TestDataDir::new();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let project = format!("project-{suffix}");
+        let reference = format!("reference-{suffix}");
+        tm_registry::create(&project).unwrap();
+        tm_registry::create(&reference).unwrap();
+        let key = |source: &str| TmKey::new(source, Some("zh-Hans")).encode();
+        tm_registry::open(&project)
+            .unwrap()
+            .upsert_phrases([(key("Zorblax").as_str(), "项目译名")])
+            .unwrap();
+        tm_registry::open(&reference)
+            .unwrap()
+            .upsert_phrases([
+                (key("Zorblax").as_str(), "参考译名"),
+                (key("Quuxify").as_str(), "参考动词"),
+            ])
+            .unwrap();
+
+        let server = StubServer::start(Vec::new()).await;
+        let mut translator = mock_translator(&server, true);
+        let stack =
+            tm_registry::MemoryStack::open(Some(&project), std::slice::from_ref(&reference))
+                .unwrap();
+        translator.set_memory_stack(&stack).unwrap();
+        assert_eq!(translator.memory_name(), project);
+        let (translations, sources) = translator
+            .translate_batch_with_sources(texts(&["Zorblax", "Quuxify", "Blorp"]), None, None)
+            .await
+            .unwrap();
+        // 同分时项目记忆库优先；命中来源附带记忆库名称
+        assert_eq!(translations[..2], ["项目译名", "参考动词"]);
+        assert_eq!(
+            sources,
+            vec![
+                format!("tm:{project}"),
+                format!("tm:{reference}"),
+                "ai".to_string()
+            ]
+        );
+
+        // 学习结果只写入项目记忆库的存储，参考记忆库不变
+        auto_save_translation_memory(&mut translator);
+        let project_store = tm_registry::open(&project).unwrap();
+        let reference_store = tm_registry::open(&reference).unwrap();
+        assert_eq!(
+            project_store.phrase(&key("Blorp")).unwrap(),
+            Some(echoed("Blorp"))
+        );
+        assert_eq!(reference_store.phrase(&key("Blorp")).unwrap(), None);
+        assert_eq!(reference_store.phrase_count().unwrap(), 2);
+        tm_registry::delete(&project).unwrap();
+        tm_registry::delete(&reference).unwrap();
+    }
+
+    fn mock_config(server: &StubServer) -> AIConfig {
+        AIConfig {
+            provider_id: "mock".to_string(),
+            api_key: "mock-key".to_string(),
+            base_url: Some(server.url()),
+            model: None,
+            proxy: None,
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// 翻译任务参数：每批 2 条、单 worker，按顺序使用给定的供应商链
+    fn job_settings(
+        ai_configs: Vec<AIConfig>,
+        primary_retry_cooldown: Duration,
+    ) -> ChannelJobSettings {
+        register_mock_provider().unwrap();
+        response_cache::configure(&ResponseCacheConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        ChannelJobSettings {
+            ai_configs,
+            custom_prompt: None,
+            batch_size: 2,
+            adaptive_batch_size: false,
+            max_concurrent: 1,
+            budget: BudgetConfig::default(),
+            primary_retry_cooldown,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_channel_job_streams_progress_and_stats() {
+        let _data = TestDataDir::new();
+        let server = StubServer::start(Vec::new()).await;
+        let settings = job_settings(vec![mock_config(&server)], Duration::ZERO);
+
+        let progress = Arc::new(Mutex::new(Vec::<BatchProgressEvent>::new()));
+        let stats = Arc::new(Mutex::new(Vec::<BatchStatsEvent>::new()));
+        let progress_channel = {
+            let progress = Arc::clone(&progress);
+            Channel::new(move |body: InvokeResponseBody| {
+                progress.lock().push(body.deserialize().unwrap());
+                Ok(())
+            })
+        };
+        let stats_channel = {
+            let stats = Arc::clone(&stats);
+            Channel::new(move |body: InvokeResponseBody| {
+                stats.lock().push(body.deserialize().unwrap());
+                Ok(())
+            })
+        };
+
+        let app = tauri::test::mock_app();
+        let job = PausedJob::new(
+            texts(&["Open file", "Save file", "Close file"]),
+            Some("zh-Hans".to_string()),
+        );
+        let result = run_channel_job(
+            app.handle().clone(),
+            job,
+            settings,
+            progress_channel,
+            stats_channel,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result.translations,
+            vec![
+                echoed("Open file"),
+                echoed("Save file"),
+                echoed("Close file")
+            ]
+        );
+        assert_eq!(result.translation_sources, vec!["ai"; 3]);
+        assert_eq!(server.request_count(), 2);
+
+        // 首个进度事件携带任务ID；逐条事件的下标与译文对应
+        {
+            let progress = progress.lock();
+            assert_eq!(progress[0].task_id, Some(result.task_id));
+            assert_eq!(progress[0].processed, 0);
+            let streamed: Vec<_> = progress.iter().filter(|e| e.index.is_some()).collect();
+            assert!(!streamed.is_empty());
+            for event in streamed {
+                let index = event.index.unwrap();
+                assert_eq!(
+                    event.current_item.as_deref(),
+                    Some(result.translations[index].as_str())
+                );
+            }
+        }
+
+        // 每批一个增量统计事件，合计等于任务统计
+        let stats = stats.lock();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.iter().map(|e| e.ai_translated).sum::<usize>(), 3);
+        assert_eq!(
+            stats
+                .iter()
+                .map(|e| e.token_stats.total_tokens)
+                .sum::<usize>(),
+            result.stats.token_stats.total_tokens as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channel_job_retries_primary_after_cooldown() {
+        let _data = TestDataDir::new();
+        // 主供应商第一批失败（401 不重试）后切换到备用供应商；
+        // 冷却期内的第二批仍用备用供应商（回复延迟到冷却结束之后），第三批回到主供应商
+        let primary = StubServer::start(vec![StubReply::error(401, "Invalid API key")]).await;
+        let fallback = StubServer::start(vec![
+            StubReply::echo(),
+            StubReply::delayed(Duration::from_millis(800), StubReply::echo()),
+        ])
+        .await;
+        let settings = job_settings(
+            vec![mock_config(&primary), mock_config(&fallback)],
+            Duration::from_millis(500),
+        );
+
+        let app = tauri::test::mock_app();
+        let job = PausedJob::new(
+            texts(&[
+                "Print page",
+                "Find text",
+                "Replace text",
+                "Select all",
+                "Zoom in",
+            ]),
+            Some("zh-Hans".to_string()),
+        );
+        let result = run_channel_job(
+            app.handle().clone(),
+            job,
+            settings,
+            Channel::new(|_| Ok(())),
+            Channel::new(|_| Ok(())),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result.translations,
+            vec![
+                echoed("Print page"),
+                echoed("Find text"),
+                echoed("Replace text"),
+                echoed("Select all"),
+                echoed("Zoom in")
+            ]
+        );
+        assert_eq!(fallback.request_count(), 2);
+        assert_eq!(primary.request_count(), 2);
+        assert!(
+            primary.requests()[1]["messages"]
+                .to_string()
+                .contains("Zoom in")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_channel_job_keeps_learned_memory() {
+        let _data = TestDataDir::new();
+        // 第一批成功、第二批失败（401 不重试）：任务报错，但第一批的译文已写入记忆库
+        let server = StubServer::start(vec![
+            StubReply::echo(),
+            StubReply::error(401, "Invalid API key"),
+        ])
+        .await;
+        let settings = job_settings(vec![mock_config(&server)], Duration::ZERO);
+
+        let app = tauri::test::mock_app();
+        let job = PausedJob::new(
+            texts(&["Zorblax", "Quuxify", "Blorp"]),
+            Some("zh-Hans".to_string()),
+        );
+        let result = run_channel_job(
+            app.handle().clone(),
+            job,
+            settings,
+            Channel::new(|_| Ok(())),
+            Channel::new(|_| Ok(())),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(server.request_count(), 2);
+        let key = |source: &str| TmKey::new(source, Some("zh-Hans")).encode();
+        let store = tm_registry::open(tm_registry::DEFAULT_MEMORY).unwrap();
+        assert_eq!(
+            store.phrase(&key("Zorblax")).unwrap(),
+            Some(echoed("Zorblax"))
+        );
+        assert_eq!(
+            store.phrase(&key("Quuxify")).unwrap(),
+            Some(echoed("Quuxify"))
+        );
+        assert_eq!(store.phrase(&key("Blorp")).unwrap(), None);
+    }
+}
@@ -4,4 +4,7 @@
 
 mod ai_translator_tests;
 mod batch_translator_simple_tests;
+mod mock_provider_tests;
 mod po_parser_tests;
+mod stub_server;
+pub mod test_env;
@@ -0,0 +1,287 @@
+//! 进程内 HTTP 桩服务器
+//!
+//! 监听本地随机端口，按脚本依次回放预设响应（脚本用完后按序号回显原文），
+//! 配合内置 mock 供应商离线覆盖重试、解析错误、流式输出等路径
+
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 回显时给原文加的默认前缀
+pub const ECHO_PREFIX: &str = "译:";
+
+/// 预设响应
+#[derive(Debug, Clone)]
+pub enum StubReply {
+    /// 按请求中带序号的行回显 `序号. 前缀原文`（请求 stream 时以 SSE 逐行返回）
+    Echo { prefix: String },
+    /// 固定的助手回复内容
+    Content(String),
+    /// 指定状态码与响应体，可带 Retry-After（秒）
+    Status {
+        status: u16,
+        body: String,
+        retry_after: Option<u64>,
+    },
+    /// 延迟后再回复（超过客户端超时即模拟超时）
+    Delay(Duration, Box<StubReply>),
+    /// 以 SSE 分片返回内容（每个元素一个数据块）
+    Stream(Vec<String>),
+}
+
+impl StubReply {
+    pub fn echo() -> Self {
+        Self::Echo {
+            prefix: ECHO_PREFIX.to_string(),
+        }
+    }
+
+    pub fn content(content: &str) -> Self {
+        Self::Content(content.to_string())
+    }
+
+    /// 429 频率超限
+    pub fn rate_limited(retry_after: u64) -> Self {
+        Self::Status {
+            status: 429,
+            body: json!({ "error": { "message": "Rate limit reached" } }).to_string(),
+            retry_after: Some(retry_after),
+        }
+    }
+
+    /// 指定状态码的错误响应
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::Status {
+            status,
+            body: json!({ "error": { "message": message } }).to_string(),
+            retry_after: None,
+        }
+    }
+
+    pub fn delayed(delay: Duration, reply: StubReply) -> Self {
+        Self::Delay(delay, Box::new(reply))
+    }
+}
+
+struct StubState {
+    script: Mutex<VecDeque<StubReply>>,
+    requests: Mutex<Vec<Value>>,
+}
+
+/// 桩服务器（drop 时停止监听）
+pub struct StubServer {
+    addr: SocketAddr,
+    state: Arc<StubState>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl StubServer {
+    /// 启动服务器，按顺序回放 `script`
+    pub async fn start(script: Vec<StubReply>) -> Self {
+        #[allow(clippy::expect_used)]
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("桩服务器绑定端口失败");
+        #[allow(clippy::expect_used)]
+        let addr = listener.local_addr().expect("读取桩服务器地址失败");
+        let state = Arc::new(StubState {
+            script: Mutex::new(script.into()),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let accept_state = Arc::clone(&state);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&accept_state);
+                tokio::spawn(async move {
+                    // 客户端超时断开时写入失败，忽略即可
+                    let _ = handle_connection(stream, state).await;
+                });
+            }
+        });
+
+        Self { addr, state, task }
+    }
+
+    /// 作为供应商 base_url 使用的地址
+    pub fn url(&self) -> String {
+        format!("http://{}/v1", self.addr)
+    }
+
+    /// 收到的请求体（按到达顺序）
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.requests.lock().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.state.requests.lock().len()
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<StubState>) -> std::io::Result<()> {
+    let body = read_request_body(&mut stream).await?;
+    let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    state.requests.lock().push(request.clone());
+    let next = state.script.lock().pop_front();
+    let mut reply = next.unwrap_or_else(StubReply::echo);
+
+    while let StubReply::Delay(delay, inner) = reply {
+        tokio::time::sleep(delay).await;
+        reply = *inner;
+    }
+
+    let streaming = request["stream"].as_bool().unwrap_or(false);
+    match reply {
+        StubReply::Echo { prefix } => {
+            let content = echo_content(&request, &prefix);
+            if streaming {
+                let chunks = content.lines().map(|line| format!("{}\n", line)).collect();
+                write_event_stream(&mut stream, chunks).await
+            } else {
+                write_completion(&mut stream, &content).await
+            }
+        }
+        StubReply::Content(content) if streaming => {
+            write_event_stream(&mut stream, vec![content]).await
+        }
+        StubReply::Content(content) => write_completion(&mut stream, &content).await,
+        StubReply::Status {
+            status,
+            body,
+            retry_after,
+        } => {
+            let retry_after = retry_after.map(|seconds| seconds.to_string());
+            let headers: Vec<(&str, &str)> = retry_after
+                .as_deref()
+                .map(|value| ("Retry-After", value))
+                .into_iter()
+                .collect();
+            write_response(&mut stream, status, &headers, body.as_bytes()).await
+        }
+        StubReply::Stream(chunks) => write_event_stream(&mut stream, chunks).await,
+        StubReply::Delay(..) => Ok(()),
+    }
+}
+
+/// 读取请求头后按 Content-Length 读取请求体
+async fn read_request_body(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(Vec::new());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&buffer[..header_end]).to_lowercase();
+    let content_length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buffer.len() < header_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    Ok(buffer.split_off(header_end))
+}
+
+/// 把最后一条用户消息中的 `序号. 原文` 回显为 `序号. 前缀原文`
+fn echo_content(request: &Value, prefix: &str) -> String {
+    let prompt = request["messages"]
+        .as_array()
+        .and_then(|messages| messages.last())
+        .and_then(|message| message["content"].as_str())
+        .unwrap_or_default();
+    prompt
+        .lines()
+        .filter_map(|line| {
+            let (number, text) = line.split_once(". ")?;
+            number
+                .chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| format!("{}. {}{}", number, prefix, text))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn usage() -> Value {
+    json!({ "prompt_tokens": 100, "completion_tokens": 50, "total_tokens": 150 })
+}
+
+async fn write_completion(stream: &mut TcpStream, content: &str) -> std::io::Result<()> {
+    let body = json!({
+        "id": "chatcmpl-stub",
+        "object": "chat.completion",
+        "model": "mock-echo",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": usage(),
+    });
+    write_response(stream, 200, &[], body.to_string().as_bytes()).await
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} STUB\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+/// OpenAI 格式的 SSE：每个分片一个 delta，最后附带 usage 与 [DONE]
+async fn write_event_stream(stream: &mut TcpStream, chunks: Vec<String>) -> std::io::Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+        )
+        .await?;
+    for chunk in chunks {
+        let data = json!({ "choices": [{ "index": 0, "delta": { "content": chunk } }] });
+        stream
+            .write_all(format!("data: {}\n\n", data).as_bytes())
+            .await?;
+        stream.flush().await?;
+    }
+    let data = json!({ "choices": [], "usage": usage() });
+    stream
+        .write_all(format!("data: {}\n\ndata: [DONE]\n\n", data).as_bytes())
+        .await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,39 @@
+//! 测试数据目录
+//!
+//! 记忆库、用量台账、响应缓存等共享存储都位于应用数据目录下。用到它们的测试持有
+//! `TestDataDir`：独占数据目录并切换到新建的临时目录，结束时删除，
+//! 避免测试之间或多次运行之间互相影响（也不会写入真实的用户数据目录）
+
+use crate::services::response_cache::{self, ResponseCacheConfig};
+use crate::utils::paths;
+use parking_lot::{Mutex, MutexGuard};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// 数据目录是进程级状态，持有者之间串行执行
+static DATA_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// 测试期间的数据目录（先删除目录再释放锁）
+pub struct TestDataDir {
+    dir: TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestDataDir {
+    #[allow(clippy::expect_used, clippy::new_without_default)]
+    pub fn new() -> Self {
+        let lock = DATA_DIR_LOCK.lock();
+        let dir = tempfile::Builder::new()
+            .prefix("po-translator-test-")
+            .tempdir()
+            .expect("创建测试数据目录失败");
+        paths::set_app_home_dir(dir.path().join(paths::APP_ID));
+        response_cache::configure(&ResponseCacheConfig::default());
+        Self { dir, _lock: lock }
+    }
+
+    /// 应用数据目录（`paths::app_home_dir`）
+    pub fn home(&self) -> PathBuf {
+        self.dir.path().join(paths::APP_ID)
+    }
+}
@@ -0,0 +1,915 @@
+/**
+ * 记忆库维护：检索、批量替换、清理、重复/冲突查找与按使用情况淘汰
+ *
+ * - 过滤与分页在 SQL 中完成（短语键由注册的 SQL 函数解析），前端不再需要加载整个记忆库
+ * - 短语表与句段库统一为 `TmRecord`，短语键解析出源语言、上下文与目标语言
+ * - 批量替换只修改译文；替换与清理都可先预览（`dry_run`）再执行
+ * - 重复：同一 (目标语言, 上下文, 原文) 有多条译文相同的条目（如旧键与新键、短语表与句段库并存）；
+ *   冲突：译文不同
+ * - 容量：设置了条目数上限时，保存后按 LRU（最久未使用）或 LFU（命中最少）淘汰超出的条目，
+ *   已审校通过的句段与手动保存/导入的短语不淘汰；使用报告列出命中最多与从未命中的条目
+ */
+use crate::services::segment_memory::ReviewStatus;
+use crate::services::tm_store::TmStore;
+use crate::services::translation_memory::normalize_lang_code;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 检索默认每页条数
+const DEFAULT_PAGE_SIZE: usize = 100;
+/// 检索每页条数上限
+const MAX_PAGE_SIZE: usize = 1000;
+/// 替换/清理报告中预览的条数上限
+const MAX_PREVIEW: usize = 200;
+
+/// printf 风格与花括号占位符
+#[allow(clippy::unwrap_used)]
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"%(\d+\$)?[-+ #0]*\d*(\.\d+)?[a-zA-Z@]|\{[^{}]*\}").unwrap());
+
+/// 清理时视为“未翻译占位”的默认标记
+const DEFAULT_PLACEHOLDER_MARKERS: [&str; 8] = [
+    "TODO",
+    "FIXME",
+    "TBD",
+    "[untranslated]",
+    "<untranslated>",
+    "???",
+    "未翻译",
+    "待翻译",
+];
+
+/// 条目所在的层
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum TmTier {
+    Phrase,
+    Segment,
+}
+
+/// 条目引用（层 + 记忆库键）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmRecordRef {
+    pub tier: TmTier,
+    pub key: String,
+}
+
+/// 短语表/句段库中的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmRecord {
+    pub tier: TmTier,
+    /// 记忆库键（`TmKey` 字符串形式）
+    pub key: String,
+    pub source: String,
+    pub target: String,
+    pub source_lang: Option<String>,
+    pub target_lang: Option<String>,
+    pub context: Option<String>,
+    /// 最近修改时间（RFC 3339）
+    pub updated_at: String,
+    /// 复用命中次数
+    pub hits: u32,
+    /// 最近一次命中时间（RFC 3339）
+    pub last_used_at: Option<String>,
+    /// 审校状态（仅句段）
+    pub review_status: Option<ReviewStatus>,
+    /// 手动保存或导入的短语（不参与按容量淘汰）
+    pub pinned: bool,
+}
+
+impl TmRecord {
+    pub fn to_ref(&self) -> TmRecordRef {
+        TmRecordRef {
+            tier: self.tier,
+            key: self.key.clone(),
+        }
+    }
+}
+
+/// 检索的匹配范围
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum TmSearchField {
+    Source,
+    Target,
+    #[default]
+    Both,
+}
+
+/// 检索条件（所有条件同时满足）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmSearchQuery {
+    /// 子串或正则（`regex` 为 true 时）
+    pub text: Option<String>,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub field: TmSearchField,
+    pub source_lang: Option<String>,
+    pub target_lang: Option<String>,
+    pub tier: Option<TmTier>,
+    /// 修改时间下限（RFC 3339 或 `YYYY-MM-DD`，含当天）
+    pub updated_from: Option<String>,
+    /// 修改时间上限（RFC 3339 或 `YYYY-MM-DD`，含当天）
+    pub updated_to: Option<String>,
+    pub min_hits: Option<u32>,
+    pub max_hits: Option<u32>,
+    pub offset: usize,
+    /// 每页条数（默认 100，最多 1000）
+    pub limit: Option<usize>,
+}
+
+/// 一页检索结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmSearchPage {
+    /// 满足条件的总条数
+    pub total: usize,
+    pub offset: usize,
+    pub items: Vec<TmRecord>,
+}
+
+/// 批量替换：在 `scope` 筛出的条目的译文中把 `find` 替换为 `replace`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmReplaceRequest {
+    /// 限定范围（忽略分页）
+    pub scope: TmSearchQuery,
+    pub find: String,
+    /// 替换文本；正则模式下可用 `$1` 引用分组
+    pub replace: String,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub dry_run: bool,
+}
+
+/// 一条替换（预览）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmReplacement {
+    pub tier: TmTier,
+    pub key: String,
+    pub source: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmReplaceReport {
+    /// 译文发生变化的条数（`dry_run` 时为将要修改的条数）
+    pub changed: usize,
+    /// 前若干条替换的预览
+    pub preview: Vec<TmReplacement>,
+}
+
+/// 清理选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmCleanupOptions {
+    /// 删除译文与原文相同的条目（原文去掉占位符后不含字母的除外，如 `%s`、`{0}`）
+    pub same_as_source: bool,
+    /// 删除译文含未翻译占位标记的条目
+    pub placeholders: bool,
+    /// 追加的占位标记
+    pub extra_markers: Vec<String>,
+    /// 只清理该目标语言
+    pub target_lang: Option<String>,
+    pub dry_run: bool,
+}
+
+impl Default for TmCleanupOptions {
+    fn default() -> Self {
+        Self {
+            same_as_source: true,
+            placeholders: true,
+            extra_markers: Vec::new(),
+            target_lang: None,
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmCleanupReport {
+    /// 译文与原文相同的条数
+    pub same_as_source: usize,
+    /// 含占位标记的条数
+    pub placeholders: usize,
+    /// 前若干条被删除（`dry_run` 时为将要删除）的条目
+    pub removed: Vec<TmRecord>,
+}
+
+/// 重复组的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum TmDuplicateKind {
+    /// 译文相同，可只保留一条
+    Duplicate,
+    /// 译文不同，需要人工选择
+    Conflict,
+}
+
+/// 同一 (目标语言, 上下文, 原文) 的多条记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmDuplicateGroup {
+    pub source: String,
+    pub target_lang: Option<String>,
+    pub context: Option<String>,
+    pub kind: TmDuplicateKind,
+    pub records: Vec<TmRecord>,
+}
+
+/// 超出容量时的淘汰策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum TmEvictionPolicy {
+    /// 最久未使用的先淘汰（从未命中的按修改时间计）
+    Lru,
+    /// 命中次数最少的先淘汰，次数相同时最久未使用的先淘汰
+    #[default]
+    Lfu,
+}
+
+/// 记忆库容量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmCapacityConfig {
+    /// 每个记忆库最多保留的条目数（短语与句段合计），0 表示不限
+    pub max_entries: usize,
+    pub policy: TmEvictionPolicy,
+}
+
+/// 使用情况报告
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmUsageReport {
+    pub total: usize,
+    /// 至少命中过一次的条数
+    pub used: usize,
+    /// 命中次数合计
+    pub total_hits: usize,
+    /// 命中最多的条目（次数相同时最近使用的在前）
+    pub most_valuable: Vec<TmRecord>,
+    /// 从未命中的条目（最早修改的在前）
+    pub never_used: Vec<TmRecord>,
+}
+
+static CAPACITY: RwLock<Option<TmCapacityConfig>> = RwLock::new(None);
+
+/// 应用容量配置（随配置加载/更新同步）
+pub fn configure(config: &TmCapacityConfig) {
+    *CAPACITY.write() = Some(config.clone());
+}
+
+/// 当前容量配置
+pub fn capacity() -> TmCapacityConfig {
+    CAPACITY.read().clone().unwrap_or_default()
+}
+
+/// 分页检索
+pub fn search(store: &TmStore, query: &TmSearchQuery) -> Result<TmSearchPage> {
+    let filter = TmRecordFilter::from_query(query)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    Ok(TmSearchPage {
+        total: store.count_records(&filter)?,
+        offset: query.offset,
+        items: store.query_records(&filter, Some((query.offset, limit)))?,
+    })
+}
+
+/// 批量查找替换译文
+pub fn replace_targets(store: &TmStore, request: &TmReplaceRequest) -> Result<TmReplaceReport> {
+    if request.find.is_empty() {
+        return Err(anyhow!("查找内容不能为空"));
+    }
+    let pattern = build_regex(&request.find, request.regex, request.case_sensitive)?;
+    let filter = TmRecordFilter {
+        target_pattern: Some(pattern.as_str().to_string()),
+        ..TmRecordFilter::from_query(&request.scope)?
+    };
+
+    let mut report = TmReplaceReport::default();
+    let mut updates = Vec::new();
+    for record in store.query_records(&filter, None)? {
+        let after = if request.regex {
+            pattern.replace_all(&record.target, request.replace.as_str())
+        } else {
+            pattern.replace_all(&record.target, regex::NoExpand(&request.replace))
+        }
+        .into_owned();
+        // 替换后为空的译文不写入
+        if after == record.target || after.trim().is_empty() {
+            continue;
+        }
+        report.changed += 1;
+        if report.preview.len() < MAX_PREVIEW {
+            report.preview.push(TmReplacement {
+                tier: record.tier,
+                key: record.key.clone(),
+                source: record.source.clone(),
+                before: record.target.clone(),
+                after: after.clone(),
+            });
+        }
+        updates.push((record.to_ref(), after));
+    }
+
+    if !request.dry_run && !updates.is_empty() {
+        store.update_targets(&updates)?;
+        crate::app_log!(
+            "[TM维护] 批量替换 '{}' -> '{}': {} 条",
+            request.find,
+            request.replace,
+            report.changed
+        );
+    }
+    Ok(report)
+}
+
+/// 删除译文与原文相同或含未翻译占位标记的条目
+pub fn cleanup(store: &TmStore, options: &TmCleanupOptions) -> Result<TmCleanupReport> {
+    let markers: Vec<&str> = DEFAULT_PLACEHOLDER_MARKERS
+        .into_iter()
+        .chain(options.extra_markers.iter().map(String::as_str))
+        .filter(|marker| !marker.trim().is_empty())
+        .collect();
+    let filter = TmRecordFilter {
+        target_lang: normalized_lang(options.target_lang.as_deref()),
+        ..TmRecordFilter::default()
+    };
+
+    let mut report = TmCleanupReport::default();
+    let mut removed = Vec::new();
+    for record in store.query_records(&filter, None)? {
+        if options.same_as_source && is_same_as_source(&record) {
+            report.same_as_source += 1;
+        } else if options.placeholders
+            && markers
+                .iter()
+                .any(|marker| record.target.contains(marker) && !record.source.contains(marker))
+        {
+            report.placeholders += 1;
+        } else {
+            continue;
+        }
+        removed.push(record);
+    }
+
+    if !options.dry_run && !removed.is_empty() {
+        let refs: Vec<TmRecordRef> = removed.iter().map(TmRecord::to_ref).collect();
+        store.delete_records(&refs)?;
+        crate::app_log!(
+            "[TM维护] 清理：译文同原文 {} 条，含占位标记 {} 条",
+            report.same_as_source,
+            report.placeholders
+        );
+    }
+    removed.truncate(MAX_PREVIEW);
+    report.removed = removed;
+    Ok(report)
+}
+
+/// 查找重复与冲突（冲突在前），最多返回 `limit` 组
+pub fn find_duplicates(
+    store: &TmStore,
+    target_lang: Option<&str>,
+    limit: usize,
+) -> Result<Vec<TmDuplicateGroup>> {
+    let filter = TmRecordFilter {
+        target_lang: normalized_lang(target_lang),
+        ..TmRecordFilter::default()
+    };
+    let mut groups: IndexMap<(Option<String>, Option<String>, String), Vec<TmRecord>> =
+        IndexMap::new();
+    for record in store.duplicate_records(&filter)? {
+        let lang = record
+            .target_lang
+            .as_deref()
+            .map(|lang| normalize_lang_code(lang).to_string());
+        let key = (
+            lang,
+            record.context.clone(),
+            record.source.trim().to_string(),
+        );
+        groups.entry(key).or_default().push(record);
+    }
+
+    let mut result: Vec<TmDuplicateGroup> = groups
+        .into_iter()
+        .filter(|(_, records)| records.len() > 1)
+        .map(|((target_lang, context, source), records)| {
+            let first = records[0].target.trim();
+            let kind = if records.iter().all(|record| record.target.trim() == first) {
+                TmDuplicateKind::Duplicate
+            } else {
+                TmDuplicateKind::Conflict
+            };
+            TmDuplicateGroup {
+                source,
+                target_lang,
+                context,
+                kind,
+                records,
+            }
+        })
+        .collect();
+    result.sort_by_key(|group| group.kind != TmDuplicateKind::Conflict);
+    result.truncate(limit);
+    Ok(result)
+}
+
+/// 超出容量时按策略淘汰条目（已审校通过的句段与固定的短语不淘汰），返回淘汰的条数
+pub fn evict(store: &TmStore, config: &TmCapacityConfig) -> Result<usize> {
+    if config.max_entries == 0 {
+        return Ok(0);
+    }
+    let total = store.phrase_count()? + store.segment_stats()?.total;
+    if total <= config.max_entries {
+        return Ok(0);
+    }
+
+    let mut candidates: Vec<TmRecord> = store
+        .records()?
+        .into_iter()
+        .filter(|record| !record.pinned && record.review_status != Some(ReviewStatus::Approved))
+        .collect();
+    match config.policy {
+        TmEvictionPolicy::Lru => candidates.sort_by_cached_key(last_used),
+        TmEvictionPolicy::Lfu => {
+            candidates.sort_by_cached_key(|record| (record.hits, last_used(record)))
+        }
+    }
+    let refs: Vec<TmRecordRef> = candidates
+        .iter()
+        .take(total - config.max_entries)
+        .map(TmRecord::to_ref)
+        .collect();
+    let removed = store.delete_records(&refs)?;
+    crate::app_log!(
+        "[TM维护] 超出容量 {}（{:?}），淘汰 {} 条",
+        config.max_entries,
+        config.policy,
+        removed
+    );
+    Ok(removed)
+}
+
+/// 命中最多与从未命中的条目（各最多 `limit` 条）
+pub fn usage_report(store: &TmStore, limit: usize) -> Result<TmUsageReport> {
+    let records = store.records()?;
+    let total = records.len();
+    let total_hits = records.iter().map(|record| record.hits as usize).sum();
+    let (mut used, mut never_used): (Vec<TmRecord>, Vec<TmRecord>) =
+        records.into_iter().partition(|record| record.hits > 0);
+
+    used.sort_by_cached_key(|record| std::cmp::Reverse((record.hits, last_used(record))));
+    never_used.sort_by_cached_key(|record| timestamp(&record.updated_at));
+    let used_count = used.len();
+    used.truncate(limit);
+    never_used.truncate(limit);
+    Ok(TmUsageReport {
+        total,
+        used: used_count,
+        total_hits,
+        most_valuable: used,
+        never_used,
+    })
+}
+
+/// 最近使用时间（从未命中的取修改时间，新学到的条目不会立即被淘汰）
+fn last_used(record: &TmRecord) -> DateTime<Utc> {
+    timestamp(record.last_used_at.as_deref().unwrap_or(&record.updated_at))
+}
+
+/// 无法解析的时间视为最早
+fn timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// 记录过滤条件（由存储在 SQL 中执行；语言已标准化，正则已校验）
+#[derive(Debug, Clone, Default)]
+pub struct TmRecordFilter {
+    pub tier: Option<TmTier>,
+    pub source_lang: Option<String>,
+    pub target_lang: Option<String>,
+    pub updated_from: Option<DateTime<Utc>>,
+    pub updated_to: Option<DateTime<Utc>>,
+    pub min_hits: Option<u32>,
+    pub max_hits: Option<u32>,
+    /// 原文/译文须匹配的正则
+    pub text: Option<(String, TmSearchField)>,
+    /// 译文须匹配的正则（批量替换的查找内容）
+    pub target_pattern: Option<String>,
+}
+
+impl TmRecordFilter {
+    pub fn from_query(query: &TmSearchQuery) -> Result<Self> {
+        let text = query
+            .text
+            .as_deref()
+            .filter(|text| !text.is_empty())
+            .map(|text| build_regex(text, query.regex, query.case_sensitive))
+            .transpose()?
+            .map(|pattern| (pattern.as_str().to_string(), query.field));
+        Ok(Self {
+            tier: query.tier,
+            source_lang: normalized_lang(query.source_lang.as_deref()),
+            target_lang: normalized_lang(query.target_lang.as_deref()),
+            updated_from: query
+                .updated_from
+                .as_deref()
+                .map(|date| parse_date(date, false))
+                .transpose()?,
+            updated_to: query
+                .updated_to
+                .as_deref()
+                .map(|date| parse_date(date, true))
+                .transpose()?,
+            min_hits: query.min_hits,
+            max_hits: query.max_hits,
+            text,
+            target_pattern: None,
+        })
+    }
+}
+
+/// 子串按字面匹配；不区分大小写时两种模式都加 `(?i)`（写在源码中，`as_str()` 交给 SQL 时保持一致）
+fn build_regex(text: &str, regex: bool, case_sensitive: bool) -> Result<Regex> {
+    let pattern = if regex {
+        text.to_string()
+    } else {
+        regex::escape(text)
+    };
+    let pattern = if case_sensitive {
+        pattern
+    } else {
+        format!("(?i){}", pattern)
+    };
+    Regex::new(&pattern).map_err(|e| anyhow!("无效的正则表达式: {}", e))
+}
+
+/// `YYYY-MM-DD` 取当天开始（`end_of_day` 时取当天结束），否则按 RFC 3339 解析
+fn parse_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        return time
+            .map(|time| time.and_utc())
+            .ok_or_else(|| anyhow!("无效的日期: {}", value));
+    }
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| anyhow!("无效的日期: {}", value))
+}
+
+/// 去掉占位符后仍有字母才算“未翻译”（`%s`、`{0}`、`100%` 原样保留是正常的）
+fn is_same_as_source(record: &TmRecord) -> bool {
+    record.target.trim() == record.source.trim()
+        && PLACEHOLDER_REGEX
+            .replace_all(&record.source, "")
+            .chars()
+            .any(char::is_alphabetic)
+}
+
+fn normalized_lang(value: Option<&str>) -> Option<String> {
+    value
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| normalize_lang_code(value).to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::services::segment_memory::SegmentEntry;
+    use crate::services::translation_memory::TmKey;
+
+    fn store() -> TmStore {
+        let store = TmStore::open_in_memory().unwrap();
+        let key = |source: &str, lang: &str| TmKey::new(source, Some(lang)).encode();
+        store
+            .upsert_phrases([
+                (key("Open", "zh-Hans").as_str(), "打开"),
+                ("Open|zh-CN", "开启"),
+                (key("Save", "zh-Hans").as_str(), "保存"),
+                (key("Save", "ja").as_str(), "保存"),
+                (key("Inventory", "zh-Hans").as_str(), "Inventory"),
+                (key("%s", "zh-Hans").as_str(), "%s"),
+                (key("Quit", "zh-Hans").as_str(), "TODO"),
+            ])
+            .unwrap();
+        let segment = SegmentEntry::new(
+            "Save the current file.",
+            "保存当前文件。",
+            "zh-Hans",
+            ReviewStatus::Approved,
+        );
+        let duplicate = SegmentEntry::new("Save", "保存", "zh-Hans", ReviewStatus::Approved)
+            .with_source_lang(Some("en"));
+        store.upsert_segments([&segment, &duplicate]).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_search_filters_and_pages() {
+        let store = store();
+        let query = TmSearchQuery {
+            text: Some("save".to_string()),
+            target_lang: Some("zh-CN".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = search(&store, &query).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items[0].tier, TmTier::Phrase);
+        let second = search(
+            &store,
+            &TmSearchQuery {
+                offset: 2,
+                ..query.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!(second.total, 3);
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].tier, TmTier::Segment);
+
+        let source_lang = TmSearchQuery {
+            source_lang: Some("en-US".to_string()),
+            updated_to: Some("2999-12-31".to_string()),
+            ..Default::default()
+        };
+        let page = search(&store, &source_lang).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].source, "Save");
+        let past = TmSearchQuery {
+            updated_to: Some("2000-01-01".to_string()),
+            ..source_lang
+        };
+        assert_eq!(search(&store, &past).unwrap().total, 0);
+
+        let regex = TmSearchQuery {
+            text: Some("^保存.+。$".to_string()),
+            regex: true,
+            field: TmSearchField::Target,
+            tier: Some(TmTier::Segment),
+            updated_from: Some("2000-01-01".to_string()),
+            ..Default::default()
+        };
+        let page = search(&store, &regex).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].source, "Save the current file.");
+
+        let hits = TmSearchQuery {
+            min_hits: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(search(&store, &hits).unwrap().total, 0);
+        let open = TmKey::new("Open", Some("zh-Hans")).encode();
+        store.record_phrase_hits([(open.as_str(), 3)]).unwrap();
+        let page = search(&store, &hits).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].hits, 3);
+        assert!(page.items[0].last_used_at.is_some());
+
+        let invalid = TmSearchQuery {
+            text: Some("(".to_string()),
+            regex: true,
+            ..Default::default()
+        };
+        assert!(search(&store, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_replace_targets_with_preview() {
+        let store = store();
+        let request = TmReplaceRequest {
+            scope: TmSearchQuery {
+                target_lang: Some("zh-Hans".to_string()),
+                ..Default::default()
+            },
+            find: "保存".to_string(),
+            replace: "存储".to_string(),
+            dry_run: true,
+            ..Default::default()
+        };
+        let preview = replace_targets(&store, &request).unwrap();
+        assert_eq!(preview.changed, 3);
+        assert_eq!(
+            store
+                .phrase(&TmKey::new("Save", Some("zh-Hans")).encode())
+                .unwrap()
+                .as_deref(),
+            Some("保存")
+        );
+
+        let applied = replace_targets(
+            &store,
+            &TmReplaceRequest {
+                dry_run: false,
+                ..request
+            },
+        )
+        .unwrap();
+        assert_eq!(applied.changed, 3);
+        assert_eq!(
+            store
+                .phrase(&TmKey::new("Save", Some("zh-Hans")).encode())
+                .unwrap()
+                .as_deref(),
+            Some("存储")
+        );
+        // 范围外的语言不受影响
+        assert_eq!(
+            store
+                .phrase(&TmKey::new("Save", Some("ja")).encode())
+                .unwrap()
+                .as_deref(),
+            Some("保存")
+        );
+        let segment = TmKey::new("Save the current file.", Some("zh-Hans"));
+        assert_eq!(
+            store.segment(&segment).unwrap().unwrap().target,
+            "存储当前文件。"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_and_duplicates() {
+        let store = store();
+        let report = cleanup(&store, &TmCleanupOptions::default()).unwrap();
+        assert_eq!((report.same_as_source, report.placeholders), (1, 1));
+        let removed: Vec<&str> = report.removed.iter().map(|r| r.source.as_str()).collect();
+        assert_eq!(removed, vec!["Inventory", "Quit"]);
+        // 只有占位符的条目保留
+        assert!(
+            store
+                .phrase(&TmKey::new("%s", Some("zh-Hans")).encode())
+                .unwrap()
+                .is_some()
+        );
+
+        let groups = find_duplicates(&store, Some("zh-Hans"), 10).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].kind, TmDuplicateKind::Conflict);
+        assert_eq!(groups[0].source, "Open");
+        assert_eq!(groups[1].kind, TmDuplicateKind::Duplicate);
+        assert_eq!(groups[1].records.len(), 2);
+
+        // 删除句段中的重复条目
+        let segment = groups[1]
+            .records
+            .iter()
+            .find(|record| record.tier == TmTier::Segment)
+            .unwrap();
+        assert_eq!(store.delete_records(&[segment.to_ref()]).unwrap(), 1);
+        assert_eq!(
+            find_duplicates(&store, Some("zh-Hans"), 10).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_usage_report_and_eviction() {
+        let store = store();
+        let open = TmKey::new("Open", Some("zh-Hans")).encode();
+        let save = TmKey::new("Save", Some("zh-Hans")).encode();
+        store
+            .record_phrase_hits([(open.as_str(), 3), (save.as_str(), 1)])
+            .unwrap();
+
+        let report = usage_report(&store, 2).unwrap();
+        assert_eq!((report.total, report.used, report.total_hits), (9, 2, 4));
+        let valuable: Vec<u32> = report.most_valuable.iter().map(|r| r.hits).collect();
+        assert_eq!(valuable, vec![3, 1]);
+        assert_eq!(report.never_used.len(), 2);
+        assert!(report.never_used.iter().all(|record| record.hits == 0));
+
+        // 不限容量时不淘汰
+        assert_eq!(evict(&store, &TmCapacityConfig::default()).unwrap(), 0);
+
+        // LFU：淘汰从未命中的短语，命中过的与已审校句段保留
+        let lfu = TmCapacityConfig {
+            max_entries: 6,
+            policy: TmEvictionPolicy::Lfu,
+        };
+        assert_eq!(evict(&store, &lfu).unwrap(), 3);
+        assert_eq!(evict(&store, &lfu).unwrap(), 0);
+        let remaining = store.records().unwrap();
+        assert_eq!(remaining.len(), 6);
+        assert!(remaining.iter().any(|record| record.key == open));
+        assert!(remaining.iter().any(|record| record.key == save));
+
+        // LRU：只保留最近命中的短语（命中次数较少也一样）
+        let lru = TmCapacityConfig {
+            max_entries: 3,
+            policy: TmEvictionPolicy::Lru,
+        };
+        store.record_phrase_hits([(save.as_str(), 1)]).unwrap();
+        assert_eq!(evict(&store, &lru).unwrap(), 3);
+        let mut remaining: Vec<String> = store
+            .records()
+            .unwrap()
+            .into_iter()
+            .map(|record| record.source)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["Save", "Save", "Save the current file."]);
+        assert!(store.phrase(&open).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pinned_phrases_are_not_evicted() {
+        let store = TmStore::open_in_memory().unwrap();
+        store
+            .upsert_phrases([("Open|zh-Hans", "打开"), ("Quit|zh-Hans", "退出")])
+            .unwrap();
+        // 记忆库管理界面保存：只有新增/修改的条目标记为固定
+        let mut memory = IndexMap::new();
+        memory.insert("Open|zh-Hans".to_string(), "打开".to_string());
+        memory.insert("Quit|zh-Hans".to_string(), "离开".to_string());
+        store.replace_phrases(&memory).unwrap();
+        store
+            .upsert_pinned_phrases([("Help|zh-Hans", "帮助")])
+            .unwrap();
+        // 之后的自动学习不会取消固定标记
+        store.upsert_phrases([("Quit|zh-Hans", "退出")]).unwrap();
+        store.upsert_phrases([("Close|zh-Hans", "关闭")]).unwrap();
+
+        let pinned: Vec<String> = store
+            .records()
+            .unwrap()
+            .into_iter()
+            .filter(|record| record.pinned)
+            .map(|record| record.source)
+            .collect();
+        assert_eq!(pinned, vec!["Quit", "Help"]);
+
+        let lfu = TmCapacityConfig {
+            max_entries: 1,
+            policy: TmEvictionPolicy::Lfu,
+        };
+        assert_eq!(evict(&store, &lfu).unwrap(), 2);
+        assert!(store.phrase("Open|zh-Hans").unwrap().is_none());
+        assert!(store.phrase("Close|zh-Hans").unwrap().is_none());
+        assert_eq!(
+            store.phrase("Quit|zh-Hans").unwrap().as_deref(),
+            Some("退出")
+        );
+        assert!(store.phrase("Help|zh-Hans").unwrap().is_some());
+    }
+}
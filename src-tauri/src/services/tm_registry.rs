@@ -0,0 +1,264 @@
+/**
+ * 命名记忆库（按项目/客户分开保存）
+ *
+ * - `default` 为原有的全局记忆库 `translation_memory.db`（术语库也保存在其中）
+ * - 其余记忆库各自是 `memories/<名称>.db`，结构与默认记忆库相同（只使用短语表与句段库）
+ * - 翻译任务指定一个可写的项目记忆库与若干只读参考记忆库（按优先级排列），
+ *   学习结果只写入项目记忆库
+ */
+use crate::services::tm_store::{self, TmStore};
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 默认（全局）记忆库的名称
+pub const DEFAULT_MEMORY: &str = "default";
+
+/// 名称最大长度（字符）
+const MAX_NAME_LEN: usize = 64;
+
+/// 已打开的命名记忆库（按文件路径缓存，并发任务共用连接）
+static OPENED: Lazy<Mutex<HashMap<PathBuf, Arc<TmStore>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 记忆库概况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct MemoryInfo {
+    pub name: String,
+    pub phrases: usize,
+    pub segments: usize,
+}
+
+/// 已打开的一个记忆库
+#[derive(Debug, Clone)]
+pub struct OpenedMemory {
+    pub name: String,
+    pub store: Arc<TmStore>,
+}
+
+/// 翻译任务的记忆库组合：任务开始时打开一次，各 worker 与备用供应商的翻译器共用
+#[derive(Debug, Clone)]
+pub struct MemoryStack {
+    /// 可写的项目记忆库
+    pub project: OpenedMemory,
+    /// 只读参考记忆库（按优先级排列，已去重并排除项目记忆库）
+    pub references: Vec<OpenedMemory>,
+}
+
+impl MemoryStack {
+    /// 打开项目记忆库（未指定时为默认记忆库）与参考记忆库，任一不存在时报错
+    pub fn open(project: Option<&str>, references: &[String]) -> Result<Self> {
+        let project = resolve_name(project);
+        let mut stack = Self {
+            project: OpenedMemory {
+                name: project.to_string(),
+                store: open(project)?,
+            },
+            references: Vec::new(),
+        };
+        for name in references.iter().map(|name| name.trim()) {
+            if name.is_empty()
+                || name == stack.project.name
+                || stack
+                    .references
+                    .iter()
+                    .any(|reference| reference.name == name)
+            {
+                continue;
+            }
+            stack.references.push(OpenedMemory {
+                name: name.to_string(),
+                store: open(name)?,
+            });
+        }
+        Ok(stack)
+    }
+}
+
+/// 名称只允许字母、数字（含中文等）、`-` 与 `_`，用作文件名时不会穿越目录
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "无效的记忆库名称: '{}'（只允许字母、数字、- 与 _，最多 {} 个字符）",
+            name,
+            MAX_NAME_LEN
+        ))
+    }
+}
+
+/// 未指定或为空时使用默认记忆库
+pub fn resolve_name(name: Option<&str>) -> &str {
+    name.map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(DEFAULT_MEMORY)
+}
+
+fn memories_dir() -> PathBuf {
+    crate::utils::paths::get_memories_dir()
+}
+
+fn memory_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(dir.join(format!("{name}.db")))
+}
+
+/// 打开记忆库（默认记忆库为共用存储；命名记忆库必须已创建）
+pub fn open(name: &str) -> Result<Arc<TmStore>> {
+    if name == DEFAULT_MEMORY {
+        return tm_store::shared();
+    }
+    open_in(&memories_dir(), name)
+}
+
+fn open_in(dir: &Path, name: &str) -> Result<Arc<TmStore>> {
+    let path = memory_path(dir, name)?;
+    if !path.exists() {
+        return Err(anyhow!("记忆库不存在: {}", name));
+    }
+    open_path(path)
+}
+
+fn open_path(path: PathBuf) -> Result<Arc<TmStore>> {
+    let mut opened = OPENED.lock();
+    if let Some(store) = opened.get(&path) {
+        return Ok(Arc::clone(store));
+    }
+    let store = Arc::new(TmStore::open(&path)?);
+    opened.insert(path, Arc::clone(&store));
+    Ok(store)
+}
+
+/// 新建命名记忆库（已存在时报错）
+pub fn create(name: &str) -> Result<()> {
+    if name == DEFAULT_MEMORY {
+        return Err(anyhow!("记忆库已存在: {}", name));
+    }
+    create_in(&memories_dir(), name)
+}
+
+fn create_in(dir: &Path, name: &str) -> Result<()> {
+    let path = memory_path(dir, name)?;
+    if path.exists() {
+        return Err(anyhow!("记忆库已存在: {}", name));
+    }
+    open_path(path)?;
+    crate::app_log!("[记忆库] 新建: {}", name);
+    Ok(())
+}
+
+/// 删除命名记忆库（默认记忆库不可删除）
+pub fn delete(name: &str) -> Result<()> {
+    if name == DEFAULT_MEMORY {
+        return Err(anyhow!("默认记忆库不能删除"));
+    }
+    delete_in(&memories_dir(), name)
+}
+
+fn delete_in(dir: &Path, name: &str) -> Result<()> {
+    let path = memory_path(dir, name)?;
+    if !path.exists() {
+        return Err(anyhow!("记忆库不存在: {}", name));
+    }
+    OPENED.lock().remove(&path);
+    std::fs::remove_file(&path).map_err(|e| anyhow!("删除记忆库失败（可能正在使用）: {}", e))?;
+    // WAL 模式的附属文件
+    for suffix in ["-wal", "-shm"] {
+        let mut extra = path.clone().into_os_string();
+        extra.push(suffix);
+        let _ = std::fs::remove_file(extra);
+    }
+    crate::app_log!("[记忆库] 删除: {}", name);
+    Ok(())
+}
+
+/// 默认记忆库在前，其余按名称排序
+pub fn list() -> Result<Vec<MemoryInfo>> {
+    let store = tm_store::shared()?;
+    let mut memories = vec![info(DEFAULT_MEMORY, &store)?];
+    for name in names_in(&memories_dir())? {
+        let store = open(&name)?;
+        memories.push(info(&name, &store)?);
+    }
+    Ok(memories)
+}
+
+fn names_in(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .filter(|name| name != DEFAULT_MEMORY && validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+fn info(name: &str, store: &TmStore) -> Result<MemoryInfo> {
+    Ok(MemoryInfo {
+        name: name.to_string(),
+        phrases: store.phrase_count()?,
+        segments: store.segment_stats()?.total,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("client-a_2026").is_ok());
+        assert!(validate_name("客户甲").is_ok());
+        for name in ["", "../x", "a/b", "a.b", "a b", &"x".repeat(65)] {
+            assert!(validate_name(name).is_err(), "{name}");
+        }
+        assert_eq!(resolve_name(None), DEFAULT_MEMORY);
+        assert_eq!(resolve_name(Some(" ")), DEFAULT_MEMORY);
+        assert_eq!(resolve_name(Some("client-a")), "client-a");
+    }
+
+    #[test]
+    fn test_create_open_list_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(open_in(dir.path(), "client-a").is_err());
+
+        create_in(dir.path(), "client-a").unwrap();
+        create_in(dir.path(), "client-b").unwrap();
+        assert!(create_in(dir.path(), "client-a").is_err());
+
+        let store = open_in(dir.path(), "client-a").unwrap();
+        store.upsert_phrases([("Open|zh-Hans", "打开")]).unwrap();
+        // 同一记忆库共用连接
+        let again = open_in(dir.path(), "client-a").unwrap();
+        assert!(Arc::ptr_eq(&store, &again));
+        assert_eq!(info("client-a", &again).unwrap().phrases, 1);
+        assert_eq!(names_in(dir.path()).unwrap(), vec!["client-a", "client-b"]);
+
+        drop((store, again));
+        delete_in(dir.path(), "client-a").unwrap();
+        assert_eq!(names_in(dir.path()).unwrap(), vec!["client-b"]);
+        assert!(delete_in(dir.path(), "client-a").is_err());
+    }
+}
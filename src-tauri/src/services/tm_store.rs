@@ -0,0 +1,1340 @@
+/**
+ * 翻译记忆库 / 术语库的嵌入式存储（SQLite）
+ *
+ * - 短语表、句段库、术语库共用一个数据库文件 `data/translation_memory.db`（WAL 模式）
+ * - 按主键索引精确查询；学习结果按条增量写入（upsert），不再整文件重写，不再有固定的条目数上限（可配置容量，超出时按使用情况淘汰）
+ * - 翻译时按需查询（有界缓存），不整表加载；模糊匹配索引每个数据库只构建一份，由各翻译器共用，写入后失效
+ * - 批量写入在同一事务中完成；并发翻译任务共用一个连接，写入由互斥锁与事务串行化，外部进程访问时等待 busy_timeout
+ * - 首次打开时迁移旧的 `translation_memory.json` / `segment_memory.json` / `term_library.json`，原文件保留作为备份
+ */
+use crate::services::fuzzy_match::FuzzyIndex;
+use crate::services::segment_memory::{
+    ReviewStatus, SegmentEntry, SegmentMemory, SegmentMemoryStats,
+};
+use crate::services::term_library::{TermEntry, TermLibrary, TermLibraryMetadata};
+use crate::services::tm_maintenance::{
+    TmRecord, TmRecordFilter, TmRecordRef, TmSearchField, TmTier,
+};
+use crate::services::translation_memory::{
+    TmKey, TranslationMemory, get_builtin_memory, normalize_lang_code,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use regex::Regex;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 数据库结构版本（记录在 meta 表，结构变化时据此升级）
+///
+/// 2：句段表主键加入源语言与上下文
+/// 3：短语与句段记录命中次数与最近使用时间
+/// 4：句段记录备注
+/// 5：短语记录是否手动保存或导入（不参与按容量淘汰）
+const SCHEMA_VERSION: u32 = 5;
+
+/// 旧 JSON 数据已迁移的标记
+const META_LEGACY_MIGRATED: &str = "legacy_json_migrated";
+/// 术语库的风格总结与元数据（JSON）
+const META_TERM_LIBRARY: &str = "term_library";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS phrases (
+    key          TEXT PRIMARY KEY,
+    target       TEXT NOT NULL,
+    updated_at   TEXT NOT NULL,
+    hits         INTEGER NOT NULL DEFAULT 0,
+    last_used_at TEXT,
+    pinned       INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS terms (
+    position         INTEGER NOT NULL,
+    source           TEXT PRIMARY KEY,
+    user_translation TEXT NOT NULL,
+    ai_translation   TEXT NOT NULL,
+    context          TEXT,
+    frequency        INTEGER NOT NULL,
+    created_at       TEXT NOT NULL
+);
+";
+
+/// 句段表（源语言、上下文未知时存空字符串，保证主键唯一）
+const SEGMENTS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS segments (
+    source_lang   TEXT NOT NULL DEFAULT '',
+    target_lang   TEXT NOT NULL,
+    context       TEXT NOT NULL DEFAULT '',
+    source        TEXT NOT NULL,
+    target        TEXT NOT NULL,
+    file          TEXT,
+    provider      TEXT,
+    note          TEXT,
+    created_at    TEXT NOT NULL,
+    updated_at    TEXT NOT NULL,
+    review_status TEXT NOT NULL,
+    hits          INTEGER NOT NULL DEFAULT 0,
+    last_used_at  TEXT,
+    PRIMARY KEY (source_lang, target_lang, context, source)
+);
+CREATE INDEX IF NOT EXISTS idx_segments_file ON segments (file);
+";
+
+/// 版本 1 → 2：旧句段的上下文原样保留，源语言记为未知
+const MIGRATE_SEGMENTS_V1: &str = "
+ALTER TABLE segments RENAME TO segments_v1;
+DROP INDEX IF EXISTS idx_segments_file;
+";
+const COPY_SEGMENTS_V1: &str = "
+INSERT OR IGNORE INTO segments
+    (source_lang, target_lang, context, source, target, file, provider,
+     created_at, updated_at, review_status)
+SELECT '', target_lang, COALESCE(context, ''), source, target, file, provider,
+       created_at, updated_at, review_status
+FROM segments_v1 ORDER BY rowid;
+DROP TABLE segments_v1;
+";
+
+/// 术语库中除术语条目以外的部分
+#[derive(Serialize, Deserialize)]
+struct TermLibraryMeta {
+    style_summary: Option<crate::services::term_library::StyleSummary>,
+    metadata: TermLibraryMetadata,
+}
+
+/// 查询缓存的默认容量（条）
+pub const LOOKUP_CACHE_CAPACITY: usize = 4096;
+
+/// 记忆库 / 术语库存储
+#[derive(Debug)]
+pub struct TmStore {
+    conn: Mutex<Connection>,
+    /// 短语或句段的写入次数（查询缓存与模糊索引据此判断是否过期）
+    generation: AtomicU64,
+    indexes: Mutex<StoreIndexes>,
+}
+
+/// 共用的模糊匹配索引（按需构建，记录构建时的写入次数）
+#[derive(Debug, Default)]
+struct StoreIndexes {
+    phrases: Option<(u64, Arc<FuzzyIndex>)>,
+    segments: Option<(u64, Arc<SegmentIndex>)>,
+}
+
+/// 句段库的模糊匹配索引，附带已审校句段的键（自动套用只取已审校的句段）
+#[derive(Debug, Default)]
+pub struct SegmentIndex {
+    pub index: FuzzyIndex,
+    approved: HashSet<String>,
+}
+
+impl SegmentIndex {
+    pub fn is_approved(&self, key: &str) -> bool {
+        self.approved.contains(key)
+    }
+}
+
+/// 按需查询的有界缓存：超出容量时淘汰最久未用的条目，存储有写入后整体失效
+///
+/// 值为 `Option` 时同时缓存未命中，避免重复查询
+#[derive(Debug)]
+pub struct LookupCache<V> {
+    capacity: usize,
+    entries: Mutex<(u64, IndexMap<String, V>)>,
+}
+
+impl<V: Clone> LookupCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new((0, IndexMap::new())),
+        }
+    }
+
+    /// 先查缓存，未缓存时调用 `load` 从存储读取并缓存
+    pub fn get_or_load(
+        &self,
+        store: &TmStore,
+        key: &str,
+        load: impl FnOnce() -> Result<V>,
+    ) -> Result<V> {
+        let generation = store.generation();
+        {
+            let mut guard = self.entries.lock();
+            let (cached_generation, entries) = &mut *guard;
+            if *cached_generation != generation {
+                *cached_generation = generation;
+                entries.clear();
+            }
+            if let Some(index) = entries.get_index_of(key) {
+                let last = entries.len() - 1;
+                entries.move_index(index, last);
+                return Ok(entries[last].clone());
+            }
+        }
+        let value = load()?;
+        let mut guard = self.entries.lock();
+        let (cached_generation, entries) = &mut *guard;
+        if *cached_generation == generation && self.capacity > 0 {
+            if entries.len() >= self.capacity {
+                entries.shift_remove_index(0);
+            }
+            entries.insert(key.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().1.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<V: Clone> Default for LookupCache<V> {
+    fn default() -> Self {
+        Self::new(LOOKUP_CACHE_CAPACITY)
+    }
+}
+
+/// 复制时不复制缓存内容（各翻译器各自缓存）
+impl<V: Clone> Clone for LookupCache<V> {
+    fn clone(&self) -> Self {
+        Self::new(self.capacity)
+    }
+}
+
+impl TmStore {
+    /// 打开（不存在时创建）数据库文件
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("无法打开记忆库数据库: {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::init(conn)
+    }
+
+    /// 内存数据库（测试用）
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.busy_timeout(Duration::from_secs(5))?;
+        register_functions(&conn)?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        let version: Option<String> = tx
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let upgrade_v1 = version.as_deref() == Some("1");
+        if upgrade_v1 {
+            tx.execute_batch(MIGRATE_SEGMENTS_V1)?;
+        }
+        tx.execute_batch(SEGMENTS_SCHEMA)?;
+        if upgrade_v1 {
+            tx.execute_batch(COPY_SEGMENTS_V1)?;
+            crate::app_log!("[TM存储] 句段表已升级：主键加入源语言与上下文");
+        }
+        // 版本 3 之前创建的表补上使用统计列
+        for table in ["phrases", "segments"] {
+            add_column_if_missing(&tx, table, "hits", "INTEGER NOT NULL DEFAULT 0")?;
+            add_column_if_missing(&tx, table, "last_used_at", "TEXT")?;
+        }
+        add_column_if_missing(&tx, "segments", "note", "TEXT")?;
+        add_column_if_missing(&tx, "phrases", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES ('schema_version', ?1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![SCHEMA_VERSION.to_string()],
+        )?;
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            generation: AtomicU64::new(0),
+            indexes: Mutex::new(StoreIndexes::default()),
+        })
+    }
+
+    /// 短语或句段的写入次数（每次写入后递增）
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// 短语或句段发生变化：查询缓存与模糊索引随之失效
+    fn changed(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn meta(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    // ========== 短语表 ==========
+
+    /// 按键（`原文|语言`）精确查询
+    pub fn phrase(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row("SELECT target FROM phrases WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    pub fn phrase_count(&self) -> Result<usize> {
+        let conn = self.conn.lock();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM phrases", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// 短语表的模糊匹配索引（只读取键；同一数据库的翻译器共用，写入后重建）
+    pub fn phrase_index(&self) -> Result<Arc<FuzzyIndex>> {
+        let generation = self.generation();
+        if let Some((built, index)) = &self.indexes.lock().phrases {
+            if *built == generation {
+                return Ok(Arc::clone(index));
+            }
+        }
+        let keys: Vec<String> = {
+            let conn = self.conn.lock();
+            let mut stmt = conn.prepare("SELECT key FROM phrases ORDER BY rowid")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let index = Arc::new(FuzzyIndex::build(&keys));
+        self.indexes.lock().phrases = Some((generation, Arc::clone(&index)));
+        Ok(index)
+    }
+
+    /// 按写入顺序加载全部短语
+    pub fn load_phrases(&self) -> Result<IndexMap<String, String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT key, target FROM phrases ORDER BY rowid")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 在一个事务中写入（新增或更新）多条短语，返回写入条数
+    pub fn upsert_phrases<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let count = upsert_phrases_in(&tx, entries, false)?;
+        tx.commit()?;
+        self.changed();
+        Ok(count)
+    }
+
+    /// 写入手动保存或导入的短语并标记为固定（不参与按容量淘汰），返回写入条数
+    pub fn upsert_pinned_phrases<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let count = upsert_phrases_in(&tx, entries, true)?;
+        tx.commit()?;
+        self.changed();
+        Ok(count)
+    }
+
+    /// 用给定内容整体替换短语表（记忆库管理界面保存）
+    ///
+    /// 只删除不再存在的键、写入新增或译文有变化的条目（标记为固定）；
+    /// 未变的条目保留命中次数、最近使用、修改时间与固定标记
+    pub fn replace_phrases(&self, memory: &IndexMap<String, String>) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let existing: HashMap<String, String> = {
+            let mut stmt = tx.prepare("SELECT key, target FROM phrases")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        {
+            let mut delete = tx.prepare_cached("DELETE FROM phrases WHERE key = ?1")?;
+            for key in existing.keys().filter(|key| !memory.contains_key(*key)) {
+                delete.execute([key])?;
+            }
+        }
+        upsert_phrases_in(
+            &tx,
+            memory
+                .iter()
+                .filter(|(key, target)| existing.get(*key) != Some(*target))
+                .map(|(key, target)| (key.as_str(), target.as_str())),
+            true,
+        )?;
+        tx.commit()?;
+        self.changed();
+        Ok(())
+    }
+
+    // ========== 句段库 ==========
+
+    /// 按完整键精确查询一条句段
+    pub fn segment(&self, key: &TmKey) -> Result<Option<SegmentEntry>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                &format!(
+                    "{SEGMENT_COLUMNS} WHERE source_lang = ?1 AND target_lang = ?2
+                     AND context = ?3 AND source = ?4"
+                ),
+                params![
+                    key.source_lang.as_deref().unwrap_or_default(),
+                    key.target_lang.as_deref().unwrap_or_default(),
+                    key.context.as_deref().unwrap_or_default(),
+                    key.source,
+                ],
+                segment_from_row,
+            )
+            .optional()?)
+    }
+
+    /// 句段库的模糊匹配索引（只读取键与审校状态；同一数据库的翻译器共用，写入后重建）
+    pub fn segment_index(&self) -> Result<Arc<SegmentIndex>> {
+        let generation = self.generation();
+        if let Some((built, index)) = &self.indexes.lock().segments {
+            if *built == generation {
+                return Ok(Arc::clone(index));
+            }
+        }
+        let rows: Vec<(String, bool)> = {
+            let conn = self.conn.lock();
+            let mut stmt = conn.prepare(
+                "SELECT source_lang, target_lang, context, source, review_status
+                 FROM segments ORDER BY rowid",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                let text = |index: usize| -> rusqlite::Result<Option<String>> {
+                    Ok(Some(row.get::<_, String>(index)?).filter(|value| !value.is_empty()))
+                };
+                let key = TmKey {
+                    source_lang: text(0)?,
+                    target_lang: text(1)?,
+                    context: text(2)?,
+                    source: row.get(3)?,
+                };
+                let status: String = row.get(4)?;
+                Ok((
+                    key.encode(),
+                    ReviewStatus::parse(&status) == ReviewStatus::Approved,
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let index = Arc::new(SegmentIndex {
+            index: FuzzyIndex::build(rows.iter().map(|(key, _)| key)),
+            approved: rows
+                .iter()
+                .filter(|(_, approved)| *approved)
+                .map(|(key, _)| key.clone())
+                .collect(),
+        });
+        self.indexes.lock().segments = Some((generation, Arc::clone(&index)));
+        Ok(index)
+    }
+
+    /// 按写入顺序加载全部句段
+    pub fn load_segments(&self) -> Result<Vec<SegmentEntry>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("{SEGMENT_COLUMNS} ORDER BY rowid"))?;
+        let rows = stmt.query_map([], segment_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 在一个事务中写入（新增或更新）多条句段，返回写入条数
+    ///
+    /// 已审校的句段不会被未审校的译文覆盖（其他任务可能在本任务加载后确认了译文）
+    pub fn upsert_segments<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a SegmentEntry>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let count = upsert_segments_in(&tx, entries)?;
+        tx.commit()?;
+        self.changed();
+        Ok(count)
+    }
+
+    // ========== 使用统计 ==========
+
+    /// 累加短语的命中次数并更新最近使用时间（键不存在时忽略）
+    pub fn record_phrase_hits<'a>(
+        &self,
+        hits: impl IntoIterator<Item = (&'a str, u32)>,
+    ) -> Result<usize> {
+        let now = Utc::now().to_rfc3339();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE phrases SET hits = hits + ?2, last_used_at = ?3 WHERE key = ?1",
+            )?;
+            for (key, hits) in hits {
+                count += stmt.execute(params![key, hits, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// 累加句段的命中次数并更新最近使用时间（句段不存在时忽略）
+    pub fn record_segment_hits<'a>(
+        &self,
+        hits: impl IntoIterator<Item = (&'a TmKey, u32)>,
+    ) -> Result<usize> {
+        let now = Utc::now().to_rfc3339();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE segments SET hits = hits + ?5, last_used_at = ?6
+                 WHERE source_lang = ?1 AND target_lang = ?2 AND context = ?3 AND source = ?4",
+            )?;
+            for (key, hits) in hits {
+                count += stmt.execute(params![
+                    key.source_lang.as_deref().unwrap_or_default(),
+                    key.target_lang.as_deref().unwrap_or_default(),
+                    key.context.as_deref().unwrap_or_default(),
+                    key.source,
+                    hits,
+                    now,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    pub fn segment_stats(&self) -> Result<SegmentMemoryStats> {
+        let conn = self.conn.lock();
+        let (total, approved): (i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(review_status = 'approved'), 0) FROM segments",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(SegmentMemoryStats {
+            total: total as usize,
+            approved: approved as usize,
+            unreviewed: (total - approved) as usize,
+        })
+    }
+
+    // ========== 维护 ==========
+
+    /// 短语与句段的统一视图（先短语后句段，各自按写入顺序）
+    pub fn records(&self) -> Result<Vec<TmRecord>> {
+        self.query_records(&TmRecordFilter::default(), None)
+    }
+
+    /// 满足条件的记录（过滤与分页在 SQL 中完成）；`page` 为 (偏移, 条数)
+    pub fn query_records(
+        &self,
+        filter: &TmRecordFilter,
+        page: Option<(usize, usize)>,
+    ) -> Result<Vec<TmRecord>> {
+        let (condition, mut values) = filter_sql(filter);
+        let mut sql = format!(
+            "{RECORDS_VIEW} SELECT {RECORD_COLUMNS} FROM records WHERE {condition}
+             ORDER BY tier, seq"
+        );
+        if let Some((offset, limit)) = page {
+            sql.push_str(" LIMIT ? OFFSET ?");
+            values.push(Value::Integer(limit as i64));
+            values.push(Value::Integer(offset as i64));
+        }
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), record_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 满足条件的记录条数
+    pub fn count_records(&self, filter: &TmRecordFilter) -> Result<usize> {
+        let (condition, values) = filter_sql(filter);
+        let conn = self.conn.lock();
+        let count: i64 = conn.query_row(
+            &format!("{RECORDS_VIEW} SELECT COUNT(*) FROM records WHERE {condition}"),
+            params_from_iter(values),
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// 满足条件、且同一 (目标语言, 上下文, 原文) 有多条的记录（分组在 SQL 中完成）
+    pub fn duplicate_records(&self, filter: &TmRecordFilter) -> Result<Vec<TmRecord>> {
+        let (condition, values) = filter_sql(filter);
+        let sql = format!(
+            "{RECORDS_VIEW}, keyed AS (
+                SELECT *, COALESCE(tm_lang(target_lang), '') AS group_lang,
+                       COALESCE(context, '') AS group_context,
+                       trim(source, char(32, 9, 10, 13)) AS group_source
+                FROM records WHERE {condition}
+            )
+            SELECT {RECORD_COLUMNS} FROM keyed
+            WHERE (group_lang, group_context, group_source) IN (
+                SELECT group_lang, group_context, group_source FROM keyed
+                GROUP BY group_lang, group_context, group_source HAVING COUNT(*) > 1
+            )
+            ORDER BY tier, seq"
+        );
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), record_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 在一个事务中修改多条记录的译文，返回修改条数
+    pub fn update_targets(&self, updates: &[(TmRecordRef, String)]) -> Result<usize> {
+        let now = Utc::now().to_rfc3339();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut phrase = tx
+                .prepare_cached("UPDATE phrases SET target = ?2, updated_at = ?3 WHERE key = ?1")?;
+            let mut segment = tx.prepare_cached(
+                "UPDATE segments SET target = ?5, updated_at = ?6
+                 WHERE source_lang = ?1 AND target_lang = ?2 AND context = ?3 AND source = ?4",
+            )?;
+            for (record, target) in updates {
+                count += match record.tier {
+                    TmTier::Phrase => phrase.execute(params![record.key, target, now])?,
+                    TmTier::Segment => {
+                        let key = TmKey::parse(&record.key);
+                        segment.execute(params![
+                            key.source_lang.as_deref().unwrap_or_default(),
+                            key.target_lang.as_deref().unwrap_or_default(),
+                            key.context.as_deref().unwrap_or_default(),
+                            key.source,
+                            target,
+                            now,
+                        ])?
+                    }
+                };
+            }
+        }
+        tx.commit()?;
+        self.changed();
+        Ok(count)
+    }
+
+    /// 在一个事务中删除多条记录，返回删除条数
+    pub fn delete_records(&self, records: &[TmRecordRef]) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut phrase = tx.prepare_cached("DELETE FROM phrases WHERE key = ?1")?;
+            let mut segment = tx.prepare_cached(
+                "DELETE FROM segments
+                 WHERE source_lang = ?1 AND target_lang = ?2 AND context = ?3 AND source = ?4",
+            )?;
+            for record in records {
+                count += match record.tier {
+                    TmTier::Phrase => phrase.execute([&record.key])?,
+                    TmTier::Segment => {
+                        let key = TmKey::parse(&record.key);
+                        segment.execute(params![
+                            key.source_lang.as_deref().unwrap_or_default(),
+                            key.target_lang.as_deref().unwrap_or_default(),
+                            key.context.as_deref().unwrap_or_default(),
+                            key.source,
+                        ])?
+                    }
+                };
+            }
+        }
+        tx.commit()?;
+        self.changed();
+        Ok(count)
+    }
+
+    // ========== 术语库 ==========
+
+    pub fn load_term_library(&self) -> Result<TermLibrary> {
+        let meta = self.meta(META_TERM_LIBRARY)?;
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT source, user_translation, ai_translation, context, frequency, created_at
+             FROM terms ORDER BY position",
+        )?;
+        let terms = stmt
+            .query_map([], |row| {
+                Ok(TermEntry {
+                    source: row.get(0)?,
+                    user_translation: row.get(1)?,
+                    ai_translation: row.get(2)?,
+                    context: row.get(3)?,
+                    frequency: row.get(4)?,
+                    created_at: parse_time(&row.get::<_, String>(5)?),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut library = TermLibrary::new();
+        if let Some(meta) = meta {
+            let meta: TermLibraryMeta = serde_json::from_str(&meta)?;
+            library.style_summary = meta.style_summary;
+            library.metadata = meta.metadata;
+        }
+        library.terms = terms;
+        Ok(library)
+    }
+
+    /// 在一个事务中保存整个术语库（术语条目数量很少，整体替换即可）
+    pub fn save_term_library(&self, library: &TermLibrary) -> Result<()> {
+        let meta = serde_json::to_string(&TermLibraryMeta {
+            style_summary: library.style_summary.clone(),
+            metadata: library.metadata.clone(),
+        })?;
+
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM terms", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO terms
+                 (position, source, user_translation, ai_translation, context, frequency, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (position, term) in library.terms.iter().enumerate() {
+                stmt.execute(params![
+                    position as i64,
+                    term.source,
+                    term.user_translation,
+                    term.ai_translation,
+                    term.context,
+                    term.frequency,
+                    term.created_at.to_rfc3339(),
+                ])?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![META_TERM_LIBRARY, meta],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ========== 迁移 ==========
+
+    /// 首次打开时迁移旧的 JSON 数据（只执行一次；旧文件不删除）
+    ///
+    /// 没有旧记忆库文件时写入内置短语（与原来的首次使用行为一致）
+    pub fn migrate_legacy_json(
+        &self,
+        memory_path: &Path,
+        segments_path: &Path,
+        term_library_path: &Path,
+    ) -> Result<()> {
+        if self.meta(META_LEGACY_MIGRATED)?.is_some() {
+            return Ok(());
+        }
+
+        let phrases = if memory_path.exists() {
+            TranslationMemory::new_from_file(memory_path)?.memory
+        } else {
+            get_builtin_memory()
+        };
+        let segments = SegmentMemory::load_from_file(segments_path)?;
+        let library = term_library_path
+            .exists()
+            .then(|| TermLibrary::load_from_file(term_library_path))
+            .transpose()?;
+
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        upsert_phrases_in(
+            &tx,
+            phrases
+                .iter()
+                .map(|(key, target)| (key.as_str(), target.as_str())),
+            false,
+        )?;
+        upsert_segments_in(&tx, segments.iter())?;
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+            params![META_LEGACY_MIGRATED, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        drop(conn);
+        self.changed();
+
+        if let Some(library) = &library {
+            self.save_term_library(library)?;
+        }
+
+        crate::app_log!(
+            "[TM存储] 迁移完成：短语 {} 条，句段 {} 条，术语 {} 条",
+            phrases.len(),
+            segments.len(),
+            library.as_ref().map_or(0, |library| library.terms.len())
+        );
+        Ok(())
+    }
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{table}') WHERE name = ?1"),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+    }
+    Ok(())
+}
+
+/// 短语与句段的统一视图：短语的语言、上下文与原文由键解析，句段的空字符串列视为未知
+const RECORDS_VIEW: &str = "
+WITH records AS (
+    SELECT 0 AS tier, rowid AS seq, key, tm_key_part(key, 'source') AS source, target,
+           tm_key_part(key, 'source_lang') AS source_lang,
+           tm_key_part(key, 'target_lang') AS target_lang,
+           tm_key_part(key, 'context') AS context,
+           updated_at, hits, last_used_at, NULL AS review_status, pinned
+    FROM phrases
+    UNION ALL
+    SELECT 1, rowid, NULL, source, target, NULLIF(source_lang, ''), NULLIF(target_lang, ''),
+           NULLIF(context, ''), updated_at, hits, last_used_at, review_status, 0
+    FROM segments
+)";
+
+const RECORD_COLUMNS: &str = "tier, key, source, target, source_lang, target_lang, context,
+    updated_at, hits, last_used_at, review_status, pinned";
+
+fn record_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TmRecord> {
+    let key = TmKey {
+        source: row.get(2)?,
+        source_lang: row.get(4)?,
+        target_lang: row.get(5)?,
+        context: row.get(6)?,
+    };
+    let (tier, encoded) = match row.get::<_, i64>(0)? {
+        0 => (TmTier::Phrase, row.get(1)?),
+        // 键直接由存储的主键列组成（不再标准化），保证能原样定位回这一行
+        _ => (TmTier::Segment, key.encode()),
+    };
+    Ok(TmRecord {
+        tier,
+        key: encoded,
+        target: row.get(3)?,
+        source: key.source,
+        source_lang: key.source_lang,
+        target_lang: key.target_lang,
+        context: key.context,
+        updated_at: row.get(7)?,
+        hits: row.get(8)?,
+        last_used_at: row.get(9)?,
+        review_status: row
+            .get::<_, Option<String>>(10)?
+            .map(|status| ReviewStatus::parse(&status)),
+        pinned: row.get(11)?,
+    })
+}
+
+/// 过滤条件对应的 WHERE 子句与参数（针对 `RECORDS_VIEW`）
+fn filter_sql(filter: &TmRecordFilter) -> (String, Vec<Value>) {
+    let mut conditions = vec!["1"];
+    let mut values = Vec::new();
+    let mut push = |condition: &'static str, params: &[Value]| {
+        conditions.push(condition);
+        values.extend_from_slice(params);
+    };
+    if let Some(tier) = filter.tier {
+        let tier = match tier {
+            TmTier::Phrase => 0,
+            TmTier::Segment => 1,
+        };
+        push("tier = ?", &[Value::Integer(tier)]);
+    }
+    if let Some(lang) = &filter.source_lang {
+        push("tm_lang(source_lang) = ?", &[Value::Text(lang.clone())]);
+    }
+    if let Some(lang) = &filter.target_lang {
+        push("tm_lang(target_lang) = ?", &[Value::Text(lang.clone())]);
+    }
+    // 无法解析的修改时间为 NULL，设置了时间范围时不匹配
+    if let Some(from) = filter.updated_from {
+        push(
+            "tm_time(updated_at) >= ?",
+            &[Value::Integer(from.timestamp_micros())],
+        );
+    }
+    if let Some(to) = filter.updated_to {
+        push(
+            "tm_time(updated_at) <= ?",
+            &[Value::Integer(to.timestamp_micros())],
+        );
+    }
+    if let Some(min) = filter.min_hits {
+        push("hits >= ?", &[Value::Integer(min.into())]);
+    }
+    if let Some(max) = filter.max_hits {
+        push("hits <= ?", &[Value::Integer(max.into())]);
+    }
+    if let Some((pattern, field)) = &filter.text {
+        let pattern = Value::Text(pattern.clone());
+        match field {
+            TmSearchField::Source => push("source REGEXP ?", &[pattern]),
+            TmSearchField::Target => push("target REGEXP ?", &[pattern]),
+            TmSearchField::Both => push(
+                "(source REGEXP ? OR target REGEXP ?)",
+                &[pattern.clone(), pattern],
+            ),
+        }
+    }
+    if let Some(pattern) = &filter.target_pattern {
+        push("target REGEXP ?", &[Value::Text(pattern.clone())]);
+    }
+    (conditions.join(" AND "), values)
+}
+
+/// 维护查询用到的 SQL 函数：解析短语键、标准化语言代码、解析时间与正则匹配
+fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("tm_key_part", 2, flags, |ctx| {
+        let key = TmKey::parse(&ctx.get::<String>(0)?);
+        Ok(match ctx.get::<String>(1)?.as_str() {
+            "source" => Some(key.source),
+            "source_lang" => key.source_lang,
+            "target_lang" => key.target_lang,
+            "context" => key.context,
+            _ => None,
+        })
+    })?;
+    conn.create_scalar_function("tm_lang", 1, flags, |ctx| {
+        Ok(ctx
+            .get::<Option<String>>(0)?
+            .map(|lang| normalize_lang_code(&lang).to_string()))
+    })?;
+    conn.create_scalar_function("tm_time", 1, flags, |ctx| {
+        Ok(ctx.get::<Option<String>>(0)?.and_then(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|date| date.timestamp_micros())
+        }))
+    })?;
+    // `X REGEXP Y` 调用 regexp(Y, X)；同一语句内编译结果按参数缓存
+    conn.create_scalar_function("regexp", 2, flags, |ctx| {
+        let pattern: Arc<Regex> = ctx.get_or_create_aux(0, |value| {
+            Regex::new(value.as_str()?).map_err(|e| anyhow::anyhow!(e))
+        })?;
+        Ok(ctx
+            .get::<Option<String>>(1)?
+            .is_some_and(|text| pattern.is_match(&text)))
+    })?;
+    Ok(())
+}
+
+const SEGMENT_COLUMNS: &str = "SELECT source, target_lang, target, context, file, provider,
+    created_at, updated_at, review_status, source_lang, note FROM segments";
+
+fn segment_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SegmentEntry> {
+    let optional = |value: String| (!value.is_empty()).then_some(value);
+    Ok(SegmentEntry {
+        source: row.get(0)?,
+        target_lang: row.get(1)?,
+        target: row.get(2)?,
+        context: optional(row.get(3)?),
+        file: row.get(4)?,
+        provider: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        review_status: ReviewStatus::parse(&row.get::<_, String>(8)?),
+        source_lang: optional(row.get(9)?),
+        note: row.get(10)?,
+    })
+}
+
+/// 自动学习的写入（`pinned` 为 false）不会取消已有的固定标记
+fn upsert_phrases_in<'a>(
+    conn: &Connection,
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    pinned: bool,
+) -> Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO phrases (key, target, updated_at, pinned) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (key) DO UPDATE SET target = excluded.target, updated_at = excluded.updated_at,
+            pinned = MAX(phrases.pinned, excluded.pinned)",
+    )?;
+    let mut count = 0;
+    for (key, target) in entries {
+        stmt.execute(params![key, target, now, pinned])?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn upsert_segments_in<'a>(
+    conn: &Connection,
+    entries: impl IntoIterator<Item = &'a SegmentEntry>,
+) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO segments
+         (source, target_lang, target, context, file, provider, created_at, updated_at,
+          review_status, source_lang, note)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         ON CONFLICT (source_lang, target_lang, context, source) DO UPDATE SET
+            target = excluded.target, file = excluded.file,
+            provider = excluded.provider, updated_at = excluded.updated_at,
+            review_status = excluded.review_status,
+            note = COALESCE(excluded.note, segments.note)
+         WHERE segments.review_status != 'approved' OR excluded.review_status = 'approved'",
+    )?;
+    let mut count = 0;
+    for entry in entries {
+        count += stmt.execute(params![
+            entry.source,
+            entry.target_lang,
+            entry.target,
+            entry.context.as_deref().unwrap_or_default(),
+            entry.file,
+            entry.provider,
+            entry.created_at,
+            entry.updated_at,
+            entry.review_status.as_str(),
+            entry.source_lang.as_deref().unwrap_or_default(),
+            entry.note,
+        ])?;
+    }
+    Ok(count)
+}
+
+fn parse_time(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+/// 应用共用的存储（首次调用时打开数据库并迁移旧 JSON 数据；数据目录变化时重新打开）
+pub fn shared() -> Result<Arc<TmStore>> {
+    use crate::utils::paths;
+
+    static SHARED: Mutex<Option<(PathBuf, Arc<TmStore>)>> = Mutex::new(None);
+
+    let path = paths::get_tm_database_path();
+    let mut shared = SHARED.lock();
+    if let Some((opened, store)) = shared.as_ref()
+        && *opened == path
+    {
+        return Ok(Arc::clone(store));
+    }
+
+    let store = TmStore::open(&path)?;
+    store.migrate_legacy_json(
+        &paths::get_translation_memory_path(),
+        &paths::get_segment_memory_path(),
+        &paths::get_term_library_path(),
+    )?;
+    let store = Arc::new(store);
+    *shared = Some((path, Arc::clone(&store)));
+    Ok(store)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phrases_upsert_keeps_order_and_has_no_cap() {
+        let store = TmStore::open_in_memory().unwrap();
+        store
+            .upsert_phrases([("Open|zh-Hans", "打开"), ("Close|zh-Hans", "关闭")])
+            .unwrap();
+        store.upsert_phrases([("Open|zh-Hans", "开启")]).unwrap();
+
+        let phrases = store.load_phrases().unwrap();
+        assert_eq!(
+            phrases.keys().collect::<Vec<_>>(),
+            vec!["Open|zh-Hans", "Close|zh-Hans"]
+        );
+        assert_eq!(
+            store.phrase("Open|zh-Hans").unwrap().as_deref(),
+            Some("开启")
+        );
+        assert_eq!(store.phrase("Save|zh-Hans").unwrap(), None);
+
+        // 旧 JSON 实现的 10000 条上限已取消
+        let many: Vec<(String, String)> = (0..10_050)
+            .map(|i| (format!("Item {i}|zh-Hans"), format!("条目 {i}")))
+            .collect();
+        store
+            .upsert_phrases(many.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+            .unwrap();
+        assert_eq!(store.phrase_count().unwrap(), 10_052);
+
+        store.replace_phrases(&IndexMap::new()).unwrap();
+        assert_eq!(store.phrase_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_replace_phrases_keeps_usage_stats() {
+        let store = TmStore::open_in_memory().unwrap();
+        store
+            .upsert_phrases([
+                ("Open|zh-Hans", "打开"),
+                ("Close|zh-Hans", "关闭"),
+                ("Save|zh-Hans", "保存"),
+            ])
+            .unwrap();
+        store
+            .record_phrase_hits([("Open|zh-Hans", 3), ("Close|zh-Hans", 2)])
+            .unwrap();
+        let before = store.records().unwrap();
+
+        // 界面保存：Open 未变、Close 改译、Save 删除、新增 Quit
+        let memory: IndexMap<String, String> = [
+            ("Open|zh-Hans", "打开"),
+            ("Close|zh-Hans", "关掉"),
+            ("Quit|zh-Hans", "退出"),
+        ]
+        .into_iter()
+        .map(|(key, target)| (key.to_string(), target.to_string()))
+        .collect();
+        store.replace_phrases(&memory).unwrap();
+
+        let after = store.records().unwrap();
+        let find = |records: &[TmRecord], key: &str| {
+            records.iter().find(|record| record.key == key).cloned()
+        };
+        assert_eq!(find(&after, "Open|zh-Hans"), find(&before, "Open|zh-Hans"));
+        let close = find(&after, "Close|zh-Hans").unwrap();
+        assert_eq!((close.target.as_str(), close.hits), ("关掉", 2));
+        assert!(close.last_used_at.is_some());
+        assert!(find(&after, "Save|zh-Hans").is_none());
+        assert_eq!(find(&after, "Quit|zh-Hans").unwrap().hits, 0);
+    }
+
+    #[test]
+    fn test_lookup_cache_is_bounded_and_invalidated_by_writes() {
+        let store = TmStore::open_in_memory().unwrap();
+        store
+            .upsert_phrases([("Open|zh-Hans", "打开"), ("Close|zh-Hans", "关闭")])
+            .unwrap();
+        let cache: LookupCache<Option<String>> = LookupCache::new(2);
+        let load = |key: &str| {
+            cache
+                .get_or_load(&store, key, || store.phrase(key))
+                .unwrap()
+        };
+
+        assert_eq!(load("Open|zh-Hans").as_deref(), Some("打开"));
+        assert_eq!(load("Missing|zh-Hans"), None);
+        // 超出容量时淘汰最久未用的条目
+        assert_eq!(load("Close|zh-Hans").as_deref(), Some("关闭"));
+        assert_eq!(cache.len(), 2);
+
+        // 写入后缓存失效，读到新译文
+        store.upsert_phrases([("Open|zh-Hans", "开启")]).unwrap();
+        assert_eq!(load("Open|zh-Hans").as_deref(), Some("开启"));
+        assert_eq!(cache.len(), 1);
+
+        // 模糊索引在各次查询间共用，写入后重建
+        let index = store.phrase_index().unwrap();
+        assert!(Arc::ptr_eq(&index, &store.phrase_index().unwrap()));
+        store.upsert_phrases([("Quit|zh-Hans", "退出")]).unwrap();
+        assert_eq!(store.phrase_index().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_segments_and_term_library_round_trip() {
+        let store = TmStore::open_in_memory().unwrap();
+        let mut entry = SegmentEntry::new(
+            "The file could not be opened.",
+            "无法打开该文件。",
+            "zh-CN",
+            ReviewStatus::Unreviewed,
+        )
+        .with_file(Some("a.po".to_string()))
+        .with_note(Some("Shown in the error dialog".to_string()));
+        store.upsert_segments([&entry]).unwrap();
+        // 不带备注的更新保留原备注
+        entry.target = "无法打开此文件。".to_string();
+        entry.review_status = ReviewStatus::Approved;
+        store
+            .upsert_segments([&entry.clone().with_note(None)])
+            .unwrap();
+
+        let loaded = store
+            .segment(&TmKey::new(
+                "The file could not be opened.",
+                Some("zh-Hans"),
+            ))
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded, entry);
+        assert_eq!(store.load_segments().unwrap().len(), 1);
+        assert_eq!(store.segment_stats().unwrap().approved, 1);
+
+        let mut library = TermLibrary::new();
+        library
+            .add_term(
+                "Actor".to_string(),
+                "Actor".to_string(),
+                "演员".to_string(),
+                None,
+            )
+            .unwrap();
+        library.update_style_summary("保留引擎术语".to_string());
+        store.save_term_library(&library).unwrap();
+
+        let loaded = store.load_term_library().unwrap();
+        assert_eq!(loaded.terms.len(), 1);
+        assert_eq!(loaded.terms[0].ai_translation, "演员");
+        assert_eq!(loaded.metadata.total_terms, 1);
+        assert_eq!(
+            loaded.style_summary.map(|summary| summary.prompt),
+            Some("保留引擎术语".to_string())
+        );
+    }
+
+    #[test]
+    fn test_migrates_legacy_json_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory_path = dir.path().join("translation_memory.json");
+        let segments_path = dir.path().join("segment_memory.json");
+        let terms_path = dir.path().join("term_library.json");
+
+        let mut tm = TranslationMemory::new();
+        tm.memory.clear();
+        tm.add_translation("Open".to_string(), "打开".to_string(), Some("zh-Hans"));
+        tm.save_to_file(&memory_path).unwrap();
+        let mut segments = SegmentMemory::new();
+        segments.record(SegmentEntry::new(
+            "Save the current file.",
+            "保存当前文件。",
+            "zh-Hans",
+            ReviewStatus::Approved,
+        ));
+        segments.save_to_file(&segments_path).unwrap();
+
+        let store = TmStore::open(dir.path().join("translation_memory.db")).unwrap();
+        store
+            .migrate_legacy_json(&memory_path, &segments_path, &terms_path)
+            .unwrap();
+        assert_eq!(store.phrase_count().unwrap(), 1);
+        assert_eq!(store.load_segments().unwrap().len(), 1);
+        assert!(store.load_term_library().unwrap().terms.is_empty());
+        assert!(memory_path.exists());
+
+        // 迁移只执行一次：之后清空的短语表不会被旧文件覆盖
+        store.replace_phrases(&IndexMap::new()).unwrap();
+        store
+            .migrate_legacy_json(&memory_path, &segments_path, &terms_path)
+            .unwrap();
+        assert_eq!(store.phrase_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_upgrades_v1_segments_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translation_memory.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 INSERT INTO meta VALUES ('schema_version', '1');
+                 CREATE TABLE segments (
+                     source TEXT NOT NULL, target_lang TEXT NOT NULL, target TEXT NOT NULL,
+                     context TEXT, file TEXT, provider TEXT, created_at TEXT NOT NULL,
+                     updated_at TEXT NOT NULL, review_status TEXT NOT NULL,
+                     PRIMARY KEY (source, target_lang));
+                 INSERT INTO segments VALUES ('Open', 'zh-Hans', '打开', 'menu', NULL, NULL,
+                     '2026-10-01T00:00:00+00:00', '2026-10-01T00:00:00+00:00', 'approved');",
+            )
+            .unwrap();
+        }
+
+        let store = TmStore::open(&path).unwrap();
+        assert_eq!(
+            store.meta("schema_version").unwrap(),
+            Some(SCHEMA_VERSION.to_string())
+        );
+        let key = TmKey::new("Open", Some("zh-Hans")).with_context(Some("menu"));
+        let entry = store.segment(&key).unwrap().unwrap();
+        assert_eq!(entry.target, "打开");
+        assert_eq!(entry.source_lang, None);
+        assert!(entry.is_approved());
+
+        // 同一原文的其他上下文可以并存
+        let state = SegmentEntry::new("Open", "已打开", "zh-Hans", ReviewStatus::Unreviewed)
+            .with_context(Some("state".to_string()));
+        store.upsert_segments([&state]).unwrap();
+        assert_eq!(store.load_segments().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_upgrades_v2_tables_with_usage_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translation_memory.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 INSERT INTO meta VALUES ('schema_version', '2');
+                 CREATE TABLE phrases (key TEXT PRIMARY KEY, target TEXT NOT NULL,
+                     updated_at TEXT NOT NULL);
+                 INSERT INTO phrases VALUES ('Open|zh-Hans', '打开', '2026-10-01T00:00:00+00:00');",
+            )
+            .unwrap();
+        }
+
+        let store = TmStore::open(&path).unwrap();
+        assert_eq!(store.record_phrase_hits([("Open|zh-Hans", 2)]).unwrap(), 1);
+        assert_eq!(store.record_phrase_hits([("Save|zh-Hans", 1)]).unwrap(), 0);
+        let records = store.records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].hits, 2);
+        assert!(records[0].last_used_at.is_some());
+        assert_eq!(records[0].target_lang.as_deref(), Some("zh-Hans"));
+    }
+}
@@ -0,0 +1,529 @@
+/**
+ * TMX 1.4b 导入/导出
+ *
+ * - 导出：短语表中带语言标识的条目与句段库，句段的上下文、文件、供应商与审校状态写入 `prop`，备注写入 `note`
+ * - 导入：一个 `tu` 可包含多种语言；源语言取 `tu@srclang`，其次 `header@srclang`（`*all*` 时取第一个 `tuv`），
+ *   其余每个 `tuv` 生成一条译文
+ * - 读取 `prop`、`note` 与 `creationdate`/`changedate`；`seg` 中的内联标记（`bpt`/`ept`/`ph`/`it`/`ut`）只保留文本
+ * - 语言代码先按 BCP-47 规范大小写（`zh-hans` → `zh-Hans`，`en_us` → `en-US`），再按记忆库规则标准化
+ * - 不带上下文与备注的简单短语写入短语表，其余写入句段库；外部记忆视为已审校，源语言记入记忆库键
+ */
+use crate::services::segment_memory::{ReviewStatus, SegmentEntry, SegmentMemory};
+use crate::services::translation_memory::{TmKey, TranslationMemory, normalize_lang_code};
+use crate::utils::common::is_simple_phrase;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// TMX 日期格式（UTC）
+const TMX_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// 只包含原文格式代码、不含可译文本的内联元素
+const NATIVE_CODE_ELEMENTS: [&str; 5] = ["bpt", "ept", "ph", "it", "ut"];
+
+const PROP_CONTEXT: &str = "x-context";
+const PROP_FILE: &str = "x-file";
+const PROP_PROVIDER: &str = "x-provider";
+const PROP_REVIEW_STATUS: &str = "x-review-status";
+
+/// 从 TMX 中读取的一条译文对
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TmxUnit {
+    pub source: String,
+    pub source_lang: String,
+    pub target: String,
+    pub target_lang: String,
+    pub context: Option<String>,
+    pub file: Option<String>,
+    pub provider: Option<String>,
+    pub note: Option<String>,
+    /// 创建时间（RFC 3339）
+    pub created_at: Option<String>,
+    /// 明确标记为未审校时为 false（其余一律视为已审校）
+    pub approved: bool,
+}
+
+/// TMX 导入结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct TmxImportReport {
+    /// 读取到的译文对数量
+    pub pairs: usize,
+    /// 写入短语表的条数
+    pub phrases: usize,
+    /// 写入句段库的条数
+    pub segments: usize,
+    /// 因缺少原文/译文或语言而跳过的 `tu`/`tuv` 数量
+    pub skipped: usize,
+    /// 涉及的目标语言（标准化后）
+    pub languages: Vec<String>,
+}
+
+/// 按 BCP-47 规范大小写：语言小写、文字首字母大写、地区大写；`_` 视为 `-`
+pub fn normalize_bcp47(tag: &str) -> String {
+    tag.trim()
+        .split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| match part.len() {
+            _ if i == 0 => part.to_ascii_lowercase(),
+            4 if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let lower = part.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            2 | 3 => part.to_ascii_uppercase(),
+            _ => part.to_ascii_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// 记忆库使用的语言代码（BCP-47 规范化后再映射，如 `zh-cn` → `zh-Hans`）
+pub(crate) fn memory_lang(tag: &str) -> String {
+    normalize_lang_code(&normalize_bcp47(tag)).to_string()
+}
+
+/// 解析 TMX 文档为译文对
+pub fn parse_tmx(content: &str) -> Result<(Vec<TmxUnit>, usize)> {
+    let doc = roxmltree::Document::parse(content).map_err(|e| anyhow!("TMX 解析失败: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "tmx" {
+        return Err(anyhow!(
+            "不是 TMX 文件：根元素为 <{}>",
+            root.tag_name().name()
+        ));
+    }
+    let header_srclang = child(root, "header")
+        .and_then(|header| header.attribute("srclang"))
+        .filter(|lang| !lang.eq_ignore_ascii_case("*all*"));
+    let body = child(root, "body").ok_or_else(|| anyhow!("TMX 缺少 <body>"))?;
+
+    let mut units = Vec::new();
+    let mut skipped = 0;
+    for tu in body.children().filter(|n| n.has_tag_name("tu")) {
+        let variants: Vec<(String, String)> = tu
+            .children()
+            .filter(|n| n.has_tag_name("tuv"))
+            .filter_map(|tuv| {
+                let lang = tuv_lang(tuv)?;
+                let text = child(tuv, "seg").map(seg_text)?;
+                (!text.trim().is_empty()).then_some((lang, text))
+            })
+            .collect();
+
+        let srclang = tu
+            .attribute("srclang")
+            .filter(|lang| !lang.eq_ignore_ascii_case("*all*"))
+            .or(header_srclang)
+            .map(memory_lang);
+        let source_pos = match &srclang {
+            Some(lang) => variants.iter().position(|(l, _)| memory_lang(l) == *lang),
+            None => (!variants.is_empty()).then_some(0),
+        };
+        let Some(source_pos) = source_pos else {
+            skipped += 1;
+            continue;
+        };
+        let (source_lang, source) = &variants[source_pos];
+        let source_lang = memory_lang(source_lang);
+
+        let props = props(tu);
+        let prop = |name: &str| {
+            props
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+        let note = tu
+            .children()
+            .filter(|n| n.has_tag_name("note"))
+            .filter_map(|n| n.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let created_at = tu
+            .attribute("creationdate")
+            .or_else(|| tu.attribute("changedate"))
+            .and_then(parse_tmx_date);
+        let approved = prop(PROP_REVIEW_STATUS).is_none_or(|status| status != "unreviewed");
+
+        for (i, (lang, target)) in variants.iter().enumerate() {
+            let target_lang = memory_lang(lang);
+            if i == source_pos || target_lang == source_lang {
+                continue;
+            }
+            units.push(TmxUnit {
+                source: source.clone(),
+                source_lang: source_lang.clone(),
+                target: target.clone(),
+                target_lang,
+                context: prop(PROP_CONTEXT).or_else(|| prop("context")),
+                file: prop(PROP_FILE),
+                provider: prop(PROP_PROVIDER),
+                note: (!note.is_empty()).then(|| note.clone()),
+                created_at: created_at.clone(),
+                approved,
+            });
+        }
+        if variants.len() < 2 {
+            skipped += 1;
+        }
+    }
+    Ok((units, skipped))
+}
+
+/// 导入 TMX：简单短语写入短语表，其余写入句段库
+pub fn import_tmx(
+    content: &str,
+    tm: &mut TranslationMemory,
+    segments: &mut SegmentMemory,
+) -> Result<TmxImportReport> {
+    let (units, skipped) = parse_tmx(content)?;
+    let mut report = TmxImportReport {
+        pairs: units.len(),
+        skipped,
+        ..Default::default()
+    };
+    let mut languages = BTreeSet::new();
+
+    for unit in units {
+        languages.insert(unit.target_lang.clone());
+        // 带备注的单元写入句段库（短语表不记录备注）
+        if unit.context.is_none()
+            && unit.note.is_none()
+            && is_simple_phrase(&unit.source)
+            && unit.target.len() <= 50
+        {
+            let key = TmKey::new(&unit.source, Some(&unit.target_lang))
+                .with_source_lang(Some(&unit.source_lang));
+            tm.insert(&key, unit.target);
+            report.phrases += 1;
+            continue;
+        }
+
+        let status = if unit.approved {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::Unreviewed
+        };
+        let mut entry = SegmentEntry::new(&unit.source, &unit.target, &unit.target_lang, status)
+            .with_source_lang(Some(&unit.source_lang))
+            .with_context(unit.context)
+            .with_file(unit.file)
+            .with_provider(unit.provider)
+            .with_note(unit.note);
+        if let Some(created_at) = unit.created_at {
+            entry.created_at = created_at;
+        }
+        segments.record(entry);
+        report.segments += 1;
+    }
+
+    report.languages = languages.into_iter().collect();
+    crate::app_log!(
+        "[TMX] 导入 {} 对译文：短语 {} 条，句段 {} 条，跳过 {} 个",
+        report.pairs,
+        report.phrases,
+        report.segments,
+        report.skipped
+    );
+    Ok(report)
+}
+
+/// 导出为 TMX 1.4b，返回 (文档, 译文单元数)
+///
+/// 条目记录了源语言时使用条目的源语言，否则使用 `source_lang`；不带语言标识的旧条目不导出
+pub fn export_tmx(
+    tm: &TranslationMemory,
+    segments: Option<&SegmentMemory>,
+    source_lang: &str,
+) -> (String, usize) {
+    let source_lang = normalize_bcp47(source_lang);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<tmx version=\"1.4\">\n");
+    xml.push_str(&format!(
+        "  <header creationtool=\"PO Translator\" creationtoolversion=\"{}\" \
+         segtype=\"sentence\" o-tmf=\"po-translator-json\" adminlang=\"en-US\" \
+         srclang=\"{}\" datatype=\"plaintext\" creationdate=\"{}\"/>\n",
+        env!("CARGO_PKG_VERSION"),
+        escape(&source_lang),
+        Utc::now().format(TMX_DATE_FORMAT)
+    ));
+    xml.push_str("  <body>\n");
+
+    let mut count = 0;
+    for (key, target) in &tm.memory {
+        let key = TmKey::parse(key);
+        let Some(lang) = key.target_lang.as_deref() else {
+            continue;
+        };
+        if !looks_like_lang(lang) || key.source.is_empty() {
+            continue;
+        }
+        let entry_source_lang = key
+            .source_lang
+            .as_deref()
+            .map_or_else(|| source_lang.clone(), normalize_bcp47);
+        let props = [(PROP_CONTEXT, key.context.as_deref())];
+        write_tu(
+            &mut xml,
+            (&entry_source_lang, &key.source),
+            (lang, target),
+            None,
+            &props,
+            None,
+        );
+        count += 1;
+    }
+
+    for entry in segments.into_iter().flat_map(SegmentMemory::iter) {
+        let props = [
+            (PROP_CONTEXT, entry.context.as_deref()),
+            (PROP_FILE, entry.file.as_deref()),
+            (PROP_PROVIDER, entry.provider.as_deref()),
+            (PROP_REVIEW_STATUS, Some(entry.review_status.as_str())),
+        ];
+        let entry_source_lang = entry
+            .source_lang
+            .as_deref()
+            .map_or_else(|| source_lang.clone(), normalize_bcp47);
+        write_tu(
+            &mut xml,
+            (&entry_source_lang, &entry.source),
+            (&entry.target_lang, &entry.target),
+            Some(&entry.created_at),
+            &props,
+            entry.note.as_deref(),
+        );
+        count += 1;
+    }
+
+    xml.push_str("  </body>\n</tmx>\n");
+    (xml, count)
+}
+
+/// 写入一个译文单元，`source`/`target` 为 (语言, 文本)
+fn write_tu(
+    xml: &mut String,
+    source: (&str, &str),
+    target: (&str, &str),
+    created_at: Option<&str>,
+    props: &[(&str, Option<&str>)],
+    note: Option<&str>,
+) {
+    let date = created_at
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| {
+            format!(
+                " creationdate=\"{}\"",
+                date.with_timezone(&Utc).format(TMX_DATE_FORMAT)
+            )
+        })
+        .unwrap_or_default();
+    xml.push_str(&format!("    <tu{}>\n", date));
+    if let Some(note) = note {
+        xml.push_str(&format!("      <note>{}</note>\n", escape(note)));
+    }
+    for (name, value) in props {
+        if let Some(value) = value {
+            xml.push_str(&format!(
+                "      <prop type=\"{}\">{}</prop>\n",
+                name,
+                escape(value)
+            ));
+        }
+    }
+    for (lang, text) in [source, target] {
+        xml.push_str(&format!(
+            "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+            escape(lang),
+            escape(text)
+        ));
+    }
+    xml.push_str("    </tu>\n");
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// `tuv` 的语言：`xml:lang`（1.4）或 `lang`（1.1 兼容）
+fn tuv_lang(tuv: roxmltree::Node) -> Option<String> {
+    tuv.attribute(("http://www.w3.org/XML/1998/namespace", "lang"))
+        .or_else(|| tuv.attribute("lang"))
+        .filter(|lang| !lang.trim().is_empty())
+        .map(str::to_string)
+}
+
+/// `seg` 的文本：跳过只含格式代码的内联元素，保留 `hi`/`sub` 等元素中的文本
+fn seg_text(seg: roxmltree::Node) -> String {
+    let mut text = String::new();
+    for node in seg.children() {
+        if node.is_text() {
+            text.push_str(node.text().unwrap_or_default());
+        } else if node.is_element() && !NATIVE_CODE_ELEMENTS.contains(&node.tag_name().name()) {
+            text.push_str(&seg_text(node));
+        }
+    }
+    text
+}
+
+fn props(tu: roxmltree::Node) -> Vec<(String, String)> {
+    tu.children()
+        .filter(|n| n.has_tag_name("prop"))
+        .filter_map(|n| {
+            let kind = n.attribute("type")?;
+            let value = n.text()?.trim();
+            (!value.is_empty()).then(|| (kind.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn parse_tmx_date(date: &str) -> Option<String> {
+    NaiveDateTime::parse_from_str(date, TMX_DATE_FORMAT)
+        .ok()
+        .map(|date| date.and_utc().to_rfc3339())
+}
+
+/// 短语表键的后缀是否为语言代码（旧格式的键可能以普通文本结尾）
+pub(crate) fn looks_like_lang(lang: &str) -> bool {
+    let mut parts = lang.split('-');
+    let primary = parts.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_lowercase())
+        && parts.all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// XML 转义（并去除 XML 1.0 不允许的控制字符）
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header creationtool="Trados" creationtoolversion="1" segtype="sentence" o-tmf="x"
+          adminlang="en-us" srclang="EN-US" datatype="plaintext"/>
+  <body>
+    <tu creationdate="20240102T030405Z">
+      <prop type="x-context">toolbar</prop>
+      <note>Shown on hover</note>
+      <tuv xml:lang="en-us"><seg>Save <bpt i="1">&lt;b&gt;</bpt>all<ept i="1">&lt;/b&gt;</ept> open documents &amp; close.</seg></tuv>
+      <tuv xml:lang="zh-cn"><seg>保存所有打开的文档并关闭。</seg></tuv>
+      <tuv xml:lang="DE_de"><seg>Alle offenen Dokumente speichern &amp; schließen.</seg></tuv>
+    </tu>
+    <tu>
+      <tuv xml:lang="en-US"><seg>Open</seg></tuv>
+      <tuv xml:lang="ja"><seg>開く</seg></tuv>
+    </tu>
+    <tu>
+      <tuv xml:lang="en-US"><seg>Orphan without translation</seg></tuv>
+    </tu>
+  </body>
+</tmx>"#;
+
+    #[test]
+    fn test_normalize_bcp47() {
+        assert_eq!(normalize_bcp47("zh-hans"), "zh-Hans");
+        assert_eq!(normalize_bcp47("EN_us"), "en-US");
+        assert_eq!(normalize_bcp47("es-419"), "es-419");
+        assert_eq!(normalize_bcp47("sr-latn-rs"), "sr-Latn-RS");
+        assert_eq!(memory_lang("zh-cn"), "zh-Hans");
+    }
+
+    #[test]
+    fn test_parse_multilingual_tu() {
+        let (units, skipped) = parse_tmx(SAMPLE).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(units.len(), 3);
+
+        let zh = &units[0];
+        assert_eq!(zh.source, "Save all open documents & close.");
+        assert_eq!(zh.source_lang, "en");
+        assert_eq!(zh.target_lang, "zh-Hans");
+        assert_eq!(zh.context.as_deref(), Some("toolbar"));
+        assert_eq!(zh.note.as_deref(), Some("Shown on hover"));
+        assert_eq!(zh.created_at.as_deref(), Some("2024-01-02T03:04:05+00:00"));
+        assert_eq!(units[1].target_lang, "de-DE");
+        assert_eq!(units[2].target, "開く");
+    }
+
+    #[test]
+    fn test_import_then_export_round_trip() {
+        let mut tm = TranslationMemory::new();
+        tm.memory.clear();
+        let mut segments = SegmentMemory::new();
+        let report = import_tmx(SAMPLE, &mut tm, &mut segments).unwrap();
+        assert_eq!(report.pairs, 3);
+        assert_eq!(report.phrases, 1);
+        assert_eq!(report.segments, 2);
+        assert_eq!(report.languages, vec!["de-DE", "ja", "zh-Hans"]);
+        // 导入的条目记录了源语言
+        let open = TmKey::new("Open", Some("ja")).with_source_lang(Some("en"));
+        assert_eq!(
+            tm.lookup(&open, None).map(|hit| hit.target),
+            Some("開く".to_string())
+        );
+        let key = TmKey::new("Save all open documents & close.", Some("zh-CN"))
+            .with_source_lang(Some("en"))
+            .with_context(Some("toolbar"));
+        let entry = segments.get(&key).unwrap();
+        assert!(entry.is_approved());
+        assert_eq!(entry.context.as_deref(), Some("toolbar"));
+        assert_eq!(entry.note.as_deref(), Some("Shown on hover"));
+
+        let (xml, count) = export_tmx(&tm, Some(&segments), "en-us");
+        assert_eq!(count, 3);
+        assert!(xml.contains("srclang=\"en-US\""));
+        assert!(xml.contains("<seg>Save all open documents &amp; close.</seg>"));
+        assert!(xml.contains("creationdate=\"20240102T030405Z\""));
+        assert!(xml.contains("<note>Shown on hover</note>"));
+
+        let mut reimported_tm = TranslationMemory::new();
+        reimported_tm.memory.clear();
+        let mut reimported_segments = SegmentMemory::new();
+        let again = import_tmx(&xml, &mut reimported_tm, &mut reimported_segments).unwrap();
+        assert_eq!((again.phrases, again.segments), (1, 2));
+        let reimported = reimported_segments.get(&key).unwrap();
+        assert_eq!(reimported.target, entry.target);
+        assert_eq!(reimported.context, entry.context);
+        assert_eq!(reimported.created_at, entry.created_at);
+        assert_eq!(reimported.note, entry.note);
+        assert!(reimported.is_approved());
+    }
+
+    #[test]
+    fn test_rejects_non_tmx() {
+        assert!(parse_tmx("<xliff/>").is_err());
+        assert!(parse_tmx("not xml").is_err());
+    }
+}
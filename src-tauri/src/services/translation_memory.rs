@@ -1,15 +1,177 @@
+use crate::services::fuzzy_match::{FuzzyIndex, FuzzyMatch};
+use crate::services::tm_store::{LookupCache, TmStore};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use indexmap::IndexMap;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+
+/// 上下文与原文之间的分隔符（与 gettext MO 文件中 msgctxt 的分隔符相同）
+pub const CONTEXT_SEPARATOR: char = '\u{4}';
+
+/// 上下文一致的完全匹配（101%）
+pub const IN_CONTEXT_SCORE: u8 = 101;
+
+/// 查询带上下文、只找到无上下文条目时的扣分
+pub const CONTEXT_FALLBACK_PENALTY: u8 = 3;
+
+/// 记忆库键：(源语言, 目标语言, 上下文, 原文)
+///
+/// 字符串形式为 `[上下文\u{4}]原文|[源语言>]目标语言`。
+/// 旧格式 `原文|目标语言` 与不带语言的 `原文` 解析为无源语言、无上下文的条目，无需迁移
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TmKey {
+    pub source_lang: Option<String>,
+    pub target_lang: Option<String>,
+    pub context: Option<String>,
+    pub source: String,
+}
+
+impl TmKey {
+    /// 无上下文、未知源语言的键（语言代码按记忆库规则标准化）
+    pub fn new(source: &str, target_lang: Option<&str>) -> Self {
+        Self {
+            source: source.to_string(),
+            target_lang: non_empty(target_lang).map(|lang| normalize_lang_code(lang).to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_context(mut self, context: Option<&str>) -> Self {
+        self.context = non_empty(context).map(str::to_string);
+        self
+    }
+
+    pub fn with_source_lang(mut self, source_lang: Option<&str>) -> Self {
+        self.source_lang = non_empty(source_lang).map(|lang| normalize_lang_code(lang).to_string());
+        self
+    }
+
+    pub fn encode(&self) -> String {
+        let mut key = String::new();
+        if let Some(context) = &self.context {
+            key.push_str(context);
+            key.push(CONTEXT_SEPARATOR);
+        }
+        key.push_str(&self.source);
+        if let Some(target_lang) = &self.target_lang {
+            key.push('|');
+            if let Some(source_lang) = &self.source_lang {
+                key.push_str(source_lang);
+                key.push('>');
+            }
+            key.push_str(target_lang);
+        }
+        key
+    }
+
+    pub fn parse(key: &str) -> Self {
+        let (body, langs) = match key.rsplit_once('|') {
+            Some((body, langs)) => (body, Some(langs)),
+            None => (key, None),
+        };
+        let (context, source) = match body.split_once(CONTEXT_SEPARATOR) {
+            Some((context, source)) => (Some(context), source),
+            None => (None, body),
+        };
+        let (source_lang, target_lang) = match langs.map(|langs| langs.split_once('>')) {
+            Some(Some((source_lang, target_lang))) => (Some(source_lang), Some(target_lang)),
+            Some(None) => (None, langs),
+            None => (None, None),
+        };
+        Self {
+            source_lang: source_lang.map(str::to_string),
+            target_lang: target_lang.map(str::to_string),
+            context: context.map(str::to_string),
+            source: source.to_string(),
+        }
+    }
+
+    /// 从键字符串中取出目标语言（不分配）
+    pub fn target_lang_of(key: &str) -> Option<&str> {
+        let (_, langs) = key.rsplit_once('|')?;
+        langs.rsplit('>').next()
+    }
+
+    /// 精确查询时依次尝试的键与匹配度
+    ///
+    /// 上下文一致（101%）优先，其次无上下文条目（带上下文查询时扣分）；
+    /// 每一级先找同源语言的条目，再找未记录源语言的旧条目；目标语言同时尝试标准化前后的写法，
+    /// 最后是不带语言标识的旧条目
+    pub fn lookup_candidates(&self, raw_target_lang: Option<&str>) -> Vec<(String, u8)> {
+        let contexts: Vec<(Option<&str>, u8)> = match self.context.as_deref() {
+            Some(context) => vec![
+                (Some(context), IN_CONTEXT_SCORE),
+                (None, 100 - CONTEXT_FALLBACK_PENALTY),
+            ],
+            None => vec![(None, 100)],
+        };
+        let source_langs: Vec<Option<&str>> = match self.source_lang.as_deref() {
+            Some(lang) => vec![Some(lang), None],
+            None => vec![None],
+        };
+        let mut target_langs: Vec<Option<&str>> = vec![self.target_lang.as_deref()];
+        if let Some(raw) = non_empty(raw_target_lang) {
+            if Some(raw) != self.target_lang.as_deref() {
+                target_langs.push(Some(raw));
+            }
+        }
+        if self.target_lang.is_some() {
+            target_langs.push(None);
+        }
+
+        let mut candidates = Vec::new();
+        for &(context, score) in &contexts {
+            for &target_lang in &target_langs {
+                for &source_lang in &source_langs {
+                    // 不带语言标识的旧条目没有源语言
+                    if target_lang.is_none() && source_lang.is_some() {
+                        continue;
+                    }
+                    let key = TmKey {
+                        source_lang: source_lang.map(str::to_string),
+                        target_lang: target_lang.map(str::to_string),
+                        context: context.map(str::to_string),
+                        source: self.source.clone(),
+                    };
+                    candidates.push((key.encode(), score));
+                }
+            }
+        }
+        candidates
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.filter(|value| !value.is_empty())
+}
+
+/// 精确查询结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmHit {
+    pub target: String,
+    /// 101 = 上下文一致，100 = 完全匹配，低于 100 = 退回到无上下文条目
+    pub score: u8,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TranslationMemory {
-    pub memory: IndexMap<String, String>, // 使用 IndexMap 保持插入顺序
+    pub memory: IndexMap<String, String>, // 使用 IndexMap 保持插入顺序（修改请用 insert/remove，模糊索引随之失效）
     pub stats: MemoryStats,
     pub last_updated: DateTime<Utc>,
+    #[serde(skip)]
+    fuzzy_index: OnceCell<FuzzyIndex>, // 模糊匹配索引（首次查询时构建，insert/remove/merge_from 时失效）
+    #[serde(skip)]
+    pending: IndexMap<String, String>, // 尚未写入存储的新增/修改条目
+    #[serde(skip)]
+    pending_hits: IndexMap<String, u32>, // 尚未写入存储的命中次数
+    #[serde(skip)]
+    store: Option<Arc<TmStore>>, // 按需查询的存储（此时 memory 只含本次写入的条目）
+    #[serde(skip)]
+    cache: LookupCache<Option<String>>, // 按需查询的有界缓存（含未命中）
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,14 +181,6 @@ pub struct MemoryStats {
     pub misses: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MemoryEntry {
-    pub source: String,
-    pub target: String,
-    pub created_at: DateTime<Utc>,
-    pub hit_count: u32,
-}
-
 impl TranslationMemory {
     pub fn new() -> Self {
         // 只加载内置短语
@@ -41,6 +195,11 @@ impl TranslationMemory {
                 misses: 0,
             },
             last_updated: Utc::now(),
+            fuzzy_index: OnceCell::new(),
+            pending: IndexMap::new(),
+            pending_hits: IndexMap::new(),
+            store: None,
+            cache: LookupCache::default(),
         }
     }
 
@@ -61,6 +220,11 @@ impl TranslationMemory {
                     misses: 0,
                 },
                 last_updated: Utc::now(),
+                fuzzy_index: OnceCell::new(),
+                pending: IndexMap::new(),
+                pending_hits: IndexMap::new(),
+                store: None,
+                cache: LookupCache::default(),
             });
         }
 
@@ -106,9 +270,106 @@ impl TranslationMemory {
                 misses: 0,
             },
             last_updated: Utc::now(),
+            fuzzy_index: OnceCell::new(),
+            pending: IndexMap::new(),
+            pending_hits: IndexMap::new(),
+            store: None,
+            cache: LookupCache::default(),
+        })
+    }
+
+    /// 从嵌入式存储加载短语表（首次使用时存储中已写入内置短语）
+    pub fn load_from_store(store: &TmStore) -> Result<Self> {
+        let memory = store.load_phrases()?;
+        let total_entries = memory.len();
+        crate::app_log!("[TM] 加载翻译记忆库: {} 条记录", total_entries);
+        Ok(Self {
+            memory,
+            stats: MemoryStats {
+                total_entries,
+                hits: 0,
+                misses: 0,
+            },
+            last_updated: Utc::now(),
+            fuzzy_index: OnceCell::new(),
+            pending: IndexMap::new(),
+            pending_hits: IndexMap::new(),
+            store: None,
+            cache: LookupCache::default(),
         })
     }
 
+    /// 按需查询存储中的短语表（翻译时使用，不整表加载）
+    ///
+    /// `memory` 只保存本次新增的条目；精确查询经有界缓存读取存储，模糊匹配使用存储共用的索引
+    pub fn open(store: Arc<TmStore>) -> Result<Self> {
+        let total_entries = store.phrase_count()?;
+        Ok(Self {
+            memory: IndexMap::new(),
+            stats: MemoryStats {
+                total_entries,
+                hits: 0,
+                misses: 0,
+            },
+            last_updated: Utc::now(),
+            fuzzy_index: OnceCell::new(),
+            pending: IndexMap::new(),
+            pending_hits: IndexMap::new(),
+            store: Some(store),
+            cache: LookupCache::default(),
+        })
+    }
+
+    /// 按完整键取译文：先查本地条目，按需模式下再经缓存查询存储
+    fn target_of(&self, key: &str) -> Option<String> {
+        if let Some(target) = self.memory.get(key) {
+            return Some(target.clone());
+        }
+        let store = self.store.as_ref()?;
+        self.cache
+            .get_or_load(store, key, || store.phrase(key))
+            .unwrap_or_else(|e| {
+                log::error!("[TM] 查询记忆库失败: {}", e);
+                None
+            })
+    }
+
+    /// 把加载后新增/修改的条目与命中次数增量写入存储，返回写入的条目数
+    pub fn save_to_store(&mut self, store: &TmStore) -> Result<usize> {
+        self.save_pending(store, false)
+    }
+
+    /// 同 `save_to_store`，写入的条目标记为固定（导入的条目不参与按容量淘汰）
+    pub fn save_imported_to_store(&mut self, store: &TmStore) -> Result<usize> {
+        self.save_pending(store, true)
+    }
+
+    fn save_pending(&mut self, store: &TmStore, pinned: bool) -> Result<usize> {
+        if !self.pending_hits.is_empty() {
+            store.record_phrase_hits(
+                self.pending_hits
+                    .iter()
+                    .map(|(key, hits)| (key.as_str(), *hits)),
+            )?;
+            self.pending_hits.clear();
+        }
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let entries = self
+            .pending
+            .iter()
+            .map(|(key, target)| (key.as_str(), target.as_str()));
+        let count = if pinned {
+            store.upsert_pinned_phrases(entries)?
+        } else {
+            store.upsert_phrases(entries)?
+        };
+        self.pending.clear();
+        crate::app_log!("[TM] 写入记忆库: {} 条记录", count);
+        Ok(count)
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(file_path: P) -> Result<Self> {
         let path = file_path.as_ref();
         if !path.exists() {
@@ -147,79 +408,144 @@ impl TranslationMemory {
         self.save_to_file(file_path)
     }
 
-    /// 根据源文本和目标语言查询翻译
+    /// 根据源文本和目标语言查询翻译（无上下文）
     /// target_lang: 目标语言代码（如 "zh-CN", "ja", "en"）
     pub fn get_translation(&mut self, source: &str, target_lang: Option<&str>) -> Option<String> {
-        // 标准化语言代码映射
-        fn normalize_lang_code(lang: &str) -> &str {
-            match lang {
-                "zh-CN" | "zh-TW" | "zh-HK" => "zh-Hans",
-                "en-US" | "en-GB" => "en",
-                other => other,
-            }
-        }
+        self.lookup(&TmKey::new(source, target_lang), target_lang)
+            .map(|hit| hit.target)
+    }
 
-        // 尝试按"源文本|目标语言"查询（新格式，支持多语言）
-        if let Some(lang) = target_lang {
-            let normalized_lang = normalize_lang_code(lang);
-            let key_with_lang = format!("{}|{}", source, normalized_lang);
-            if let Some(translation) = self.memory.get(&key_with_lang) {
+    /// 精确查询：上下文一致的条目优先（101%），其次无上下文条目（带上下文查询时扣分）
+    ///
+    /// `raw_target_lang` 为标准化前的目标语言代码，用于兼容按原始代码保存的旧条目
+    pub fn lookup(&mut self, query: &TmKey, raw_target_lang: Option<&str>) -> Option<TmHit> {
+        let found = query
+            .lookup_candidates(raw_target_lang)
+            .into_iter()
+            .find_map(|(key, score)| {
+                let target = self.target_of(&key)?;
+                Some((key, TmHit { target, score }))
+            });
+        let hit = found.map(|(key, hit)| {
+            *self.pending_hits.entry(key).or_default() += 1;
+            hit
+        });
+        match &hit {
+            Some(hit) => {
                 self.stats.hits += 1;
-                crate::app_log!("[TM] 命中翻译（{}）: {} -> {}", lang, source, translation);
-                return Some(translation.clone());
+                crate::app_log!(
+                    "[TM] 命中翻译（{}%）: {} -> {}",
+                    hit.score,
+                    query.source,
+                    hit.target
+                );
             }
+            None => self.stats.misses += 1,
         }
+        hit
+    }
 
-        // 降级：尝试不带语言的查询（兼容旧数据）
-        self.memory
-            .get(source)
-            .map(|translation| {
-                self.stats.hits += 1;
-                crate::app_log!("[TM] 命中翻译（无语言标识）: {} -> {}", source, translation);
-                translation.clone()
-            })
-            .or_else(|| {
-                self.stats.misses += 1;
-                None
+    /// 是否已有该键的条目
+    pub fn contains(&self, key: &TmKey) -> bool {
+        self.target_of(&key.encode()).is_some()
+    }
+
+    /// 模糊查询：返回目标语言下匹配度不低于 `min_score` 的前 `top_k` 条（按匹配度降序）
+    ///
+    /// 不带语言标识的旧条目不参与模糊匹配
+    pub fn fuzzy_matches(
+        &self,
+        source: &str,
+        target_lang: &str,
+        top_k: usize,
+        min_score: u8,
+    ) -> Vec<FuzzyMatch> {
+        let normalized_lang = normalize_lang_code(target_lang);
+        let same_lang = |key: &str| {
+            TmKey::target_lang_of(key)
+                .is_some_and(|lang| lang == target_lang || lang == normalized_lang)
+        };
+
+        let index = self
+            .fuzzy_index
+            .get_or_init(|| FuzzyIndex::build(self.memory.keys()));
+
+        let mut matches = index.search(source, top_k, min_score, same_lang);
+        // 按需模式：再查存储共用的索引（本地已有的键以本地为准）
+        let shared = self.store.as_ref().and_then(|store| {
+            store
+                .phrase_index()
+                .map_err(|e| log::error!("[TM] 构建模糊匹配索引失败: {}", e))
+                .ok()
+        });
+        if let Some(shared) = &shared {
+            matches.extend(shared.search(source, top_k, min_score, |key| {
+                same_lang(key) && !self.memory.contains_key(key)
+            }));
+            matches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            matches.truncate(top_k);
+        }
+
+        matches
+            .into_iter()
+            .filter_map(|(key, score)| {
+                Some(FuzzyMatch {
+                    source: TmKey::parse(key).source,
+                    target: self.target_of(key)?,
+                    score,
+                })
             })
+            .collect()
     }
 
     /// 添加翻译到记忆库
     /// target_lang: 目标语言代码（如 "zh-CN", "ja", "en"）
     pub fn add_translation(&mut self, source: String, target: String, target_lang: Option<&str>) {
-        const MAX_CAPACITY: usize = 10000;
-
-        // 修复：使用"源文本|目标语言"作为键，支持多语言
-        let key = if let Some(lang) = target_lang {
-            format!("{}|{}", source, lang)
-        } else {
-            source.clone() // 降级：不带语言标识（兼容旧数据）
-        };
+        // 不带语言时降级为只有原文的键（兼容旧数据）
+        self.insert(&TmKey::new(&source, target_lang), target);
+    }
 
-        // 检查容量限制
-        if self.memory.len() >= MAX_CAPACITY {
-            // 获取内置短语列表，保护它们不被移除
-            let builtin = get_builtin_memory();
-
-            // 查找第一个非内置短语并移除（FIFO策略）
-            if let Some(old_key) = self
-                .memory
-                .keys()
-                .find(|k| !builtin.contains_key(k.as_str()))
-                .cloned()
-            {
-                self.memory.shift_remove(&old_key);
-                crate::app_log!(
-                    "[TM] 达到容量上限({})，移除最早的条目: {}",
-                    MAX_CAPACITY,
-                    old_key
-                );
-            }
+    /// 按完整键（源语言、目标语言、上下文、原文）写入
+    pub fn insert(&mut self, key: &TmKey, target: String) {
+        let key = key.encode();
+        if self.target_of(&key).is_none() {
+            self.stats.total_entries += 1;
         }
-
+        self.pending.insert(key.clone(), target.clone());
         self.memory.insert(key, target);
-        self.stats.total_entries = self.memory.len();
         self.last_updated = Utc::now();
+        self.fuzzy_index = OnceCell::new();
+    }
+
+    /// 删除一个条目，返回原译文（不影响存储，由调用方保存）
+    pub fn remove(&mut self, key: &TmKey) -> Option<String> {
+        let key = key.encode();
+        let removed = self.memory.shift_remove(&key)?;
+        self.pending.shift_remove(&key);
+        self.stats.total_entries = self.stats.total_entries.saturating_sub(1);
+        self.last_updated = Utc::now();
+        self.fuzzy_index = OnceCell::new();
+        Some(removed)
+    }
+
+    /// 合并另一份记忆库中本库没有的条目（并发翻译时各 worker 学习结果汇总）
+    ///
+    /// 返回新增条目数
+    pub fn merge_from(&mut self, other: &TranslationMemory) -> usize {
+        let mut added = 0;
+        for (key, target) in &other.memory {
+            if self.target_of(key).is_none() {
+                self.pending.insert(key.clone(), target.clone());
+                self.memory.insert(key.clone(), target.clone());
+                added += 1;
+            }
+        }
+        if added > 0 {
+            self.stats.total_entries += added;
+            self.last_updated = Utc::now();
+            self.fuzzy_index = OnceCell::new();
+        }
+        added
     }
 
     /// 批量添加翻译（兼容旧接口，不带语言信息）
@@ -251,6 +577,15 @@ impl TranslationMemory {
     }
 }
 
+/// 标准化语言代码映射
+pub(crate) fn normalize_lang_code(lang: &str) -> &str {
+    match lang {
+        "zh-CN" | "zh-TW" | "zh-HK" => "zh-Hans",
+        "en-US" | "en-GB" => "en",
+        other => other,
+    }
+}
+
 impl Default for TranslationMemory {
     fn default() -> Self {
         Self::new()
@@ -409,3 +744,132 @@ pub fn get_builtin_memory() -> IndexMap<String, String> {
 
     memory
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_from_adds_only_missing_entries() {
+        let mut base = TranslationMemory::new();
+        base.add_translation(
+            "Reticulate splines".to_string(),
+            "网格化样条".to_string(),
+            Some("zh-Hans"),
+        );
+
+        let mut other = base.clone();
+        other.add_translation(
+            "Reticulate splines".to_string(),
+            "样条网格化".to_string(),
+            Some("zh-Hans"),
+        );
+        other.add_translation(
+            "Frobnicate widget".to_string(),
+            "摆弄控件".to_string(),
+            Some("zh-Hans"),
+        );
+
+        let added = base.merge_from(&other);
+        assert_eq!(added, 1);
+        assert_eq!(
+            base.memory
+                .get("Reticulate splines|zh-Hans")
+                .map(String::as_str),
+            Some("网格化样条")
+        );
+        assert_eq!(
+            base.memory
+                .get("Frobnicate widget|zh-Hans")
+                .map(String::as_str),
+            Some("摆弄控件")
+        );
+        assert_eq!(base.stats.total_entries, base.memory.len());
+    }
+
+    #[test]
+    fn test_fuzzy_matches_filter_language_and_refresh() {
+        let mut tm = TranslationMemory::new();
+        tm.add_translation(
+            "Delete selected items".to_string(),
+            "删除所选项目".to_string(),
+            Some("zh-Hans"),
+        );
+        tm.add_translation(
+            "Delete selected items".to_string(),
+            "選択した項目を削除".to_string(),
+            Some("ja"),
+        );
+
+        let matches = tm.fuzzy_matches("Delete selected item", "zh-CN", 3, 80);
+        assert_eq!(
+            matches,
+            vec![FuzzyMatch {
+                source: "Delete selected items".to_string(),
+                target: "删除所选项目".to_string(),
+                score: 95,
+            }]
+        );
+
+        // 新增条目后索引重建
+        tm.add_translation(
+            "Delete selected item!".to_string(),
+            "删除所选项目！".to_string(),
+            Some("zh-Hans"),
+        );
+        assert_eq!(
+            tm.fuzzy_matches("Delete selected item", "zh-Hans", 1, 80)[0].target,
+            "删除所选项目！"
+        );
+
+        // 删除条目后索引同样失效，不会返回过期结果
+        let removed = TmKey::new("Delete selected item!", Some("zh-Hans"));
+        assert_eq!(tm.remove(&removed).as_deref(), Some("删除所选项目！"));
+        assert_eq!(
+            tm.fuzzy_matches("Delete selected item", "zh-Hans", 1, 80)[0].target,
+            "删除所选项目"
+        );
+    }
+
+    #[test]
+    fn test_open_queries_store_on_demand() {
+        let store = Arc::new(TmStore::open_in_memory().unwrap());
+        store
+            .upsert_phrases([("Delete selected items|zh-Hans", "删除所选项目")])
+            .unwrap();
+
+        let mut tm = TranslationMemory::open(Arc::clone(&store)).unwrap();
+        assert!(tm.memory.is_empty());
+        assert_eq!(tm.stats.total_entries, 1);
+        assert_eq!(
+            tm.get_translation("Delete selected items", Some("zh-CN"))
+                .as_deref(),
+            Some("删除所选项目")
+        );
+        assert_eq!(
+            tm.fuzzy_matches("Delete selected item", "zh-Hans", 1, 80)[0].score,
+            95
+        );
+
+        // 新学习的条目先在本地可查，保存后其他翻译器经存储可查
+        tm.add_translation(
+            "Delete selected item".to_string(),
+            "删除所选项".to_string(),
+            Some("zh-Hans"),
+        );
+        assert_eq!(tm.memory.len(), 1);
+        assert_eq!(tm.stats.total_entries, 2);
+        let matches = tm.fuzzy_matches("Delete selected item", "zh-Hans", 2, 80);
+        assert_eq!(matches[0].target, "删除所选项");
+        assert_eq!(matches.len(), 2);
+        assert_eq!(tm.save_to_store(&store).unwrap(), 1);
+
+        let other = TranslationMemory::open(store).unwrap();
+        assert!(other.contains(&TmKey::new("Delete selected item", Some("zh-Hans"))));
+        assert_eq!(
+            other.fuzzy_matches("Delete selected item", "zh-Hans", 1, 80)[0].target,
+            "删除所选项"
+        );
+    }
+}
@@ -16,6 +16,9 @@ pub struct TokenStats {
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub total_tokens: u32,
+    /// 推理 token（已计入 output_tokens）
+    #[serde(default)]
+    pub reasoning_tokens: u32,
     pub cost: f64,
 }
 
@@ -25,6 +28,7 @@ impl Default for TokenStats {
             input_tokens: 0,
             output_tokens: 0,
             total_tokens: 0,
+            reasoning_tokens: 0,
             cost: 0.0,
         }
     }
@@ -52,6 +56,26 @@ impl TokenStats {
     pub fn reset(&mut self) {
         *self = Self::default();
     }
+
+    /// 计算相对上一次快照的增量
+    pub fn delta_since(&self, previous: &TokenStats) -> TokenStats {
+        TokenStats {
+            input_tokens: self.input_tokens.saturating_sub(previous.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(previous.output_tokens),
+            total_tokens: self.total_tokens.saturating_sub(previous.total_tokens),
+            reasoning_tokens: self
+                .reasoning_tokens
+                .saturating_sub(previous.reasoning_tokens),
+            cost: (self.cost - previous.cost).max(0.0),
+        }
+    }
+
+    /// 累加另一份统计（并发批次汇总）
+    pub fn accumulate(&mut self, other: &TokenStats) {
+        self.update(other.input_tokens, other.output_tokens, other.total_tokens);
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.add_cost(other.cost);
+    }
 }
 
 /// 批量翻译统计
@@ -62,6 +86,9 @@ pub struct BatchStats {
     pub deduplicated: usize,
     pub ai_translated: usize,
     pub tm_learned: usize,
+    /// 命中响应缓存的条目（未付费，不计入 ai_translated）
+    #[serde(default)]
+    pub cache_hits: usize,
 }
 
 impl BatchStats {
@@ -77,6 +104,7 @@ impl BatchStats {
         self.deduplicated = 0;
         self.ai_translated = 0;
         self.tm_learned = 0;
+        self.cache_hits = 0;
     }
 
     /// 重置统计
@@ -103,6 +131,16 @@ impl BatchStats {
     pub fn record_tm_learning(&mut self) {
         self.tm_learned += 1;
     }
+
+    /// 累加另一批次的统计（并发批次汇总）
+    pub fn accumulate(&mut self, other: &BatchStats) {
+        self.total += other.total;
+        self.tm_hits += other.tm_hits;
+        self.deduplicated += other.deduplicated;
+        self.ai_translated += other.ai_translated;
+        self.tm_learned += other.tm_learned;
+        self.cache_hits += other.cache_hits;
+    }
 }
 
 /// 计算并更新 token 成本
@@ -111,19 +149,22 @@ impl BatchStats {
 /// - `token_stats`: 可变的 token 统计引用
 /// - `provider_id`: AI 供应商 ID
 /// - `model`: 模型名称
-/// - `prompt_tokens`: 输入 token 数
+/// - `prompt_tokens`: 输入 token 数（含命中缓存的部分）
 /// - `completion_tokens`: 输出 token 数
+/// - `cached_tokens`: 输入中命中提示词缓存的 token 数（按缓存读取价格计费）
 ///
 /// # 返回
-/// 成功返回 ()，失败返回错误
+/// 成功返回 ()，失败返回错误；成本以用户选择的显示货币累加
 pub fn update_token_cost(
     token_stats: &mut TokenStats,
     provider_id: &str,
     model: &str,
     prompt_tokens: u32,
     completion_tokens: u32,
+    cached_tokens: u32,
 ) -> Result<()> {
     use crate::services::ai::CostCalculator;
+    use crate::services::ai::currency;
     use crate::services::ai::provider::with_global_registry;
 
     // 更新 token 统计
@@ -150,16 +191,25 @@ pub fn update_token_cost(
         &model_info,
         prompt_tokens as usize,
         completion_tokens as usize,
-        0, // TODO: 支持从 API 响应中提取缓存 token
         0,
+        cached_tokens as usize,
     );
 
-    token_stats.add_cost(breakdown.total_cost);
+    // 插件模型价格已是显示货币；内置模型等其他货币的价格在此换算
+    let cost = currency::currency_table()
+        .convert(
+            breakdown.total_cost,
+            &breakdown.currency,
+            &currency::display_currency(),
+        )
+        .map_err(|e| anyhow::anyhow!("成本换算失败: {}", e))?;
+    token_stats.add_cost(cost);
 
     Ok(())
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
 
@@ -216,4 +266,68 @@ mod tests {
         assert_eq!(stats.ai_translated, 78);
         assert_eq!(stats.tm_learned, 1);
     }
+
+    #[test]
+    fn test_token_stats_delta_and_accumulate() {
+        let mut previous = TokenStats::new();
+        previous.update(100, 50, 150);
+        previous.add_cost(0.5);
+
+        let mut current = previous.clone();
+        current.update(20, 10, 30);
+        current.add_cost(0.25);
+
+        let delta = current.delta_since(&previous);
+        assert_eq!(delta.input_tokens, 20);
+        assert_eq!(delta.output_tokens, 10);
+        assert_eq!(delta.total_tokens, 30);
+        assert!((delta.cost - 0.25).abs() < 1e-9);
+
+        let mut total = TokenStats::new();
+        total.accumulate(&previous);
+        total.accumulate(&delta);
+        assert_eq!(total.total_tokens, current.total_tokens);
+        assert!((total.cost - current.cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_batch_stats_accumulate() {
+        let mut total = BatchStats::new();
+        let batch = BatchStats {
+            total: 10,
+            tm_hits: 2,
+            deduplicated: 1,
+            ai_translated: 7,
+            tm_learned: 3,
+            cache_hits: 2,
+        };
+        total.accumulate(&batch);
+        total.accumulate(&batch);
+        assert_eq!(total.total, 20);
+        assert_eq!(total.tm_hits, 4);
+        assert_eq!(total.deduplicated, 2);
+        assert_eq!(total.ai_translated, 14);
+        assert_eq!(total.tm_learned, 6);
+        assert_eq!(total.cache_hits, 4);
+    }
+
+    #[test]
+    fn test_update_token_cost_uses_model_prices() {
+        crate::services::ai::plugin_loader::register_mock_provider().unwrap();
+        let mut stats = TokenStats::new();
+        // mock-echo：输入 1.0 / 输出 2.0 USD 每百万 token，未声明缓存价格时按输入价计
+        update_token_cost(&mut stats, "mock", "mock-echo", 1_000_000, 500_000, 400_000).unwrap();
+        assert_eq!(stats.input_tokens, 1_000_000);
+        assert_eq!(stats.total_tokens, 1_500_000);
+        let expected = crate::services::ai::currency::currency_table()
+            .convert(
+                2.0,
+                "USD",
+                &crate::services::ai::currency::display_currency(),
+            )
+            .unwrap();
+        assert!((stats.cost - expected).abs() < 1e-9);
+
+        assert!(update_token_cost(&mut stats, "mock", "no-such-model", 1, 1, 0).is_err());
+    }
 }
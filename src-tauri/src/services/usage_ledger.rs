@@ -0,0 +1,410 @@
+/**
+ * API 用量台账
+ *
+ * - 每次 API 调用后追加一条记录（时间、供应商、模型、文件、项目、token、缓存 token、成本）
+ * - 以 JSON Lines 追加写入 `data/usage_ledger.jsonl`，不修改历史记录
+ * - 支持按日期范围筛选，并按日 / 供应商 / 模型 / 项目汇总
+ * - 导出 CSV 用于核对供应商账单
+ * - 每条记录保存当时的货币；查询时按汇率表统一换算为当前显示货币
+ * - 预算的每日/每月花费由本台账汇总（见 `budget::SpendingLedger`）
+ */
+use crate::error::AppError;
+use crate::services::ai::currency::{self, CurrencyTable};
+use chrono::{DateTime, Local, NaiveDate};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+/// 单次 API 调用的用量记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct UsageRecord {
+    /// RFC 3339 时间戳（本地时区）
+    pub timestamp: String,
+    pub provider: String,
+    pub model: String,
+    /// 翻译的文件（未知时为空）
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 所属项目（目录翻译的根目录，或文件所在目录）
+    #[serde(default)]
+    pub project: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// 缓存命中的输入 token（包含在 input_tokens 中）
+    #[serde(default)]
+    pub cached_tokens: u64,
+    pub cost: f64,
+    /// 成本的货币（旧记录无此字段，按 USD 处理）
+    #[serde(default = "default_currency")]
+    pub currency: String,
+}
+
+fn default_currency() -> String {
+    currency::DEFAULT_CURRENCY.to_string()
+}
+
+impl UsageRecord {
+    /// 记录所在日期（本地时区）
+    pub fn date(&self) -> Option<NaiveDate> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|ts| ts.with_timezone(&Local).date_naive())
+    }
+}
+
+/// 用量归属（由调用方设置，随每条记录写入）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageContext {
+    pub file: Option<String>,
+    pub project: Option<String>,
+}
+
+impl UsageContext {
+    /// 单个文件：项目取文件所在目录
+    pub fn for_file(file: Option<&str>) -> Self {
+        let project = file
+            .and_then(|file| Path::new(file).parent())
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| !dir.is_empty());
+        Self {
+            file: file.map(str::to_string),
+            project,
+        }
+    }
+}
+
+/// 汇总维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub enum UsageGroupBy {
+    Day,
+    Provider,
+    Model,
+    Project,
+}
+
+/// 汇总结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "../src/types/generated/"))]
+pub struct UsageAggregate {
+    /// 分组键（日期、供应商、`供应商/模型` 或项目路径）
+    pub key: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub cost: f64,
+    pub currency: String,
+}
+
+/// 未归属项目的记录的分组键
+const UNKNOWN_PROJECT: &str = "(未知项目)";
+
+/// 把记录成本换算为同一货币（汇率表中没有的货币保持原值）
+pub fn convert_currency(records: &mut [UsageRecord], target: &str, table: &CurrencyTable) {
+    for record in records.iter_mut() {
+        if record.currency == target {
+            continue;
+        }
+        match table.convert(record.cost, &record.currency, target) {
+            Ok(cost) => {
+                record.cost = cost;
+                record.currency = target.to_string();
+            }
+            Err(e) => log::error!("[用量台账] 成本换算失败: {}", e),
+        }
+    }
+}
+
+/// 按维度汇总（结果按分组键排序）
+///
+/// 记录应已换算为同一货币；无法换算的记录按货币单独成组，不与其他货币相加
+pub fn aggregate(records: &[UsageRecord], group_by: UsageGroupBy) -> Vec<UsageAggregate> {
+    let mut groups: BTreeMap<(String, String), UsageAggregate> = BTreeMap::new();
+    for record in records {
+        let key = match group_by {
+            UsageGroupBy::Day => record
+                .date()
+                .map(|date| date.to_string())
+                .unwrap_or_else(|| record.timestamp.clone()),
+            UsageGroupBy::Provider => record.provider.clone(),
+            UsageGroupBy::Model => format!("{}/{}", record.provider, record.model),
+            UsageGroupBy::Project => record
+                .project
+                .clone()
+                .unwrap_or_else(|| UNKNOWN_PROJECT.to_string()),
+        };
+        let group = groups
+            .entry((key.clone(), record.currency.clone()))
+            .or_insert_with(|| UsageAggregate {
+                key,
+                currency: record.currency.clone(),
+                ..Default::default()
+            });
+        group.requests += 1;
+        group.input_tokens += record.input_tokens;
+        group.output_tokens += record.output_tokens;
+        group.cached_tokens += record.cached_tokens;
+        group.cost += record.cost;
+    }
+    groups.into_values().collect()
+}
+
+/// 导出为 CSV（含表头）
+pub fn to_csv(records: &[UsageRecord]) -> String {
+    let mut csv = String::from(
+        "timestamp,provider,model,file,project,input_tokens,output_tokens,cached_tokens,cost,currency\n",
+    );
+    for record in records {
+        let fields = [
+            csv_field(&record.timestamp),
+            csv_field(&record.provider),
+            csv_field(&record.model),
+            csv_field(record.file.as_deref().unwrap_or_default()),
+            csv_field(record.project.as_deref().unwrap_or_default()),
+            record.input_tokens.to_string(),
+            record.output_tokens.to_string(),
+            record.cached_tokens.to_string(),
+            format!("{:.6}", record.cost),
+            csv_field(&record.currency),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// 含逗号、引号或换行的字段加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// ========== 持久化 ==========
+
+/// 串行化追加写入（多个翻译器并发记录）
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 用量台账文件路径
+pub fn ledger_path() -> PathBuf {
+    crate::utils::paths::app_data_dir()
+        .map(|dir| dir.join("usage_ledger.jsonl"))
+        .unwrap_or_else(|_| PathBuf::from("usage_ledger.jsonl"))
+}
+
+/// 追加一条记录，并计入预算的花费台账
+pub fn append(record: &UsageRecord) -> Result<(), AppError> {
+    // 先取得花费台账（首次使用时汇总本文件），写入后再计入，避免重复计算
+    let spending = crate::services::budget::shared_ledger();
+    append_to(&ledger_path(), record)?;
+    spending.lock().add_usage(record);
+    Ok(())
+}
+
+/// 读取日期范围内的记录（包含两端，未指定则不限）
+pub fn load(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<Vec<UsageRecord>, AppError> {
+    load_from(&ledger_path(), from, to)
+}
+
+fn append_to(path: &Path, record: &UsageRecord) -> Result<(), AppError> {
+    let line = serde_json::to_string(record)?;
+    let _guard = WRITE_LOCK.lock();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+fn load_from(
+    path: &Path,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<UsageRecord>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = std::fs::File::open(path)?;
+    let mut records = Vec::new();
+    for (line_no, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // 跳过损坏的行（如写入中断），不影响其余记录
+        let record: UsageRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                log::error!("[用量台账] 第 {} 行解析失败: {}", line_no + 1, e);
+                continue;
+            }
+        };
+        let in_range = record.date().is_none_or(|date| {
+            from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+        });
+        if in_range {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: &str, provider: &str, model: &str, project: Option<&str>) -> UsageRecord {
+        UsageRecord {
+            timestamp: timestamp.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            file: project.map(|p| format!("{}/zh_CN.po", p)),
+            project: project.map(str::to_string),
+            input_tokens: 100,
+            output_tokens: 50,
+            cached_tokens: 20,
+            cost: 0.01,
+            currency: "USD".to_string(),
+        }
+    }
+
+    fn local(date: &str) -> String {
+        let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        naive.and_local_timezone(Local).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_append_and_load_with_date_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage_ledger.jsonl");
+        for date in ["2026-10-16", "2026-10-17", "2026-10-18"] {
+            append_to(&path, &record(&local(date), "openai", "gpt-4o", None)).unwrap();
+        }
+        // 损坏的行被跳过
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{broken\n")
+            .unwrap();
+
+        assert_eq!(load_from(&path, None, None).unwrap().len(), 3);
+        let from = NaiveDate::from_ymd_opt(2026, 10, 17);
+        let records = load_from(&path, from, from).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].date(), from);
+    }
+
+    #[test]
+    fn test_aggregate_by_dimensions() {
+        let records = vec![
+            record(&local("2026-10-17"), "openai", "gpt-4o", Some("/work/app")),
+            record(
+                &local("2026-10-18"),
+                "openai",
+                "gpt-4o-mini",
+                Some("/work/app"),
+            ),
+            record(&local("2026-10-18"), "deepseek", "deepseek-chat", None),
+        ];
+
+        let by_day = aggregate(&records, UsageGroupBy::Day);
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[1].key, "2026-10-18");
+        assert_eq!(by_day[1].requests, 2);
+        assert_eq!(by_day[1].cached_tokens, 40);
+
+        let by_provider = aggregate(&records, UsageGroupBy::Provider);
+        assert_eq!(by_provider[1].key, "openai");
+        assert!((by_provider[1].cost - 0.02).abs() < 1e-9);
+
+        assert_eq!(aggregate(&records, UsageGroupBy::Model).len(), 3);
+
+        let by_project = aggregate(&records, UsageGroupBy::Project);
+        assert_eq!(by_project.len(), 2);
+        assert!(by_project.iter().any(|g| g.key == UNKNOWN_PROJECT));
+    }
+
+    #[test]
+    fn test_csv_export_escapes_fields() {
+        let mut entry = record("2026-10-18T12:00:00+08:00", "openai", "gpt-4o", None);
+        entry.file = Some("/work/a,b/\"zh\".po".to_string());
+        let csv = to_csv(&[entry]);
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("timestamp,provider,model")
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "2026-10-18T12:00:00+08:00,openai,gpt-4o,\"/work/a,b/\"\"zh\"\".po\",,100,50,20,0.010000,USD"
+        );
+    }
+
+    #[test]
+    fn test_convert_records_to_display_currency() {
+        let mut records = vec![
+            record(&local("2026-10-18"), "openai", "gpt-4o", None),
+            record(&local("2026-10-18"), "deepseek", "deepseek-chat", None),
+        ];
+        records[1].currency = "CNY".to_string();
+        records[1].cost = 0.072;
+
+        convert_currency(&mut records, "CNY", &CurrencyTable::builtin());
+        assert!(records.iter().all(|r| r.currency == "CNY"));
+        assert!((records[0].cost - 0.072).abs() < 1e-9);
+
+        let by_day = aggregate(&records, UsageGroupBy::Day);
+        assert_eq!(by_day[0].currency, "CNY");
+        assert!((by_day[0].cost - 0.144).abs() < 1e-9);
+
+        // 汇率表中没有的货币不与其他货币相加
+        records[1].currency = "XYZ".to_string();
+        convert_currency(&mut records, "CNY", &CurrencyTable::builtin());
+        let by_day = aggregate(&records, UsageGroupBy::Day);
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[0].currency, "CNY");
+        assert!((by_day[0].cost - 0.072).abs() < 1e-9);
+        assert_eq!(by_day[1].currency, "XYZ");
+        assert_eq!(by_day[1].requests, 1);
+
+        // 旧记录没有货币字段时按 USD 读取
+        let legacy: UsageRecord = serde_json::from_str(
+            r#"{"timestamp":"2026-10-18T12:00:00+08:00","provider":"openai","model":"gpt-4o","inputTokens":1,"outputTokens":1,"cost":0.5}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.currency, "USD");
+    }
+
+    #[test]
+    fn test_context_for_file() {
+        let context = UsageContext::for_file(Some("/work/app/locale/zh_CN.po"));
+        assert_eq!(context.project.as_deref(), Some("/work/app/locale"));
+        assert_eq!(UsageContext::for_file(None), UsageContext::default());
+    }
+}
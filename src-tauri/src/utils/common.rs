@@ -65,6 +65,13 @@ pub fn is_simple_phrase(text: &str) -> bool {
     true
 }
 
+/// 按字符截断文本（不会切断多字节字符），未超长时返回 None
+pub fn truncate_chars(text: &str, max_chars: usize) -> Option<&str> {
+    text.char_indices()
+        .nth(max_chars)
+        .map(|(index, _)| &text[..index])
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -88,4 +95,12 @@ mod tests {
         assert!(!is_simple_phrase("Description of the distance"));
         assert!(!is_simple_phrase("Max Distance")); // distance 是描述性词汇
     }
+
+    #[test]
+    fn test_truncate_chars_respects_char_boundaries() {
+        assert_eq!(truncate_chars("hello", 5), None);
+        assert_eq!(truncate_chars("hello", 3), Some("hel"));
+        assert_eq!(truncate_chars("翻译结果：你好", 4), Some("翻译结果"));
+        assert_eq!(truncate_chars("a翻译", 2), Some("a翻"));
+    }
 }
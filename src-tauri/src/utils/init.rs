@@ -15,6 +15,7 @@ pub static LOGGER_HANDLE: OnceLock<flexi_logger::LoggerHandle> = OnceLock::new()
 
 pub async fn init_app() -> Result<()> {
     paths::init_portable_flag()?;
+    paths::init_app_home()?;
     paths::init_app_directories()?;
     init_logger().await?;
     init_ai_providers()?;
@@ -241,9 +242,11 @@ pub async fn delete_old_logs(retention_days: Option<u32>) -> Result<()> {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::services::tests::test_env::TestDataDir;
 
     #[tokio::test]
     async fn test_init_app() {
+        let _data = TestDataDir::new();
         let result = init_app().await;
         if result.is_err() {
             println!("Init failed (expected in test env): {:?}", result);
@@ -252,6 +255,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_delete_old_logs() {
+        let _data = TestDataDir::new();
         let result = delete_old_logs(Some(7)).await;
         assert!(result.is_ok());
     }
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use once_cell::sync::OnceCell;
+use parking_lot::RwLock;
 use std::{fs, path::PathBuf};
 
 pub static APP_ID: &str = "com.potranslator.gui";
@@ -21,7 +22,30 @@ pub fn init_portable_flag() -> Result<()> {
     Ok(())
 }
 
+/// 数据目录（启动时确定；未设置时按便携模式或系统目录计算）
+static APP_HOME: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 启动时确定数据目录（已指定时保持不变），之后的路径都以它为准
+pub fn init_app_home() -> Result<()> {
+    if APP_HOME.read().is_none() {
+        set_app_home_dir(default_app_home_dir()?);
+    }
+    Ok(())
+}
+
+/// 指定数据目录（记忆库、用量台账等共享存储随之切换）
+pub fn set_app_home_dir(dir: PathBuf) {
+    *APP_HOME.write() = Some(dir);
+}
+
 pub fn app_home_dir() -> Result<PathBuf> {
+    if let Some(dir) = APP_HOME.read().clone() {
+        return Ok(dir);
+    }
+    default_app_home_dir()
+}
+
+fn default_app_home_dir() -> Result<PathBuf> {
     let flag = PORTABLE_FLAG.get().unwrap_or(&false);
 
     if *flag {
@@ -82,6 +106,21 @@ pub fn get_translation_memory_path() -> PathBuf {
         })
 }
 
+/// 句段级记忆库与短语记忆库放在同一目录
+pub fn get_segment_memory_path() -> PathBuf {
+    get_translation_memory_path().with_file_name("segment_memory.json")
+}
+
+/// 记忆库 / 术语库数据库（取代上面两个 JSON 文件与术语库 JSON）
+pub fn get_tm_database_path() -> PathBuf {
+    get_translation_memory_path().with_file_name("translation_memory.db")
+}
+
+/// 命名记忆库（每个一个数据库文件）所在目录
+pub fn get_memories_dir() -> PathBuf {
+    get_translation_memory_path().with_file_name("memories")
+}
+
 pub fn ensure_tm_dir() -> std::io::Result<()> {
     let tm_path = get_translation_memory_path();
     if let Some(parent) = tm_path.parent() {
@@ -116,6 +155,7 @@ pub fn init_app_directories() -> Result<()> {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::services::tests::test_env::TestDataDir;
 
     #[test]
     fn test_portable_flag_init() {
@@ -127,27 +167,17 @@ mod tests {
     #[test]
     fn test_app_home_dir() {
         let _ = init_portable_flag();
-        let result = app_home_dir();
+        let result = default_app_home_dir();
         assert!(result.is_ok());
         let path = result.unwrap();
         assert!(path.to_string_lossy().contains(APP_ID));
     }
 
     #[test]
-    fn test_app_logs_dir() {
-        let _ = init_portable_flag();
-        let result = app_logs_dir();
-        assert!(result.is_ok());
-        let path = result.unwrap();
-        assert!(path.to_string_lossy().contains("logs"));
-    }
-
-    #[test]
-    fn test_app_data_dir() {
-        let _ = init_portable_flag();
-        let result = app_data_dir();
-        assert!(result.is_ok());
-        let path = result.unwrap();
-        assert!(path.to_string_lossy().contains("data"));
+    fn test_app_dirs_follow_app_home() {
+        let data = TestDataDir::new();
+        assert_eq!(app_home_dir().unwrap(), data.home());
+        assert_eq!(app_logs_dir().unwrap(), data.home().join("logs"));
+        assert_eq!(app_data_dir().unwrap(), data.home().join("data"));
     }
 }
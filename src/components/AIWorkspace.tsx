@@ -12,7 +12,14 @@ import {
 } from '@ant-design/icons';
 import type { TranslationStats } from '../types/tauri';
 import { CSS_COLORS } from '../hooks/useCssColors';
-import { useCumulativeStats, useResetCumulativeStatsAction, useSessionStats } from '../store';
+import {
+  useCumulativeStats,
+  useResetCumulativeStatsAction,
+  useSessionStats,
+  useSessionStore,
+  useCumulativeCostCurrency,
+  useConvertCumulativeCostAction,
+} from '../store';
 import { createModuleLogger } from '../utils/logger';
 import { useTermLibrary } from '../hooks/useTermLibrary';
 import {
@@ -20,10 +27,12 @@ import {
   formatPercentage,
   formatCostByLocale,
   formatDateTime,
+  convertCost,
 } from '../utils/formatters';
 import { useAppStore } from '../store/useAppStore';
 import { useAppData } from '../hooks/useConfig';
 import { aiModelCommands } from '../services/aiCommands';
+import { currencyCommands } from '../services/translationCommands';
 import type { ModelInfo } from '../types/generated/ModelInfo';
 
 const log = createModuleLogger('AIWorkspace');
@@ -52,11 +61,13 @@ interface SessionStatsSectionProps {
   sessionStats: TranslationStats;
   modelInfo: ModelInfo | null;
   language: string;
+  currency: string;
 }
 
 interface CumulativeStatsSectionProps {
   cumulativeStats: TranslationStats;
   language: string;
+  currency: string;
   onReset: () => void;
 }
 
@@ -68,12 +79,14 @@ interface TermLibrarySectionProps {
 interface CostBreakdownProps {
   cost: number;
   language: string;
+  currency: string;
 }
 
 const SESSION_CARD_DATA = [
   { key: 'tm_hits', label: '记忆库命中', color: 'statusTranslated', percentage: true },
   { key: 'deduplicated', label: '去重节省', color: 'statusUntranslated', percentage: true },
   { key: 'ai_translated', label: 'AI调用', color: 'textPrimary', percentage: true },
+  { key: 'cache_hits', label: '缓存命中', color: 'statusTranslated', percentage: true },
   { key: 'tm_learned', label: '记忆库新增', color: 'statusTranslated', percentage: false },
 ] as const;
 
@@ -155,7 +168,11 @@ const TokenCard = memo(function TokenCard({ label, value }: { label: string; val
 });
 
 // 成本展示组件
-const CostBreakdown = memo(function CostBreakdown({ cost, language }: CostBreakdownProps) {
+const CostBreakdown = memo(function CostBreakdown({
+  cost,
+  language,
+  currency,
+}: CostBreakdownProps) {
   const containerStyle: React.CSSProperties = {
     padding: 'var(--space-2)',
     backgroundColor: CSS_COLORS.bgTertiary,
@@ -187,7 +204,7 @@ const CostBreakdown = memo(function CostBreakdown({ cost, language }: CostBreakd
           fontFamily: 'monospace',
         }}
       >
-        {formatCostByLocale(cost, language)}
+        {formatCostByLocale(cost, language, currency)}
       </span>
     </div>
   );
@@ -255,8 +272,12 @@ const SessionStatsSection = memo(function SessionStatsSection({
   sessionStats,
   modelInfo,
   language,
+  currency,
 }: SessionStatsSectionProps) {
-  const hasData = (sessionStats.tm_hits ?? 0) > 0 || (sessionStats.ai_translated ?? 0) > 0;
+  const hasData =
+    (sessionStats.tm_hits ?? 0) > 0 ||
+    (sessionStats.ai_translated ?? 0) > 0 ||
+    (sessionStats.cache_hits ?? 0) > 0;
 
   if (!hasData) {
     return (
@@ -277,10 +298,12 @@ const SessionStatsSection = memo(function SessionStatsSection({
   const totalTokens = sessionStats.token_stats?.total_tokens ?? 0;
   const inputTokens = sessionStats.token_stats?.input_tokens ?? 0;
   const outputTokens = sessionStats.token_stats?.output_tokens ?? 0;
+  const reasoningTokens = sessionStats.token_stats?.reasoning_tokens ?? 0;
   const tmHits = sessionStats.tm_hits ?? 0;
   const deduplicated = sessionStats.deduplicated ?? 0;
   const aiTranslated = sessionStats.ai_translated ?? 0;
-  const actualTotal = tmHits + deduplicated + aiTranslated;
+  const cacheHits = sessionStats.cache_hits ?? 0;
+  const actualTotal = tmHits + deduplicated + aiTranslated + cacheHits;
 
   const getStatValue = (key: (typeof SESSION_CARD_DATA)[number]['key']) => {
     const value = sessionStats[key] ?? 0;
@@ -337,9 +360,20 @@ const SessionStatsSection = memo(function SessionStatsSection({
         <TokenCard label="输出" value={formatTokens(outputTokens)} />
         <TokenCard label="总计" value={formatTokens(totalTokens)} />
       </div>
+      {reasoningTokens > 0 && (
+        <div
+          style={{
+            fontSize: 'var(--font-size-xs)',
+            color: CSS_COLORS.textTertiary,
+            marginBottom: 'var(--space-2)',
+          }}
+        >
+          其中推理 {formatTokens(reasoningTokens)}（按输出价格计费）
+        </div>
+      )}
 
       {/* 成本 */}
-      <CostBreakdown cost={cost} language={language} />
+      <CostBreakdown cost={cost} language={language} currency={currency} />
 
       {/* 缓存支持提示 */}
       {modelInfo && <CacheInfo modelInfo={modelInfo} />}
@@ -354,6 +388,7 @@ const SessionStatsSection = memo(function SessionStatsSection({
 const CumulativeStatsSection = memo(function CumulativeStatsSection({
   cumulativeStats,
   language,
+  currency,
   onReset,
 }: CumulativeStatsSectionProps) {
   if (cumulativeStats.total === 0) {
@@ -476,7 +511,7 @@ const CumulativeStatsSection = memo(function CumulativeStatsSection({
         <span
           style={{ fontWeight: 600, color: CSS_COLORS.statusTranslated, fontFamily: 'monospace' }}
         >
-          {formatCostByLocale(cost, language)}
+          {formatCostByLocale(cost, language, currency)}
         </span>
       </div>
     </div>
@@ -573,9 +608,31 @@ export const AIWorkspace = memo(function AIWorkspace({
   const resetCumulativeStats = useResetCumulativeStatsAction();
   const sessionStats = useSessionStats();
   const language = useAppStore((state) => state.language);
-  const { activeAIConfig } = useAppData();
+  const { config, activeAIConfig } = useAppData();
+  const currency = config?.currency ?? 'USD';
+  const costCurrency = useCumulativeCostCurrency();
+  const convertCumulativeCost = useConvertCumulativeCostAction();
   const [modelInfo, setModelInfo] = useState<ModelInfo | null>(null);
 
+  // 显示货币变更后按汇率换算已记录的花费（会话统计与累计统计使用同一货币）
+  React.useEffect(() => {
+    if (!config?.currency || config.currency === costCurrency) return;
+    currencyCommands
+      .getSettings()
+      .then(({ currency: target, rates }) => {
+        const { sessionStats, setSessionStats } = useSessionStore.getState();
+        const sessionCost = convertCost(sessionStats.token_stats.cost, costCurrency, target, rates);
+        if (sessionCost !== null) {
+          setSessionStats({
+            ...sessionStats,
+            token_stats: { ...sessionStats.token_stats, cost: sessionCost },
+          });
+        }
+        convertCumulativeCost(target, rates);
+      })
+      .catch((err) => log.error('换算花费货币失败:', err));
+  }, [config?.currency, costCurrency, convertCumulativeCost]);
+
   React.useEffect(() => {
     if (activeAIConfig?.providerId && activeAIConfig?.model) {
       aiModelCommands
@@ -672,6 +729,7 @@ export const AIWorkspace = memo(function AIWorkspace({
         <CumulativeStatsSection
           cumulativeStats={cumulativeStats}
           language={language}
+          currency={costCurrency}
           onReset={handleReset}
         />
 
@@ -682,6 +740,7 @@ export const AIWorkspace = memo(function AIWorkspace({
           sessionStats={sessionStats}
           modelInfo={modelInfo}
           language={language}
+          currency={currency}
         />
 
         <Divider style={{ margin: 'var(--space-3) 0' }} />
@@ -2,10 +2,11 @@ import React, { useState, useEffect, useRef, memo, useCallback, useMemo } from '
 import { Progress, Button, Badge } from 'antd';
 import { CheckOutlined, ThunderboltOutlined } from '@ant-design/icons';
 import { useVirtualizer } from '@tanstack/react-virtual';
-import { POEntry } from '../types/tauri';
+import { POEntry, TranslationSource } from '../types/tauri';
 import { useUpdateEntry } from '../store';
 import { CSS_COLORS } from '../hooks/useCssColors';
 import { createModuleLogger } from '../utils/logger';
+import { parseTranslationSource } from '../utils/translationSource';
 import {
   announceToScreenReader,
   getBatchActionAriaLabel,
@@ -90,9 +91,9 @@ const SelectionActions = memo(function SelectionActions({
   );
 });
 
-// 获取翻译来源样式
+// 获取翻译来源样式（来自命名记忆库时 title 为记忆库名称）
 function getSourceStyle(
-  source: 'tm' | 'dedup' | 'ai' | undefined,
+  source: TranslationSource | undefined,
   colors: {
     sourceTmBg: string;
     sourceTmColor: string;
@@ -104,10 +105,12 @@ function getSourceStyle(
 ) {
   const styles = {
     tm: { bg: colors.sourceTmBg, color: colors.sourceTmColor, label: '记忆' },
+    fuzzy: { bg: colors.sourceTmBg, color: colors.sourceTmColor, label: '模糊' },
     dedup: { bg: colors.sourceDedupBg, color: colors.sourceDedupColor, label: '去重' },
     ai: { bg: colors.sourceAiBg, color: colors.sourceAiColor, label: 'AI' },
   };
-  return styles[source || 'ai'];
+  const { kind, memory } = parseTranslationSource(source);
+  return { ...styles[kind], title: memory ? `记忆库：${memory}` : undefined };
 }
 
 // 获取条目状态CSS类名
@@ -156,6 +159,9 @@ const renderVirtualItem = (
   const isCurrent = currentEntry === entry;
   const status = getEntryStatus(entry) as 'untranslated' | 'needs-review' | 'translated' | 'empty';
   const statusClass = getStatusClassName(entry);
+  const sourceStyle = entry.translationSource
+    ? getSourceStyle(entry.translationSource, CSS_COLORS)
+    : null;
 
   return (
     <div
@@ -185,15 +191,16 @@ const renderVirtualItem = (
     >
       <div className={styles.virtualItemMeta}>
         <span className={styles.indexLabel}>#{globalIndex + 1}</span>
-        {status === 'needs-review' && entry.translationSource && (
+        {status === 'needs-review' && sourceStyle && (
           <span
             className={styles.sourceBadge}
+            title={sourceStyle.title}
             style={{
-              backgroundColor: getSourceStyle(entry.translationSource, CSS_COLORS).bg,
-              color: getSourceStyle(entry.translationSource, CSS_COLORS).color,
+              backgroundColor: sourceStyle.bg,
+              color: sourceStyle.color,
             }}
           >
-            {getSourceStyle(entry.translationSource, CSS_COLORS).label}
+            {sourceStyle.label}
           </span>
         )}
       </div>
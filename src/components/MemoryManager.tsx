@@ -1,5 +1,5 @@
 import { useState, useEffect, useMemo } from 'react';
-import { Modal, Table, Input, Button, message, Space, Popconfirm, Tag } from 'antd';
+import { Modal, Table, Input, Button, message, Space, Popconfirm, Tag, Select } from 'antd';
 import {
   DeleteOutlined,
   PlusOutlined,
@@ -7,6 +7,7 @@ import {
   ClearOutlined,
   ExportOutlined,
   ImportOutlined,
+  BarChartOutlined,
 } from '@ant-design/icons';
 import { save, open } from '@tauri-apps/plugin-dialog';
 import { writeTextFile, readTextFile } from '@tauri-apps/plugin-fs';
@@ -14,7 +15,8 @@ import { translationMemoryCommands } from '../services/termCommands';
 import { createModuleLogger } from '../utils/logger';
 import { useTranslationMemory } from '../hooks/useTranslationMemory';
 import { useSupportedLanguages } from '../hooks/useLanguage';
-import { useStatsStore } from '../store';
+import { useStatsStore, useMemoryStack, useSetMemoryStack } from '../store';
+import type { MemoryInfo } from '../types/generated/MemoryInfo';
 
 const log = createModuleLogger('MemoryManager');
 
@@ -23,13 +25,22 @@ interface MemoryEntry {
   source: string;
   target: string;
   language?: string;
+  sourceLanguage?: string;
+  context?: string;
 }
 
-const buildMemoryKey = (source: string, language?: string): string => {
-  if (language) {
-    return `${source}|${language}`;
+// 记忆库键：[上下文\u0004]原文|[源语言>]目标语言（与后端 TmKey 一致）
+const CONTEXT_SEPARATOR = '\u0004';
+
+const buildMemoryKey = (entry: Omit<MemoryEntry, 'key' | 'target'>): string => {
+  const body = entry.context ? `${entry.context}${CONTEXT_SEPARATOR}${entry.source}` : entry.source;
+  if (entry.language) {
+    const langs = entry.sourceLanguage
+      ? `${entry.sourceLanguage}>${entry.language}`
+      : entry.language;
+    return `${body}|${langs}`;
   }
-  return source;
+  return body;
 };
 
 interface MemoryManagerProps {
@@ -40,7 +51,13 @@ interface MemoryManagerProps {
 export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
   const [memories, setMemories] = useState<MemoryEntry[]>([]);
   const [loading, setLoading] = useState(false);
-  const { tm, isLoading: loadingTM, mutate } = useTranslationMemory();
+  // 项目记忆库（为 null 时即默认记忆库）：本界面的编辑、导入导出与清理都作用于它
+  const memoryStack = useMemoryStack();
+  const setMemoryStack = useSetMemoryStack();
+  const projectMemory = memoryStack.projectMemory;
+  const [memoryList, setMemoryList] = useState<MemoryInfo[]>([]);
+  const [newMemoryName, setNewMemoryName] = useState('');
+  const { tm, isLoading: loadingTM, mutate } = useTranslationMemory(projectMemory);
   const { languages } = useSupportedLanguages(); // 从后端动态获取语言列表
   const [searchText, setSearchText] = useState('');
   const [newSource, setNewSource] = useState('');
@@ -57,25 +74,46 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
 
   const parseMemoryKey = useMemo(
     () =>
-      (key: string): { source: string; language?: string } => {
+      (key: string): Omit<MemoryEntry, 'key' | 'target'> => {
+        const splitContext = (body: string) => {
+          const pos = body.indexOf(CONTEXT_SEPARATOR);
+          return pos >= 0
+            ? { context: body.slice(0, pos), source: body.slice(pos + 1) }
+            : { source: body };
+        };
         const parts = key.split('|');
 
         if (parts.length >= 2) {
           const lastPart = parts[parts.length - 1];
-          if (languageConfig[lastPart]) {
-            const source = parts.slice(0, -1).join('|');
-            return { source, language: lastPart };
+          const arrow = lastPart.indexOf('>');
+          const language = lastPart.slice(arrow + 1);
+          const sourceLanguage = arrow >= 0 ? lastPart.slice(0, arrow) : undefined;
+          if (languageConfig[language]) {
+            return {
+              ...splitContext(parts.slice(0, -1).join('|')),
+              language,
+              sourceLanguage,
+            };
           }
         }
 
-        return { source: key, language: undefined };
+        return splitContext(key);
       },
     [languageConfig]
   );
 
+  const loadMemoryList = async () => {
+    try {
+      setMemoryList(await translationMemoryCommands.list());
+    } catch (error) {
+      log.logError(error, '加载记忆库列表失败');
+    }
+  };
+
   useEffect(() => {
     if (visible) {
       mutate();
+      loadMemoryList();
     }
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [visible]);
@@ -84,15 +122,11 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
     if (visible) {
       if (tm && (tm as any).memory) {
         const entries: MemoryEntry[] = Object.entries((tm as any).memory).map(
-          ([memoryKey, target], index) => {
-            const { source, language } = parseMemoryKey(memoryKey);
-            return {
-              key: `${index}`,
-              source,
-              target: target as string,
-              language,
-            };
-          }
+          ([memoryKey, target], index) => ({
+            ...parseMemoryKey(memoryKey),
+            key: `${index}`,
+            target: target as string,
+          })
         );
         setMemories(entries);
         log.info('记忆库加载成功', { count: entries.length });
@@ -141,19 +175,22 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
     try {
       const memoryMap: Record<string, string> = {};
       memories.forEach((entry) => {
-        const key = buildMemoryKey(entry.source, entry.language);
+        const key = buildMemoryKey(entry);
         memoryMap[key] = entry.target;
       });
 
-      await translationMemoryCommands.save({
-        memory: memoryMap,
-        stats: {
-          total_entries: memories.length,
-          hits: 0,
-          misses: 0,
+      await translationMemoryCommands.save(
+        {
+          memory: memoryMap,
+          stats: {
+            total_entries: memories.length,
+            hits: 0,
+            misses: 0,
+          },
+          last_updated: new Date().toISOString(),
         },
-        last_updated: new Date().toISOString(),
-      });
+        projectMemory
+      );
 
       message.success('记忆库已保存');
       await mutate();
@@ -174,17 +211,20 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
       setLoading(true);
       setMemories([]);
 
-      await translationMemoryCommands.save({
-        memory: {},
-        stats: {
-          total_entries: 0,
-          hits: 0,
-          misses: 0,
+      await translationMemoryCommands.save(
+        {
+          memory: {},
+          stats: {
+            total_entries: 0,
+            hits: 0,
+            misses: 0,
+          },
+          last_updated: new Date().toISOString(),
         },
-        last_updated: new Date().toISOString(),
-      });
+        projectMemory
+      );
 
-      const freshTM = await translationMemoryCommands.get();
+      const freshTM = await translationMemoryCommands.get(projectMemory);
       log.debug('清空后重新获取记忆库', { hasTM: !!freshTM });
 
       await mutate(freshTM, false);
@@ -209,25 +249,21 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
     try {
       setLoading(true);
 
-      const addedCount = await translationMemoryCommands.mergeBuiltinPhrases();
+      const addedCount = await translationMemoryCommands.mergeBuiltinPhrases(projectMemory);
       log.info('内置词库合并完成', { addedCount });
 
-      const freshTM = await translationMemoryCommands.get();
+      const freshTM = await translationMemoryCommands.get(projectMemory);
       log.debug('重新获取记忆库', { hasTM: !!freshTM });
 
       await mutate(freshTM, false);
 
       if (freshTM && (freshTM as any).memory) {
         const entries: MemoryEntry[] = Object.entries((freshTM as any).memory).map(
-          ([memoryKey, target], index) => {
-            const { source, language } = parseMemoryKey(memoryKey);
-            return {
-              key: `${index}`,
-              source,
-              target: target as string,
-              language,
-            };
-          }
+          ([memoryKey, target], index) => ({
+            ...parseMemoryKey(memoryKey),
+            key: `${index}`,
+            target: target as string,
+          })
         );
         setMemories(entries);
         log.info('记忆库界面已更新', { count: entries.length });
@@ -257,7 +293,7 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
       if (filePath) {
         const memoryMap: Record<string, string> = {};
         memories.forEach((entry) => {
-          const key = buildMemoryKey(entry.source, entry.language);
+          const key = buildMemoryKey(entry);
           memoryMap[key] = entry.target;
         });
 
@@ -297,12 +333,10 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
         if (data.memory) {
           const entries: MemoryEntry[] = Object.entries(data.memory).map(
             ([memoryKey, target], index) => {
-              const { source, language } = parseMemoryKey(memoryKey);
               return {
+                ...parseMemoryKey(memoryKey),
                 key: `${index}`,
-                source,
                 target: target as string,
-                language,
               };
             }
           );
@@ -315,6 +349,217 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
     }
   };
 
+  const handleImportTmx = async () => {
+    try {
+      const filePath = await open({
+        filters: [{ name: 'TMX', extensions: ['tmx'] }],
+        multiple: false,
+      });
+      if (!filePath || typeof filePath !== 'string') return;
+
+      setLoading(true);
+      const report = await translationMemoryCommands.importTmx(filePath, projectMemory);
+      log.info('TMX 导入完成', report);
+      await mutate(await translationMemoryCommands.get(projectMemory), false);
+      message.success(
+        `已导入 ${report.pairs} 对译文（短语 ${report.phrases} 条，句段 ${report.segments} 条）`
+      );
+    } catch (error) {
+      log.logError(error, '导入 TMX 失败');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 对齐历史译文：JSON 按英文源文件配对，冲突的原文不写入，列出供人工处理
+  const handleImportCatalogs = async () => {
+    try {
+      const directory = await open({ directory: true, multiple: false });
+      if (!directory || typeof directory !== 'string') return;
+
+      setLoading(true);
+      const report = await translationMemoryCommands.importCatalogs({
+        directory,
+        sourceLanguage: 'en',
+        memoryName: projectMemory,
+      });
+      log.info('历史译文对齐导入完成', report);
+      await mutate(await translationMemoryCommands.get(projectMemory), false);
+      message.success(
+        `已扫描 ${report.files} 个文件：短语 ${report.phrases} 条，句段 ${report.segments} 条，` +
+          `未变 ${report.unchanged} 条，跳过 fuzzy ${report.fuzzy} 条、` +
+          `与原文相同 ${report.sameAsSource} 条`
+      );
+      if (report.conflicts.length > 0) {
+        Modal.warning({
+          title: `${report.conflicts.length} 条原文存在冲突译文，未导入`,
+          width: 640,
+          content: (
+            <ul style={{ maxHeight: 360, overflow: 'auto', paddingLeft: 'var(--space-4)' }}>
+              {report.conflicts.slice(0, 50).map((conflict, index) => (
+                <li key={index}>
+                  {conflict.context ? `[${conflict.context}] ` : ''}
+                  {conflict.source}（{conflict.targetLang}）：
+                  {[
+                    ...(conflict.existing ? [`记忆库：${conflict.existing}`] : []),
+                    ...conflict.variants.map((v) => `${v.target}（${v.files.length} 个文件）`),
+                  ].join(' / ')}
+                </li>
+              ))}
+            </ul>
+          ),
+        });
+      }
+    } catch (error) {
+      log.logError(error, '导入历史译文失败');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 质量清理：先预览将删除的条目（译文同原文、含未翻译占位），确认后执行；同时提示冲突条数
+  const handleCleanup = async () => {
+    try {
+      setLoading(true);
+      const [preview, duplicates] = await Promise.all([
+        translationMemoryCommands.cleanup({ dryRun: true }, projectMemory),
+        translationMemoryCommands.findDuplicates(null, projectMemory),
+      ]);
+      setLoading(false);
+      const conflicts = duplicates.filter((group) => group.kind === 'conflict').length;
+      const total = preview.sameAsSource + preview.placeholders;
+      if (total === 0) {
+        const hint = conflicts > 0 ? `（${conflicts} 组原文存在冲突译文）` : '';
+        message.info(`没有需要清理的条目${hint}`);
+        return;
+      }
+      Modal.confirm({
+        title: `清理 ${total} 条低质量记忆？`,
+        width: 640,
+        content: (
+          <div>
+            <p>
+              译文与原文相同 {preview.sameAsSource} 条，含未翻译占位 {preview.placeholders} 条
+              {conflicts > 0 ? `；另有 ${conflicts} 组原文存在冲突译文，需人工处理` : ''}
+            </p>
+            <ul style={{ maxHeight: 300, overflow: 'auto', paddingLeft: 'var(--space-4)' }}>
+              {preview.removed.slice(0, 50).map((record) => (
+                <li key={`${record.tier}:${record.key}`}>
+                  {record.source} → {record.target}
+                </li>
+              ))}
+            </ul>
+          </div>
+        ),
+        okText: '清理',
+        okButtonProps: { danger: true },
+        cancelText: '取消',
+        onOk: async () => {
+          const report = await translationMemoryCommands.cleanup({ dryRun: false }, projectMemory);
+          await mutate(await translationMemoryCommands.get(projectMemory), false);
+          message.success(`已清理 ${report.sameAsSource + report.placeholders} 条记忆`);
+        },
+      });
+    } catch (error) {
+      setLoading(false);
+      log.logError(error, '清理记忆库失败');
+    }
+  };
+
+  // 使用统计：命中最多的条目与从未命中的条目（后者可考虑清理）
+  const handleShowUsage = async () => {
+    try {
+      const report = await translationMemoryCommands.usage(20, projectMemory);
+      const renderRecords = (records: typeof report.mostValuable, showHits: boolean) => (
+        <ul style={{ maxHeight: 240, overflow: 'auto', paddingLeft: 'var(--space-4)' }}>
+          {records.map((record) => (
+            <li key={`${record.tier}:${record.key}`}>
+              {record.source} → {record.target}
+              {showHits ? `（命中 ${record.hits} 次）` : ''}
+            </li>
+          ))}
+        </ul>
+      );
+      Modal.info({
+        title: '记忆库使用统计',
+        width: 640,
+        content: (
+          <div>
+            <p>
+              共 {report.total} 条，命中过 {report.used} 条，累计命中 {report.totalHits} 次，
+              从未命中 {report.total - report.used} 条
+            </p>
+            <h4>最有价值</h4>
+            {renderRecords(report.mostValuable, true)}
+            <h4>从未命中（最早的在前）</h4>
+            {renderRecords(report.neverUsed, false)}
+          </div>
+        ),
+      });
+    } catch (error) {
+      log.logError(error, '统计记忆库使用情况失败');
+    }
+  };
+
+  const handleExportTmx = async () => {
+    try {
+      const filePath = await save({
+        filters: [{ name: 'TMX', extensions: ['tmx'] }],
+        defaultPath: 'translation_memory.tmx',
+      });
+      if (!filePath) return;
+
+      const count = await translationMemoryCommands.exportTmx(filePath, undefined, projectMemory);
+      message.success(`已导出 ${count} 个译文单元`);
+    } catch (error) {
+      log.logError(error, '导出 TMX 失败');
+    }
+  };
+
+  // 切换项目记忆库时从参考列表中去掉它（同一记忆库不会同时作为项目与参考）
+  const handleSelectProject = (name: string) => {
+    const next = name === 'default' ? null : name;
+    setMemoryStack({
+      projectMemory: next,
+      referenceMemories: memoryStack.referenceMemories.filter((ref) => ref !== name),
+    });
+  };
+
+  const handleCreateMemory = async () => {
+    const name = newMemoryName.trim();
+    if (!name) {
+      message.warning('请输入记忆库名称');
+      return;
+    }
+    try {
+      await translationMemoryCommands.create(name);
+      setNewMemoryName('');
+      await loadMemoryList();
+      handleSelectProject(name);
+      message.success(`已新建记忆库 ${name}`);
+    } catch (error) {
+      log.logError(error, '新建记忆库失败');
+    }
+  };
+
+  const handleDeleteMemory = async () => {
+    if (!projectMemory) return;
+    try {
+      await translationMemoryCommands.remove(projectMemory);
+      setMemoryStack({
+        projectMemory: null,
+        referenceMemories: memoryStack.referenceMemories.filter((ref) => ref !== projectMemory),
+      });
+      await loadMemoryList();
+      message.success(`已删除记忆库 ${projectMemory}`);
+    } catch (error) {
+      log.logError(error, '删除记忆库失败');
+    }
+  };
+
+  const memoryLabel = (info: MemoryInfo) =>
+    `${info.name === 'default' ? '默认' : info.name}（${info.phrases + info.segments}）`;
+
   const handleAdd = () => {
     if (!newSource || !newTarget) {
       message.warning('请输入原文和译文');
@@ -354,6 +599,7 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
       render: (text: string, record: MemoryEntry) => (
         <Input
           value={text}
+          addonBefore={record.context}
           onChange={(e) => handleEdit(record.key, 'source', e.target.value)}
           size="small"
         />
@@ -430,6 +676,54 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
       }}
     >
       <div style={{ marginBottom: 'var(--space-4)' }}>
+        <Space wrap style={{ marginBottom: 'var(--space-3)', width: '100%' }}>
+          <span>项目记忆库</span>
+          <Select
+            style={{ width: 200 }}
+            value={projectMemory ?? 'default'}
+            onChange={handleSelectProject}
+            options={memoryList.map((info) => ({ value: info.name, label: memoryLabel(info) }))}
+          />
+          <span>参考记忆库</span>
+          <Select
+            mode="multiple"
+            allowClear
+            style={{ minWidth: 240 }}
+            placeholder="按优先级选择（只读）"
+            value={memoryStack.referenceMemories}
+            onChange={(referenceMemories: string[]) =>
+              setMemoryStack({ projectMemory, referenceMemories })
+            }
+            options={memoryList
+              .filter((info) => info.name !== (projectMemory ?? 'default'))
+              .map((info) => ({ value: info.name, label: memoryLabel(info) }))}
+          />
+          <Space.Compact>
+            <Input
+              placeholder="新记忆库名称"
+              value={newMemoryName}
+              onChange={(e) => setNewMemoryName(e.target.value)}
+              onPressEnter={handleCreateMemory}
+            />
+            <Button icon={<PlusOutlined />} onClick={handleCreateMemory}>
+              新建
+            </Button>
+          </Space.Compact>
+          <Popconfirm
+            title={`确定删除记忆库 ${projectMemory ?? ''} 吗？`}
+            description="此操作不可恢复！"
+            onConfirm={handleDeleteMemory}
+            okText="确定"
+            cancelText="取消"
+            okButtonProps={{ danger: true }}
+            disabled={!projectMemory}
+          >
+            <Button danger icon={<DeleteOutlined />} disabled={!projectMemory}>
+              删除记忆库
+            </Button>
+          </Popconfirm>
+        </Space>
+
         <Space
           style={{ marginBottom: 'var(--space-3)', width: '100%', justifyContent: 'space-between' }}
         >
@@ -440,6 +734,21 @@ export function MemoryManager({ visible, onClose }: MemoryManagerProps) {
             <Button icon={<ExportOutlined />} onClick={handleExport}>
               导出
             </Button>
+            <Button icon={<ImportOutlined />} onClick={handleImportTmx}>
+              导入 TMX
+            </Button>
+            <Button icon={<ExportOutlined />} onClick={handleExportTmx}>
+              导出 TMX
+            </Button>
+            <Button icon={<ImportOutlined />} onClick={handleImportCatalogs}>
+              对齐历史译文
+            </Button>
+            <Button icon={<ClearOutlined />} onClick={handleCleanup}>
+              质量清理
+            </Button>
+            <Button icon={<BarChartOutlined />} onClick={handleShowUsage}>
+              使用统计
+            </Button>
             <Button icon={<PlusOutlined />} onClick={handleLoadBuiltin}>
               加载内置词库
             </Button>
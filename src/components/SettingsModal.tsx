@@ -10,11 +10,13 @@ import {
   BgColorsOutlined,
   BellOutlined,
   InfoCircleOutlined,
+  DollarOutlined,
 } from '@ant-design/icons';
 import AIConfigTab from './settings/AIConfigTab';
 import SystemPromptTab from './settings/SystemPromptTab';
 import AppearanceTab from './settings/AppearanceTab';
 import NotificationTab from './settings/NotificationTab';
+import CurrencyTab from './settings/CurrencyTab';
 import LogsTab from './settings/LogsTab';
 
 interface SettingsModalProps {
@@ -88,6 +90,22 @@ export function SettingsModal({ visible, onClose }: SettingsModalProps) {
       ),
       children: <NotificationTab />,
     },
+    {
+      key: 'currency',
+      label: (
+        <span
+          style={{
+            display: 'flex',
+            alignItems: 'center',
+            gap: 'var(--space-2)',
+            fontSize: 'var(--font-size-base)',
+          }}
+        >
+          <DollarOutlined /> 货币
+        </span>
+      ),
+      children: <CurrencyTab />,
+    },
     {
       key: 'logs',
       label: (
@@ -3,18 +3,22 @@ import { TranslationOutlined } from '@ant-design/icons';
 import { Input, Badge } from 'antd';
 import { CSS_COLORS } from '../../hooks/useCssColors';
 import { SectionHeader } from '../ui/SectionHeader';
-import { POEntry } from '../../types/tauri';
+import { POEntry, TranslationSource } from '../../types/tauri';
+import { parseTranslationSource } from '../../utils/translationSource';
 
 const { TextArea } = Input;
 
-// 获取翻译来源样式
-function getSourceStyle(source: 'tm' | 'dedup' | 'ai' | undefined, colors: typeof CSS_COLORS) {
+// 获取翻译来源样式（来自命名记忆库时标签附带名称）
+function getSourceStyle(source: TranslationSource | undefined, colors: typeof CSS_COLORS) {
   const styles = {
     tm: { bg: colors.sourceTmBg, color: colors.sourceTmColor, label: '记忆库' },
+    fuzzy: { bg: colors.sourceTmBg, color: colors.sourceTmColor, label: '模糊匹配' },
     dedup: { bg: colors.sourceDedupBg, color: colors.sourceDedupColor, label: '去重' },
     ai: { bg: colors.sourceAiBg, color: colors.sourceAiColor, label: 'AI翻译' },
   };
-  return styles[source || 'ai'];
+  const { kind, memory } = parseTranslationSource(source);
+  const style = styles[kind];
+  return memory ? { ...style, label: `${style.label}（${memory}）` } : style;
 }
 
 interface TargetSectionProps {
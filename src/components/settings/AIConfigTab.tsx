@@ -12,6 +12,7 @@ import {
   Space,
   AutoComplete,
   Empty,
+  InputNumber,
 } from 'antd';
 import {
   PlusOutlined,
@@ -21,7 +22,7 @@ import {
   ApiOutlined,
 } from '@ant-design/icons';
 import { aiConfigCommands, aiModelCommands, aiProviderCommands } from '../../services/aiCommands';
-import type { AIConfig } from '../../types/aiProvider';
+import type { AIConfig, GenerationParams } from '../../types/aiProvider';
 import { createModuleLogger } from '../../utils/logger';
 import { useAIConfigs } from '../../hooks/useConfig';
 import type { ProviderInfo } from '../../types/generated/ProviderInfo';
@@ -44,6 +45,43 @@ function mapProviderInfoToConfig(provider: ProviderInfo): ProviderConfig {
   };
 }
 
+type GenerationFormValues = {
+  temperature?: number | null;
+  topP?: number | null;
+  maxTokens?: number | null;
+  extraBody?: string;
+};
+
+type AIConfigFormValues = Omit<AIConfig, 'generation'> & { generation?: GenerationFormValues };
+
+function toGenerationParams(values?: GenerationFormValues): GenerationParams {
+  const extraBody = values?.extraBody?.trim();
+  return {
+    temperature: values?.temperature ?? null,
+    topP: values?.topP ?? null,
+    maxTokens: values?.maxTokens ?? null,
+    extraBody: extraBody ? (JSON.parse(extraBody) as Record<string, unknown>) : {},
+  };
+}
+
+function toGenerationFormValues(params?: GenerationParams): GenerationFormValues {
+  const extraBody = params?.extraBody ?? {};
+  return {
+    temperature: params?.temperature ?? null,
+    topP: params?.topP ?? null,
+    maxTokens: params?.maxTokens ?? null,
+    extraBody: Object.keys(extraBody).length > 0 ? JSON.stringify(extraBody, null, 2) : '',
+  };
+}
+
+async function validateExtraBody(_: unknown, value?: string) {
+  if (!value?.trim()) return;
+  const parsed: unknown = JSON.parse(value);
+  if (typeof parsed !== 'object' || parsed === null || Array.isArray(parsed)) {
+    throw new Error('Extra body must be a JSON object');
+  }
+}
+
 interface AIConfigTabProps {
   onProviderChange?: (providerId: string) => void;
 }
@@ -119,7 +157,7 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
     }
   }
 
-  async function handleTestConnection(values: AIConfig) {
+  async function handleTestConnection(values: AIConfigFormValues) {
     const apiKey = values.apiKey?.trim();
     if (!apiKey) {
       message.warning('测试连接前请重新输入 API Key');
@@ -140,11 +178,15 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
               port: values.proxy.port,
             }
           : null,
+        generation: toGenerationParams(values.generation),
       };
       await aiConfigCommands.testConnection(
         testConfig.providerId,
         testConfig.apiKey,
-        testConfig.baseUrl || undefined
+        testConfig.baseUrl || undefined,
+        testConfig.model || undefined,
+        undefined,
+        testConfig.generation
       );
       message.success('连接测试成功');
       log.info('连接测试成功', { providerId: values.providerId });
@@ -175,6 +217,7 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
       model: config.model,
       apiKey: '',
       proxy: config.proxy || { enabled: false, host: '', port: '' },
+      generation: toGenerationFormValues(config.generation),
     });
   }
 
@@ -213,7 +256,32 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
     }
   }
 
-  async function handleSave(values: AIConfig) {
+  // 当前备用链（按顺序）
+  const fallbackIndices = useMemo(
+    () =>
+      configs
+        .filter((config) => config.fallbackOrder !== null)
+        .sort((a, b) => (a.fallbackOrder ?? 0) - (b.fallbackOrder ?? 0))
+        .map((config) => config.index),
+    [configs]
+  );
+
+  async function handleToggleFallback(index: number) {
+    const next = fallbackIndices.includes(index)
+      ? fallbackIndices.filter((fallback) => fallback !== index)
+      : [...fallbackIndices, index];
+    try {
+      log.info('设置备用配置', { next });
+      await aiConfigCommands.setFallbacks(next);
+      mutateAll();
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : '设置失败';
+      message.error(`设置备用配置失败: ${errorMsg}`);
+      log.error('设置备用配置失败', { error, index });
+    }
+  }
+
+  async function handleSave(values: AIConfigFormValues) {
     try {
       const apiKey = values.apiKey?.trim() ?? '';
       if (isAddingNew && !apiKey) {
@@ -234,6 +302,7 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
               port: values.proxy.port,
             }
           : null,
+        generation: toGenerationParams(values.generation),
       };
 
       log.info('保存配置', { isAddingNew, editingIndex, providerId: config.providerId });
@@ -328,6 +397,28 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
                         启用中
                       </Tag>
                     )}
+                    {activeIndex !== index &&
+                      (config.fallbackOrder !== null ? (
+                        <Tag
+                          color="blue"
+                          closable
+                          onClose={(event) => {
+                            event.preventDefault();
+                            handleToggleFallback(config.index);
+                          }}
+                          title="启用的配置重试失败后按顺序切换到备用配置"
+                        >
+                          备用 {config.fallbackOrder + 1}
+                        </Tag>
+                      ) : (
+                        <Button
+                          size="small"
+                          onClick={() => handleToggleFallback(config.index)}
+                          title="启用的配置重试失败后按顺序切换到备用配置"
+                        >
+                          设为备用
+                        </Button>
+                      ))}
                     <Button
                       size="small"
                       type="link"
@@ -443,6 +534,31 @@ export function AIConfigTab({ onProviderChange }: AIConfigTabProps) {
               />
             </Form.Item>
 
+            <Space size="middle" wrap>
+              <Form.Item
+                label="Temperature"
+                name={['generation', 'temperature']}
+                tooltip="Leave empty to use the model default (0.2 for translation)"
+              >
+                <InputNumber min={0} max={2} step={0.1} placeholder="Default" />
+              </Form.Item>
+              <Form.Item label="Top P" name={['generation', 'topP']}>
+                <InputNumber min={0.01} max={1} step={0.05} placeholder="Default" />
+              </Form.Item>
+              <Form.Item label="Max Tokens" name={['generation', 'maxTokens']}>
+                <InputNumber min={1} precision={0} placeholder="Default" />
+              </Form.Item>
+            </Space>
+
+            <Form.Item
+              label="Extra Body (JSON)"
+              name={['generation', 'extraBody']}
+              rules={[{ validator: validateExtraBody, message: 'Please enter a JSON object' }]}
+              extra='Merged into the request body, e.g. {"enable_thinking": false}'
+            >
+              <Input.TextArea rows={2} placeholder="Optional" />
+            </Form.Item>
+
             <Form.Item>
               <Space>
                 <Button type="primary" htmlType="submit" loading={testing} icon={<CheckOutlined />}>
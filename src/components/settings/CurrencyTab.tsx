@@ -0,0 +1,105 @@
+import { useEffect, useState } from 'react';
+import { Card, Form, Select, Button, message } from 'antd';
+import { DollarOutlined, ReloadOutlined } from '@ant-design/icons';
+import { configCommands } from '../../services/configCommands';
+import { currencyCommands } from '../../services/translationCommands';
+import { useAppConfig } from '../../hooks/useConfig';
+import { createModuleLogger } from '../../utils/logger';
+import { CSS_COLORS } from '../../hooks/useCssColors';
+import type { CurrencySettings } from '../../types/generated/CurrencySettings';
+
+const log = createModuleLogger('CurrencyTab');
+
+export function CurrencyTab() {
+  const [settings, setSettings] = useState<CurrencySettings | null>(null);
+  const [loading, setLoading] = useState(false);
+  const { mutate: refreshConfig } = useAppConfig();
+
+  useEffect(() => {
+    currencyCommands
+      .getSettings()
+      .then(setSettings)
+      .catch((err) => log.error('加载货币设置失败:', err));
+  }, []);
+
+  async function handleCurrencyChange(currency: string) {
+    setLoading(true);
+    try {
+      // 预算上限由后端按汇率换算，已记录的花费由统计面板换算
+      await configCommands.update({ currency });
+      setSettings(await currencyCommands.getSettings());
+      await refreshConfig();
+      message.success(`成本显示货币已切换为 ${currency}`);
+      log.info('显示货币已切换', { currency });
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : '保存失败';
+      message.error(errorMsg);
+      log.error('切换显示货币失败', { error });
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  async function handleReloadRates() {
+    setLoading(true);
+    try {
+      setSettings(await currencyCommands.reloadRates());
+      message.success('汇率已重新加载');
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : '重新加载失败';
+      message.error(errorMsg);
+      log.error('重新加载汇率失败', { error });
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  const rates = Object.entries(settings?.rates ?? {});
+
+  return (
+    <Card
+      title={
+        <span>
+          <DollarOutlined /> 成本货币
+        </span>
+      }
+      size="small"
+    >
+      <p
+        style={{
+          marginBottom: 'var(--space-4)',
+          color: CSS_COLORS.textSecondary,
+          fontSize: 'var(--font-size-base)',
+        }}
+      >
+        所有成本、预算上限与用量统计均以所选货币显示，插件价格按离线汇率表换算。汇率可在
+        {settings?.ratesFile ? <code> {settings.ratesFile} </code> : ' currency_rates.toml '}
+        中覆盖或补充（1 USD = ? 目标货币），修改后点击重新加载。
+      </p>
+
+      <Form layout="vertical">
+        <Form.Item label="显示货币" tooltip="切换后已记录的花费与预算上限按汇率换算">
+          <Select
+            value={settings?.currency}
+            onChange={handleCurrencyChange}
+            loading={loading}
+            disabled={loading || !settings}
+            showSearch
+            options={rates.map(([code, rate]) => ({
+              value: code,
+              label: code === 'USD' ? code : `${code}（1 USD = ${rate} ${code}）`,
+            }))}
+          />
+        </Form.Item>
+
+        <Form.Item style={{ marginBottom: 0 }}>
+          <Button icon={<ReloadOutlined />} onClick={handleReloadRates} loading={loading}>
+            重新加载汇率
+          </Button>
+        </Form.Item>
+      </Form>
+    </Card>
+  );
+}
+
+export default CurrencyTab;
@@ -2,7 +2,8 @@ import { useState, useCallback, useRef } from 'react';
 import { Channel } from '@tauri-apps/api/core';
 import { invoke } from '../services/tauriInvoke';
 import { createModuleLogger } from '../utils/logger';
-import type { TranslationStats, TokenStats } from '../types/tauri';
+import type { TranslationSource, TranslationStats, TokenStats } from '../types/tauri';
+import type { TmQueryContext } from '../types/generated/TmQueryContext';
 
 const log = createModuleLogger('useChannelTranslation');
 
@@ -13,6 +14,7 @@ export interface BatchProgressEvent {
   total: number;
   percentage: number;
   text?: string;
+  index?: number | null; // 条目在本次任务文本中的下标
   task_id?: number; // 新增：任务ID，用于取消翻译
 }
 
@@ -21,6 +23,7 @@ export interface BatchStatsEvent {
   tm_hits: number;
   deduplicated: number;
   ai_translated: number;
+  cache_hits: number;
   token_stats: TokenStatsEvent;
   tm_learned: number;
 }
@@ -29,6 +32,7 @@ export interface TokenStatsEvent {
   total_tokens: number;
   prompt_tokens: number;
   completion_tokens: number;
+  reasoning_tokens: number;
   cost: number;
 }
 
@@ -36,12 +40,19 @@ export type { TranslationStats, TokenStats };
 
 export interface BatchResult {
   translations: string[];
-  translation_sources: string[];
+  translation_sources: TranslationSource[];
   stats: TranslationStats;
 }
 
+export interface TranslationProvider {
+  provider_id: string;
+  model: string;
+}
+
 export interface BatchResultWithTaskId extends BatchResult {
   task_id: number;
+  /** 与 translation_sources 对应：AI 译文记录实际使用的供应商/模型（含备用切换） */
+  translation_providers: (TranslationProvider | null)[];
 }
 
 export interface TranslationCallbacks {
@@ -66,7 +77,9 @@ export const useChannelTranslation = () => {
     async (
       texts: string[],
       targetLanguage: string,
-      callbacks?: TranslationCallbacks
+      callbacks?: TranslationCallbacks,
+      filePath?: string | null,
+      tmContext?: TmQueryContext
     ): Promise<BatchResult> => {
       if (texts.length === 0) {
         throw new Error('没有需要翻译的文本');
@@ -92,7 +105,7 @@ export const useChannelTranslation = () => {
           const total = progressEvent.total;
           const percentage = progressEvent.percentage;
           const text = progressEvent.text;
-          const index: number | null = null;
+          const index = progressEvent.index ?? null;
           const taskId = progressEvent.task_id ?? null;
 
           // 如果事件中包含任务ID，立即保存（用于取消翻译）
@@ -129,6 +142,8 @@ export const useChannelTranslation = () => {
           {
             texts,
             targetLanguage,
+            filePath: filePath ?? null,
+            tmContext: tmContext ?? null,
             progressChannel,
             statsChannel,
           },
@@ -30,13 +30,17 @@ import {
   useResetSessionStats,
   useUpdateSessionStats,
   useUpdateCumulativeStatsAction,
+  useMemoryStack,
 } from '../store';
 import { useAsync } from './useAsync';
 import { POEntry, TranslationStats, TranslationQueueItem } from '../types/tauri';
 import type { LanguageInfo } from '../types/generated/LanguageInfo';
+import type { BudgetWarningEvent } from '../types/generated/BudgetWarningEvent';
 import { poFileCommands, dialogCommands } from '../services/fileCommands';
 import { i18nCommands, translatorCommands } from '../services/translationCommands';
+import { translationMemoryCommands } from '../services/termCommands';
 import { createModuleLogger } from '../utils/logger';
+import { sourceNeedsReview } from '../utils/translationSource';
 
 const log = createModuleLogger('useTranslationFlow');
 
@@ -46,6 +50,7 @@ export function useTranslationFlow() {
   const currentEntry = useCurrentEntry();
   const currentFilePath = useCurrentFilePath();
   const isTranslating = useIsTranslating();
+  const memoryStack = useMemoryStack();
 
   // Actions
   const setEntries = useSetEntries();
@@ -63,6 +68,8 @@ export function useTranslationFlow() {
   // UI 状态
   const [translationStats, setTranslationStats] = useState<TranslationStats | null>(null);
   const [sourceLanguage, setSourceLanguage] = useState<string>('');
+  // 源语言代码（参与记忆库键），检测失败时为 null
+  const [sourceLanguageCode, setSourceLanguageCode] = useState<string | null>(null);
   const [targetLanguage, setTargetLanguage] = useState<string>('zh-CN');
 
   // 渐进式上屏队列
@@ -95,7 +102,8 @@ export function useTranslationFlow() {
       // 更新条目并标记为刚更新（触发动画）
       updateEntry(item.index, {
         msgstr: item.translation,
-        needsReview: item.source === 'ai',
+        needsReview: sourceNeedsReview(item.source),
+        translationSource: item.source,
         justUpdated: true,
       });
 
@@ -112,10 +120,12 @@ export function useTranslationFlow() {
           deduplicated: item.incrementalStats.deduplicated || 0,
           ai_translated: item.incrementalStats.aiTranslated || 0,
           tm_learned: item.incrementalStats.tmLearned || 0,
+          cache_hits: item.incrementalStats.cacheHits || 0,
           token_stats: {
             input_tokens: item.incrementalStats.tokenStats?.inputTokens || 0,
             output_tokens: item.incrementalStats.tokenStats?.outputTokens || 0,
             total_tokens: item.incrementalStats.tokenStats?.totalTokens || 0,
+            reasoning_tokens: item.incrementalStats.tokenStats?.reasoningTokens || 0,
             cost: item.incrementalStats.tokenStats?.cost || 0,
           },
         };
@@ -199,6 +209,42 @@ export function useTranslationFlow() {
     };
   }, [updateSessionStats, updateCumulativeStats]);
 
+  // 预算预警监听
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+    let isActive = true;
+
+    const scopeLabels: Record<BudgetWarningEvent['scope'], string> = {
+      job: '本次任务',
+      daily: '今日',
+      monthly: '本月',
+    };
+
+    const setupListener = async () => {
+      const unlisten = await listen<BudgetWarningEvent>('budget:warning', (event) => {
+        if (!isActive) return;
+        const { scope, threshold, spent, limit } = event.payload;
+        log.warn('预算预警', event.payload);
+        msg.warning(
+          `${scopeLabels[scope]}花费已达上限的 ${Math.round(threshold * 100)}%（${spent.toFixed(4)} / ${limit.toFixed(4)}）`
+        );
+      });
+
+      if (isActive) {
+        unlistenFn = unlisten;
+      } else {
+        unlisten();
+      }
+    };
+
+    setupListener().catch((err) => log.logError(err, '注册预算预警监听失败'));
+
+    return () => {
+      isActive = false;
+      unlistenFn?.();
+    };
+  }, []);
+
   // 文件拖放监听 - 修复竞态条件和依赖
   useEffect(() => {
     let unlistenFn: (() => void) | null = null;
@@ -252,6 +298,7 @@ export function useTranslationFlow() {
       if (sampleTexts) {
         const detectedLang = await i18nCommands.detectLanguage(sampleTexts);
         setSourceLanguage(detectedLang.display_name);
+        setSourceLanguageCode(detectedLang.code);
         const defaultTarget = await i18nCommands.getDefaultTargetLanguage(detectedLang.code);
         setTargetLanguage(defaultTarget.code);
         log.info('语言检测完成', {
@@ -262,6 +309,7 @@ export function useTranslationFlow() {
     } catch (error) {
       log.logError(error, '语言检测失败');
       setSourceLanguage('未知');
+      setSourceLanguageCode(null);
       setTargetLanguage('zh-CN');
     }
   };
@@ -285,6 +333,30 @@ export function useTranslationFlow() {
     }
   };
 
+  // 保存后把已确认（非待审核）的译文记入句段级记忆库；失败不影响保存结果
+  const approveSavedSegments = async (filePath: string) => {
+    const segments = entries
+      .filter((entry) => entry.msgid && entry.msgstr && !entry.needsReview)
+      .map((entry) => ({
+        source: entry.msgid,
+        target: entry.msgstr,
+        context: entry.msgctxt || null,
+      }));
+    if (segments.length === 0) return;
+    try {
+      const changed = await translationMemoryCommands.approveSegments(
+        segments,
+        targetLanguage,
+        filePath,
+        sourceLanguageCode,
+        memoryStack.projectMemory
+      );
+      log.info('已确认译文记入句段记忆库', { count: segments.length, changed });
+    } catch (error) {
+      log.logError(error, '记录已确认译文失败');
+    }
+  };
+
   const saveFile = async () => {
     if (!currentFilePath) {
       msg.warning('没有打开的文件，请使用"另存为"');
@@ -297,6 +369,7 @@ export function useTranslationFlow() {
     try {
       await poFileCommands.save(currentFilePath, entries);
       msg.success('保存成功！');
+      await approveSavedSegments(currentFilePath);
       log.info('文件保存成功', { filePath: currentFilePath });
     } catch (error) {
       log.logError(error, '保存文件失败');
@@ -315,6 +388,7 @@ export function useTranslationFlow() {
         await poFileCommands.save(filePath, entries);
         setCurrentFilePath(filePath);
         msg.success('保存成功！');
+        await approveSavedSegments(filePath);
         log.info('文件另存为成功', { filePath });
       }
     } catch (error) {
@@ -326,6 +400,8 @@ export function useTranslationFlow() {
   const executeTranslation = async (entriesToTranslate: POEntry[]) => {
     const texts = entriesToTranslate.map((e) => e.msgid);
     let completedCount = 0;
+    // 已流式上屏（入队）的条目
+    const streamed = new Set<number>();
 
     try {
       setTranslating(true);
@@ -333,67 +409,99 @@ export function useTranslationFlow() {
 
       log.info('开始翻译', { count: texts.length });
 
-      const result = await channelTranslation.translateBatch(texts, targetLanguage, {
-        onProgress: (current, _total, percentage) => {
-          setProgress(percentage);
-          completedCount = current;
-        },
-        onStats: (stats) => {
-          const convertedStats = {
-            ...stats,
-            token_stats: {
-              total_tokens: stats.token_stats.total_tokens,
-              prompt_tokens: stats.token_stats.prompt_tokens,
-              completion_tokens: stats.token_stats.completion_tokens,
-              input_tokens: stats.token_stats.prompt_tokens,
-              output_tokens: stats.token_stats.completion_tokens,
-              cost: stats.token_stats.cost,
-            },
-          } as TranslationStats;
-          setTranslationStats(convertedStats);
-
-          // 批量统计到达后，分配到队列中的每一项作为增量统计
-          const aiQueueItems = updateQueue.current.filter((item) => item.source === 'ai');
-          const queueLength = aiQueueItems.length;
-          if (queueLength > 0) {
-            const incrementalStats = {
-              tmHits: 0,
-              deduplicated: 0,
-              aiTranslated: Math.ceil(stats.ai_translated / queueLength),
-              tmLearned: Math.ceil(stats.tm_learned / queueLength),
-              tokenStats: {
-                inputTokens: Math.ceil(stats.token_stats.prompt_tokens / queueLength),
-                outputTokens: Math.ceil(stats.token_stats.completion_tokens / queueLength),
-                totalTokens: Math.ceil(stats.token_stats.total_tokens / queueLength),
-                cost: stats.token_stats.cost / queueLength,
+      const result = await channelTranslation.translateBatch(
+        texts,
+        targetLanguage,
+        {
+          onProgress: (current, _total, percentage) => {
+            setProgress(percentage);
+            completedCount = current;
+          },
+          onStats: (stats) => {
+            const convertedStats = {
+              ...stats,
+              token_stats: {
+                total_tokens: stats.token_stats.total_tokens,
+                prompt_tokens: stats.token_stats.prompt_tokens,
+                completion_tokens: stats.token_stats.completion_tokens,
+                input_tokens: stats.token_stats.prompt_tokens,
+                output_tokens: stats.token_stats.completion_tokens,
+                reasoning_tokens: stats.token_stats.reasoning_tokens,
+                cost: stats.token_stats.cost,
               },
-            };
-
-            // 仅为AI翻译项添加增量统计
-            aiQueueItems.forEach((item) => {
-              if (!item.incrementalStats) {
-                item.incrementalStats = incrementalStats;
-              }
-            });
-          }
-        },
-        onItem: (index, translation) => {
-          const entry = entriesToTranslate[index];
-          const entryIndex = getEntryIndex(entry);
-          if (entryIndex >= 0) {
-            // 入队而非立即更新
-            enqueueUpdate({
-              index: entryIndex,
-              translation,
-              source: 'ai',
-            });
-          }
+            } as TranslationStats;
+            setTranslationStats(convertedStats);
+
+            // 批量统计到达后，分配到队列中的每一项作为增量统计
+            const aiQueueItems = updateQueue.current.filter((item) => item.source === 'ai');
+            const queueLength = aiQueueItems.length;
+            if (queueLength > 0) {
+              const incrementalStats = {
+                tmHits: 0,
+                deduplicated: 0,
+                aiTranslated: Math.ceil(stats.ai_translated / queueLength),
+                tmLearned: Math.ceil(stats.tm_learned / queueLength),
+                cacheHits: Math.ceil(stats.cache_hits / queueLength),
+                tokenStats: {
+                  inputTokens: Math.ceil(stats.token_stats.prompt_tokens / queueLength),
+                  outputTokens: Math.ceil(stats.token_stats.completion_tokens / queueLength),
+                  totalTokens: Math.ceil(stats.token_stats.total_tokens / queueLength),
+                  reasoningTokens: Math.ceil(stats.token_stats.reasoning_tokens / queueLength),
+                  cost: stats.token_stats.cost / queueLength,
+                },
+              };
+
+              // 仅为AI翻译项添加增量统计
+              aiQueueItems.forEach((item) => {
+                if (!item.incrementalStats) {
+                  item.incrementalStats = incrementalStats;
+                }
+              });
+            }
+          },
+          onItem: (index, translation) => {
+            const entry = entriesToTranslate[index];
+            const entryIndex = getEntryIndex(entry);
+            if (entryIndex >= 0) {
+              streamed.add(entryIndex);
+              // 入队而非立即更新
+              enqueueUpdate({
+                index: entryIndex,
+                translation,
+                source: 'ai',
+              });
+            }
+          },
         },
+        currentFilePath,
+        {
+          sourceLanguage: sourceLanguageCode,
+          contexts: entriesToTranslate.map((e) => e.msgctxt || null),
+          projectMemory: memoryStack.projectMemory,
+          referenceMemories: memoryStack.referenceMemories,
+        }
+      );
+
+      // 按最终结果更正来源（记忆库/模糊匹配/去重），并补上未流式上屏的条目
+      result.translations.forEach((translation, i) => {
+        const entryIndex = getEntryIndex(entriesToTranslate[i]);
+        if (entryIndex < 0) return;
+        const source = result.translation_sources[i] ?? 'ai';
+        const queued = updateQueue.current.find((item) => item.index === entryIndex);
+        if (queued) {
+          queued.translation = translation;
+          queued.source = source;
+        } else if (streamed.has(entryIndex)) {
+          updateEntry(entryIndex, {
+            msgstr: translation,
+            needsReview: sourceNeedsReview(source),
+            translationSource: source,
+          });
+        } else {
+          enqueueUpdate({ index: entryIndex, translation, source });
+        }
       });
 
-      // 注意：由于使用渐进式上屏，不在这里立即更新条目
-      // 所有更新都通过 onItem 回调入队处理
-
       if (result.stats) {
         const finalStats: TranslationStats = {
           total: texts.length,
@@ -404,9 +512,11 @@ export function useTranslationFlow() {
             input_tokens: result.stats.token_stats.input_tokens || 0,
             output_tokens: result.stats.token_stats.output_tokens || 0,
             total_tokens: result.stats.token_stats.total_tokens || 0,
+            reasoning_tokens: result.stats.token_stats.reasoning_tokens || 0,
             cost: result.stats.token_stats.cost || 0,
           },
           tm_learned: result.stats.tm_learned || 0,
+          cache_hits: result.stats.cache_hits || 0,
         };
 
         log.info('统计已更新', finalStats);
@@ -5,8 +5,11 @@ import { translationMemoryCommands } from '../services/termCommands';
 
 const TM_KEY = 'translation_memory';
 
-export function useTranslationMemory() {
-  const { data, error, isLoading, mutate } = useSWR(TM_KEY, () => translationMemoryCommands.get(), {
+/** memoryName 为空时加载默认记忆库 */
+export function useTranslationMemory(memoryName?: string | null) {
+  const key = [TM_KEY, memoryName ?? null] as const;
+  const fetcher = () => translationMemoryCommands.get(memoryName);
+  const { data, error, isLoading, mutate } = useSWR(key, fetcher, {
     keepPreviousData: true,
     revalidateOnFocus: false,
     revalidateOnReconnect: false,
@@ -1,6 +1,7 @@
-import type { AIConfig, AIConfigSummary } from '../types/aiProvider';
+import type { AIConfig, AIConfigSummary, GenerationParams } from '../types/aiProvider';
 import type { ModelInfo } from '../types/generated/ModelInfo';
 import type { ProviderInfo } from '../types/generated/ProviderInfo';
+import type { TranslationEstimate } from '../types/generated/TranslationEstimate';
 import { invoke } from './apiClient';
 
 export const aiConfigCommands = {
@@ -31,6 +32,21 @@ export const aiConfigCommands = {
     );
   },
 
+  /** 设置备用供应商链（配置索引，按顺序尝试） */
+  async setFallbacks(indices: number[]): Promise<void> {
+    if (indices.some((index) => index < 0 || !Number.isInteger(index))) {
+      throw new Error(`无效的配置索引: ${indices.join(', ')}`);
+    }
+
+    return invoke<void>(
+      'set_fallback_ai_configs',
+      { indices },
+      {
+        errorMessage: '设置备用AI配置失败',
+      }
+    );
+  },
+
   async add(config: AIConfig): Promise<void> {
     return invoke<void>('add_ai_config', { config }, { errorMessage: '添加AI配置失败' });
   },
@@ -69,7 +85,8 @@ export const aiConfigCommands = {
     apiKey: string,
     baseUrl?: string,
     model?: string,
-    proxy?: unknown
+    proxy?: unknown,
+    generation?: GenerationParams
   ): Promise<{ success: boolean; message: string }> {
     return invoke<{ success: boolean; message: string }>(
       'test_ai_connection',
@@ -80,6 +97,7 @@ export const aiConfigCommands = {
           baseUrl: baseUrl || null,
           model: model || null,
           proxy: proxy || null,
+          ...(generation ? { generation } : {}),
         },
       },
       {
@@ -114,16 +132,43 @@ export const aiModelCommands = {
   async estimateCost(
     providerId: string,
     modelId: string,
-    totalChars: number,
+    texts: string[],
+    targetLanguage?: string,
     cacheHitRate?: number
   ): Promise<number> {
     return invoke<number>(
       'estimate_translation_cost',
-      { providerId, modelId, totalChars, cacheHitRate: cacheHitRate ?? null },
+      {
+        providerId,
+        modelId,
+        texts,
+        targetLanguage: targetLanguage ?? null,
+        cacheHitRate: cacheHitRate ?? null,
+      },
       { errorMessage: '估算成本失败' }
     );
   },
 
+  async estimateTokens(
+    providerId: string,
+    modelId: string,
+    texts: string[],
+    targetLanguage?: string,
+    cacheHitRate?: number
+  ): Promise<TranslationEstimate> {
+    return invoke<TranslationEstimate>(
+      'estimate_translation_tokens',
+      {
+        providerId,
+        modelId,
+        texts,
+        targetLanguage: targetLanguage ?? null,
+        cacheHitRate: cacheHitRate ?? null,
+      },
+      { errorMessage: '预估 token 失败' }
+    );
+  },
+
   async calculatePreciseCost(
     providerId: string,
     modelId: string,
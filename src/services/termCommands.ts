@@ -1,4 +1,19 @@
 import type { TranslationMemory } from '../types/tauri';
+import type { CatalogFilePair } from '../types/generated/CatalogFilePair';
+import type { CatalogImportReport } from '../types/generated/CatalogImportReport';
+import type { MemoryInfo } from '../types/generated/MemoryInfo';
+import type { SegmentApproval } from '../types/generated/SegmentApproval';
+import type { SegmentMemoryStats } from '../types/generated/SegmentMemoryStats';
+import type { TmCleanupOptions } from '../types/generated/TmCleanupOptions';
+import type { TmCleanupReport } from '../types/generated/TmCleanupReport';
+import type { TmDuplicateGroup } from '../types/generated/TmDuplicateGroup';
+import type { TmRecordRef } from '../types/generated/TmRecordRef';
+import type { TmReplaceReport } from '../types/generated/TmReplaceReport';
+import type { TmReplaceRequest } from '../types/generated/TmReplaceRequest';
+import type { TmSearchPage } from '../types/generated/TmSearchPage';
+import type { TmSearchQuery } from '../types/generated/TmSearchQuery';
+import type { TmUsageReport } from '../types/generated/TmUsageReport';
+import type { TmxImportReport } from '../types/generated/TmxImportReport';
 import type { TermLibrary } from '../types/termLibrary';
 import { invoke } from './apiClient';
 
@@ -33,11 +48,14 @@ export const termLibraryCommands = {
   },
 };
 
+// 以下记忆库命令的 memoryName 为空时操作默认记忆库
 export const translationMemoryCommands = {
-  async get(): Promise<TranslationMemory> {
-    return invoke<TranslationMemory>('get_translation_memory', undefined, {
-      errorMessage: '加载翻译记忆库失败',
-    });
+  async get(memoryName?: string | null): Promise<TranslationMemory> {
+    return invoke<TranslationMemory>(
+      'get_translation_memory',
+      { memoryName: memoryName ?? null },
+      { errorMessage: '加载翻译记忆库失败' }
+    );
   },
 
   async getBuiltinPhrases(): Promise<TranslationMemory> {
@@ -46,17 +64,167 @@ export const translationMemoryCommands = {
     });
   },
 
-  async mergeBuiltinPhrases(): Promise<number> {
-    return invoke<number>('merge_builtin_phrases', undefined, {
-      errorMessage: '合并内置词库失败',
-    });
+  async mergeBuiltinPhrases(memoryName?: string | null): Promise<number> {
+    return invoke<number>(
+      'merge_builtin_phrases',
+      { memoryName: memoryName ?? null },
+      { errorMessage: '合并内置词库失败' }
+    );
   },
 
-  async save(memory: Record<string, unknown>): Promise<void> {
+  async save(memory: Record<string, unknown>, memoryName?: string | null): Promise<void> {
     return invoke<void>(
       'save_translation_memory',
-      { memory },
+      { memory, memoryName: memoryName ?? null },
       { errorMessage: '保存翻译记忆库失败' }
     );
   },
+
+  /** 记录用户确认的译文到句段级记忆库，返回新增或更新的条数 */
+  async approveSegments(
+    segments: SegmentApproval[],
+    targetLanguage: string,
+    file: string | null,
+    sourceLanguage?: string | null,
+    memoryName?: string | null
+  ): Promise<number> {
+    return invoke<number>(
+      'approve_tm_segments',
+      {
+        segments,
+        targetLanguage,
+        sourceLanguage: sourceLanguage ?? null,
+        file,
+        memoryName: memoryName ?? null,
+      },
+      { errorMessage: '记录已确认译文失败' }
+    );
+  },
+
+  async getSegmentStats(memoryName?: string | null): Promise<SegmentMemoryStats> {
+    return invoke<SegmentMemoryStats>('get_segment_memory_stats', {
+      memoryName: memoryName ?? null,
+    });
+  },
+
+  async importTmx(filePath: string, memoryName?: string | null): Promise<TmxImportReport> {
+    return invoke<TmxImportReport>(
+      'import_tmx',
+      { filePath, memoryName: memoryName ?? null },
+      { errorMessage: '导入 TMX 失败' }
+    );
+  },
+
+  /** 导出短语表与句段库为 TMX，返回导出的译文单元数 */
+  async exportTmx(
+    filePath: string,
+    sourceLanguage?: string,
+    memoryName?: string | null
+  ): Promise<number> {
+    return invoke<number>(
+      'export_tmx',
+      { filePath, sourceLanguage: sourceLanguage ?? null, memoryName: memoryName ?? null },
+      { errorMessage: '导出 TMX 失败' }
+    );
+  },
+
+  /** 对齐已翻译的历史文件（目录中的 PO/JSON 或 JSON 文件对）并导入，冲突的原文只出现在报告中 */
+  async importCatalogs(options: {
+    directory?: string | null;
+    pairs?: CatalogFilePair[];
+    sourceLanguage?: string | null;
+    targetLanguage?: string | null;
+    memoryName?: string | null;
+  }): Promise<CatalogImportReport> {
+    return invoke<CatalogImportReport>(
+      'import_translated_catalogs',
+      {
+        directory: options.directory ?? null,
+        pairs: options.pairs ?? null,
+        sourceLanguage: options.sourceLanguage ?? null,
+        targetLanguage: options.targetLanguage ?? null,
+        memoryName: options.memoryName ?? null,
+      },
+      { errorMessage: '导入历史译文失败' }
+    );
+  },
+
+  /** 分页检索短语与句段（未给出的条件不过滤） */
+  async search(query: Partial<TmSearchQuery>, memoryName?: string | null): Promise<TmSearchPage> {
+    return invoke<TmSearchPage>(
+      'search_translation_memory',
+      { query, memoryName: memoryName ?? null },
+      { errorMessage: '检索记忆库失败' }
+    );
+  },
+
+  /** 批量查找替换译文，dryRun 时只返回预览 */
+  async replace(
+    request: Omit<Partial<TmReplaceRequest>, 'scope'> & {
+      find: string;
+      scope?: Partial<TmSearchQuery>;
+    },
+    memoryName?: string | null
+  ): Promise<TmReplaceReport> {
+    return invoke<TmReplaceReport>(
+      'replace_in_translation_memory',
+      { request, memoryName: memoryName ?? null },
+      { errorMessage: '批量替换失败' }
+    );
+  },
+
+  /** 删除译文与原文相同或含未翻译占位标记的条目 */
+  async cleanup(
+    options: Partial<TmCleanupOptions>,
+    memoryName?: string | null
+  ): Promise<TmCleanupReport> {
+    return invoke<TmCleanupReport>(
+      'cleanup_translation_memory',
+      { options, memoryName: memoryName ?? null },
+      { errorMessage: '清理记忆库失败' }
+    );
+  },
+
+  async findDuplicates(
+    targetLanguage?: string | null,
+    memoryName?: string | null
+  ): Promise<TmDuplicateGroup[]> {
+    return invoke<TmDuplicateGroup[]>(
+      'find_translation_memory_duplicates',
+      { targetLanguage: targetLanguage ?? null, memoryName: memoryName ?? null },
+      { errorMessage: '查找重复条目失败' }
+    );
+  },
+
+  async deleteEntries(records: TmRecordRef[], memoryName?: string | null): Promise<number> {
+    return invoke<number>(
+      'delete_translation_memory_entries',
+      { records, memoryName: memoryName ?? null },
+      { errorMessage: '删除记忆库条目失败' }
+    );
+  },
+
+  /** 命中最多与从未命中的条目（各最多 limit 条） */
+  async usage(limit?: number, memoryName?: string | null): Promise<TmUsageReport> {
+    return invoke<TmUsageReport>(
+      'get_translation_memory_usage',
+      { limit: limit ?? null, memoryName: memoryName ?? null },
+      { errorMessage: '统计记忆库使用情况失败' }
+    );
+  },
+
+  /** 记忆库列表（默认记忆库在前） */
+  async list(): Promise<MemoryInfo[]> {
+    return invoke<MemoryInfo[]>('list_translation_memories', undefined, {
+      errorMessage: '加载记忆库列表失败',
+    });
+  },
+
+  async create(name: string): Promise<void> {
+    return invoke<void>('create_translation_memory', { name }, { errorMessage: '新建记忆库失败' });
+  },
+
+  async remove(name: string): Promise<void> {
+    return invoke<void>('delete_translation_memory', { name }, { errorMessage: '删除记忆库失败' });
+  },
 };
@@ -1,11 +1,27 @@
+import type { MemoryStack } from '../store/useSessionStore';
 import type { ContextualRefineRequest } from '../types/tauri';
+import type { CurrencySettings } from '../types/generated/CurrencySettings';
+import type { PausedJobSummary } from '../types/generated/PausedJobSummary';
+import type { SpendingSummary } from '../types/generated/SpendingSummary';
+import type { UsageAggregate } from '../types/generated/UsageAggregate';
+import type { UsageGroupBy } from '../types/generated/UsageGroupBy';
 import { invoke } from './apiClient';
 
 export const translatorCommands = {
-  async translateEntry(text: string, targetLanguage?: string): Promise<string> {
+  /** 未指定记忆库组合时使用默认记忆库 */
+  async translateEntry(
+    text: string,
+    targetLanguage?: string,
+    memoryStack?: MemoryStack
+  ): Promise<string> {
     return invoke<string>(
       'translate_entry',
-      { text, targetLanguage: targetLanguage || null },
+      {
+        text,
+        targetLanguage: targetLanguage || null,
+        projectMemory: memoryStack?.projectMemory ?? null,
+        referenceMemories: memoryStack?.referenceMemories ?? [],
+      },
       { errorMessage: '翻译失败', silent: false }
     );
   },
@@ -22,6 +38,63 @@ export const translatorCommands = {
   },
 };
 
+export const budgetCommands = {
+  async getSpendingSummary(): Promise<SpendingSummary> {
+    return invoke<SpendingSummary>('get_spending_summary', undefined, {
+      errorMessage: '获取花费统计失败',
+    });
+  },
+
+  async listPausedJobs(): Promise<PausedJobSummary[]> {
+    return invoke<PausedJobSummary[]>('list_paused_translation_jobs', undefined, {
+      errorMessage: '获取暂停任务失败',
+    });
+  },
+
+  async discardPausedJob(jobId: string): Promise<void> {
+    return invoke<void>(
+      'discard_paused_translation_job',
+      { jobId },
+      { errorMessage: '丢弃暂停任务失败' }
+    );
+  },
+};
+
+export const currencyCommands = {
+  async getSettings(): Promise<CurrencySettings> {
+    return invoke<CurrencySettings>('get_currency_settings', undefined, {
+      errorMessage: '获取货币设置失败',
+    });
+  },
+
+  /** 重新读取用户汇率文件 currency_rates.toml */
+  async reloadRates(): Promise<CurrencySettings> {
+    return invoke<CurrencySettings>('reload_currency_rates', undefined, {
+      errorMessage: '重新加载汇率失败',
+    });
+  },
+};
+
+export const usageCommands = {
+  /** 按维度汇总用量，日期格式 YYYY-MM-DD（包含两端） */
+  async query(groupBy: UsageGroupBy, from?: string, to?: string): Promise<UsageAggregate[]> {
+    return invoke<UsageAggregate[]>(
+      'query_usage',
+      { groupBy, from: from ?? null, to: to ?? null },
+      { errorMessage: '查询用量失败' }
+    );
+  },
+
+  /** 导出明细为 CSV，返回导出的记录数 */
+  async exportCsv(filePath: string, from?: string, to?: string): Promise<number> {
+    return invoke<number>(
+      'export_usage_csv',
+      { filePath, from: from ?? null, to: to ?? null },
+      { errorMessage: '导出用量 CSV 失败' }
+    );
+  },
+};
+
 export const i18nCommands = {
   async getSupportedLanguages(): Promise<string[]> {
     return invoke<string[]>('get_supported_langs', undefined, {
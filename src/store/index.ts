@@ -49,13 +49,18 @@ export {
   useSetProgress,
   useResetSessionStats,
   useUpdateSessionStats,
+  useMemoryStack,
+  useSetMemoryStack,
 } from './useSessionStore';
+export type { MemoryStack } from './useSessionStore';
 export {
   useStatsStore,
   // 原子化 Selectors Hooks
   useCumulativeStats,
   useUpdateCumulativeStatsAction,
   useResetCumulativeStatsAction,
+  useCumulativeCostCurrency,
+  useConvertCumulativeCostAction,
 } from './useStatsStore';
 export { tauriStore } from './tauriStore';
 
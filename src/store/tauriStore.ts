@@ -22,14 +22,20 @@ export interface AppStoreData {
     totalTranslated: number;
     totalTokens: number;
     totalCost: number;
+    /** totalCost 的货币（旧数据无此字段，按 USD） */
+    costCurrency?: string;
     sessionCount: number;
     lastUpdated: number;
     tmHits: number;
     deduplicated: number;
     aiTranslated: number;
     tmLearned: number;
+    /** 响应缓存命中（旧数据无此字段） */
+    cacheHits?: number;
     inputTokens: number;
     outputTokens: number;
+    /** 推理 token（已计入 outputTokens，旧数据无此字段） */
+    reasoningTokens?: number;
   };
 
   // 最近文件列表
@@ -272,8 +278,10 @@ class TauriStore {
         deduplicated: 0,
         aiTranslated: 0,
         tmLearned: 0,
+        cacheHits: 0,
         inputTokens: 0,
         outputTokens: 0,
+        reasoningTokens: 0,
       }
     );
   }
@@ -21,11 +21,19 @@ const INITIAL_SESSION_STATS: TranslationStats = {
     input_tokens: 0,
     output_tokens: 0,
     total_tokens: 0,
+    reasoning_tokens: 0,
     cost: 0,
   },
   tm_learned: 0,
+  cache_hits: 0,
 };
 
+/** 翻译任务使用的记忆库：可写的项目记忆库（null 为默认记忆库）与按优先级排列的只读参考记忆库 */
+export interface MemoryStack {
+  projectMemory: string | null;
+  referenceMemories: string[];
+}
+
 interface SessionState {
   // 翻译状态
   isTranslating: boolean;
@@ -35,6 +43,9 @@ interface SessionState {
   // 本次会话统计（打开文件后的所有翻译聚合）
   sessionStats: TranslationStats;
 
+  // 记忆库组合（用于之后的翻译任务与记忆库管理）
+  memoryStack: MemoryStack;
+
   // Actions - 翻译状态
   setTranslating: (isTranslating: boolean) => void;
   setProgress: (progress: number) => void;
@@ -44,6 +55,9 @@ interface SessionState {
   updateSessionStats: (stats: TranslationStats) => void;
   setSessionStats: (stats: TranslationStats) => void;
   resetSessionStats: () => void;
+
+  // Actions - 记忆库组合
+  setMemoryStack: (memoryStack: MemoryStack) => void;
 }
 
 export const useSessionStore = create<SessionState>()(
@@ -54,6 +68,7 @@ export const useSessionStore = create<SessionState>()(
       progress: 0,
       report: null,
       sessionStats: INITIAL_SESSION_STATS,
+      memoryStack: { projectMemory: null, referenceMemories: [] },
 
       // Actions - 翻译状态
       setTranslating: (isTranslating) => set({ isTranslating }),
@@ -72,9 +87,12 @@ export const useSessionStore = create<SessionState>()(
             input_tokens: sessionStats.token_stats.input_tokens + stats.token_stats.input_tokens,
             output_tokens: sessionStats.token_stats.output_tokens + stats.token_stats.output_tokens,
             total_tokens: sessionStats.token_stats.total_tokens + stats.token_stats.total_tokens,
+            reasoning_tokens:
+              sessionStats.token_stats.reasoning_tokens + (stats.token_stats.reasoning_tokens ?? 0),
             cost: sessionStats.token_stats.cost + stats.token_stats.cost,
           },
           tm_learned: sessionStats.tm_learned + stats.tm_learned,
+          cache_hits: sessionStats.cache_hits + (stats.cache_hits ?? 0),
         };
         set({ sessionStats: newStats });
       },
@@ -86,6 +104,8 @@ export const useSessionStore = create<SessionState>()(
       resetSessionStats: () => {
         set({ sessionStats: INITIAL_SESSION_STATS });
       },
+
+      setMemoryStack: (memoryStack) => set({ memoryStack }),
     }),
     { name: 'SessionStore' }
   )
@@ -100,6 +120,7 @@ export const selectIsTranslating = (state: SessionState) => state.isTranslating;
 export const selectProgress = (state: SessionState) => state.progress;
 export const selectReport = (state: SessionState) => state.report;
 export const selectSessionStats = (state: SessionState) => state.sessionStats;
+export const selectMemoryStack = (state: SessionState) => state.memoryStack;
 
 // Actions Selectors
 export const selectSetTranslating = (state: SessionState) => state.setTranslating;
@@ -108,15 +129,18 @@ export const selectSetReport = (state: SessionState) => state.setReport;
 export const selectUpdateSessionStats = (state: SessionState) => state.updateSessionStats;
 export const selectSetSessionStats = (state: SessionState) => state.setSessionStats;
 export const selectResetSessionStats = (state: SessionState) => state.resetSessionStats;
+export const selectSetMemoryStack = (state: SessionState) => state.setMemoryStack;
 
 // 便捷 Hooks
 export const useIsTranslating = () => useSessionStore(selectIsTranslating);
 export const useProgress = () => useSessionStore(selectProgress);
 export const useReport = () => useSessionStore(selectReport);
 export const useSessionStats = () => useSessionStore(selectSessionStats);
+export const useMemoryStack = () => useSessionStore(selectMemoryStack);
 
 // Actions Hooks
 export const useSetTranslating = () => useSessionStore(selectSetTranslating);
 export const useSetProgress = () => useSessionStore(selectSetProgress);
 export const useUpdateSessionStats = () => useSessionStore(selectUpdateSessionStats);
 export const useResetSessionStats = () => useSessionStore(selectResetSessionStats);
+export const useSetMemoryStack = () => useSessionStore(selectSetMemoryStack);
@@ -12,9 +12,12 @@ import { devtools } from 'zustand/middleware';
 import { TranslationStats } from '../types/tauri';
 import { tauriStore } from './tauriStore';
 import { createModuleLogger } from '../utils/logger';
+import { convertCost } from '../utils/formatters';
 
 const log = createModuleLogger('useStatsStore');
 
+const DEFAULT_COST_CURRENCY = 'USD';
+
 const INITIAL_STATS: TranslationStats = {
   total: 0,
   tm_hits: 0,
@@ -24,26 +27,32 @@ const INITIAL_STATS: TranslationStats = {
     input_tokens: 0,
     output_tokens: 0,
     total_tokens: 0,
+    reasoning_tokens: 0,
     cost: 0,
   },
   tm_learned: 0,
+  cache_hits: 0,
 };
 
 interface StatsState {
   // 累计统计
   cumulativeStats: TranslationStats;
+  // 累计花费的货币（显示货币变更时整体换算，而非改标签）
+  costCurrency: string;
 
   // Actions
   setCumulativeStats: (stats: TranslationStats) => void;
   updateCumulativeStats: (stats: TranslationStats) => void;
   resetCumulativeStats: () => void;
+  convertCumulativeCost: (currency: string, rates: Record<string, number>) => void;
 }
 
 export const useStatsStore = create<StatsState>()(
   devtools(
-    (set) => ({
+    (set, get) => ({
       // 初始状态
       cumulativeStats: INITIAL_STATS,
+      costCurrency: DEFAULT_COST_CURRENCY,
 
       // Actions
       setCumulativeStats: (stats) => {
@@ -61,8 +70,10 @@ export const useStatsStore = create<StatsState>()(
             deduplicated: stats.deduplicated,
             aiTranslated: stats.ai_translated,
             tmLearned: stats.tm_learned,
+            cacheHits: stats.cache_hits,
             inputTokens: stats.token_stats.input_tokens,
             outputTokens: stats.token_stats.output_tokens,
+            reasoningTokens: stats.token_stats.reasoning_tokens,
           })
           .catch((err) => log.error('保存累计统计失败', err));
       },
@@ -79,9 +90,12 @@ export const useStatsStore = create<StatsState>()(
               input_tokens: prev.token_stats.input_tokens + stats.token_stats.input_tokens,
               output_tokens: prev.token_stats.output_tokens + stats.token_stats.output_tokens,
               total_tokens: prev.token_stats.total_tokens + stats.token_stats.total_tokens,
+              reasoning_tokens:
+                prev.token_stats.reasoning_tokens + (stats.token_stats.reasoning_tokens ?? 0),
               cost: prev.token_stats.cost + stats.token_stats.cost,
             },
             tm_learned: prev.tm_learned + stats.tm_learned,
+            cache_hits: prev.cache_hits + (stats.cache_hits ?? 0),
           };
 
           tauriStore
@@ -95,8 +109,10 @@ export const useStatsStore = create<StatsState>()(
               deduplicated: next.deduplicated,
               aiTranslated: next.ai_translated,
               tmLearned: next.tm_learned,
+              cacheHits: next.cache_hits,
               inputTokens: next.token_stats.input_tokens,
               outputTokens: next.token_stats.output_tokens,
+              reasoningTokens: next.token_stats.reasoning_tokens,
             })
             .catch((err) => log.error('保存累计统计失败', err));
 
@@ -119,11 +135,34 @@ export const useStatsStore = create<StatsState>()(
             deduplicated: 0,
             aiTranslated: 0,
             tmLearned: 0,
+            cacheHits: 0,
             inputTokens: 0,
             outputTokens: 0,
           })
           .catch((err) => log.error('重置累计统计失败', err));
       },
+
+      convertCumulativeCost: (currency, rates) => {
+        const { cumulativeStats, costCurrency } = get();
+        if (currency === costCurrency) return;
+        const cost = convertCost(cumulativeStats.token_stats.cost, costCurrency, currency, rates);
+        if (cost === null) {
+          log.warn('累计花费换算失败：汇率表中缺少货币', { from: costCurrency, to: currency });
+          return;
+        }
+        set({
+          cumulativeStats: {
+            ...cumulativeStats,
+            token_stats: { ...cumulativeStats.token_stats, cost },
+          },
+          costCurrency: currency,
+        });
+        log.info('累计花费已换算', { from: costCurrency, to: currency });
+
+        tauriStore
+          .updateCumulativeStats({ totalCost: cost, costCurrency: currency })
+          .catch((err) => log.error('保存累计统计失败', err));
+      },
     }),
     { name: 'StatsStore' }
   )
@@ -137,11 +176,15 @@ export const selectCumulativeStats = (state: StatsState) => state.cumulativeStat
 export const selectSetCumulativeStats = (state: StatsState) => state.setCumulativeStats;
 export const selectUpdateCumulativeStats = (state: StatsState) => state.updateCumulativeStats;
 export const selectResetCumulativeStats = (state: StatsState) => state.resetCumulativeStats;
+export const selectCostCurrency = (state: StatsState) => state.costCurrency;
+export const selectConvertCumulativeCost = (state: StatsState) => state.convertCumulativeCost;
 
 // 便捷 Hooks
 export const useCumulativeStats = () => useStatsStore(selectCumulativeStats);
 export const useUpdateCumulativeStatsAction = () => useStatsStore(selectUpdateCumulativeStats);
 export const useResetCumulativeStatsAction = () => useStatsStore(selectResetCumulativeStats);
+export const useCumulativeCostCurrency = () => useStatsStore(selectCostCurrency);
+export const useConvertCumulativeCostAction = () => useStatsStore(selectConvertCumulativeCost);
 
 /**
  * 从 TauriStore 加载统计数据
@@ -162,10 +205,13 @@ export async function loadStats() {
           input_tokens: stats.inputTokens,
           output_tokens: stats.outputTokens,
           total_tokens: stats.totalTokens,
+          reasoning_tokens: stats.reasoningTokens ?? 0,
           cost: stats.totalCost,
         },
         tm_learned: stats.tmLearned,
+        cache_hits: stats.cacheHits ?? 0,
       },
+      costCurrency: stats.costCurrency ?? DEFAULT_COST_CURRENCY,
     });
 
     log.info('统计加载成功', stats);
@@ -5,7 +5,9 @@
 // 导出自动生成的类型
 export type { ProxyConfig } from './generated/ProxyConfig';
 export type { AIConfig } from './generated/AIConfig';
+export type { GenerationParams } from './generated/GenerationParams';
 import type { ProxyConfig } from './generated/ProxyConfig';
+import type { GenerationParams } from './generated/GenerationParams';
 
 export interface AIConfigSummary {
   index: number;
@@ -15,7 +17,10 @@ export interface AIConfigSummary {
   baseUrl: string | null;
   model: string | null;
   proxy: ProxyConfig | null;
+  generation: GenerationParams;
   isActive: boolean;
+  /** 在备用供应商链中的位置（从 0 开始），不是备用配置时为 null */
+  fallbackOrder: number | null;
 }
 
 /**
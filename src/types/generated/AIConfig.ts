@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GenerationParams } from "./GenerationParams";
 import type { ProxyConfig } from "./ProxyConfig";
 
-export interface AIConfig { providerId: string, apiKey: string, baseUrl: string | null, model: string | null, proxy: ProxyConfig | null, }
\ No newline at end of file
+export interface AIConfig { providerId: string, apiKey: string, baseUrl: string | null, model: string | null, proxy: ProxyConfig | null, generation: GenerationParams, }
\ No newline at end of file
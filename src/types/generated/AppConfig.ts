@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AIConfig } from "./AIConfig";
+import type { BudgetConfig } from "./BudgetConfig";
+import type { FuzzyMatchConfig } from "./FuzzyMatchConfig";
+import type { ResponseCacheConfig } from "./ResponseCacheConfig";
+import type { TmCapacityConfig } from "./TmCapacityConfig";
 
-export interface AppConfig { apiKey: string, provider: string, model: string, baseUrl: string | null, useTranslationMemory: boolean, translationMemoryPath: string | null, logLevel: string, autoSave: boolean, batchSize: number, maxConcurrent: number, timeoutSeconds: bigint, aiConfigs: Array<AIConfig>, activeConfigIndex: number | null, systemPrompt: string | null, themeMode: string | null, language: string | null, logRetentionDays: number | null, logMaxSize: number | null, logMaxCount: number | null, configVersion: bigint, lastModified: string | null, }
\ No newline at end of file
+export interface AppConfig { apiKey: string, provider: string, model: string, baseUrl: string | null, useTranslationMemory: boolean, translationMemoryPath: string | null, logLevel: string, autoSave: boolean, batchSize: number, maxConcurrent: number, timeoutSeconds: bigint, adaptiveBatchSize: boolean, aiConfigs: Array<AIConfig>, activeConfigIndex: number | null, fallbackConfigIndices: Array<number>, budget: BudgetConfig, currency: string, responseCache: ResponseCacheConfig, fuzzyMatch: FuzzyMatchConfig, tmCapacity: TmCapacityConfig, systemPrompt: string | null, themeMode: string | null, language: string | null, logRetentionDays: number | null, logMaxSize: number | null, logMaxCount: number | null, configVersion: bigint, lastModified: string | null, }
\ No newline at end of file
@@ -1,4 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { TokenStatsEvent } from "./TokenStatsEvent";
 
-export interface BatchStatsEvent { tm_hits: number, deduplicated: number, ai_translated: number, token_stats: TokenStatsEvent, }
\ No newline at end of file
+export interface BatchStatsEvent { tm_hits: number, deduplicated: number, ai_translated: number, cache_hits: number, token_stats: TokenStatsEvent, }
\ No newline at end of file
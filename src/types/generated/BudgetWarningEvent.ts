@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetScope } from "./BudgetScope";
+
+export interface BudgetWarningEvent { job_id: string, scope: BudgetScope, threshold: number, spent: number, limit: number, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConflictVariant } from "./ConflictVariant";
+
+export interface CatalogConflict { source: string, context: string | null, targetLang: string, existing: string | null, variants: Array<ConflictVariant>, }
\ No newline at end of file
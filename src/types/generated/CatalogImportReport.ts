@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CatalogConflict } from "./CatalogConflict";
+
+export interface CatalogImportReport { files: number, skippedFiles: Array<string>, pairs: number, phrases: number, segments: number, unchanged: number, fuzzy: number, untranslated: number, sameAsSource: number, conflicts: Array<CatalogConflict>, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface CostBreakdown { input_tokens: number, output_tokens: number, cache_write_tokens: number, cache_read_tokens: number, input_cost: number, output_cost: number, cache_write_cost: number, cache_read_cost: number, total_cost: number, cache_savings: number, cache_hit_rate: number, }
\ No newline at end of file
+export interface CostBreakdown { input_tokens: number, output_tokens: number, reasoning_tokens: number, cache_write_tokens: number, cache_read_tokens: number, currency: string, input_cost: number, output_cost: number, reasoning_cost: number, cache_write_cost: number, cache_read_cost: number, total_cost: number, cache_savings: number, cache_hit_rate: number, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface GenerationParams { temperature: number | null, topP: number | null, maxTokens: number | null, extraBody: Record<string, unknown>, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface ModelInfo { id: string, name: string, provider: string, context_window: number, max_output_tokens: number, input_price: number, output_price: number, cache_reads_price: number | null, cache_writes_price: number | null, supports_cache: boolean, supports_images: boolean, description: string | null, recommended: boolean, }
\ No newline at end of file
+export interface ModelInfo { id: string, name: string, provider: string, context_window: number, max_output_tokens: number, currency: string, input_price: number, output_price: number, cache_reads_price: number | null, cache_writes_price: number | null, supports_cache: boolean, supports_images: boolean, description: string | null, recommended: boolean, }
\ No newline at end of file
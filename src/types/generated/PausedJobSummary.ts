@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface PausedJobSummary { id: string, createdAt: string, targetLanguage: string | null, total: number, completed: number, cost: number, reason: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewStatus } from "./ReviewStatus";
+
+export interface SegmentEntry { source: string, target: string, targetLang: string, sourceLang: string | null, context: string | null, file: string | null, provider: string | null, note: string | null, createdAt: string, updatedAt: string, reviewStatus: ReviewStatus, }
\ No newline at end of file
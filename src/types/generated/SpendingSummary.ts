@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SpendingSummary { currency: string, today: number, thisMonth: number, dailyCap: number | null, monthlyCap: number | null, }
\ No newline at end of file
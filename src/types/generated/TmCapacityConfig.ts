@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmEvictionPolicy } from "./TmEvictionPolicy";
+
+export interface TmCapacityConfig { maxEntries: number, policy: TmEvictionPolicy, }
\ No newline at end of file
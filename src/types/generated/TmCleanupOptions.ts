@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface TmCleanupOptions { sameAsSource: boolean, placeholders: boolean, extraMarkers: Array<string>, targetLang: string | null, dryRun: boolean, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmRecord } from "./TmRecord";
+
+export interface TmCleanupReport { sameAsSource: number, placeholders: number, removed: Array<TmRecord>, }
\ No newline at end of file
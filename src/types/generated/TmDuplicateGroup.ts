@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmDuplicateKind } from "./TmDuplicateKind";
+import type { TmRecord } from "./TmRecord";
+
+export interface TmDuplicateGroup { source: string, targetLang: string | null, context: string | null, kind: TmDuplicateKind, records: Array<TmRecord>, }
\ No newline at end of file
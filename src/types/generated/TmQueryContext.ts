@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface TmQueryContext { sourceLanguage: string | null, contexts: Array<string | null>, projectMemory: string | null, referenceMemories: Array<string>, }
\ No newline at end of file
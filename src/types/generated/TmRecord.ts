@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewStatus } from "./ReviewStatus";
+import type { TmTier } from "./TmTier";
+
+export interface TmRecord { tier: TmTier, key: string, source: string, target: string, sourceLang: string | null, targetLang: string | null, context: string | null, updatedAt: string, hits: number, lastUsedAt: string | null, reviewStatus: ReviewStatus | null, pinned: boolean, }
\ No newline at end of file
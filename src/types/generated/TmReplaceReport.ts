@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmReplacement } from "./TmReplacement";
+
+export interface TmReplaceReport { changed: number, preview: Array<TmReplacement>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmSearchQuery } from "./TmSearchQuery";
+
+export interface TmReplaceRequest { scope: TmSearchQuery, find: string, replace: string, regex: boolean, caseSensitive: boolean, dryRun: boolean, }
\ No newline at end of file
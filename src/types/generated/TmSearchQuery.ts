@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmSearchField } from "./TmSearchField";
+import type { TmTier } from "./TmTier";
+
+export interface TmSearchQuery { text: string | null, regex: boolean, caseSensitive: boolean, field: TmSearchField, sourceLang: string | null, targetLang: string | null, tier: TmTier | null, updatedFrom: string | null, updatedTo: string | null, minHits: number | null, maxHits: number | null, offset: number, limit: number | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TmRecord } from "./TmRecord";
+
+export interface TmUsageReport { total: number, used: number, totalHits: number, mostValuable: Array<TmRecord>, neverUsed: Array<TmRecord>, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface TokenStats { input_tokens: number, output_tokens: number, total_tokens: number, cost: number, }
\ No newline at end of file
+export interface TokenStats { input_tokens: number, output_tokens: number, total_tokens: number, reasoning_tokens: number, cost: number, }
\ No newline at end of file
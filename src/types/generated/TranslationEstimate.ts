@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenizerKind } from "./TokenizerKind";
+
+export interface TranslationEstimate { tokenizer: TokenizerKind, batches: number, system_prompt_tokens: number, input_tokens: number, estimated_output_tokens: number, estimated_cost: number, currency: string, }
\ No newline at end of file
@@ -1,4 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { TokenStats } from "./TokenStats";
 
-export interface TranslationStats { total: number, tm_hits: number, deduplicated: number, ai_translated: number, token_stats: TokenStats, tm_learned: number, }
\ No newline at end of file
+export interface TranslationStats { total: number, tm_hits: number, deduplicated: number, ai_translated: number, token_stats: TokenStats, tm_learned: number, cache_hits: number, }
\ No newline at end of file
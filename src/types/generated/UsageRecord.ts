@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface UsageRecord { timestamp: string, provider: string, model: string, file: string | null, project: string | null, inputTokens: bigint, outputTokens: bigint, cachedTokens: bigint, cost: number, currency: string, }
\ No newline at end of file
@@ -17,6 +17,12 @@ export type { TranslationPair } from './generated/TranslationPair';
 
 // 前端扩展类型
 
+/** 译文来源类别 */
+export type TranslationSourceKind = 'tm' | 'fuzzy' | 'dedup' | 'ai';
+
+/** 译文来源：来自命名记忆库时附带名称（如 'tm:client-a'、'fuzzy:client-a'） */
+export type TranslationSource = TranslationSourceKind | `tm:${string}` | `fuzzy:${string}`;
+
 /**
  * POEntry 类型（前端扩展版本）
  * 合并后端生成的 POEntry 与前端特有的运行时状态字段
@@ -25,7 +31,7 @@ export type { TranslationPair } from './generated/TranslationPair';
  */
 export interface POEntry extends BasePOEntry {
   needsReview?: boolean;
-  translationSource?: 'tm' | 'dedup' | 'ai';
+  translationSource?: TranslationSource;
   justUpdated?: boolean;
 }
 
@@ -52,15 +58,18 @@ export interface TranslationMemory {
 export interface TranslationQueueItem {
   index: number;
   translation: string;
-  source: 'tm' | 'ai';
+  /** 流式到达时为 'ai'，任务完成后按后端返回的来源更正 */
+  source: TranslationSource;
   incrementalStats?: {
     tmHits?: number;
     deduplicated?: number;
     aiTranslated?: number;
     tmLearned?: number;
+    cacheHits?: number;
     tokenStats?: {
       inputTokens: number;
       outputTokens: number;
+      reasoningTokens?: number;
       totalTokens: number;
       cost: number;
     };
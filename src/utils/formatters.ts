@@ -5,8 +5,6 @@ export function getErrorMessage(error: unknown): string {
   return String(error);
 }
 
-const USD_TO_CNY = 7.2;
-
 function resolveLocale(locale?: string): string {
   if (locale) {
     return locale;
@@ -19,17 +17,10 @@ function resolveLocale(locale?: string): string {
   return 'en-US';
 }
 
-function isChineseLocale(locale: string): boolean {
-  const normalized = locale.toLowerCase();
-  return ['zh', 'zh-cn', 'zh-tw', 'zh-hans', 'zh-hant'].some((value) =>
-    normalized.startsWith(value)
-  );
-}
-
-export function formatCost(cost: number, locale?: string): string {
+export function formatCost(cost: number, locale?: string, currency: string = 'USD'): string {
   return new Intl.NumberFormat(resolveLocale(locale), {
     style: 'currency',
-    currency: 'USD',
+    currency,
     minimumFractionDigits: cost < 1 ? 4 : 2,
     maximumFractionDigits: cost < 1 ? 4 : 2,
   }).format(cost);
@@ -44,14 +35,26 @@ export function formatCNY(amount: number, locale?: string): string {
   }).format(amount);
 }
 
-export function formatCostByLocale(cost: number, locale?: string): string {
-  const currentLocale = resolveLocale(locale);
-
-  if (isChineseLocale(currentLocale)) {
-    return formatCNY(cost * USD_TO_CNY, currentLocale);
-  }
+// 后端已把成本换算为设置中选择的货币，这里只负责按语言环境格式化
+export function formatCostByLocale(cost: number, locale?: string, currency?: string): string {
+  return formatCost(cost, resolveLocale(locale), currency);
+}
 
-  return formatCost(cost, currentLocale);
+/**
+ * 按汇率表换算金额（汇率为 1 USD 可兑换的数量，与后端汇率表一致）
+ * 汇率表中没有该货币时返回 null
+ */
+export function convertCost(
+  cost: number,
+  from: string,
+  to: string,
+  rates: Record<string, number>
+): number | null {
+  if (from === to) return cost;
+  const fromRate = rates[from];
+  const toRate = rates[to];
+  if (!fromRate || !toRate) return null;
+  return (cost / fromRate) * toRate;
 }
 
 export function formatTokens(tokens: number, locale?: string): string {
@@ -123,7 +126,7 @@ export function formatFileSize(bytes: number, locale?: string): string {
 
 export function formatPrice(
   price: number,
-  currency: 'USD' | 'CNY' | 'EUR' = 'USD',
+  currency: string = 'USD',
   decimals: number = 2,
   locale?: string
 ): string {
@@ -174,7 +177,8 @@ export function formatTranslationStatsSummary(
       cost: number;
     };
   },
-  locale?: string
+  locale?: string,
+  currency?: string
 ) {
   const total = stats.total || 0;
   const tmHits = stats.tm_hits || 0;
@@ -198,6 +202,6 @@ export function formatTranslationStatsSummary(
       percentage: formatPercentage(aiTranslated, total, 1, locale),
     },
     tokens: formatTokens(tokens, locale),
-    cost: formatCostByLocale(cost, locale),
+    cost: formatCostByLocale(cost, locale, currency),
   };
 }
@@ -0,0 +1,26 @@
+import type { TranslationSourceKind } from '../types/tauri';
+
+const KINDS: readonly TranslationSourceKind[] = ['tm', 'fuzzy', 'dedup', 'ai'];
+
+export interface ParsedTranslationSource {
+  kind: TranslationSourceKind;
+  /** 命中的命名记忆库（默认记忆库或非记忆库来源为 null） */
+  memory: string | null;
+}
+
+/**
+ * 拆分译文来源：命名记忆库的命中为 `tm:<名称>` / `fuzzy:<名称>`，无法识别的来源按 AI 译文处理
+ */
+export function parseTranslationSource(source: string | undefined): ParsedTranslationSource {
+  if (!source) return { kind: 'ai', memory: null };
+  const separator = source.indexOf(':');
+  const kind = separator < 0 ? source : source.slice(0, separator);
+  const memory = separator < 0 ? null : source.slice(separator + 1) || null;
+  const known = KINDS.find((candidate) => candidate === kind);
+  return known ? { kind: known, memory } : { kind: 'ai', memory: null };
+}
+
+/** 记忆库完全匹配无需复核；模糊匹配、去重与 AI 译文需要复核 */
+export function sourceNeedsReview(source: string | undefined): boolean {
+  return parseTranslationSource(source).kind !== 'tm';
+}